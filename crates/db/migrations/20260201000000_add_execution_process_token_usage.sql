@@ -0,0 +1,16 @@
+-- Token usage reported by coding agents, one row per execution process
+CREATE TABLE execution_process_token_usage (
+    execution_process_id BLOB PRIMARY KEY,
+    executor             TEXT NOT NULL,
+    model                TEXT,
+    input_tokens         INTEGER NOT NULL DEFAULT 0,
+    output_tokens        INTEGER NOT NULL DEFAULT 0,
+    cache_read_tokens    INTEGER NOT NULL DEFAULT 0,
+    cache_write_tokens   INTEGER NOT NULL DEFAULT 0,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_execution_process_token_usage_executor
+ON execution_process_token_usage (executor);
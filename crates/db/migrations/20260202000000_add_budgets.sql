@@ -0,0 +1,18 @@
+-- Token and spend limits for projects and tasks; NULL means unlimited
+CREATE TABLE project_budgets (
+    project_id    BLOB PRIMARY KEY,
+    max_tokens    INTEGER,
+    max_cost_usd  REAL,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE TABLE task_budgets (
+    task_id       BLOB PRIMARY KEY,
+    max_tokens    INTEGER,
+    max_cost_usd  REAL,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
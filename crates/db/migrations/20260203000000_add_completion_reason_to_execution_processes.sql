@@ -0,0 +1,2 @@
+-- Record why vibe-kanban stopped an execution process ('timeout', 'tool_call_limit')
+ALTER TABLE execution_processes ADD COLUMN completion_reason TEXT;
@@ -0,0 +1,2 @@
+-- Executor profiles a session fell back to after failures, as a JSON array
+ALTER TABLE sessions ADD COLUMN executor_fallbacks TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,8 @@
+-- Ordered approval rules for tool calls made by coding agents in a project
+CREATE TABLE project_approval_policies (
+    project_id    BLOB PRIMARY KEY,
+    rules         TEXT NOT NULL DEFAULT '[]',
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
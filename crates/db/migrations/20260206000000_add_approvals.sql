@@ -0,0 +1,20 @@
+-- Audit trail of tool call approval requests and how they were decided. Records outlive
+-- their execution process, which is cleared when the process is deleted.
+CREATE TABLE approvals (
+    id                    TEXT PRIMARY KEY,
+    execution_process_id  BLOB,
+    tool_name             TEXT NOT NULL,
+    tool_call_id          TEXT NOT NULL,
+    tool_input            TEXT NOT NULL,
+    status                TEXT NOT NULL DEFAULT 'pending',
+    reason                TEXT,
+    decided_by            TEXT,
+    responder             TEXT,
+    latency_ms            INTEGER,
+    requested_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    responded_at          TEXT,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_approvals_execution_process_id ON approvals(execution_process_id);
+CREATE INDEX idx_approvals_requested_at ON approvals(requested_at);
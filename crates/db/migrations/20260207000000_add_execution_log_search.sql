@@ -0,0 +1,21 @@
+-- Full-text index over the normalized entries of coding agent executions
+CREATE VIRTUAL TABLE execution_log_search USING fts5(
+    content,
+    kind UNINDEXED,
+    execution_process_id UNINDEXED,
+    entry_index UNINDEXED
+);
+
+-- Executions whose logs have been indexed, so startup backfill can skip them
+CREATE TABLE execution_log_search_state (
+    execution_process_id  BLOB PRIMARY KEY,
+    indexed_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+-- FTS5 tables cannot take part in foreign keys
+CREATE TRIGGER execution_log_search_cleanup
+AFTER DELETE ON execution_processes
+BEGIN
+    DELETE FROM execution_log_search WHERE execution_process_id = old.id;
+END;
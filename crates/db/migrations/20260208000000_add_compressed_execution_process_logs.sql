@@ -0,0 +1,4 @@
+-- Log lines of finished executions are compacted into zstd compressed chunks.
+-- Chunk rows keep `logs` empty and store the compressed JSONL in `compressed`;
+-- `byte_size` stays the uncompressed size.
+ALTER TABLE execution_process_logs ADD COLUMN compressed BLOB;
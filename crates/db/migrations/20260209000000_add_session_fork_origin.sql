@@ -0,0 +1,5 @@
+-- The session and coding agent turn a session was forked from
+ALTER TABLE sessions ADD COLUMN forked_from_session_id BLOB REFERENCES sessions(id) ON DELETE SET NULL;
+ALTER TABLE sessions ADD COLUMN forked_from_execution_process_id BLOB REFERENCES execution_processes(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_sessions_forked_from_session_id ON sessions(forked_from_session_id);
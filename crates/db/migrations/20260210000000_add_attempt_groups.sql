@@ -0,0 +1,22 @@
+-- Workspaces started together on the same task to compare executors (best-of-N)
+CREATE TABLE attempt_groups (
+    id                   BLOB PRIMARY KEY,
+    task_id              BLOB NOT NULL,
+    winner_workspace_id  BLOB,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (winner_workspace_id) REFERENCES workspaces(id) ON DELETE SET NULL
+);
+
+CREATE TABLE attempt_group_workspaces (
+    attempt_group_id  BLOB NOT NULL,
+    workspace_id      BLOB NOT NULL,
+    position          INTEGER NOT NULL,
+    PRIMARY KEY (attempt_group_id, workspace_id),
+    FOREIGN KEY (attempt_group_id) REFERENCES attempt_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_attempt_groups_task_id ON attempt_groups(task_id);
+CREATE INDEX idx_attempt_group_workspaces_workspace_id ON attempt_group_workspaces(workspace_id);
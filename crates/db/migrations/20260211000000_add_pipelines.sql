@@ -0,0 +1,27 @@
+-- Ordered agent, approval, review and script stages that task attempts of a project run through
+CREATE TABLE project_pipelines (
+    project_id    BLOB PRIMARY KEY,
+    stages        TEXT NOT NULL DEFAULT '[]',
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Progress of a workspace through a snapshot of its project's pipeline
+CREATE TABLE pipeline_runs (
+    id                           BLOB PRIMARY KEY,
+    workspace_id                 BLOB NOT NULL,
+    session_id                   BLOB NOT NULL,
+    stages                       TEXT NOT NULL,
+    default_executor_profile_id  TEXT NOT NULL,
+    current_stage                INTEGER NOT NULL DEFAULT 0,
+    status                       TEXT NOT NULL DEFAULT 'running'
+                                 CHECK (status IN ('running','awaiting_approval','completed','failed','cancelled')),
+    plan                         TEXT,
+    created_at                   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at                   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_pipeline_runs_workspace_id ON pipeline_runs(workspace_id);
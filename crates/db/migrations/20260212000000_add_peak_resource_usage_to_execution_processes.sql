@@ -0,0 +1,3 @@
+-- Highest memory and CPU use sampled from the process group of an execution while it ran
+ALTER TABLE execution_processes ADD COLUMN peak_rss_bytes INTEGER;
+ALTER TABLE execution_processes ADD COLUMN peak_cpu_percent REAL;
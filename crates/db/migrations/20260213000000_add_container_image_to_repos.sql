@@ -0,0 +1,2 @@
+-- OCI image that executions of workspaces containing the repo run in
+ALTER TABLE repos ADD COLUMN container_image TEXT;
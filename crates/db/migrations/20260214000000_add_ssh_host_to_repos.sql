@@ -0,0 +1,2 @@
+-- SSH host that executions of workspaces containing the repo run on
+ALTER TABLE repos ADD COLUMN ssh_host TEXT;
@@ -0,0 +1,2 @@
+-- Git hosting provider used for pull requests, overriding detection from the remote URL
+ALTER TABLE repos ADD COLUMN git_host_provider TEXT;
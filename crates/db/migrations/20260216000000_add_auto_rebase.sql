@@ -0,0 +1,6 @@
+-- When workspaces of a repo are rebased automatically after their target branch moves
+ALTER TABLE repos ADD COLUMN auto_rebase TEXT NOT NULL DEFAULT 'never'
+    CHECK (auto_rebase IN ('never', 'idle_only', 'always'));
+
+-- Target branch commit an automatic rebase of the workspace hit conflicts with
+ALTER TABLE workspace_repos ADD COLUMN auto_rebase_conflict_oid TEXT;
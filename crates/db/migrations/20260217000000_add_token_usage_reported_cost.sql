@@ -0,0 +1,2 @@
+-- Cost reported by agents that price their own runs, e.g. OpenCode
+ALTER TABLE execution_process_token_usage ADD COLUMN reported_cost_usd REAL;
@@ -0,0 +1,19 @@
+-- Approval decisions the user asked to apply to later tool calls of a session or workspace.
+-- Exactly one scope is set, and the decisions go away with it.
+CREATE TABLE remembered_approvals (
+    id            BLOB PRIMARY KEY,
+    session_id    BLOB,
+    workspace_id  BLOB,
+    tool_name     TEXT NOT NULL,
+    tool_input    TEXT NOT NULL,
+    matcher       TEXT NOT NULL,
+    status        TEXT NOT NULL,
+    responder     TEXT NOT NULL,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    CHECK ((session_id IS NULL) <> (workspace_id IS NULL))
+);
+
+CREATE INDEX idx_remembered_approvals_session_id ON remembered_approvals(session_id);
+CREATE INDEX idx_remembered_approvals_workspace_id ON remembered_approvals(workspace_id);
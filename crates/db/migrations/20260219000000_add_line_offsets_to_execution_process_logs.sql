@@ -0,0 +1,4 @@
+-- Per-line timing of compressed chunks: a JSON array with the offset in
+-- milliseconds of each line from the chunk's `inserted_at`, so compaction keeps
+-- when every line was logged.
+ALTER TABLE execution_process_logs ADD COLUMN line_offsets TEXT;
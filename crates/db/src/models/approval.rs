@@ -0,0 +1,420 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use utils::approvals::{ApprovalRequest, ApprovalStatus};
+use uuid::Uuid;
+
+/// Final state of an approval request
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "approval_outcome", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalOutcome {
+    Pending,
+    Approved,
+    Denied,
+    TimedOut,
+}
+
+/// Who decided an approval request
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "approval_decided_by", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecidedBy {
+    User,
+    /// A project or executor profile approval rule
+    Rule,
+    /// A decision the user asked to remember for the session or workspace
+    Remembered,
+    Timeout,
+}
+
+/// Audit record of a tool call approval request
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct Approval {
+    pub id: String,
+    /// Cleared when the execution process is deleted; the record is kept for auditing
+    pub execution_process_id: Option<Uuid>,
+    pub tool_name: String,
+    pub tool_call_id: String,
+    #[ts(type = "JsonValue")]
+    pub tool_input: Json<Value>,
+    pub status: ApprovalOutcome,
+    pub reason: Option<String>,
+    pub decided_by: Option<ApprovalDecidedBy>,
+    /// The user or rule that decided the request
+    pub responder: Option<String>,
+    /// Time from the request to its decision in milliseconds
+    pub latency_ms: Option<i64>,
+    pub requested_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct ApprovalQuery {
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub execution_process_id: Option<Uuid>,
+    pub tool_name: Option<String>,
+    pub status: Option<ApprovalOutcome>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl ApprovalOutcome {
+    fn from_status(status: &ApprovalStatus) -> (Self, Option<&str>) {
+        match status {
+            ApprovalStatus::Pending => (Self::Pending, None),
+            ApprovalStatus::Approved => (Self::Approved, None),
+            ApprovalStatus::Denied { reason } => (Self::Denied, reason.as_deref()),
+            ApprovalStatus::TimedOut => (Self::TimedOut, None),
+        }
+    }
+}
+
+impl Approval {
+    pub async fn create(pool: &SqlitePool, request: &ApprovalRequest) -> Result<(), sqlx::Error> {
+        let tool_input = Json(&request.tool_input);
+        sqlx::query!(
+            r#"INSERT INTO approvals (id, execution_process_id, tool_name, tool_call_id, tool_input, requested_at)
+               VALUES ($1, $2, $3, $4, $5, $6)"#,
+            request.id,
+            request.execution_process_id,
+            request.tool_name,
+            request.tool_call_id,
+            tool_input,
+            request.created_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the decision of a pending request. Requests that were already decided are left
+    /// untouched, so the first decision wins.
+    pub async fn record_response(
+        pool: &SqlitePool,
+        id: &str,
+        status: &ApprovalStatus,
+        decided_by: ApprovalDecidedBy,
+        responder: Option<&str>,
+        requested_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let (outcome, reason) = ApprovalOutcome::from_status(status);
+        let now = Utc::now();
+        let latency_ms = (now - requested_at).num_milliseconds().max(0);
+        sqlx::query!(
+            r#"UPDATE approvals
+               SET status = $2, reason = $3, decided_by = $4, responder = $5, latency_ms = $6,
+                   responded_at = $7
+               WHERE id = $1 AND status = 'pending'"#,
+            id,
+            outcome,
+            reason,
+            decided_by,
+            responder,
+            latency_ms,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Approval,
+            r#"SELECT
+                id as "id!",
+                execution_process_id as "execution_process_id: Uuid",
+                tool_name,
+                tool_call_id,
+                tool_input as "tool_input!: Json<Value>",
+                status as "status!: ApprovalOutcome",
+                reason,
+                decided_by as "decided_by: ApprovalDecidedBy",
+                responder,
+                latency_ms,
+                requested_at as "requested_at!: DateTime<Utc>",
+                responded_at as "responded_at: DateTime<Utc>"
+               FROM approvals
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Approvals matching every filter that is set, newest first
+    pub async fn search(
+        pool: &SqlitePool,
+        query: &ApprovalQuery,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+        let offset = query.offset.unwrap_or(0).max(0);
+        sqlx::query_as!(
+            Approval,
+            r#"SELECT
+                a.id as "id!",
+                a.execution_process_id as "execution_process_id: Uuid",
+                a.tool_name,
+                a.tool_call_id,
+                a.tool_input as "tool_input!: Json<Value>",
+                a.status as "status!: ApprovalOutcome",
+                a.reason,
+                a.decided_by as "decided_by: ApprovalDecidedBy",
+                a.responder,
+                a.latency_ms,
+                a.requested_at as "requested_at!: DateTime<Utc>",
+                a.responded_at as "responded_at: DateTime<Utc>"
+               FROM approvals a
+               LEFT JOIN execution_processes ep ON ep.id = a.execution_process_id
+               LEFT JOIN sessions s ON s.id = ep.session_id
+               LEFT JOIN workspaces w ON w.id = s.workspace_id
+               LEFT JOIN tasks t ON t.id = w.task_id
+               WHERE ($1 IS NULL OR t.project_id = $1)
+                 AND ($2 IS NULL OR t.id = $2)
+                 AND ($3 IS NULL OR a.execution_process_id = $3)
+                 AND ($4 IS NULL OR a.tool_name = $4)
+                 AND ($5 IS NULL OR a.status = $5)
+               ORDER BY a.requested_at DESC
+               LIMIT $6 OFFSET $7"#,
+            query.project_id,
+            query.task_id,
+            query.execution_process_id,
+            query.tool_name,
+            query.status,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use serde_json::json;
+    use utils::approvals::CreateApprovalRequest;
+
+    use super::*;
+    use crate::{
+        DBService,
+        models::task::Task,
+        test_utils::{add_task, create_coding_agent_process, create_task_fixture},
+    };
+
+    async fn request(
+        db: &DBService,
+        execution_process_id: Uuid,
+        tool_name: &str,
+        requested_at: DateTime<Utc>,
+    ) -> ApprovalRequest {
+        let mut request = ApprovalRequest::from_create(
+            CreateApprovalRequest {
+                tool_name: tool_name.to_string(),
+                tool_input: json!({ "command": "cargo test" }),
+                tool_call_id: Uuid::new_v4().to_string(),
+            },
+            execution_process_id,
+        );
+        request.created_at = requested_at;
+        Approval::create(&db.pool, &request).await.unwrap();
+        request
+    }
+
+    fn ids(approvals: &[Approval]) -> Vec<&str> {
+        approvals
+            .iter()
+            .map(|approval| approval.id.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn create_stores_a_pending_request() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Approvals").await;
+        let process = create_coding_agent_process(&db, fixture.session.id).await;
+
+        let request = request(&db, process.id, "Bash", Utc::now()).await;
+        let approval = Approval::find_by_id(&db.pool, &request.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(approval.execution_process_id, Some(process.id));
+        assert_eq!(approval.tool_name, "Bash");
+        assert_eq!(approval.tool_call_id, request.tool_call_id);
+        assert_eq!(approval.tool_input.0, request.tool_input);
+        assert_eq!(approval.status, ApprovalOutcome::Pending);
+        assert_eq!(approval.requested_at, request.created_at);
+        assert!(approval.decided_by.is_none());
+        assert!(approval.responded_at.is_none());
+        assert!(approval.latency_ms.is_none());
+    }
+
+    #[tokio::test]
+    async fn first_recorded_response_wins() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Approvals").await;
+        let process = create_coding_agent_process(&db, fixture.session.id).await;
+        let requested_at = Utc::now() - Duration::seconds(5);
+        let request = request(&db, process.id, "Bash", requested_at).await;
+
+        Approval::record_response(
+            &db.pool,
+            &request.id,
+            &ApprovalStatus::Denied {
+                reason: Some("not on main".to_string()),
+            },
+            ApprovalDecidedBy::User,
+            Some("alice"),
+            requested_at,
+        )
+        .await
+        .unwrap();
+        // A late timeout does not overwrite the user's decision
+        Approval::record_response(
+            &db.pool,
+            &request.id,
+            &ApprovalStatus::TimedOut,
+            ApprovalDecidedBy::Timeout,
+            None,
+            requested_at,
+        )
+        .await
+        .unwrap();
+
+        let approval = Approval::find_by_id(&db.pool, &request.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(approval.status, ApprovalOutcome::Denied);
+        assert_eq!(approval.reason.as_deref(), Some("not on main"));
+        assert_eq!(approval.decided_by, Some(ApprovalDecidedBy::User));
+        assert_eq!(approval.responder.as_deref(), Some("alice"));
+        assert!(approval.latency_ms.unwrap() >= 5000);
+        assert!(approval.responded_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn records_outlive_their_execution_process() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Approvals").await;
+        let process = create_coding_agent_process(&db, fixture.session.id).await;
+        let request = request(&db, process.id, "Bash", Utc::now()).await;
+
+        Task::delete(&db.pool, fixture.task.id).await.unwrap();
+
+        let approval = Approval::find_by_id(&db.pool, &request.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(approval.execution_process_id.is_none());
+        assert_eq!(
+            ids(&Approval::search(&db.pool, &ApprovalQuery::default())
+                .await
+                .unwrap()),
+            vec![request.id.as_str()]
+        );
+    }
+
+    #[tokio::test]
+    async fn search_applies_every_filter() {
+        let db = DBService::new_in_memory().await;
+        let first = create_task_fixture(&db, "First").await;
+        let second = add_task(&db, first.project.clone(), "Second").await;
+        let other = create_task_fixture(&db, "Other project").await;
+        let first_process = create_coding_agent_process(&db, first.session.id).await;
+        let second_process = create_coding_agent_process(&db, second.session.id).await;
+        let other_process = create_coding_agent_process(&db, other.session.id).await;
+
+        let now = Utc::now();
+        let oldest = request(&db, first_process.id, "Bash", now - Duration::seconds(3)).await;
+        let edit = request(&db, first_process.id, "Edit", now - Duration::seconds(2)).await;
+        let second_bash = request(&db, second_process.id, "Bash", now - Duration::seconds(1)).await;
+        let other_bash = request(&db, other_process.id, "Bash", now).await;
+        Approval::record_response(
+            &db.pool,
+            &edit.id,
+            &ApprovalStatus::Approved,
+            ApprovalDecidedBy::Rule,
+            Some("allow edits"),
+            edit.created_at,
+        )
+        .await
+        .unwrap();
+
+        let search = |query: ApprovalQuery| {
+            let pool = db.pool.clone();
+            async move { Approval::search(&pool, &query).await.unwrap() }
+        };
+
+        // Newest first without filters
+        assert_eq!(
+            ids(&search(ApprovalQuery::default()).await),
+            vec![
+                other_bash.id.as_str(),
+                second_bash.id.as_str(),
+                edit.id.as_str(),
+                oldest.id.as_str()
+            ]
+        );
+        assert_eq!(
+            ids(&search(ApprovalQuery {
+                project_id: Some(first.project.id),
+                ..Default::default()
+            })
+            .await),
+            vec![
+                second_bash.id.as_str(),
+                edit.id.as_str(),
+                oldest.id.as_str()
+            ]
+        );
+        assert_eq!(
+            ids(&search(ApprovalQuery {
+                task_id: Some(second.task.id),
+                ..Default::default()
+            })
+            .await),
+            vec![second_bash.id.as_str()]
+        );
+        assert_eq!(
+            ids(&search(ApprovalQuery {
+                execution_process_id: Some(first_process.id),
+                ..Default::default()
+            })
+            .await),
+            vec![edit.id.as_str(), oldest.id.as_str()]
+        );
+        assert_eq!(
+            ids(&search(ApprovalQuery {
+                project_id: Some(first.project.id),
+                tool_name: Some("Bash".to_string()),
+                status: Some(ApprovalOutcome::Pending),
+                ..Default::default()
+            })
+            .await),
+            vec![second_bash.id.as_str(), oldest.id.as_str()]
+        );
+        assert_eq!(
+            ids(&search(ApprovalQuery {
+                status: Some(ApprovalOutcome::Approved),
+                ..Default::default()
+            })
+            .await),
+            vec![edit.id.as_str()]
+        );
+        assert_eq!(
+            ids(&search(ApprovalQuery {
+                limit: Some(2),
+                offset: Some(1),
+                ..Default::default()
+            })
+            .await),
+            vec![second_bash.id.as_str(), edit.id.as_str()]
+        );
+    }
+}
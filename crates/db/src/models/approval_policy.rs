@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use executors::approvals::policy::ApprovalRule;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Approval rules of a project. They are checked before the rules of the executor profile.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProjectApprovalPolicy {
+    pub project_id: Uuid,
+    #[ts(type = "Array<ApprovalRule>")]
+    pub rules: Json<Vec<ApprovalRule>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetApprovalPolicy {
+    pub rules: Vec<ApprovalRule>,
+}
+
+impl ProjectApprovalPolicy {
+    pub async fn find_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectApprovalPolicy,
+            r#"SELECT
+                project_id as "project_id!: Uuid",
+                rules as "rules!: Json<Vec<ApprovalRule>>",
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_approval_policies
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &SetApprovalPolicy,
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+        let rules = Json(&data.rules);
+        sqlx::query_as!(
+            ProjectApprovalPolicy,
+            r#"INSERT INTO project_approval_policies (project_id, rules, created_at, updated_at)
+               VALUES ($1, $2, $3, $3)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   rules = excluded.rules,
+                   updated_at = excluded.updated_at
+               RETURNING
+                   project_id as "project_id!: Uuid",
+                   rules as "rules!: Json<Vec<ApprovalRule>>",
+                   updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            rules,
+            now
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_approval_policies WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,232 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Workspaces started together on the same task so their results can be compared
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct AttemptGroup {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    /// Set when a workspace is picked, before it is merged
+    pub winner_workspace_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AttemptGroup {
+    /// Create an empty group, which workspaces are added to as their attempts start
+    pub async fn create(pool: &SqlitePool, id: Uuid, task_id: Uuid) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptGroup,
+            r#"INSERT INTO attempt_groups (id, task_id)
+               VALUES ($1, $2)
+               RETURNING id as "id!: Uuid",
+                         task_id as "task_id!: Uuid",
+                         winner_workspace_id as "winner_workspace_id: Uuid",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            task_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Add a workspace after the ones already in the group
+    pub async fn add_workspace(
+        pool: &SqlitePool,
+        id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO attempt_group_workspaces (attempt_group_id, workspace_id, position)
+               SELECT $1, $2, COALESCE(MAX(position) + 1, 0)
+               FROM attempt_group_workspaces
+               WHERE attempt_group_id = $1"#,
+            id,
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptGroup,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      winner_workspace_id as "winner_workspace_id: Uuid",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM attempt_groups
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Groups started for a task, newest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptGroup,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      winner_workspace_id as "winner_workspace_id: Uuid",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM attempt_groups
+               WHERE task_id = $1
+               ORDER BY created_at DESC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Workspaces of the group in the order they were started
+    pub async fn workspace_ids(pool: &SqlitePool, id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT workspace_id as "workspace_id!: Uuid"
+               FROM attempt_group_workspaces
+               WHERE attempt_group_id = $1
+               ORDER BY position ASC"#,
+            id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record `workspace_id` as the winner unless another workspace already is. Returns false
+    /// when a different winner was picked first.
+    pub async fn set_winner(
+        pool: &SqlitePool,
+        id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE attempt_groups
+               SET winner_workspace_id = $1, updated_at = datetime('now', 'subsec')
+               WHERE id = $2 AND (winner_workspace_id IS NULL OR winner_workspace_id = $1)"#,
+            workspace_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Undo picking `workspace_id` as the winner, so another workspace can be picked
+    pub async fn clear_winner(
+        pool: &SqlitePool,
+        id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE attempt_groups
+               SET winner_workspace_id = NULL, updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND winner_workspace_id = $2"#,
+            id,
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DBService,
+        test_utils::{add_task, create_task_fixture},
+    };
+
+    async fn winner(db: &DBService, id: Uuid) -> Option<Uuid> {
+        AttemptGroup::find_by_id(&db.pool, id)
+            .await
+            .unwrap()
+            .unwrap()
+            .winner_workspace_id
+    }
+
+    #[tokio::test]
+    async fn workspaces_keep_the_order_they_are_added_in() {
+        let db = DBService::new_in_memory().await;
+        let first = create_task_fixture(&db, "Best of N").await;
+        let second = add_task(&db, first.project.clone(), "Second").await;
+        let third = add_task(&db, first.project.clone(), "Third").await;
+
+        let group = AttemptGroup::create(&db.pool, Uuid::new_v4(), first.task.id)
+            .await
+            .unwrap();
+        assert!(
+            AttemptGroup::workspace_ids(&db.pool, group.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        for fixture in [&third, &first, &second] {
+            AttemptGroup::add_workspace(&db.pool, group.id, fixture.workspace.id)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            AttemptGroup::workspace_ids(&db.pool, group.id)
+                .await
+                .unwrap(),
+            vec![third.workspace.id, first.workspace.id, second.workspace.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn only_the_first_winner_is_recorded() {
+        let db = DBService::new_in_memory().await;
+        let first = create_task_fixture(&db, "Best of N").await;
+        let second = add_task(&db, first.project.clone(), "Second").await;
+        let group = AttemptGroup::create(&db.pool, Uuid::new_v4(), first.task.id)
+            .await
+            .unwrap();
+
+        assert!(
+            AttemptGroup::set_winner(&db.pool, group.id, first.workspace.id)
+                .await
+                .unwrap()
+        );
+        // Picking the same winner again resumes an interrupted pick
+        assert!(
+            AttemptGroup::set_winner(&db.pool, group.id, first.workspace.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !AttemptGroup::set_winner(&db.pool, group.id, second.workspace.id)
+                .await
+                .unwrap()
+        );
+        assert_eq!(winner(&db, group.id).await, Some(first.workspace.id));
+
+        // Only the recorded winner can be cleared
+        AttemptGroup::clear_winner(&db.pool, group.id, second.workspace.id)
+            .await
+            .unwrap();
+        assert_eq!(winner(&db, group.id).await, Some(first.workspace.id));
+        AttemptGroup::clear_winner(&db.pool, group.id, first.workspace.id)
+            .await
+            .unwrap();
+        assert_eq!(winner(&db, group.id).await, None);
+        assert!(
+            AttemptGroup::set_winner(&db.pool, group.id, second.workspace.id)
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Token and spend limit of a project or task. `None` means unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct Budget {
+    pub max_tokens: Option<i64>,
+    pub max_cost_usd: Option<f64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetBudget {
+    pub max_tokens: Option<i64>,
+    pub max_cost_usd: Option<f64>,
+}
+
+impl Budget {
+    pub async fn find_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Budget,
+            r#"SELECT
+                max_tokens,
+                max_cost_usd,
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_budgets
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_for_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Budget,
+            r#"SELECT
+                max_tokens,
+                max_cost_usd,
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_budgets
+               WHERE task_id = $1"#,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &SetBudget,
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query_as!(
+            Budget,
+            r#"INSERT INTO project_budgets (project_id, max_tokens, max_cost_usd, created_at, updated_at)
+               VALUES ($1, $2, $3, $4, $4)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   max_tokens = excluded.max_tokens,
+                   max_cost_usd = excluded.max_cost_usd,
+                   updated_at = excluded.updated_at
+               RETURNING
+                   max_tokens,
+                   max_cost_usd,
+                   updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            data.max_tokens,
+            data.max_cost_usd,
+            now
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn set_for_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        data: &SetBudget,
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query_as!(
+            Budget,
+            r#"INSERT INTO task_budgets (task_id, max_tokens, max_cost_usd, created_at, updated_at)
+               VALUES ($1, $2, $3, $4, $4)
+               ON CONFLICT(task_id) DO UPDATE SET
+                   max_tokens = excluded.max_tokens,
+                   max_cost_usd = excluded.max_cost_usd,
+                   updated_at = excluded.updated_at
+               RETURNING
+                   max_tokens,
+                   max_cost_usd,
+                   updated_at as "updated_at!: DateTime<Utc>""#,
+            task_id,
+            data.max_tokens,
+            data.max_cost_usd,
+            now
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_budgets WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn delete_for_task(pool: &SqlitePool, task_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_budgets WHERE task_id = $1", task_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -157,6 +157,28 @@ impl CodingAgentTurn {
         Ok(())
     }
 
+    /// Final assistant message of the latest coding agent turn of a workspace that produced one
+    pub async fn find_latest_summary_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let summary = sqlx::query_scalar!(
+            r#"SELECT cat.summary
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON cat.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               WHERE s.workspace_id = $1
+                 AND ep.dropped = FALSE
+                 AND cat.summary IS NOT NULL
+               ORDER BY ep.created_at DESC
+               LIMIT 1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(summary.flatten())
+    }
+
     /// Mark all coding agent turns for a workspace as seen
     pub async fn mark_seen_by_workspace_id(
         pool: &SqlitePool,
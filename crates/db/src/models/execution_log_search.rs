@@ -0,0 +1,303 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// What part of a normalized conversation an indexed entry came from
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "log_search_entry_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LogSearchEntryKind {
+    AssistantMessage,
+    Command,
+    CommandOutput,
+    FilePath,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogSearchEntry {
+    /// Index of the normalized entry in the conversation
+    pub entry_index: i64,
+    pub kind: LogSearchEntryKind,
+    pub content: String,
+}
+
+/// An indexed entry matching a search, with the execution, session and task it belongs to
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct LogSearchHit {
+    pub execution_process_id: Uuid,
+    pub session_id: Uuid,
+    pub workspace_id: Uuid,
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub task_title: String,
+    pub entry_index: i64,
+    pub kind: LogSearchEntryKind,
+    /// HTML-escaped excerpt with matches wrapped in `<mark>` tags
+    pub snippet: String,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct LogSearchQuery {
+    /// Text to find; matched as a phrase of whole words
+    pub q: String,
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub kind: Option<LogSearchEntryKind>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Private use characters `snippet()` wraps matches in, so they cannot collide with log content
+const MATCH_START: char = '\u{E000}';
+const MATCH_END: char = '\u{E001}';
+
+/// Quote user input as a single FTS5 phrase so operators and punctuation in it are not parsed
+fn fts_phrase(text: &str) -> String {
+    format!("\"{}\"", text.trim().replace('"', "\"\""))
+}
+
+/// Escape a raw FTS5 snippet for HTML and turn its match markers into `<mark>` tags
+fn highlight_snippet(snippet: &str) -> String {
+    let mut out = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            MATCH_START => out.push_str("<mark>"),
+            MATCH_END => out.push_str("</mark>"),
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub struct ExecutionLogSearch;
+
+impl ExecutionLogSearch {
+    /// Replace the indexed entries of an execution process
+    pub async fn index_execution(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        entries: &[LogSearchEntry],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM execution_log_search WHERE execution_process_id = $1",
+            execution_process_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for entry in entries {
+            sqlx::query!(
+                r#"INSERT INTO execution_log_search (content, kind, execution_process_id, entry_index)
+                   VALUES ($1, $2, $3, $4)"#,
+                entry.content,
+                entry.kind,
+                execution_process_id,
+                entry.entry_index
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query!(
+            r#"INSERT INTO execution_log_search_state (execution_process_id)
+               VALUES ($1)
+               ON CONFLICT(execution_process_id) DO UPDATE SET indexed_at = datetime('now', 'subsec')"#,
+            execution_process_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    /// Mark an execution as indexed without entries, for logs that could not be normalized.
+    /// Startup backfill skips marked executions, so broken logs are not replayed on every boot.
+    pub async fn mark_indexed(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO execution_log_search_state (execution_process_id)
+               VALUES ($1)
+               ON CONFLICT(execution_process_id) DO NOTHING"#,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Finished coding agent executions with stored logs that have not been indexed yet
+    pub async fn find_unindexed_execution_ids(
+        pool: &SqlitePool,
+        limit: i64,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT ep.id as "id!: Uuid"
+               FROM execution_processes ep
+               LEFT JOIN execution_log_search_state s ON s.execution_process_id = ep.id
+               WHERE s.execution_process_id IS NULL
+                 AND ep.run_reason = 'codingagent'
+                 AND ep.status != 'running'
+                 AND EXISTS (SELECT 1 FROM execution_process_logs l WHERE l.execution_id = ep.id)
+               ORDER BY ep.created_at DESC
+               LIMIT $1"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Indexed entries matching the query, best matches first
+    pub async fn search(
+        pool: &SqlitePool,
+        query: &LogSearchQuery,
+    ) -> Result<Vec<LogSearchHit>, sqlx::Error> {
+        if query.q.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let phrase = fts_phrase(&query.q);
+        let limit = query.limit.unwrap_or(50).clamp(1, 500);
+        let offset = query.offset.unwrap_or(0).max(0);
+        let mut hits = sqlx::query_as!(
+            LogSearchHit,
+            r#"SELECT
+                s.execution_process_id as "execution_process_id!: Uuid",
+                ep.session_id as "session_id!: Uuid",
+                sess.workspace_id as "workspace_id!: Uuid",
+                w.task_id as "task_id!: Uuid",
+                t.project_id as "project_id!: Uuid",
+                t.title as "task_title!",
+                s.entry_index as "entry_index!: i64",
+                s.kind as "kind!: LogSearchEntryKind",
+                snippet(execution_log_search, 0, char(57344), char(57345), '…', 24) as "snippet!: String",
+                ep.started_at as "started_at!: DateTime<Utc>"
+               FROM execution_log_search s
+               JOIN execution_processes ep ON ep.id = s.execution_process_id
+               JOIN sessions sess ON sess.id = ep.session_id
+               JOIN workspaces w ON w.id = sess.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE execution_log_search MATCH $1
+                 AND ($2 IS NULL OR t.project_id = $2)
+                 AND ($3 IS NULL OR t.id = $3)
+                 AND ($4 IS NULL OR s.kind = $4)
+               ORDER BY rank
+               LIMIT $5 OFFSET $6"#,
+            phrase,
+            query.project_id,
+            query.task_id,
+            query.kind,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await?;
+        for hit in &mut hits {
+            hit.snippet = highlight_snippet(&hit.snippet);
+        }
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DBService,
+        models::{
+            execution_process::{ExecutionProcess, ExecutionProcessStatus},
+            execution_process_logs::ExecutionProcessLogs,
+        },
+        test_utils::{create_coding_agent_process, create_task_fixture},
+    };
+
+    #[tokio::test]
+    async fn snippets_escape_log_content_around_matches() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Escape snippets").await;
+        let process = create_coding_agent_process(&db, fixture.session.id).await;
+        ExecutionLogSearch::index_execution(
+            &db.pool,
+            process.id,
+            &[LogSearchEntry {
+                entry_index: 7,
+                kind: LogSearchEntryKind::CommandOutput,
+                content: "<script>alert('x')</script> panicked here".to_string(),
+            }],
+        )
+        .await
+        .unwrap();
+
+        let hits = ExecutionLogSearch::search(
+            &db.pool,
+            &LogSearchQuery {
+                q: "panicked".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry_index, 7);
+        assert_eq!(
+            hits[0].snippet,
+            "&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; <mark>panicked</mark> here"
+        );
+    }
+
+    #[tokio::test]
+    async fn backfill_skips_indexed_and_marked_executions() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Backfill").await;
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let process = create_coding_agent_process(&db, fixture.session.id).await;
+            ExecutionProcess::update_completion(
+                &db.pool,
+                process.id,
+                ExecutionProcessStatus::Completed,
+                Some(0),
+            )
+            .await
+            .unwrap();
+            ExecutionProcessLogs::append_log_line(&db.pool, process.id, "{\"Stdout\":\"hi\"}\n")
+                .await
+                .unwrap();
+            ids.push(process.id);
+        }
+        // Running executions are indexed when they finish
+        let running = create_coding_agent_process(&db, fixture.session.id).await;
+        ExecutionProcessLogs::append_log_line(&db.pool, running.id, "{\"Stdout\":\"hi\"}\n")
+            .await
+            .unwrap();
+
+        let mut unindexed = ExecutionLogSearch::find_unindexed_execution_ids(&db.pool, 10)
+            .await
+            .unwrap();
+        unindexed.sort();
+        let mut expected = ids.clone();
+        expected.sort();
+        assert_eq!(unindexed, expected);
+
+        ExecutionLogSearch::index_execution(&db.pool, ids[0], &[])
+            .await
+            .unwrap();
+        ExecutionLogSearch::mark_indexed(&db.pool, ids[1])
+            .await
+            .unwrap();
+        assert!(
+            ExecutionLogSearch::find_unindexed_execution_ids(&db.pool, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
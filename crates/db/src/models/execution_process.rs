@@ -59,6 +59,22 @@ pub enum ExecutionProcessRunReason {
     DevServer,
 }
 
+/// Why an execution process was ended by vibe-kanban rather than exiting on its own
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(
+    type_name = "execution_process_completion_reason",
+    rename_all = "snake_case"
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionProcessCompletionReason {
+    /// The executor profile's `max_runtime_secs` elapsed
+    Timeout,
+    /// The executor profile's `max_tool_calls` was reached
+    ToolCallLimit,
+    /// The task or project budget was used up
+    BudgetExceeded,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct ExecutionProcess {
     pub id: Uuid,
@@ -76,6 +92,12 @@ pub struct ExecutionProcess {
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set when the process was stopped for exceeding an execution limit or budget
+    pub completion_reason: Option<ExecutionProcessCompletionReason>,
+    /// Highest resident memory of the process group, sampled while it ran
+    pub peak_rss_bytes: Option<i64>,
+    /// Highest CPU use of the process group, where 100 is one core
+    pub peak_cpu_percent: Option<f64>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -147,7 +169,10 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.completion_reason as "completion_reason: ExecutionProcessCompletionReason",
+                    ep.peak_rss_bytes,
+                    ep.peak_cpu_percent
                FROM execution_processes ep WHERE ep.id = ?"#,
             id
         )
@@ -221,7 +246,10 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.completion_reason as "completion_reason: ExecutionProcessCompletionReason",
+                    ep.peak_rss_bytes,
+                    ep.peak_cpu_percent
                FROM execution_processes ep WHERE ep.rowid = ?"#,
             rowid
         )
@@ -248,7 +276,10 @@ impl ExecutionProcess {
                       ep.started_at      as "started_at!: DateTime<Utc>",
                       ep.completed_at    as "completed_at?: DateTime<Utc>",
                       ep.created_at      as "created_at!: DateTime<Utc>",
-                      ep.updated_at      as "updated_at!: DateTime<Utc>"
+                      ep.updated_at      as "updated_at!: DateTime<Utc>",
+                      ep.completion_reason as "completion_reason: ExecutionProcessCompletionReason",
+                      ep.peak_rss_bytes,
+                      ep.peak_cpu_percent
                FROM execution_processes ep
                WHERE ep.session_id = ?
                  AND (? OR ep.dropped = FALSE)
@@ -275,7 +306,10 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.completion_reason as "completion_reason: ExecutionProcessCompletionReason",
+                    ep.peak_rss_bytes,
+                    ep.peak_cpu_percent
                FROM execution_processes ep WHERE ep.status = 'running' ORDER BY ep.created_at ASC"#,
         )
         .fetch_all(pool)
@@ -291,7 +325,7 @@ impl ExecutionProcess {
             ExecutionProcess,
             r#"SELECT ep.id as "id!: Uuid", ep.session_id as "session_id!: Uuid", ep.run_reason as "run_reason!: ExecutionProcessRunReason", ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                       ep.status as "status!: ExecutionProcessStatus", ep.exit_code,
-                      ep.dropped as "dropped!: bool", ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>"
+                      ep.dropped as "dropped!: bool", ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>", ep.completion_reason as "completion_reason: ExecutionProcessCompletionReason", ep.peak_rss_bytes, ep.peak_cpu_percent
                FROM execution_processes ep
                JOIN sessions s ON ep.session_id = s.id
                JOIN workspaces w ON s.workspace_id = w.id
@@ -342,7 +376,10 @@ impl ExecutionProcess {
             ep.started_at as "started_at!: DateTime<Utc>",
             ep.completed_at as "completed_at?: DateTime<Utc>",
             ep.created_at as "created_at!: DateTime<Utc>",
-            ep.updated_at as "updated_at!: DateTime<Utc>"
+            ep.updated_at as "updated_at!: DateTime<Utc>",
+            ep.completion_reason as "completion_reason: ExecutionProcessCompletionReason",
+            ep.peak_rss_bytes,
+            ep.peak_cpu_percent
         FROM execution_processes ep
         JOIN sessions s ON ep.session_id = s.id
         WHERE s.workspace_id = ?
@@ -404,7 +441,10 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.completion_reason as "completion_reason: ExecutionProcessCompletionReason",
+                    ep.peak_rss_bytes,
+                    ep.peak_cpu_percent
                FROM execution_processes ep
                WHERE ep.session_id = ? AND ep.run_reason = ? AND ep.dropped = FALSE
                ORDER BY ep.created_at DESC LIMIT 1"#,
@@ -434,7 +474,10 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.completion_reason as "completion_reason: ExecutionProcessCompletionReason",
+                    ep.peak_rss_bytes,
+                    ep.peak_cpu_percent
                FROM execution_processes ep
                JOIN sessions s ON ep.session_id = s.id
                WHERE s.workspace_id = ? AND ep.run_reason = ? AND ep.dropped = FALSE
@@ -528,6 +571,46 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    pub async fn set_completion_reason(
+        pool: &SqlitePool,
+        id: Uuid,
+        reason: ExecutionProcessCompletionReason,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET completion_reason = $1
+               WHERE id = $2"#,
+            reason,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record the peak usage sampled from the process group, keeping earlier higher peaks
+    pub async fn update_peak_resource_usage(
+        pool: &SqlitePool,
+        id: Uuid,
+        peak_rss_bytes: i64,
+        peak_cpu_percent: f64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET peak_rss_bytes = MAX(COALESCE(peak_rss_bytes, 0), $1),
+                   peak_cpu_percent = MAX(COALESCE(peak_cpu_percent, 0), $2)
+               WHERE id = $3"#,
+            peak_rss_bytes,
+            peak_cpu_percent,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub fn executor_action(&self) -> Result<&ExecutorAction, anyhow::Error> {
         match &self.executor_action.0 {
             ExecutorActionField::ExecutorAction(action) => Ok(action),
@@ -661,7 +744,10 @@ impl ExecutionProcess {
                     ep.started_at as "started_at!: DateTime<Utc>",
                     ep.completed_at as "completed_at?: DateTime<Utc>",
                     ep.created_at as "created_at!: DateTime<Utc>",
-                    ep.updated_at as "updated_at!: DateTime<Utc>"
+                    ep.updated_at as "updated_at!: DateTime<Utc>",
+                    ep.completion_reason as "completion_reason: ExecutionProcessCompletionReason",
+                    ep.peak_rss_bytes,
+                    ep.peak_cpu_percent
                FROM execution_processes ep
                WHERE ep.session_id = ? AND ep.run_reason = ? AND ep.dropped = FALSE
                ORDER BY ep.created_at DESC LIMIT 1"#,
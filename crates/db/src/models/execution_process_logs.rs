@@ -5,6 +5,10 @@ use ts_rs::TS;
 use utils::log_msg::LogMsg;
 use uuid::Uuid;
 
+/// Uncompressed size of the JSONL held by one compressed chunk
+const CHUNK_BYTES: usize = 1024 * 1024;
+const ZSTD_LEVEL: i32 = 3;
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct ExecutionProcessLogs {
     pub execution_id: Uuid,
@@ -13,26 +17,96 @@ pub struct ExecutionProcessLogs {
     pub inserted_at: DateTime<Utc>,
 }
 
+/// A stored row, which holds either plain JSONL or a zstd compressed chunk of it
+struct StoredLogs {
+    execution_id: Uuid,
+    logs: String,
+    compressed: Option<Vec<u8>>,
+    line_offsets: Option<String>,
+    byte_size: i64,
+    inserted_at: DateTime<Utc>,
+}
+
+impl StoredLogs {
+    /// Plain rows as they are. Compressed chunks are decompressed and split back into one
+    /// record per line, each with the time it was logged at.
+    fn into_records(self) -> Result<Vec<ExecutionProcessLogs>, sqlx::Error> {
+        let Some(compressed) = self.compressed else {
+            return Ok(vec![ExecutionProcessLogs {
+                execution_id: self.execution_id,
+                logs: self.logs,
+                byte_size: self.byte_size,
+                inserted_at: self.inserted_at,
+            }]);
+        };
+        let bytes = zstd::decode_all(compressed.as_slice())
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let chunk = String::from_utf8(bytes).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let offsets: Vec<i64> = match &self.line_offsets {
+            Some(offsets) => {
+                serde_json::from_str(offsets).map_err(|e| sqlx::Error::Decode(Box::new(e)))?
+            }
+            None => Vec::new(),
+        };
+
+        let lines: Vec<&str> = chunk.split_inclusive('\n').collect();
+        // Chunks without an offset per line keep the time of their first line
+        if offsets.len() != lines.len() {
+            return Ok(vec![ExecutionProcessLogs {
+                execution_id: self.execution_id,
+                byte_size: chunk.len() as i64,
+                logs: chunk,
+                inserted_at: self.inserted_at,
+            }]);
+        }
+        Ok(lines
+            .into_iter()
+            .zip(offsets)
+            .map(|(line, offset)| ExecutionProcessLogs {
+                execution_id: self.execution_id,
+                logs: line.to_string(),
+                byte_size: line.len() as i64,
+                inserted_at: self.inserted_at + chrono::Duration::milliseconds(offset),
+            })
+            .collect())
+    }
+}
+
+/// Lines of uncompressed rows being packed into one compressed chunk
+struct Chunk {
+    inserted_at: DateTime<Utc>,
+    logs: String,
+    line_offsets: Vec<i64>,
+}
+
 impl ExecutionProcessLogs {
-    /// Find logs by execution process ID
+    /// Find logs by execution process ID. Compressed chunks are returned decompressed, as one
+    /// record per line.
     pub async fn find_by_execution_id(
         pool: &SqlitePool,
         execution_id: Uuid,
     ) -> Result<Vec<Self>, sqlx::Error> {
-        sqlx::query_as!(
-            ExecutionProcessLogs,
-            r#"SELECT 
+        let stored = sqlx::query_as!(
+            StoredLogs,
+            r#"SELECT
                 execution_id as "execution_id!: Uuid",
                 logs,
+                compressed,
+                line_offsets,
                 byte_size,
                 inserted_at as "inserted_at!: DateTime<Utc>"
-               FROM execution_process_logs 
+               FROM execution_process_logs
                WHERE execution_id = $1
-               ORDER BY inserted_at ASC"#,
+               ORDER BY inserted_at ASC, rowid ASC"#,
             execution_id
         )
         .fetch_all(pool)
-        .await
+        .await?;
+        let mut records = Vec::new();
+        for row in stored {
+            records.extend(row.into_records()?);
+        }
+        Ok(records)
     }
 
     /// Parse JSONL logs back into Vec<LogMsg>
@@ -66,4 +140,464 @@ impl ExecutionProcessLogs {
 
         Ok(())
     }
+
+    /// Finished executions that still have uncompressed log lines, oldest first
+    pub async fn find_uncompacted_execution_ids(
+        pool: &SqlitePool,
+        finished_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT ep.id as "id!: Uuid"
+               FROM execution_processes ep
+               WHERE ep.status != 'running'
+                 AND ep.completed_at < $1
+                 AND EXISTS (
+                     SELECT 1 FROM execution_process_logs l
+                     WHERE l.execution_id = ep.id AND l.compressed IS NULL
+                 )
+               ORDER BY ep.completed_at ASC
+               LIMIT $2"#,
+            finished_before,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Replace the uncompressed log lines of an execution with zstd compressed chunks. Each
+    /// chunk keeps the timestamp of its first line and the offset of every other line from it.
+    /// Only call this for finished executions, as lines appended meanwhile would be lost.
+    pub async fn compact(pool: &SqlitePool, execution_id: Uuid) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let rows = sqlx::query!(
+            r#"SELECT logs, inserted_at as "inserted_at!: DateTime<Utc>"
+               FROM execution_process_logs
+               WHERE execution_id = $1 AND compressed IS NULL
+               ORDER BY inserted_at ASC, rowid ASC"#,
+            execution_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let mut chunks: Vec<Chunk> = Vec::new();
+        for row in rows {
+            let mut logs = row.logs;
+            if !logs.ends_with('\n') {
+                logs.push('\n');
+            }
+            let chunk = match chunks.last_mut() {
+                Some(chunk) if chunk.logs.len() + logs.len() <= CHUNK_BYTES => chunk,
+                _ => {
+                    chunks.push(Chunk {
+                        inserted_at: row.inserted_at,
+                        logs: String::new(),
+                        line_offsets: Vec::new(),
+                    });
+                    chunks.last_mut().expect("chunk was just pushed")
+                }
+            };
+            let offset = (row.inserted_at - chunk.inserted_at).num_milliseconds();
+            chunk
+                .line_offsets
+                .extend(logs.split_inclusive('\n').map(|_| offset));
+            chunk.logs.push_str(&logs);
+        }
+
+        sqlx::query!(
+            "DELETE FROM execution_process_logs WHERE execution_id = $1 AND compressed IS NULL",
+            execution_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for chunk in chunks {
+            let compressed = zstd::encode_all(chunk.logs.as_bytes(), ZSTD_LEVEL)
+                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+            let line_offsets = serde_json::to_string(&chunk.line_offsets)
+                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+            let byte_size = chunk.logs.len() as i64;
+            sqlx::query!(
+                r#"INSERT INTO execution_process_logs (execution_id, logs, compressed, line_offsets, byte_size, inserted_at)
+                   VALUES ($1, '', $2, $3, $4, $5)"#,
+                execution_id,
+                compressed,
+                line_offsets,
+                byte_size,
+                chunk.inserted_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Delete the logs of the given executions together with their log search entries, so
+    /// search never points at logs that are gone. Returns the number of deleted log rows.
+    async fn delete_for_executions(
+        tx: &mut sqlx::SqliteConnection,
+        execution_ids: &[Uuid],
+    ) -> Result<u64, sqlx::Error> {
+        let mut deleted = 0;
+        for execution_id in execution_ids {
+            deleted += sqlx::query!(
+                "DELETE FROM execution_process_logs WHERE execution_id = $1",
+                execution_id
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            sqlx::query!(
+                "DELETE FROM execution_log_search WHERE execution_process_id = $1",
+                execution_id
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!(
+                "DELETE FROM execution_log_search_state WHERE execution_process_id = $1",
+                execution_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        Ok(deleted)
+    }
+
+    /// Delete the logs of executions that finished before `cutoff`
+    pub async fn delete_finished_before(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let ids = sqlx::query_scalar!(
+            r#"SELECT ep.id as "id!: Uuid"
+               FROM execution_processes ep
+               WHERE ep.status != 'running'
+                 AND ep.completed_at < $1
+                 AND EXISTS (SELECT 1 FROM execution_process_logs l WHERE l.execution_id = ep.id)"#,
+            cutoff
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let deleted = Self::delete_for_executions(&mut tx, &ids).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Delete the logs of all but the `keep` most recent executions of each project
+    pub async fn delete_beyond_last_per_project(
+        pool: &SqlitePool,
+        keep: i64,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let ids = sqlx::query_scalar!(
+            r#"SELECT id as "id!: Uuid" FROM (
+                   SELECT ep.id, ep.status,
+                          ROW_NUMBER() OVER (
+                              PARTITION BY t.project_id ORDER BY ep.created_at DESC
+                          ) AS position
+                   FROM execution_processes ep
+                   JOIN sessions s ON s.id = ep.session_id
+                   JOIN workspaces w ON w.id = s.workspace_id
+                   JOIN tasks t ON t.id = w.task_id
+               ) ranked
+               WHERE position > $1
+                 AND status != 'running'
+                 AND EXISTS (SELECT 1 FROM execution_process_logs l WHERE l.execution_id = ranked.id)"#,
+            keep
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let deleted = Self::delete_for_executions(&mut tx, &ids).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Delete the logs of the oldest executions of each project until the logs it stores take
+    /// at most `max_bytes`
+    pub async fn delete_beyond_bytes_per_project(
+        pool: &SqlitePool,
+        max_bytes: i64,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let ids = sqlx::query_scalar!(
+            r#"SELECT id as "id!: Uuid" FROM (
+                   SELECT ep.id, ep.status,
+                          SUM(l.stored_bytes) OVER (
+                              PARTITION BY t.project_id ORDER BY ep.created_at DESC
+                              ROWS UNBOUNDED PRECEDING
+                          ) AS project_bytes
+                   FROM execution_processes ep
+                   JOIN (
+                       SELECT execution_id,
+                              SUM(COALESCE(length(compressed), byte_size)) AS stored_bytes
+                       FROM execution_process_logs
+                       GROUP BY execution_id
+                   ) l ON l.execution_id = ep.id
+                   JOIN sessions s ON s.id = ep.session_id
+                   JOIN workspaces w ON w.id = s.workspace_id
+                   JOIN tasks t ON t.id = w.task_id
+               )
+               WHERE project_bytes > $1 AND status != 'running'"#,
+            max_bytes
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let deleted = Self::delete_for_executions(&mut tx, &ids).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{
+        DBService,
+        models::{
+            execution_log_search::{ExecutionLogSearch, LogSearchEntry, LogSearchEntryKind},
+            execution_process::{ExecutionProcess, ExecutionProcessStatus},
+        },
+        test_utils::{TaskFixture, add_task, create_coding_agent_process, create_task_fixture},
+    };
+
+    fn at(ms: i64) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(1_760_000_000_000 + ms).unwrap()
+    }
+
+    async fn insert_line(
+        db: &DBService,
+        execution_id: Uuid,
+        logs: &str,
+        inserted_at: DateTime<Utc>,
+    ) {
+        sqlx::query(
+            "INSERT INTO execution_process_logs (execution_id, logs, byte_size, inserted_at)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(execution_id)
+        .bind(logs)
+        .bind(logs.len() as i64)
+        .bind(inserted_at)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    }
+
+    /// A finished execution created at `created_at` with one indexed log line of `bytes` bytes
+    async fn finished_execution(
+        db: &DBService,
+        fixture: &TaskFixture,
+        created_at: DateTime<Utc>,
+        bytes: usize,
+    ) -> Uuid {
+        let process = create_coding_agent_process(db, fixture.session.id).await;
+        ExecutionProcess::update_completion(
+            &db.pool,
+            process.id,
+            ExecutionProcessStatus::Completed,
+            Some(0),
+        )
+        .await
+        .unwrap();
+        sqlx::query(
+            "UPDATE execution_processes SET created_at = $1, completed_at = $1 WHERE id = $2",
+        )
+        .bind(created_at)
+        .bind(process.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        let line = format!("{{\"Stdout\":\"{}\"}}\n", "x".repeat(bytes - 14));
+        insert_line(db, process.id, &line, created_at).await;
+        ExecutionLogSearch::index_execution(
+            &db.pool,
+            process.id,
+            &[LogSearchEntry {
+                entry_index: 0,
+                kind: LogSearchEntryKind::AssistantMessage,
+                content: "done".to_string(),
+            }],
+        )
+        .await
+        .unwrap();
+        process.id
+    }
+
+    /// Rows an execution has in the logs, the search index and the index state
+    async fn stored_rows(db: &DBService, execution_id: Uuid) -> (i64, i64, i64) {
+        let count =
+            |table: &str, column: &str| format!("SELECT COUNT(*) FROM {table} WHERE {column} = $1");
+        let mut counts = Vec::new();
+        for (table, column) in [
+            ("execution_process_logs", "execution_id"),
+            ("execution_log_search", "execution_process_id"),
+            ("execution_log_search_state", "execution_process_id"),
+        ] {
+            let n: i64 = sqlx::query_scalar(&count(table, column))
+                .bind(execution_id)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+            counts.push(n);
+        }
+        (counts[0], counts[1], counts[2])
+    }
+
+    fn lines_and_times(records: &[ExecutionProcessLogs]) -> Vec<(String, DateTime<Utc>)> {
+        records
+            .iter()
+            .map(|record| (record.logs.clone(), record.inserted_at))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn compact_keeps_every_line_and_when_it_was_logged() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Compact").await;
+        let process = create_coding_agent_process(&db, fixture.session.id).await;
+        insert_line(&db, process.id, "{\"Stdout\":\"one\"}\n", at(0)).await;
+        insert_line(&db, process.id, "{\"Stdout\":\"two\"}\n", at(250)).await;
+        insert_line(&db, process.id, "{\"Stderr\":\"three\"}", at(1500)).await;
+        let before = ExecutionProcessLogs::find_by_execution_id(&db.pool, process.id)
+            .await
+            .unwrap();
+
+        ExecutionProcessLogs::compact(&db.pool, process.id)
+            .await
+            .unwrap();
+
+        let stored: Vec<(String, Option<Vec<u8>>)> = sqlx::query_as(
+            "SELECT logs, compressed FROM execution_process_logs WHERE execution_id = $1",
+        )
+        .bind(process.id)
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].0, "");
+        assert!(stored[0].1.is_some());
+
+        let after = ExecutionProcessLogs::find_by_execution_id(&db.pool, process.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            lines_and_times(&after),
+            vec![
+                ("{\"Stdout\":\"one\"}\n".to_string(), at(0)),
+                ("{\"Stdout\":\"two\"}\n".to_string(), at(250)),
+                ("{\"Stderr\":\"three\"}\n".to_string(), at(1500)),
+            ]
+        );
+        let parsed = |records: &[ExecutionProcessLogs]| {
+            ExecutionProcessLogs::parse_logs(records)
+                .unwrap()
+                .into_iter()
+                .map(|msg| serde_json::to_string(&msg).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(parsed(&after), parsed(&before));
+
+        // Compacting again leaves the chunk alone
+        ExecutionProcessLogs::compact(&db.pool, process.id)
+            .await
+            .unwrap();
+        let again = ExecutionProcessLogs::find_by_execution_id(&db.pool, process.id)
+            .await
+            .unwrap();
+        assert_eq!(lines_and_times(&again), lines_and_times(&after));
+    }
+
+    #[tokio::test]
+    async fn chunks_without_line_offsets_decompress_as_one_record() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Legacy chunk").await;
+        let process = create_coding_agent_process(&db, fixture.session.id).await;
+        let chunk = "{\"Stdout\":\"one\"}\n{\"Stdout\":\"two\"}\n";
+        sqlx::query(
+            "INSERT INTO execution_process_logs (execution_id, logs, compressed, byte_size, inserted_at)
+             VALUES ($1, '', $2, $3, $4)",
+        )
+        .bind(process.id)
+        .bind(zstd::encode_all(chunk.as_bytes(), ZSTD_LEVEL).unwrap())
+        .bind(chunk.len() as i64)
+        .bind(at(0))
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let records = ExecutionProcessLogs::find_by_execution_id(&db.pool, process.id)
+            .await
+            .unwrap();
+
+        assert_eq!(lines_and_times(&records), vec![(chunk.to_string(), at(0))]);
+        assert_eq!(ExecutionProcessLogs::parse_logs(&records).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn delete_finished_before_removes_logs_and_search_entries() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Retention by age").await;
+        let old =
+            finished_execution(&db, &fixture, Utc::now() - chrono::Duration::days(40), 100).await;
+        let recent = finished_execution(&db, &fixture, Utc::now(), 100).await;
+        let running = create_coding_agent_process(&db, fixture.session.id).await;
+        insert_line(&db, running.id, "{\"Stdout\":\"still going\"}\n", at(0)).await;
+
+        let deleted = ExecutionProcessLogs::delete_finished_before(
+            &db.pool,
+            Utc::now() - chrono::Duration::days(30),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(deleted, 1);
+        assert_eq!(stored_rows(&db, old).await, (0, 0, 0));
+        assert_eq!(stored_rows(&db, recent).await, (1, 1, 1));
+        assert_eq!(stored_rows(&db, running.id).await, (1, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn delete_beyond_last_per_project_keeps_newest_executions_of_each_project() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Retention by count").await;
+        let oldest = finished_execution(&db, &fixture, at(0), 100).await;
+        let middle = finished_execution(&db, &fixture, at(1000), 100).await;
+        let newest = finished_execution(&db, &fixture, at(2000), 100).await;
+        let other_project = create_task_fixture(&db, "Other project").await;
+        let other = finished_execution(&db, &other_project, at(-1000), 100).await;
+
+        let deleted = ExecutionProcessLogs::delete_beyond_last_per_project(&db.pool, 2)
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, 1);
+        assert_eq!(stored_rows(&db, oldest).await, (0, 0, 0));
+        for kept in [middle, newest, other] {
+            assert_eq!(stored_rows(&db, kept).await, (1, 1, 1));
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_beyond_bytes_per_project_drops_oldest_executions_over_budget() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Retention by size").await;
+        let oldest = finished_execution(&db, &fixture, at(0), 100).await;
+        let same_project = add_task(&db, fixture.project.clone(), "Second task").await;
+        let middle = finished_execution(&db, &same_project, at(1000), 100).await;
+        let newest = finished_execution(&db, &fixture, at(2000), 100).await;
+
+        let deleted = ExecutionProcessLogs::delete_beyond_bytes_per_project(&db.pool, 250)
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, 1);
+        assert_eq!(stored_rows(&db, oldest).await, (0, 0, 0));
+        assert_eq!(stored_rows(&db, middle).await, (1, 1, 1));
+        assert_eq!(stored_rows(&db, newest).await, (1, 1, 1));
+    }
 }
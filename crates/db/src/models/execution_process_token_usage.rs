@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Token counts reported by the coding agent for a single execution process
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ExecutionProcessTokenUsage {
+    pub execution_process_id: Uuid,
+    pub executor: String,
+    pub model: Option<String>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub reported_cost_usd: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpsertExecutionProcessTokenUsage {
+    pub executor: String,
+    pub model: Option<String>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub reported_cost_usd: Option<f64>,
+}
+
+/// Token usage summed over a set of execution processes, grouped by executor and model
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TokenUsageTotals {
+    pub executor: String,
+    pub model: Option<String>,
+    pub execution_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    /// Sum of the costs agents reported themselves, None when none did
+    pub reported_cost_usd: Option<f64>,
+}
+
+/// Set of execution processes token usage is rolled up over
+#[derive(Debug, Clone, Copy)]
+pub enum TokenUsageScope {
+    Session(Uuid),
+    Workspace(Uuid),
+    Task(Uuid),
+    Project(Uuid),
+    All,
+}
+
+impl ExecutionProcessTokenUsage {
+    /// Insert or replace the usage recorded for an execution process.
+    /// Agents report cumulative counts, so the latest report always wins.
+    pub async fn upsert(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        data: &UpsertExecutionProcessTokenUsage,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"INSERT INTO execution_process_token_usage (
+                    execution_process_id,
+                    executor,
+                    model,
+                    input_tokens,
+                    output_tokens,
+                    cache_read_tokens,
+                    cache_write_tokens,
+                    reported_cost_usd,
+                    created_at,
+                    updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+                ON CONFLICT(execution_process_id) DO UPDATE SET
+                    executor = excluded.executor,
+                    model = excluded.model,
+                    input_tokens = excluded.input_tokens,
+                    output_tokens = excluded.output_tokens,
+                    cache_read_tokens = excluded.cache_read_tokens,
+                    cache_write_tokens = excluded.cache_write_tokens,
+                    reported_cost_usd = excluded.reported_cost_usd,
+                    updated_at = excluded.updated_at"#,
+            execution_process_id,
+            data.executor,
+            data.model,
+            data.input_tokens,
+            data.output_tokens,
+            data.cache_read_tokens,
+            data.cache_write_tokens,
+            data.reported_cost_usd,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessTokenUsage,
+            r#"SELECT
+                execution_process_id as "execution_process_id!: Uuid",
+                executor,
+                model,
+                input_tokens,
+                output_tokens,
+                cache_read_tokens,
+                cache_write_tokens,
+                reported_cost_usd,
+                created_at as "created_at!: DateTime<Utc>",
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_process_token_usage
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Usage summed over the execution processes of a scope, grouped by executor and model
+    pub async fn totals(
+        pool: &SqlitePool,
+        scope: TokenUsageScope,
+    ) -> Result<Vec<TokenUsageTotals>, sqlx::Error> {
+        let (session_id, workspace_id, task_id, project_id) = match scope {
+            TokenUsageScope::Session(id) => (Some(id), None, None, None),
+            TokenUsageScope::Workspace(id) => (None, Some(id), None, None),
+            TokenUsageScope::Task(id) => (None, None, Some(id), None),
+            TokenUsageScope::Project(id) => (None, None, None, Some(id)),
+            TokenUsageScope::All => (None, None, None, None),
+        };
+        sqlx::query_as!(
+            TokenUsageTotals,
+            r#"SELECT
+                u.executor as "executor!",
+                u.model,
+                COUNT(*) as "execution_count!: i64",
+                SUM(u.input_tokens) as "input_tokens!: i64",
+                SUM(u.output_tokens) as "output_tokens!: i64",
+                SUM(u.cache_read_tokens) as "cache_read_tokens!: i64",
+                SUM(u.cache_write_tokens) as "cache_write_tokens!: i64",
+                SUM(u.reported_cost_usd) as "reported_cost_usd: f64"
+               FROM execution_process_token_usage u
+               JOIN execution_processes ep ON ep.id = u.execution_process_id
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE ($1 IS NULL OR ep.session_id = $1)
+                 AND ($2 IS NULL OR s.workspace_id = $2)
+                 AND ($3 IS NULL OR w.task_id = $3)
+                 AND ($4 IS NULL OR t.project_id = $4)
+               GROUP BY u.executor, u.model
+               ORDER BY u.executor, u.model"#,
+            session_id,
+            workspace_id,
+            task_id,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -196,6 +196,47 @@ impl Merge {
         Ok(rows.into_iter().map(Into::into).collect())
     }
 
+    /// Merged PRs whose workspace still has unarchived subtask workspaces targeting its
+    /// branch in the same repo, i.e. stacked workspaces that were not restacked yet.
+    pub async fn get_merged_prs_with_stacked_children(
+        pool: &SqlitePool,
+    ) -> Result<Vec<PrMerge>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            MergeRow,
+            r#"SELECT
+                m.id as "id!: Uuid",
+                m.workspace_id as "workspace_id!: Uuid",
+                m.repo_id as "repo_id!: Uuid",
+                m.merge_type as "merge_type!: MergeType",
+                m.merge_commit,
+                m.pr_number,
+                m.pr_url,
+                m.pr_status as "pr_status?: MergeStatus",
+                m.pr_merged_at as "pr_merged_at?: DateTime<Utc>",
+                m.pr_merge_commit_sha,
+                m.created_at as "created_at!: DateTime<Utc>",
+                m.target_branch_name as "target_branch_name!: String"
+               FROM merges m
+               JOIN workspaces pw ON pw.id = m.workspace_id
+               WHERE m.merge_type = 'pr' AND m.pr_status = 'merged'
+                 AND EXISTS (
+                     SELECT 1
+                     FROM workspaces w
+                     JOIN tasks t ON w.task_id = t.id
+                     JOIN workspace_repos wr ON wr.workspace_id = w.id
+                     WHERE t.parent_workspace_id = m.workspace_id
+                       AND wr.repo_id = m.repo_id
+                       AND wr.target_branch = pw.branch
+                       AND w.archived = 0
+                 )
+               ORDER BY m.pr_merged_at ASC"#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
     /// Update PR status for a workspace
     pub async fn update_status(
         pool: &SqlitePool,
@@ -225,6 +266,23 @@ impl Merge {
 
         Ok(())
     }
+
+    /// Record that a PR now targets `target_branch_name`
+    pub async fn update_target_branch(
+        pool: &SqlitePool,
+        merge_id: Uuid,
+        target_branch_name: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE merges SET target_branch_name = $1 WHERE id = $2",
+            target_branch_name,
+            merge_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
     /// Find all merges for a workspace (returns both direct and PR merges)
     pub async fn find_by_workspace_id(
         pool: &SqlitePool,
@@ -1,11 +1,19 @@
+pub mod approval;
+pub mod approval_policy;
+pub mod attempt_group;
+pub mod budget;
 pub mod coding_agent_turn;
+pub mod execution_log_search;
 pub mod execution_process;
 pub mod execution_process_logs;
 pub mod execution_process_repo_state;
+pub mod execution_process_token_usage;
 pub mod image;
 pub mod merge;
+pub mod pipeline;
 pub mod project;
 pub mod project_repo;
+pub mod remembered_approval;
 pub mod repo;
 pub mod scratch;
 pub mod session;
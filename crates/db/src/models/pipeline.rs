@@ -0,0 +1,412 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// One step of a pipeline. Agent and review stages without a profile use the profile the
+/// attempt was started with.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PipelineStage {
+    /// A coding agent turn in a new session. `prompt` may use the `{task}` and `{plan}`
+    /// placeholders; by default the task is sent together with the plan, if there is one.
+    Agent {
+        executor_profile_id: Option<ExecutorProfileId>,
+        prompt: Option<String>,
+    },
+    /// Wait for the user to approve, and optionally edit, the plan of the previous agent stage
+    PlanApproval,
+    /// A review of the workspace changes against the plan
+    Review {
+        executor_profile_id: Option<ExecutorProfileId>,
+        additional_prompt: Option<String>,
+    },
+    /// A script run in the agent working directory, or the cleanup scripts of the workspace
+    /// repos when `script` is not set
+    Script { script: Option<String> },
+}
+
+/// Check that a pipeline starts with an agent and that every plan approval follows one
+pub fn validate_pipeline_stages(stages: &[PipelineStage]) -> Result<(), String> {
+    if !matches!(stages.first(), Some(PipelineStage::Agent { .. })) {
+        return Err("A pipeline must start with an agent stage".to_string());
+    }
+    for pair in stages.windows(2) {
+        if pair[1] == PipelineStage::PlanApproval && !matches!(pair[0], PipelineStage::Agent { .. })
+        {
+            return Err("A plan approval stage must follow an agent stage".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Pipeline that new task attempts of a project run through
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProjectPipeline {
+    pub project_id: Uuid,
+    #[ts(type = "Array<PipelineStage>")]
+    pub stages: Json<Vec<PipelineStage>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetProjectPipeline {
+    pub stages: Vec<PipelineStage>,
+}
+
+impl ProjectPipeline {
+    pub async fn find_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectPipeline,
+            r#"SELECT
+                project_id as "project_id!: Uuid",
+                stages as "stages!: Json<Vec<PipelineStage>>",
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_pipelines
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &SetProjectPipeline,
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+        let stages = Json(&data.stages);
+        sqlx::query_as!(
+            ProjectPipeline,
+            r#"INSERT INTO project_pipelines (project_id, stages, created_at, updated_at)
+               VALUES ($1, $2, $3, $3)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   stages = excluded.stages,
+                   updated_at = excluded.updated_at
+               RETURNING
+                   project_id as "project_id!: Uuid",
+                   stages as "stages!: Json<Vec<PipelineStage>>",
+                   updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            stages,
+            now
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_pipelines WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "pipeline_run_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineRunStatus {
+    Running,
+    AwaitingApproval,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Progress of a workspace through the pipeline of its project
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PipelineRun {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// Session the current stage runs in
+    pub session_id: Uuid,
+    /// The project pipeline as it was when the run started
+    #[ts(type = "Array<PipelineStage>")]
+    pub stages: Json<Vec<PipelineStage>>,
+    /// Profile the attempt was started with, used by stages that do not name one
+    #[ts(type = "ExecutorProfileId")]
+    pub default_executor_profile_id: Json<ExecutorProfileId>,
+    pub current_stage: i64,
+    pub status: PipelineRunStatus,
+    /// Plan produced by the first agent stage, passed on to the later stages
+    pub plan: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PipelineRun {
+    pub fn current(&self) -> Option<&PipelineStage> {
+        usize::try_from(self.current_stage)
+            .ok()
+            .and_then(|index| self.stages.0.get(index))
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        session_id: Uuid,
+        stages: &[PipelineStage],
+        default_executor_profile_id: &ExecutorProfileId,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let stages = Json(stages);
+        let default_executor_profile_id = Json(default_executor_profile_id);
+        sqlx::query_as!(
+            PipelineRun,
+            r#"INSERT INTO pipeline_runs (id, workspace_id, session_id, stages, default_executor_profile_id)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING
+                   id as "id!: Uuid",
+                   workspace_id as "workspace_id!: Uuid",
+                   session_id as "session_id!: Uuid",
+                   stages as "stages!: Json<Vec<PipelineStage>>",
+                   default_executor_profile_id as "default_executor_profile_id!: Json<ExecutorProfileId>",
+                   current_stage as "current_stage!: i64",
+                   status as "status!: PipelineRunStatus",
+                   plan,
+                   created_at as "created_at!: DateTime<Utc>",
+                   updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            session_id,
+            stages,
+            default_executor_profile_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The most recent run of a workspace
+    pub async fn find_latest_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PipelineRun,
+            r#"SELECT
+                id as "id!: Uuid",
+                workspace_id as "workspace_id!: Uuid",
+                session_id as "session_id!: Uuid",
+                stages as "stages!: Json<Vec<PipelineStage>>",
+                default_executor_profile_id as "default_executor_profile_id!: Json<ExecutorProfileId>",
+                current_stage as "current_stage!: i64",
+                status as "status!: PipelineRunStatus",
+                plan,
+                created_at as "created_at!: DateTime<Utc>",
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM pipeline_runs
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC
+               LIMIT 1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Move the run to `current_stage`, which runs in `session_id`
+    pub async fn update_progress(
+        pool: &SqlitePool,
+        id: Uuid,
+        current_stage: i64,
+        session_id: Uuid,
+        status: PipelineRunStatus,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE pipeline_runs
+               SET current_stage = $1, session_id = $2, status = $3,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $4"#,
+            current_stage,
+            session_id,
+            status,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_status(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: PipelineRunStatus,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE pipeline_runs
+               SET status = $1, updated_at = datetime('now', 'subsec')
+               WHERE id = $2"#,
+            status,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Move the run from `from` to `to` unless its status changed in the meantime. Returns
+    /// whether the run was moved.
+    pub async fn transition_status(
+        pool: &SqlitePool,
+        id: Uuid,
+        from: PipelineRunStatus,
+        to: PipelineRunStatus,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE pipeline_runs
+               SET status = $1, updated_at = datetime('now', 'subsec')
+               WHERE id = $2 AND status = $3"#,
+            to,
+            id,
+            from
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn set_plan(pool: &SqlitePool, id: Uuid, plan: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE pipeline_runs
+               SET plan = $1, updated_at = datetime('now', 'subsec')
+               WHERE id = $2"#,
+            plan,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::executors::BaseCodingAgent;
+
+    use super::*;
+    use crate::{DBService, test_utils::create_task_fixture};
+
+    fn agent() -> PipelineStage {
+        PipelineStage::Agent {
+            executor_profile_id: None,
+            prompt: None,
+        }
+    }
+
+    #[test]
+    fn pipelines_start_with_an_agent_and_approve_agent_plans() {
+        let review = PipelineStage::Review {
+            executor_profile_id: None,
+            additional_prompt: None,
+        };
+        let script = PipelineStage::Script { script: None };
+
+        assert!(
+            validate_pipeline_stages(&[
+                agent(),
+                PipelineStage::PlanApproval,
+                agent(),
+                review.clone(),
+                script.clone(),
+            ])
+            .is_ok()
+        );
+        assert!(validate_pipeline_stages(&[agent()]).is_ok());
+        assert!(validate_pipeline_stages(&[]).is_err());
+        assert!(validate_pipeline_stages(&[script.clone(), agent()]).is_err());
+        assert!(validate_pipeline_stages(&[PipelineStage::PlanApproval]).is_err());
+        assert!(validate_pipeline_stages(&[agent(), review, PipelineStage::PlanApproval]).is_err());
+        assert!(
+            validate_pipeline_stages(&[
+                agent(),
+                PipelineStage::PlanApproval,
+                PipelineStage::PlanApproval
+            ])
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn runs_move_through_approval_once() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Pipeline").await;
+        let stages = vec![agent(), PipelineStage::PlanApproval, agent()];
+        let run = PipelineRun::create(
+            &db.pool,
+            fixture.workspace.id,
+            fixture.session.id,
+            &stages,
+            &ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+        )
+        .await
+        .unwrap();
+        assert_eq!(run.status, PipelineRunStatus::Running);
+        assert_eq!(run.current(), Some(&stages[0]));
+
+        PipelineRun::update_progress(
+            &db.pool,
+            run.id,
+            1,
+            fixture.session.id,
+            PipelineRunStatus::AwaitingApproval,
+        )
+        .await
+        .unwrap();
+        let run = PipelineRun::find_latest_by_workspace_id(&db.pool, fixture.workspace.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.current(), Some(&PipelineStage::PlanApproval));
+
+        // Approving twice, or rejecting after approving, only moves the run once
+        let approve = || {
+            PipelineRun::transition_status(
+                &db.pool,
+                run.id,
+                PipelineRunStatus::AwaitingApproval,
+                PipelineRunStatus::Running,
+            )
+        };
+        assert!(approve().await.unwrap());
+        assert!(!approve().await.unwrap());
+        assert!(
+            !PipelineRun::transition_status(
+                &db.pool,
+                run.id,
+                PipelineRunStatus::AwaitingApproval,
+                PipelineRunStatus::Cancelled,
+            )
+            .await
+            .unwrap()
+        );
+
+        PipelineRun::update_progress(
+            &db.pool,
+            run.id,
+            stages.len() as i64,
+            fixture.session.id,
+            PipelineRunStatus::Completed,
+        )
+        .await
+        .unwrap();
+        let run = PipelineRun::find_latest_by_workspace_id(&db.pool, fixture.workspace.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.status, PipelineRunStatus::Completed);
+        assert_eq!(run.current(), None);
+    }
+}
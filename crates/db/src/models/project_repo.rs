@@ -7,7 +7,7 @@ use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::repo::Repo;
+use super::repo::{AutoRebasePolicy, ProviderKind, Repo};
 
 #[derive(Debug, Error)]
 pub enum ProjectRepoError {
@@ -83,6 +83,10 @@ impl ProjectRepo {
                       r.parallel_setup_script as "parallel_setup_script!: bool",
                       r.dev_server_script,
                       r.default_target_branch,
+                      r.container_image,
+                      r.ssh_host,
+                      r.git_host_provider as "git_host_provider: ProviderKind",
+                      r.auto_rebase as "auto_rebase!: AutoRebasePolicy",
                       r.created_at as "created_at!: DateTime<Utc>",
                       r.updated_at as "updated_at!: DateTime<Utc>"
                FROM repos r
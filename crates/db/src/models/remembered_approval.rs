@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool, types::Json};
+use utils::approvals::{ApprovalMatcher, ApprovalScope, ApprovalStatus};
+use uuid::Uuid;
+
+/// A decision the user asked to apply to later tool calls of a session or workspace
+#[derive(Debug, Clone, FromRow)]
+pub struct RememberedApproval {
+    pub id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub workspace_id: Option<Uuid>,
+    pub tool_name: String,
+    pub tool_input: Json<Value>,
+    pub matcher: Json<ApprovalMatcher>,
+    pub status: Json<ApprovalStatus>,
+    /// The user who made the decision
+    pub responder: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateRememberedApproval {
+    pub scope: ApprovalScope,
+    pub session_id: Uuid,
+    pub workspace_id: Uuid,
+    pub tool_name: String,
+    pub tool_input: Value,
+    pub matcher: ApprovalMatcher,
+    pub status: ApprovalStatus,
+    pub responder: String,
+}
+
+impl RememberedApproval {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateRememberedApproval,
+        id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let (session_id, workspace_id) = match data.scope {
+            ApprovalScope::Session => (Some(data.session_id), None),
+            ApprovalScope::Workspace => (None, Some(data.workspace_id)),
+        };
+        let tool_input = Json(&data.tool_input);
+        let matcher = Json(&data.matcher);
+        let status = Json(&data.status);
+        let now = Utc::now();
+        sqlx::query_as!(
+            RememberedApproval,
+            r#"INSERT INTO remembered_approvals
+                   (id, session_id, workspace_id, tool_name, tool_input, matcher, status, responder, created_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING
+                   id as "id!: Uuid",
+                   session_id as "session_id: Uuid",
+                   workspace_id as "workspace_id: Uuid",
+                   tool_name,
+                   tool_input as "tool_input!: Json<Value>",
+                   matcher as "matcher!: Json<ApprovalMatcher>",
+                   status as "status!: Json<ApprovalStatus>",
+                   responder,
+                   created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            session_id,
+            workspace_id,
+            data.tool_name,
+            tool_input,
+            matcher,
+            status,
+            data.responder,
+            now
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Decisions that apply to a session, those remembered for the session itself first and
+    /// newest first within each scope
+    pub async fn find_for_session(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RememberedApproval,
+            r#"SELECT
+                id as "id!: Uuid",
+                session_id as "session_id: Uuid",
+                workspace_id as "workspace_id: Uuid",
+                tool_name,
+                tool_input as "tool_input!: Json<Value>",
+                matcher as "matcher!: Json<ApprovalMatcher>",
+                status as "status!: Json<ApprovalStatus>",
+                responder,
+                created_at as "created_at!: DateTime<Utc>"
+               FROM remembered_approvals
+               WHERE session_id = $1 OR workspace_id = $2
+               ORDER BY session_id IS NULL, created_at DESC"#,
+            session_id,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DBService,
+        models::task::Task,
+        test_utils::{TaskFixture, add_task, create_task_fixture},
+    };
+
+    fn remember(
+        fixture: &TaskFixture,
+        scope: ApprovalScope,
+        tool_name: &str,
+    ) -> CreateRememberedApproval {
+        CreateRememberedApproval {
+            scope,
+            session_id: fixture.session.id,
+            workspace_id: fixture.workspace.id,
+            tool_name: tool_name.to_string(),
+            tool_input: serde_json::json!({}),
+            matcher: ApprovalMatcher::Tool,
+            status: ApprovalStatus::Approved,
+            responder: "alice".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn decisions_are_scoped_and_removed_with_their_workspace() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Remember").await;
+        let sibling = add_task(&db, fixture.project.clone(), "Sibling").await;
+
+        let workspace_wide = RememberedApproval::create(
+            &db.pool,
+            &remember(&fixture, ApprovalScope::Workspace, "Edit"),
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let session_only = RememberedApproval::create(
+            &db.pool,
+            &remember(&fixture, ApprovalScope::Session, "Bash"),
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(session_only.session_id, Some(fixture.session.id));
+        assert_eq!(session_only.workspace_id, None);
+        assert_eq!(workspace_wide.workspace_id, Some(fixture.workspace.id));
+
+        let ids = |decisions: Vec<RememberedApproval>| {
+            decisions.into_iter().map(|d| d.id).collect::<Vec<_>>()
+        };
+        // The session decision is checked before the older workspace one
+        assert_eq!(
+            ids(RememberedApproval::find_for_session(
+                &db.pool,
+                fixture.session.id,
+                fixture.workspace.id
+            )
+            .await
+            .unwrap()),
+            vec![session_only.id, workspace_wide.id]
+        );
+        // Another session of the same workspace only sees the workspace decision
+        assert_eq!(
+            ids(RememberedApproval::find_for_session(
+                &db.pool,
+                Uuid::new_v4(),
+                fixture.workspace.id
+            )
+            .await
+            .unwrap()),
+            vec![workspace_wide.id]
+        );
+        assert!(
+            RememberedApproval::find_for_session(
+                &db.pool,
+                sibling.session.id,
+                sibling.workspace.id
+            )
+            .await
+            .unwrap()
+            .is_empty()
+        );
+
+        // Deleting the task deletes its workspace, sessions and their decisions
+        Task::delete(&db.pool, fixture.task.id).await.unwrap();
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM remembered_approvals")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
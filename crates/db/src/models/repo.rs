@@ -3,7 +3,7 @@ use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::rust::double_option;
-use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -16,6 +16,45 @@ pub enum RepoError {
     NotFound,
 }
 
+/// Git hosting service that pull requests for a repo are opened on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    GitHub,
+    AzureDevOps,
+    GitLab,
+    Gitea,
+    Bitbucket,
+    Unknown,
+}
+
+impl std::fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProviderKind::GitHub => write!(f, "GitHub"),
+            ProviderKind::AzureDevOps => write!(f, "Azure DevOps"),
+            ProviderKind::GitLab => write!(f, "GitLab"),
+            ProviderKind::Gitea => write!(f, "Gitea"),
+            ProviderKind::Bitbucket => write!(f, "Bitbucket"),
+            ProviderKind::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// When workspaces of a repo are rebased onto their target branch after it moves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AutoRebasePolicy {
+    #[default]
+    Never,
+    /// Only while no coding agent or script is running in the workspace
+    IdleOnly,
+    /// Also while a coding agent is running, as long as the worktree has no uncommitted changes
+    Always,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct Repo {
     pub id: Uuid,
@@ -28,6 +67,14 @@ pub struct Repo {
     pub parallel_setup_script: bool,
     pub dev_server_script: Option<String>,
     pub default_target_branch: Option<String>,
+    /// OCI image that executions of workspaces with this repo run in, instead of the host
+    pub container_image: Option<String>,
+    /// SSH host, as accepted by `ssh`, that executions of workspaces with this repo run on
+    pub ssh_host: Option<String>,
+    /// Git hosting provider for pull requests, overriding detection from the remote URL
+    pub git_host_provider: Option<ProviderKind>,
+    /// When workspaces with this repo are rebased automatically after their target branch moves
+    pub auto_rebase: AutoRebasePolicy,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
@@ -92,6 +139,38 @@ pub struct UpdateRepo {
     )]
     #[ts(optional, type = "string | null")]
     pub default_target_branch: Option<Option<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "string | null")]
+    pub container_image: Option<Option<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "string | null")]
+    pub ssh_host: Option<Option<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "ProviderKind | null")]
+    pub git_host_provider: Option<Option<ProviderKind>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "AutoRebasePolicy | null")]
+    pub auto_rebase: Option<Option<AutoRebasePolicy>>,
 }
 
 impl Repo {
@@ -110,6 +189,10 @@ impl Repo {
                       parallel_setup_script as "parallel_setup_script!: bool",
                       dev_server_script,
                       default_target_branch,
+                      container_image,
+                      ssh_host,
+                      git_host_provider as "git_host_provider: ProviderKind",
+                      auto_rebase as "auto_rebase!: AutoRebasePolicy",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM repos
@@ -149,6 +232,10 @@ impl Repo {
                       parallel_setup_script as "parallel_setup_script!: bool",
                       dev_server_script,
                       default_target_branch,
+                      container_image,
+                      ssh_host,
+                      git_host_provider as "git_host_provider: ProviderKind",
+                      auto_rebase as "auto_rebase!: AutoRebasePolicy",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM repos
@@ -205,6 +292,10 @@ impl Repo {
                          parallel_setup_script as "parallel_setup_script!: bool",
                          dev_server_script,
                          default_target_branch,
+                         container_image,
+                         ssh_host,
+                         git_host_provider as "git_host_provider: ProviderKind",
+                         auto_rebase as "auto_rebase!: AutoRebasePolicy",
                          created_at as "created_at!: DateTime<Utc>",
                          updated_at as "updated_at!: DateTime<Utc>""#,
             id,
@@ -240,6 +331,10 @@ impl Repo {
                       parallel_setup_script as "parallel_setup_script!: bool",
                       dev_server_script,
                       default_target_branch,
+                      container_image,
+                      ssh_host,
+                      git_host_provider as "git_host_provider: ProviderKind",
+                      auto_rebase as "auto_rebase!: AutoRebasePolicy",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM repos
@@ -289,6 +384,22 @@ impl Repo {
             None => existing.default_target_branch,
             Some(v) => v.clone(),
         };
+        let container_image = match &payload.container_image {
+            None => existing.container_image,
+            Some(v) => v.clone().filter(|image| !image.trim().is_empty()),
+        };
+        let ssh_host = match &payload.ssh_host {
+            None => existing.ssh_host,
+            Some(v) => v.clone().filter(|host| !host.trim().is_empty()),
+        };
+        let git_host_provider = match &payload.git_host_provider {
+            None => existing.git_host_provider,
+            Some(v) => v.filter(|provider| *provider != ProviderKind::Unknown),
+        };
+        let auto_rebase = match &payload.auto_rebase {
+            None => existing.auto_rebase,
+            Some(v) => v.unwrap_or_default(),
+        };
 
         sqlx::query_as!(
             Repo,
@@ -300,8 +411,12 @@ impl Repo {
                    parallel_setup_script = $5,
                    dev_server_script = $6,
                    default_target_branch = $7,
+                   container_image = $8,
+                   ssh_host = $9,
+                   git_host_provider = $10,
+                   auto_rebase = $11,
                    updated_at = datetime('now', 'subsec')
-               WHERE id = $8
+               WHERE id = $12
                RETURNING id as "id!: Uuid",
                          path,
                          name,
@@ -312,6 +427,10 @@ impl Repo {
                          parallel_setup_script as "parallel_setup_script!: bool",
                          dev_server_script,
                          default_target_branch,
+                         container_image,
+                         ssh_host,
+                         git_host_provider as "git_host_provider: ProviderKind",
+                         auto_rebase as "auto_rebase!: AutoRebasePolicy",
                          created_at as "created_at!: DateTime<Utc>",
                          updated_at as "updated_at!: DateTime<Utc>""#,
             display_name,
@@ -321,6 +440,10 @@ impl Repo {
             parallel_setup_script,
             dev_server_script,
             default_target_branch,
+            container_image,
+            ssh_host,
+            git_host_provider,
+            auto_rebase,
             id
         )
         .fetch_one(pool)
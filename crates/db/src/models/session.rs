@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
 use thiserror::Error;
@@ -17,11 +18,37 @@ pub enum SessionError {
     ExecutorMismatch { expected: String, actual: String },
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutorFallbackReason {
+    AuthRequired,
+    ExecutableNotFound,
+    RateLimited,
+}
+
+/// A switch to another executor profile after a coding agent execution failed
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ExecutorFallback {
+    pub from: ExecutorProfileId,
+    pub to: ExecutorProfileId,
+    pub reason: ExecutorFallbackReason,
+    /// The execution process that failed
+    pub execution_process_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct Session {
     pub id: Uuid,
     pub workspace_id: Uuid,
     pub executor: Option<String>,
+    /// Fallbacks taken by this session, oldest first
+    #[ts(type = "Array<ExecutorFallback>")]
+    pub executor_fallbacks: sqlx::types::Json<Vec<ExecutorFallback>>,
+    /// The session this one was forked from
+    pub forked_from_session_id: Option<Uuid>,
+    /// The coding agent turn of the original session this one continues from
+    pub forked_from_execution_process_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -38,6 +65,9 @@ impl Session {
             r#"SELECT id AS "id!: Uuid",
                       workspace_id AS "workspace_id!: Uuid",
                       executor,
+                      executor_fallbacks AS "executor_fallbacks!: sqlx::types::Json<Vec<ExecutorFallback>>",
+                      forked_from_session_id AS "forked_from_session_id: Uuid",
+                      forked_from_execution_process_id AS "forked_from_execution_process_id: Uuid",
                       created_at AS "created_at!: DateTime<Utc>",
                       updated_at AS "updated_at!: DateTime<Utc>"
                FROM sessions
@@ -60,6 +90,9 @@ impl Session {
             r#"SELECT s.id AS "id!: Uuid",
                       s.workspace_id AS "workspace_id!: Uuid",
                       s.executor,
+                      s.executor_fallbacks AS "executor_fallbacks!: sqlx::types::Json<Vec<ExecutorFallback>>",
+                      s.forked_from_session_id AS "forked_from_session_id: Uuid",
+                      s.forked_from_execution_process_id AS "forked_from_execution_process_id: Uuid",
                       s.created_at AS "created_at!: DateTime<Utc>",
                       s.updated_at AS "updated_at!: DateTime<Utc>"
                FROM sessions s
@@ -89,6 +122,9 @@ impl Session {
             r#"SELECT s.id AS "id!: Uuid",
                       s.workspace_id AS "workspace_id!: Uuid",
                       s.executor,
+                      s.executor_fallbacks AS "executor_fallbacks!: sqlx::types::Json<Vec<ExecutorFallback>>",
+                      s.forked_from_session_id AS "forked_from_session_id: Uuid",
+                      s.forked_from_execution_process_id AS "forked_from_execution_process_id: Uuid",
                       s.created_at AS "created_at!: DateTime<Utc>",
                       s.updated_at AS "updated_at!: DateTime<Utc>"
                FROM sessions s
@@ -120,6 +156,9 @@ impl Session {
                RETURNING id AS "id!: Uuid",
                          workspace_id AS "workspace_id!: Uuid",
                          executor,
+                         executor_fallbacks AS "executor_fallbacks!: sqlx::types::Json<Vec<ExecutorFallback>>",
+                         forked_from_session_id AS "forked_from_session_id: Uuid",
+                         forked_from_execution_process_id AS "forked_from_execution_process_id: Uuid",
                          created_at AS "created_at!: DateTime<Utc>",
                          updated_at AS "updated_at!: DateTime<Utc>""#,
             id,
@@ -130,6 +169,37 @@ impl Session {
         .await?)
     }
 
+    /// Create a session that continues the conversation of `forked_from` after the coding agent
+    /// turn `forked_from_execution_process_id`
+    pub async fn create_fork(
+        pool: &SqlitePool,
+        forked_from: &Session,
+        forked_from_execution_process_id: Uuid,
+        id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<Self, SessionError> {
+        Ok(sqlx::query_as!(
+            Session,
+            r#"INSERT INTO sessions (id, workspace_id, executor, forked_from_session_id, forked_from_execution_process_id)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id AS "id!: Uuid",
+                         workspace_id AS "workspace_id!: Uuid",
+                         executor,
+                         executor_fallbacks AS "executor_fallbacks!: sqlx::types::Json<Vec<ExecutorFallback>>",
+                         forked_from_session_id AS "forked_from_session_id: Uuid",
+                         forked_from_execution_process_id AS "forked_from_execution_process_id: Uuid",
+                         created_at AS "created_at!: DateTime<Utc>",
+                         updated_at AS "updated_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            forked_from.executor,
+            forked_from.id,
+            forked_from_execution_process_id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
     pub async fn update_executor(
         pool: &SqlitePool,
         id: Uuid,
@@ -144,4 +214,27 @@ impl Session {
         .await?;
         Ok(())
     }
+
+    /// Switch the session to a fallback executor and append the switch to its history
+    pub async fn record_executor_fallback(
+        pool: &SqlitePool,
+        id: Uuid,
+        fallback: &ExecutorFallback,
+    ) -> Result<(), sqlx::Error> {
+        let executor = fallback.to.executor.to_string();
+        let fallback_json = sqlx::types::Json(fallback);
+        sqlx::query!(
+            r#"UPDATE sessions
+               SET executor = $1,
+                   executor_fallbacks = json_insert(executor_fallbacks, '$[#]', json($2)),
+                   updated_at = CURRENT_TIMESTAMP
+               WHERE id = $3"#,
+            executor,
+            fallback_json,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
 }
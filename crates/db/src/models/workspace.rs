@@ -298,6 +298,43 @@ impl Workspace {
         .await
     }
 
+    /// Unarchived workspaces of the subtasks of `parent_workspace_id` that target its
+    /// `parent_branch` in `repo_id`, i.e. the workspaces stacked on top of it. Oldest first.
+    pub async fn find_stacked_children(
+        pool: &SqlitePool,
+        parent_workspace_id: Uuid,
+        repo_id: Uuid,
+        parent_branch: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Workspace,
+            r#"SELECT  w.id                AS "id!: Uuid",
+                       w.task_id           AS "task_id!: Uuid",
+                       w.container_ref,
+                       w.branch,
+                       w.agent_working_dir,
+                       w.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       w.created_at        AS "created_at!: DateTime<Utc>",
+                       w.updated_at        AS "updated_at!: DateTime<Utc>",
+                       w.archived          AS "archived!: bool",
+                       w.pinned            AS "pinned!: bool",
+                       w.name
+               FROM    workspaces w
+               JOIN    tasks t ON w.task_id = t.id
+               JOIN    workspace_repos wr ON wr.workspace_id = w.id
+               WHERE   t.parent_workspace_id = $1
+                 AND   wr.repo_id = $2
+                 AND   wr.target_branch = $3
+                 AND   w.archived = 0
+               ORDER BY w.created_at ASC"#,
+            parent_workspace_id,
+            repo_id,
+            parent_branch
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn container_ref_exists(
         pool: &SqlitePool,
         container_ref: &str,
@@ -6,7 +6,7 @@ use sqlx::{FromRow, SqlitePool};
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::repo::Repo;
+use super::repo::{AutoRebasePolicy, ProviderKind, Repo};
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct WorkspaceRepo {
@@ -14,6 +14,9 @@ pub struct WorkspaceRepo {
     pub workspace_id: Uuid,
     pub repo_id: Uuid,
     pub target_branch: String,
+    /// Target branch commit an automatic rebase hit conflicts with, cleared by the next
+    /// successful rebase
+    pub auto_rebase_conflict_oid: Option<String>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
@@ -34,6 +37,18 @@ pub struct RepoWithTargetBranch {
     pub target_branch: String,
 }
 
+/// Repo of an active workspace whose repo is rebased automatically.
+#[derive(Debug, Clone)]
+pub struct AutoRebaseCandidate {
+    pub workspace_id: Uuid,
+    pub container_ref: String,
+    pub branch: String,
+    pub repo_id: Uuid,
+    pub target_branch: String,
+    pub auto_rebase: AutoRebasePolicy,
+    pub auto_rebase_conflict_oid: Option<String>,
+}
+
 /// Repo info with copy_files configuration.
 #[derive(Debug, Clone)]
 pub struct RepoWithCopyFiles {
@@ -69,6 +84,7 @@ impl WorkspaceRepo {
                              workspace_id as "workspace_id!: Uuid",
                              repo_id as "repo_id!: Uuid",
                              target_branch,
+                             auto_rebase_conflict_oid,
                              created_at as "created_at!: DateTime<Utc>",
                              updated_at as "updated_at!: DateTime<Utc>""#,
                 id,
@@ -95,6 +111,7 @@ impl WorkspaceRepo {
                       workspace_id as "workspace_id!: Uuid",
                       repo_id as "repo_id!: Uuid",
                       target_branch,
+                      auto_rebase_conflict_oid,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM workspace_repos
@@ -121,6 +138,10 @@ impl WorkspaceRepo {
                       r.parallel_setup_script as "parallel_setup_script!: bool",
                       r.dev_server_script,
                       r.default_target_branch,
+                      r.container_image,
+                      r.ssh_host,
+                      r.git_host_provider as "git_host_provider: ProviderKind",
+                      r.auto_rebase as "auto_rebase!: AutoRebasePolicy",
                       r.created_at as "created_at!: DateTime<Utc>",
                       r.updated_at as "updated_at!: DateTime<Utc>"
                FROM repos r
@@ -148,6 +169,10 @@ impl WorkspaceRepo {
                       r.parallel_setup_script as "parallel_setup_script!: bool",
                       r.dev_server_script,
                       r.default_target_branch,
+                      r.container_image,
+                      r.ssh_host,
+                      r.git_host_provider as "git_host_provider: ProviderKind",
+                      r.auto_rebase as "auto_rebase!: AutoRebasePolicy",
                       r.created_at as "created_at!: DateTime<Utc>",
                       r.updated_at as "updated_at!: DateTime<Utc>",
                       wr.target_branch
@@ -174,6 +199,10 @@ impl WorkspaceRepo {
                     parallel_setup_script: row.parallel_setup_script,
                     dev_server_script: row.dev_server_script,
                     default_target_branch: row.default_target_branch,
+                    container_image: row.container_image,
+                    ssh_host: row.ssh_host,
+                    git_host_provider: row.git_host_provider,
+                    auto_rebase: row.auto_rebase,
                     created_at: row.created_at,
                     updated_at: row.updated_at,
                 },
@@ -193,6 +222,7 @@ impl WorkspaceRepo {
                       workspace_id as "workspace_id!: Uuid",
                       repo_id as "repo_id!: Uuid",
                       target_branch,
+                      auto_rebase_conflict_oid,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM workspace_repos
@@ -221,6 +251,51 @@ impl WorkspaceRepo {
         Ok(())
     }
 
+    /// Record the target branch commit an automatic rebase conflicted with, or clear it.
+    pub async fn set_auto_rebase_conflict(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        conflict_oid: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspace_repos SET auto_rebase_conflict_oid = $1, updated_at = datetime('now') WHERE workspace_id = $2 AND repo_id = $3",
+            conflict_oid,
+            workspace_id,
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Find repos of unarchived workspaces with a worktree whose auto-rebase policy is not
+    /// `never`.
+    pub async fn find_auto_rebase_candidates(
+        pool: &SqlitePool,
+    ) -> Result<Vec<AutoRebaseCandidate>, sqlx::Error> {
+        sqlx::query_as!(
+            AutoRebaseCandidate,
+            r#"SELECT w.id as "workspace_id!: Uuid",
+                      w.container_ref as "container_ref!",
+                      w.branch,
+                      r.id as "repo_id!: Uuid",
+                      wr.target_branch,
+                      r.auto_rebase as "auto_rebase!: AutoRebasePolicy",
+                      wr.auto_rebase_conflict_oid
+               FROM workspace_repos wr
+               JOIN workspaces w ON wr.workspace_id = w.id
+               JOIN repos r ON wr.repo_id = r.id
+               WHERE r.auto_rebase != 'never'
+                 AND w.archived = 0
+                 AND w.container_ref IS NOT NULL
+                 AND w.container_ref != ''
+               ORDER BY w.updated_at DESC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn update_target_branch_for_children_of_workspace(
         pool: &SqlitePool,
         parent_workspace_id: Uuid,
@@ -261,6 +336,10 @@ impl WorkspaceRepo {
                       r.parallel_setup_script as "parallel_setup_script!: bool",
                       r.dev_server_script,
                       r.default_target_branch,
+                      r.container_image,
+                      r.ssh_host,
+                      r.git_host_provider as "git_host_provider: ProviderKind",
+                      r.auto_rebase as "auto_rebase!: AutoRebasePolicy",
                       r.created_at as "created_at!: DateTime<Utc>",
                       r.updated_at as "updated_at!: DateTime<Utc>"
                FROM repos r
@@ -0,0 +1,126 @@
+//! In-memory database and seed rows for tests of the db and services crates
+
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_initial::CodingAgentInitialRequest,
+    },
+    executors::BaseCodingAgent,
+    profile::ExecutorProfileId,
+};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use uuid::Uuid;
+
+use crate::{
+    DBService,
+    models::{
+        execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason},
+        project::{CreateProject, Project},
+        session::{CreateSession, Session},
+        task::{CreateTask, Task},
+        workspace::{CreateWorkspace, Workspace},
+    },
+    run_migrations,
+};
+
+impl DBService {
+    /// Fresh migrated database that lives as long as the returned service
+    pub async fn new_in_memory() -> DBService {
+        let options = SqliteConnectOptions::new()
+            .in_memory(true)
+            .foreign_keys(true);
+        // Every connection to `:memory:` opens its own database, so keep exactly one
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await
+            .expect("open in-memory database");
+        run_migrations(&pool).await.expect("run migrations");
+        DBService { pool }
+    }
+}
+
+/// A project with one task, one workspace on that task and one session in the workspace
+pub struct TaskFixture {
+    pub project: Project,
+    pub task: Task,
+    pub workspace: Workspace,
+    pub session: Session,
+}
+
+pub async fn create_task_fixture(db: &DBService, title: &str) -> TaskFixture {
+    let project = Project::create(
+        &db.pool,
+        &CreateProject {
+            name: format!("{title} project"),
+            repositories: vec![],
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+    add_task(db, project, title).await
+}
+
+/// Another task, workspace and session in an existing project
+pub async fn add_task(db: &DBService, project: Project, title: &str) -> TaskFixture {
+    let task = Task::create(
+        &db.pool,
+        &CreateTask::from_title_description(project.id, title.to_string(), None),
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+    let workspace = Workspace::create(
+        &db.pool,
+        &CreateWorkspace {
+            branch: format!("vk/{}", &task.id.to_string()[..8]),
+            agent_working_dir: None,
+        },
+        Uuid::new_v4(),
+        task.id,
+    )
+    .await
+    .unwrap();
+    let session = Session::create(
+        &db.pool,
+        &CreateSession {
+            executor: Some(BaseCodingAgent::ClaudeCode.to_string()),
+        },
+        Uuid::new_v4(),
+        workspace.id,
+    )
+    .await
+    .unwrap();
+    TaskFixture {
+        project,
+        task,
+        workspace,
+        session,
+    }
+}
+
+/// A running coding agent execution in the session
+pub async fn create_coding_agent_process(db: &DBService, session_id: Uuid) -> ExecutionProcess {
+    let action = ExecutorAction::new(
+        ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt: "Fix the failing test".to_string(),
+            executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            working_dir: None,
+        }),
+        None,
+    );
+    ExecutionProcess::create(
+        &db.pool,
+        &CreateExecutionProcess {
+            session_id,
+            executor_action: action,
+            run_reason: ExecutionProcessRunReason::CodingAgent,
+        },
+        Uuid::new_v4(),
+        &[],
+    )
+    .await
+    .unwrap()
+}
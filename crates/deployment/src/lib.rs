@@ -19,6 +19,7 @@ use services::services::{
     analytics::{AnalyticsContext, AnalyticsService},
     approvals::Approvals,
     auth::AuthContext,
+    auto_rebase::AutoRebaseService,
     config::{Config, ConfigError},
     container::{ContainerError, ContainerService},
     events::{EventError, EventService},
@@ -27,6 +28,7 @@ use services::services::{
     filesystem_watcher::FilesystemWatcherError,
     git::{GitService, GitServiceError},
     image::{ImageError, ImageService},
+    log_compactor::LogCompactorService,
     pr_monitor::PrMonitorService,
     project::ProjectService,
     queued_message::QueuedMessageService,
@@ -129,7 +131,25 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 user_id: self.user_id().to_string(),
                 analytics_service: analytics_service.clone(),
             });
-        PrMonitorService::spawn(db, analytics).await
+        PrMonitorService::spawn(
+            db,
+            self.config().clone(),
+            analytics,
+            self.container().workspace_locks().clone(),
+        )
+        .await
+    }
+
+    async fn spawn_log_compactor_service(&self) -> tokio::task::JoinHandle<()> {
+        LogCompactorService::spawn(self.db().clone(), self.config().clone()).await
+    }
+
+    async fn spawn_auto_rebase_service(&self) -> tokio::task::JoinHandle<()> {
+        AutoRebaseService::spawn(
+            self.db().clone(),
+            self.container().workspace_locks().clone(),
+        )
+        .await
     }
 
     async fn track_if_analytics_allowed(&self, event_name: &str, properties: Value) {
@@ -14,6 +14,7 @@ use crate::{
     approvals::ExecutorApprovalService,
     env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorError, SpawnedChild},
+    profile::ExecutorProfileId,
 };
 pub mod coding_agent_follow_up;
 pub mod coding_agent_initial;
@@ -69,6 +70,50 @@ impl ExecutorAction {
             ExecutorActionType::ScriptRequest(_) => None,
         }
     }
+
+    pub fn executor_profile_id(&self) -> Option<&ExecutorProfileId> {
+        match self.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            ExecutorActionType::ReviewRequest(request) => Some(&request.executor_profile_id),
+            ExecutorActionType::ScriptRequest(_) => None,
+        }
+    }
+
+    /// Restart this coding agent request from scratch with another executor profile, keeping
+    /// the rest of the chain. Agents cannot resume each other's sessions, so a follow-up becomes
+    /// an initial request that repeats `task_prompt` before the follow-up prompt.
+    pub fn with_fallback_profile(
+        &self,
+        executor_profile_id: ExecutorProfileId,
+        task_prompt: &str,
+    ) -> Option<ExecutorAction> {
+        let (prompt, working_dir) = match self.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                (request.prompt.clone(), request.working_dir.clone())
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => (
+                format!("{task_prompt}\n\n{}", request.prompt),
+                request.working_dir.clone(),
+            ),
+            ExecutorActionType::ReviewRequest(_) | ExecutorActionType::ScriptRequest(_) => {
+                return None;
+            }
+        };
+
+        Some(ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt,
+                executor_profile_id,
+                working_dir,
+            }),
+            self.next_action.clone(),
+        ))
+    }
 }
 
 #[async_trait]
@@ -1,3 +1,5 @@
+pub mod policy;
+
 use std::fmt;
 
 use async_trait::async_trait;
@@ -6,7 +6,9 @@ use thiserror::Error;
 use ts_rs::TS;
 use workspace_utils::shell::resolve_executable_path;
 
-use crate::executors::ExecutorError;
+use crate::{
+    approvals::policy::ApprovalRule, executors::ExecutorError, linux_sandbox::LinuxSandbox,
+};
 
 #[derive(Debug, Error)]
 pub enum CommandBuildError {
@@ -20,7 +22,7 @@ pub enum CommandBuildError {
     InvalidShellParams(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CommandParts {
     program: String,
     args: Vec<String>,
@@ -60,6 +62,48 @@ pub struct CmdOverrides {
     )]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
+    #[schemars(
+        title = "Max Runtime (seconds)",
+        description = "Stop the execution once it has been running for this many seconds"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_runtime_secs: Option<u32>,
+    #[schemars(
+        title = "Max Tool Calls",
+        description = "Stop the execution when the agent attempts more tool calls than this"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tool_calls: Option<u32>,
+    #[schemars(
+        title = "Max Memory (MB)",
+        description = "Memory limit for the processes of the execution"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u32>,
+    #[schemars(
+        title = "Max CPU (%)",
+        description = "CPU limit for the processes of the execution, where 100 is one core. Needs cgroup v2"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_percent: Option<u32>,
+    #[schemars(
+        title = "Max Processes",
+        description = "Limit on the number of processes the execution may run at once. Needs cgroup v2"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_pids: Option<u32>,
+    #[schemars(
+        title = "Approval Rules",
+        description = "Rules that approve, deny or ask about tool calls, checked in order after the project rules"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_rules: Option<Vec<ApprovalRule>>,
+    #[schemars(
+        title = "Linux Sandbox",
+        description = "Run the agent with a read-only filesystem outside its workspace and config directories, on Linux only"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linux_sandbox: Option<LinuxSandbox>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
@@ -1,8 +1,12 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, fmt::Debug, path::PathBuf, sync::Arc};
 
 use tokio::process::Command;
 
-use crate::command::CmdOverrides;
+use crate::{
+    command::CmdOverrides,
+    executors::{BaseCodingAgent, ExecutorError},
+    linux_sandbox::LinuxSandbox,
+};
 
 /// Repository context for executor operations
 #[derive(Debug, Clone, Default)]
@@ -28,12 +32,32 @@ impl RepoContext {
     }
 }
 
+/// Rewrites a fully configured command into the one that is spawned, e.g. to run it inside a
+/// container. Stdio has to be set up again on the returned command.
+pub trait CommandWrapper: Debug + Send + Sync {
+    fn wrap(&self, command: &Command) -> Command;
+}
+
+/// Confines a process spawned on the host before it runs, e.g. to the resource limits of its
+/// executor profile. Runs before the sandbox is applied.
+pub trait SpawnHook: Debug + Send + Sync {
+    fn apply(&self, command: &mut Command) -> Result<(), ExecutorError>;
+}
+
 /// Environment variables to inject into executor processes
 #[derive(Debug, Clone)]
 pub struct ExecutionEnv {
     pub vars: HashMap<String, String>,
     pub repo_context: RepoContext,
     pub commit_reminder: bool,
+    /// Sandbox of the executor profile, applied when the command is spawned
+    pub linux_sandbox: Option<LinuxSandbox>,
+    /// Agent the process runs, whose config directories stay writable in the sandbox
+    pub executor: Option<BaseCodingAgent>,
+    /// Set when processes run somewhere other than the host, which takes the place of the sandbox
+    pub command_wrapper: Option<Arc<dyn CommandWrapper>>,
+    /// Applied to commands that run on the host
+    pub spawn_hook: Option<Arc<dyn SpawnHook>>,
 }
 
 impl ExecutionEnv {
@@ -42,6 +66,10 @@ impl ExecutionEnv {
             vars: HashMap::new(),
             repo_context,
             commit_reminder,
+            linux_sandbox: None,
+            executor: None,
+            command_wrapper: None,
+            spawn_hook: None,
         }
     }
 
@@ -62,8 +90,11 @@ impl ExecutionEnv {
         self
     }
 
-    /// Return a new env with profile env from CmdOverrides merged in.
-    pub fn with_profile(self, cmd: &CmdOverrides) -> Self {
+    /// Return a new env with profile env and sandbox from CmdOverrides merged in.
+    pub fn with_profile(mut self, cmd: &CmdOverrides) -> Self {
+        if cmd.linux_sandbox.is_some() {
+            self.linux_sandbox = cmd.linux_sandbox.clone();
+        }
         if let Some(ref profile_env) = cmd.env {
             self.with_overrides(profile_env)
         } else {
@@ -71,11 +102,38 @@ impl ExecutionEnv {
         }
     }
 
-    /// Apply all environment variables to a Command
-    pub fn apply_to_command(&self, command: &mut Command) {
+    /// Apply all environment variables to a Command, and confine it to the workspace when the
+    /// profile has a sandbox or the workspace runs in a container
+    pub fn apply_to_command(&self, command: &mut Command) -> Result<(), ExecutorError> {
         for (key, value) in &self.vars {
             command.env(key, value);
         }
+
+        if let Some(wrapper) = &self.command_wrapper {
+            *command = wrapper.wrap(command);
+            return Ok(());
+        }
+
+        // The hook may need to write outside the sandbox, so it is applied first
+        if let Some(hook) = &self.spawn_hook {
+            hook.apply(command)?;
+        }
+
+        if let Some(sandbox) = &self.linux_sandbox {
+            // Without a workspace, the process is confined to the directory it runs in
+            let workspace_root = if self.repo_context.workspace_root.as_os_str().is_empty() {
+                command.as_std().get_current_dir().map(PathBuf::from)
+            } else {
+                Some(self.repo_context.workspace_root.clone())
+            };
+            let writable_paths = workspace_root
+                .map(|root| {
+                    sandbox.writable_paths(&root, &self.repo_context.repo_paths(), self.executor)
+                })
+                .unwrap_or_default();
+            sandbox.apply_to_command(command, &writable_paths)?;
+        }
+        Ok(())
     }
 
     pub fn contains_key(&self, key: &str) -> bool {
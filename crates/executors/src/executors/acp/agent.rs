@@ -0,0 +1,287 @@
+use std::{path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use derivative::Derivative;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use workspace_utils::msg_store::MsgStore;
+
+use super::AcpAgentHarness;
+use crate::{
+    approvals::ExecutorApprovalService,
+    command::{CmdOverrides, CommandBuildError, CommandBuilder, apply_overrides},
+    env::ExecutionEnv,
+    executors::{AppendPrompt, ExecutorError, SpawnedChild, StandardCodingAgentExecutor},
+};
+
+/// Session files of generic ACP agents live in their own namespace
+const ACP_SESSION_NAMESPACE: &str = "acp_sessions";
+
+/// Generic executor for any binary speaking the Agent Client Protocol over stdio
+#[derive(Derivative, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[derivative(Debug, PartialEq)]
+pub struct Acp {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    #[schemars(
+        title = "Program",
+        description = "Command that starts the ACP agent, e.g. `npx -y @zed-industries/claude-code-acp`"
+    )]
+    pub program: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Arguments",
+        description = "Arguments passed to the program, e.g. `--experimental-acp`"
+    )]
+    pub args: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Model",
+        description = "Model id requested through `session/set_model`"
+    )]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Mode",
+        description = "Session mode id requested through `session/set_mode`"
+    )]
+    pub mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Auto Approve",
+        description = "Approve every permission request without asking"
+    )]
+    pub auto_approve: Option<bool>,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+    #[serde(skip)]
+    #[ts(skip)]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub approvals: Option<Arc<dyn ExecutorApprovalService>>,
+}
+
+impl Acp {
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
+        let mut builder = CommandBuilder::new(self.program.clone());
+        if let Some(args) = &self.args {
+            builder = builder.extend_params(args.clone());
+        }
+        apply_overrides(builder, &self.cmd)
+    }
+
+    fn harness(&self) -> AcpAgentHarness {
+        let mut harness = AcpAgentHarness::with_session_namespace(ACP_SESSION_NAMESPACE);
+        if let Some(model) = &self.model {
+            harness = harness.with_model(model);
+        }
+        if let Some(mode) = &self.mode {
+            harness = harness.with_mode(mode);
+        }
+        harness
+    }
+
+    fn approvals(&self) -> Option<Arc<dyn ExecutorApprovalService>> {
+        if self.auto_approve.unwrap_or(false) {
+            None
+        } else {
+            self.approvals.clone()
+        }
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for Acp {
+    fn use_approvals(&mut self, approvals: Arc<dyn ExecutorApprovalService>) {
+        self.approvals = Some(approvals);
+    }
+
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let acp_command = self.build_command_builder()?.build_initial()?;
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        self.harness()
+            .spawn_with_command(
+                current_dir,
+                combined_prompt,
+                acp_command,
+                env,
+                &self.cmd,
+                self.approvals(),
+            )
+            .await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let acp_command = self.build_command_builder()?.build_follow_up(&[])?;
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        self.harness()
+            .spawn_follow_up_with_command(
+                current_dir,
+                combined_prompt,
+                session_id,
+                acp_command,
+                env,
+                &self.cmd,
+                self.approvals(),
+            )
+            .await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
+        super::normalize_logs(msg_store, worktree_path);
+    }
+
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use workspace_utils::log_msg::LogMsg;
+
+    use super::*;
+    use crate::{
+        approvals::NoopExecutorApprovalService,
+        command::CommandParts,
+        executors::CodingAgent,
+        logs::{ActionType, NormalizedEntryType, ToolStatus},
+        replay::{ReplayExecutor, ReplayFixture, ReplayTiming, normalized_entries},
+    };
+
+    const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/acp");
+
+    fn agent() -> Acp {
+        serde_json::from_value(json!({
+            "program": "npx -y @zed-industries/claude-code-acp",
+            "args": ["--experimental-acp"],
+            "additional_params": ["--log-level debug"]
+        }))
+        .unwrap()
+    }
+
+    fn parts(program: &str, args: &[&str]) -> CommandParts {
+        CommandParts::new(
+            program.to_string(),
+            args.iter().map(|arg| arg.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn builds_command_from_program_args_and_overrides() {
+        let command = agent()
+            .build_command_builder()
+            .unwrap()
+            .build_initial()
+            .unwrap();
+        assert_eq!(
+            command,
+            parts(
+                "npx",
+                &[
+                    "-y",
+                    "@zed-industries/claude-code-acp",
+                    "--experimental-acp",
+                    "--log-level",
+                    "debug"
+                ]
+            )
+        );
+
+        let overridden: Acp = serde_json::from_value(json!({
+            "program": "my-acp-agent",
+            "base_command_override": "/opt/agent/bin/acp --stdio"
+        }))
+        .unwrap();
+        assert_eq!(
+            overridden
+                .build_command_builder()
+                .unwrap()
+                .build_initial()
+                .unwrap(),
+            parts("/opt/agent/bin/acp", &["--stdio"])
+        );
+    }
+
+    #[test]
+    fn follow_ups_reuse_the_initial_command() {
+        // The session id is sent over the protocol (`session/load` or a resume prompt), so the
+        // command line must not change between the first run and follow-ups
+        let builder = agent().build_command_builder().unwrap();
+        assert_eq!(
+            builder.build_follow_up(&[]).unwrap(),
+            builder.build_initial().unwrap()
+        );
+    }
+
+    #[test]
+    fn auto_approve_skips_the_approval_service() {
+        let mut agent = agent();
+        agent.use_approvals(Arc::new(NoopExecutorApprovalService));
+        assert!(agent.approvals().is_some());
+
+        agent.auto_approve = Some(true);
+        assert!(agent.approvals().is_none());
+    }
+
+    #[tokio::test]
+    async fn normalizes_recorded_session() {
+        let fixture = ReplayFixture::load(&Path::new(FIXTURE_DIR).join("session.jsonl"))
+            .await
+            .unwrap();
+        let msg_store = ReplayExecutor::new(CodingAgent::Acp(agent()), fixture)
+            .with_timing(ReplayTiming::Instant)
+            .run()
+            .await;
+
+        let session_ids: Vec<_> = msg_store
+            .get_history()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                LogMsg::SessionId(id) => Some(id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(session_ids, vec!["8c1f2e4a-5b6d-4e7f-9a0b-1c2d3e4f5a6b"]);
+
+        let entries = normalized_entries(&msg_store);
+        assert_eq!(entries.len(), 4, "unexpected entries: {entries:#?}");
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::Thinking
+        ));
+        assert_eq!(
+            entries[0].content,
+            "Checking how the parser is tested today."
+        );
+        assert!(matches!(
+            entries[1].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(entries[1].content, "I'll run the existing tests first.");
+        match &entries[2].entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::CommandRun { command, .. },
+                status,
+                ..
+            } => {
+                assert_eq!(command, "cargo test -p config");
+                assert!(matches!(status, ToolStatus::Success));
+            }
+            other => panic!("expected a command run, got {other:?}"),
+        }
+        assert_eq!(entries[3].content, "All 12 tests pass.");
+    }
+}
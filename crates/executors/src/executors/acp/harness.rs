@@ -91,7 +91,7 @@ impl AcpAgentHarness {
 
         env.clone()
             .with_profile(cmd_overrides)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         let mut child = command.group_spawn()?;
 
@@ -141,7 +141,7 @@ impl AcpAgentHarness {
 
         env.clone()
             .with_profile(cmd_overrides)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         let mut child = command.group_spawn()?;
 
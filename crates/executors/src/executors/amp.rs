@@ -68,7 +68,7 @@ impl StandardCodingAgentExecutor for Amp {
 
         env.clone()
             .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         let mut child = command.group_spawn()?;
 
@@ -143,7 +143,7 @@ impl StandardCodingAgentExecutor for Amp {
 
         env.clone()
             .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         let mut child = command.group_spawn()?;
 
@@ -304,7 +304,7 @@ impl ClaudeCode {
 
         env.clone()
             .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         // Remove ANTHROPIC_API_KEY if disable_api_key is enabled
         if self.disable_api_key.unwrap_or(false) {
@@ -393,6 +393,9 @@ pub struct ClaudeLogProcessor {
     main_model_name: Option<String>,
     main_model_context_window: u32,
     context_tokens_used: u32,
+    // Cumulative usage of the whole run, summed from message deltas and
+    // replaced by the result message
+    run_usage: Option<crate::logs::TokenUsage>,
 }
 
 impl ClaudeLogProcessor {
@@ -412,6 +415,7 @@ impl ClaudeLogProcessor {
             last_assistant_message: None,
             main_model_context_window: DEFAULT_CLAUDE_CONTEXT_WINDOW,
             context_tokens_used: 0,
+            run_usage: None,
         }
     }
 
@@ -436,7 +440,8 @@ impl ClaudeLogProcessor {
                     LogMsg::JsonPatch(_)
                     | LogMsg::SessionId(_)
                     | LogMsg::Stderr(_)
-                    | LogMsg::Ready => continue,
+                    | LogMsg::Ready
+                    | LogMsg::ResourceUsage(_) => continue,
                     LogMsg::Finished => break,
                 };
 
@@ -1237,6 +1242,17 @@ impl ClaudeLogProcessor {
                 }
                 ClaudeStreamEvent::ContentBlockStop { .. } => {}
                 ClaudeStreamEvent::MessageDelta { usage, .. } => {
+                    // keep a running total until the result message reports the final usage
+                    if let Some(usage) = usage {
+                        let run_usage = self.run_usage.get_or_insert_with(Default::default);
+                        run_usage.model = self.main_model_name.clone();
+                        run_usage.input_tokens += usage.input_tokens.unwrap_or(0);
+                        run_usage.output_tokens += usage.output_tokens.unwrap_or(0);
+                        run_usage.cache_read_tokens += usage.cache_read_input_tokens.unwrap_or(0);
+                        run_usage.cache_write_tokens +=
+                            usage.cache_creation_input_tokens.unwrap_or(0);
+                    }
+
                     // do not report context token usage for subagents
                     if parent_tool_use_id.is_none()
                         && let Some(usage) = usage
@@ -1261,10 +1277,12 @@ impl ClaudeLogProcessor {
             ClaudeJson::Result {
                 is_error,
                 model_usage,
+                usage,
                 subtype,
                 result,
                 ..
             } => {
+                let mut usage_changed = false;
                 // get the real model context window and correct the context usage entry
                 if let Some(context_window) = model_usage.as_ref().and_then(|model_usage| {
                     self.main_model_name
@@ -1273,6 +1291,20 @@ impl ClaudeLogProcessor {
                         .and_then(|usage| usage.context_window)
                 }) {
                     self.main_model_context_window = context_window;
+                    usage_changed = true;
+                }
+                if let Some(usage) = usage {
+                    self.run_usage = Some(crate::logs::TokenUsage {
+                        model: self.main_model_name.clone(),
+                        input_tokens: usage.input_tokens.unwrap_or(0),
+                        output_tokens: usage.output_tokens.unwrap_or(0),
+                        cache_read_tokens: usage.cache_read_input_tokens.unwrap_or(0),
+                        cache_write_tokens: usage.cache_creation_input_tokens.unwrap_or(0),
+                        cost_usd: None,
+                    });
+                    usage_changed = true;
+                }
+                if usage_changed {
                     patches.push(self.add_token_usage_entry(entry_index_provider));
                 }
 
@@ -1465,6 +1497,7 @@ impl ClaudeLogProcessor {
             entry_type: NormalizedEntryType::TokenUsageInfo(crate::logs::TokenUsageInfo {
                 total_tokens: self.context_tokens_used,
                 model_context_window: self.main_model_context_window,
+                usage: self.run_usage.clone(),
             }),
             content: format!(
                 "Tokens used: {} / Context window: {}",
@@ -2167,6 +2200,25 @@ mod tests {
         assert_eq!(entries[0].content, "Final result");
     }
 
+    #[test]
+    fn test_result_message_reports_run_usage() {
+        let result_json = r#"{"type":"result","subtype":"success","is_error":false,"result":"Done","usage":{"input_tokens":12,"output_tokens":340,"cache_creation_input_tokens":1000,"cache_read_input_tokens":5000}}"#;
+        let parsed: ClaudeJson = serde_json::from_str(result_json).unwrap();
+
+        let entries = normalize(&parsed, "");
+        let usage = entries
+            .iter()
+            .find_map(|entry| match &entry.entry_type {
+                NormalizedEntryType::TokenUsageInfo(info) => info.usage.clone(),
+                _ => None,
+            })
+            .expect("token usage entry");
+        assert_eq!(usage.input_tokens, 12);
+        assert_eq!(usage.output_tokens, 340);
+        assert_eq!(usage.cache_read_tokens, 5000);
+        assert_eq!(usage.cache_write_tokens, 1000);
+    }
+
     #[test]
     fn test_thinking_content() {
         let thinking_json = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"thinking","thinking":"Let me think about this..."}]}}"#;
@@ -2282,6 +2334,13 @@ mod tests {
                 base_command_override: None,
                 additional_params: None,
                 env: None,
+                max_runtime_secs: None,
+                max_tool_calls: None,
+                max_memory_mb: None,
+                max_cpu_percent: None,
+                max_pids: None,
+                approval_rules: None,
+                linux_sandbox: None,
             },
             approvals_service: None,
             disable_api_key: None,
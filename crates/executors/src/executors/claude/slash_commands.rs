@@ -216,7 +216,7 @@ impl ClaudeCode {
 
         ExecutionEnv::new(RepoContext::default(), false)
             .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         if self.disable_api_key.unwrap_or(false) {
             command.env_remove("ANTHROPIC_API_KEY");
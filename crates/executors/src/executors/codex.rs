@@ -466,7 +466,7 @@ impl Codex {
 
         env.clone()
             .with_profile(&self.cmd)
-            .apply_to_command(&mut process);
+            .apply_to_command(&mut process)?;
 
         let mut child = process.group_spawn()?;
 
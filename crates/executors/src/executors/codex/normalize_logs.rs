@@ -17,8 +17,8 @@ use codex_protocol::{
         ErrorEvent, EventMsg, ExecApprovalRequestEvent, ExecCommandBeginEvent, ExecCommandEndEvent,
         ExecCommandOutputDeltaEvent, ExecOutputStream, FileChange as CodexProtoFileChange,
         McpInvocation, McpToolCallBeginEvent, McpToolCallEndEvent, PatchApplyBeginEvent,
-        PatchApplyEndEvent, StreamErrorEvent, ViewImageToolCallEvent, WarningEvent,
-        WebSearchBeginEvent, WebSearchEndEvent,
+        PatchApplyEndEvent, StreamErrorEvent, TokenUsage as CodexTokenUsage,
+        ViewImageToolCallEvent, WarningEvent, WebSearchBeginEvent, WebSearchEndEvent,
     },
 };
 use futures::StreamExt;
@@ -35,8 +35,8 @@ use crate::{
     executors::codex::session::SessionHandler,
     logs::{
         ActionType, CommandExitStatus, CommandRunResult, FileChange, NormalizedEntry,
-        NormalizedEntryError, NormalizedEntryType, TodoItem, ToolResult, ToolResultValueType,
-        ToolStatus,
+        NormalizedEntryError, NormalizedEntryType, TodoItem, TokenUsage, ToolResult,
+        ToolResultValueType, ToolStatus,
         stderr_processor::normalize_stderr_logs,
         utils::{
             ConversationPatch, EntryIndexProvider,
@@ -215,6 +215,9 @@ struct LogState {
     mcp_tools: HashMap<String, McpToolState>,
     patches: HashMap<String, PatchState>,
     web_searches: HashMap<String, WebSearchState>,
+    model: Option<String>,
+    // Thread totals reported before this execution started, so follow-ups only count their own turns
+    usage_baseline: Option<TokenUsage>,
 }
 
 enum StreamingTextKind {
@@ -232,6 +235,40 @@ impl LogState {
             mcp_tools: HashMap::new(),
             patches: HashMap::new(),
             web_searches: HashMap::new(),
+            model: None,
+            usage_baseline: None,
+        }
+    }
+
+    /// Token usage of this execution, derived from the cumulative totals of the thread
+    fn execution_token_usage(
+        &mut self,
+        total: &CodexTokenUsage,
+        last: &CodexTokenUsage,
+    ) -> TokenUsage {
+        let total = to_token_usage(total);
+        let baseline = self.usage_baseline.get_or_insert_with(|| {
+            let last = to_token_usage(last);
+            TokenUsage {
+                model: None,
+                input_tokens: total.input_tokens.saturating_sub(last.input_tokens),
+                output_tokens: total.output_tokens.saturating_sub(last.output_tokens),
+                cache_read_tokens: total
+                    .cache_read_tokens
+                    .saturating_sub(last.cache_read_tokens),
+                cache_write_tokens: 0,
+                cost_usd: None,
+            }
+        });
+        TokenUsage {
+            model: self.model.clone(),
+            input_tokens: total.input_tokens.saturating_sub(baseline.input_tokens),
+            output_tokens: total.output_tokens.saturating_sub(baseline.output_tokens),
+            cache_read_tokens: total
+                .cache_read_tokens
+                .saturating_sub(baseline.cache_read_tokens),
+            cache_write_tokens: 0,
+            cost_usd: None,
         }
     }
 
@@ -378,7 +415,9 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
             }
 
             if let Ok(response) = serde_json::from_str::<JSONRPCResponse>(&line) {
-                handle_jsonrpc_response(response, &msg_store, &entry_index);
+                if let Some(model) = handle_jsonrpc_response(response, &msg_store, &entry_index) {
+                    state.model = Some(model);
+                }
                 continue;
             }
 
@@ -387,6 +426,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                     server_notification
                 {
                     msg_store.push_session_id(session_configured.session_id.to_string());
+                    state.model = Some(session_configured.model.clone());
                     handle_model_params(
                         session_configured.model,
                         session_configured.reasoning_effort,
@@ -425,6 +465,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
             match event {
                 EventMsg::SessionConfigured(payload) => {
                     msg_store.push_session_id(payload.session_id.to_string());
+                    state.model = Some(payload.model.clone());
                     handle_model_params(
                         payload.model,
                         payload.reasoning_effort,
@@ -999,6 +1040,8 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                 }
                 EventMsg::TokenCount(payload) => {
                     if let Some(info) = payload.info {
+                        let usage = state
+                            .execution_token_usage(&info.total_token_usage, &info.last_token_usage);
                         add_normalized_entry(
                             &msg_store,
                             &entry_index,
@@ -1011,6 +1054,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                                             .model_context_window
                                             .unwrap_or_default()
                                             as u32,
+                                        usage: Some(usage),
                                     },
                                 ),
                                 content: format!(
@@ -1081,10 +1125,10 @@ fn handle_jsonrpc_response(
     response: JSONRPCResponse,
     msg_store: &Arc<MsgStore>,
     entry_index: &EntryIndexProvider,
-) {
+) -> Option<String> {
     let Ok(response) = serde_json::from_value::<NewConversationResponse>(response.result.clone())
     else {
-        return;
+        return None;
     };
 
     match SessionHandler::extract_session_id_from_rollout_path(response.rollout_path) {
@@ -1093,11 +1137,26 @@ fn handle_jsonrpc_response(
     }
 
     handle_model_params(
-        response.model,
+        response.model.clone(),
         response.reasoning_effort,
         msg_store,
         entry_index,
     );
+    Some(response.model)
+}
+
+fn to_token_usage(usage: &CodexTokenUsage) -> TokenUsage {
+    let input_tokens = usage.input_tokens.max(0) as u64;
+    let cached_input_tokens = usage.cached_input_tokens.max(0) as u64;
+    TokenUsage {
+        model: None,
+        // OpenAI counts cached prompt tokens as part of the input
+        input_tokens: input_tokens.saturating_sub(cached_input_tokens),
+        output_tokens: usage.output_tokens.max(0) as u64,
+        cache_read_tokens: cached_input_tokens,
+        cache_write_tokens: 0,
+        cost_usd: None,
+    }
 }
 
 fn handle_model_params(
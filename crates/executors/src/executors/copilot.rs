@@ -125,7 +125,7 @@ impl StandardCodingAgentExecutor for Copilot {
 
         env.clone()
             .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         let mut child = command.group_spawn()?;
 
@@ -170,7 +170,7 @@ impl StandardCodingAgentExecutor for Copilot {
 
         env.clone()
             .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         let mut child = command.group_spawn()?;
 
@@ -97,7 +97,7 @@ impl StandardCodingAgentExecutor for CursorAgent {
 
         env.clone()
             .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         let mut child = command.group_spawn()?;
 
@@ -137,7 +137,7 @@ impl StandardCodingAgentExecutor for CursorAgent {
 
         env.clone()
             .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         let mut child = command.group_spawn()?;
 
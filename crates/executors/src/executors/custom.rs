@@ -0,0 +1,634 @@
+//! Declarative executor for in-house agent CLIs.
+//!
+//! Everything that is hard-coded in the other executors (base command, follow-up arguments,
+//! session id discovery and stdout parsing) is read from the profile instead, so a new agent can
+//! be plugged in through `profiles.json` without touching this crate.
+use std::{path::Path, process::Stdio, sync::Arc};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use futures::StreamExt;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{io::AsyncWriteExt, process::Command};
+use ts_rs::TS;
+use workspace_utils::msg_store::MsgStore;
+
+use crate::{
+    command::{CmdOverrides, CommandBuildError, CommandBuilder, apply_overrides},
+    env::ExecutionEnv,
+    executors::{AppendPrompt, ExecutorError, SpawnedChild, StandardCodingAgentExecutor},
+    logs::{
+        ActionType, CommandExitStatus, CommandRunResult, NormalizedEntry, NormalizedEntryError,
+        NormalizedEntryType, ToolResult, ToolStatus,
+        plain_text_processor::PlainTextLogProcessor,
+        stderr_processor::normalize_stderr_logs,
+        utils::{ConversationPatch, EntryIndexProvider},
+    },
+};
+
+/// Placeholder substituted with the agent session id in follow-up parameters
+pub const SESSION_ID_PLACEHOLDER: &str = "{session_id}";
+
+/// How the prompt is handed to the agent process
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptDelivery {
+    /// Write the prompt to stdin and close it
+    #[default]
+    Stdin,
+    /// Append the prompt as the last command line argument
+    Argument,
+}
+
+/// Rule used to discover the agent session id from stdout
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionIdRule {
+    /// JSON pointer (e.g. `/session_id`) evaluated against every JSONL stdout line
+    JsonPointer { pointer: String },
+    /// Regex evaluated against every raw stdout line; the first capture group is the session id
+    Regex { pattern: String },
+}
+
+/// Kind of normalized entry produced by a mapping rule
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MappedEntryType {
+    UserMessage,
+    AssistantMessage,
+    Thinking,
+    SystemMessage,
+    ErrorMessage,
+    /// Generic tool call; `content` points at the tool arguments
+    ToolUse,
+    /// Shell command; `content` points at the command line
+    CommandRun,
+    /// File read; `content` points at the file path
+    FileRead,
+    /// File edit; `content` points at the file path
+    FileEdit,
+}
+
+/// Maps one kind of stdout JSONL object to a `NormalizedEntry`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct JsonlMappingRule {
+    #[schemars(
+        title = "Match Pointer",
+        description = "JSON pointer to the discriminating field, e.g. /type"
+    )]
+    pub match_pointer: String,
+    #[schemars(
+        title = "Match Value",
+        description = "Value the discriminating field must have for this rule to apply"
+    )]
+    pub match_value: String,
+    pub entry_type: MappedEntryType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Content Pointer",
+        description = "JSON pointer to the entry content (message text, command, path or tool arguments)"
+    )]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Tool Name Pointer",
+        description = "JSON pointer to the tool name, defaults to the match value"
+    )]
+    pub tool_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Result Pointer",
+        description = "JSON pointer to the tool result or command output"
+    )]
+    pub result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Status Pointer",
+        description = "JSON pointer to the tool outcome: an exit code, a success boolean or a status string such as `error`"
+    )]
+    pub status: Option<String>,
+}
+
+impl JsonlMappingRule {
+    fn matches(&self, value: &Value) -> bool {
+        value
+            .pointer(&self.match_pointer)
+            .is_some_and(|field| value_as_string(field) == self.match_value)
+    }
+
+    /// Status of a mapped tool call. Without a status pointer, a tool whose result pointer
+    /// resolves to nothing is still running.
+    fn tool_status(&self, value: &Value) -> (ToolStatus, Option<CommandExitStatus>) {
+        let Some(pointer) = &self.status else {
+            let pending = self
+                .result
+                .as_deref()
+                .is_some_and(|pointer| value.pointer(pointer).is_none_or(Value::is_null));
+            let status = if pending {
+                ToolStatus::Created
+            } else {
+                ToolStatus::Success
+            };
+            return (status, None);
+        };
+
+        match value.pointer(pointer) {
+            None | Some(Value::Null) => (ToolStatus::Created, None),
+            Some(Value::Bool(success)) => (
+                if *success {
+                    ToolStatus::Success
+                } else {
+                    ToolStatus::Failed
+                },
+                Some(CommandExitStatus::Success { success: *success }),
+            ),
+            Some(Value::Number(number)) => {
+                let code = number.as_i64().unwrap_or(-1);
+                (
+                    if code == 0 {
+                        ToolStatus::Success
+                    } else {
+                        ToolStatus::Failed
+                    },
+                    Some(CommandExitStatus::ExitCode {
+                        code: i32::try_from(code).unwrap_or(-1),
+                    }),
+                )
+            }
+            Some(Value::String(status)) => {
+                let status = match status.trim().to_ascii_lowercase().as_str() {
+                    "" | "pending" | "running" | "started" | "in_progress" => ToolStatus::Created,
+                    "ok" | "success" | "succeeded" | "completed" | "done" | "0" => {
+                        ToolStatus::Success
+                    }
+                    _ => ToolStatus::Failed,
+                };
+                (status, None)
+            }
+            Some(_) => (ToolStatus::Success, None),
+        }
+    }
+
+    fn to_entry(&self, value: &Value) -> NormalizedEntry {
+        let content_value = self
+            .content
+            .as_deref()
+            .and_then(|pointer| value.pointer(pointer));
+        let content = content_value.map(value_as_string).unwrap_or_default();
+        let tool_name = self
+            .tool_name
+            .as_deref()
+            .and_then(|pointer| value.pointer(pointer))
+            .map(value_as_string)
+            .unwrap_or_else(|| self.match_value.clone());
+        let result_value = self
+            .result
+            .as_deref()
+            .and_then(|pointer| value.pointer(pointer))
+            .filter(|result| !result.is_null());
+        let (status, exit_status) = self.tool_status(value);
+
+        let tool_use = |action_type: ActionType, content: String| NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: tool_name.clone(),
+                action_type,
+                status: status.clone(),
+            },
+            content,
+            metadata: Some(value.clone()),
+        };
+        let message = |entry_type: NormalizedEntryType| NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.clone(),
+            metadata: None,
+        };
+
+        match self.entry_type {
+            MappedEntryType::UserMessage => message(NormalizedEntryType::UserMessage),
+            MappedEntryType::AssistantMessage => message(NormalizedEntryType::AssistantMessage),
+            MappedEntryType::Thinking => message(NormalizedEntryType::Thinking),
+            MappedEntryType::SystemMessage => message(NormalizedEntryType::SystemMessage),
+            MappedEntryType::ErrorMessage => message(NormalizedEntryType::ErrorMessage {
+                error_type: NormalizedEntryError::Other,
+            }),
+            MappedEntryType::ToolUse => tool_use(
+                ActionType::Tool {
+                    tool_name: tool_name.clone(),
+                    arguments: content_value.cloned(),
+                    result: result_value.map(|result| match result {
+                        Value::String(text) => ToolResult::markdown(text.clone()),
+                        other => ToolResult::json(other.clone()),
+                    }),
+                },
+                tool_name.clone(),
+            ),
+            MappedEntryType::CommandRun => tool_use(
+                ActionType::CommandRun {
+                    command: content.clone(),
+                    result: (exit_status.is_some() || result_value.is_some()).then(|| {
+                        CommandRunResult {
+                            exit_status,
+                            output: result_value.map(value_as_string),
+                        }
+                    }),
+                },
+                content.clone(),
+            ),
+            MappedEntryType::FileRead => tool_use(
+                ActionType::FileRead {
+                    path: content.clone(),
+                },
+                content.clone(),
+            ),
+            MappedEntryType::FileEdit => tool_use(
+                ActionType::FileEdit {
+                    path: content.clone(),
+                    changes: vec![],
+                },
+                content.clone(),
+            ),
+        }
+    }
+}
+
+fn value_as_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Compiled form of [`SessionIdRule`]
+enum SessionIdMatcher {
+    JsonPointer(String),
+    Regex(Regex),
+}
+
+impl SessionIdMatcher {
+    fn compile(rule: &SessionIdRule) -> Option<Self> {
+        match rule {
+            SessionIdRule::JsonPointer { pointer } => Some(Self::JsonPointer(pointer.clone())),
+            SessionIdRule::Regex { pattern } => match Regex::new(pattern) {
+                Ok(regex) => Some(Self::Regex(regex)),
+                Err(e) => {
+                    tracing::warn!("Invalid session id regex `{pattern}`: {e}");
+                    None
+                }
+            },
+        }
+    }
+
+    fn extract(&self, line: &str, json: Option<&Value>) -> Option<String> {
+        let session_id = match self {
+            Self::JsonPointer(pointer) => json?.pointer(pointer).map(value_as_string),
+            Self::Regex(regex) => regex
+                .captures(line)
+                .and_then(|caps| caps.get(1))
+                .map(|m| m.as_str().to_string()),
+        }?;
+        let session_id = session_id.trim();
+        (!session_id.is_empty()).then(|| session_id.to_string())
+    }
+}
+
+/// Executor for agent CLIs described entirely by their profile
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct CustomAgent {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    #[schemars(
+        title = "Command",
+        description = "Command used to start the agent, e.g. `my-agent run --json`"
+    )]
+    pub command: String,
+    #[serde(default)]
+    #[schemars(
+        title = "Prompt Delivery",
+        description = "Pass the prompt on stdin or as the last argument"
+    )]
+    pub prompt_delivery: PromptDelivery,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Follow-up Parameters",
+        description = "Parameters appended for follow-ups; `{session_id}` is replaced with the session id. Follow-ups are disabled when unset"
+    )]
+    pub follow_up_params: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Session Id Rule",
+        description = "How to find the agent session id in stdout"
+    )]
+    pub session_id: Option<SessionIdRule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Log Mapping",
+        description = "Rules mapping stdout JSONL objects to conversation entries. Stdout is shown as plain text when unset"
+    )]
+    pub log_mapping: Option<Vec<JsonlMappingRule>>,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl CustomAgent {
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
+        apply_overrides(CommandBuilder::new(self.command.clone()), &self.cmd)
+    }
+
+    fn follow_up_args(&self, session_id: &str) -> Result<Vec<String>, ExecutorError> {
+        let params = self.follow_up_params.as_ref().ok_or_else(|| {
+            ExecutorError::FollowUpNotSupported(
+                "custom agent profile does not define follow_up_params".to_string(),
+            )
+        })?;
+        Ok(params
+            .iter()
+            .map(|param| param.replace(SESSION_ID_PLACEHOLDER, session_id))
+            .collect())
+    }
+
+    async fn spawn_custom(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        mut extra_args: Vec<String>,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        if self.prompt_delivery == PromptDelivery::Argument {
+            extra_args.push(combined_prompt.clone());
+        }
+        let (program_path, args) = self
+            .build_command_builder()?
+            .build_follow_up(&extra_args)?
+            .into_resolved()
+            .await?;
+
+        let mut command = Command::new(program_path);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(current_dir)
+            .args(&args);
+
+        env.clone()
+            .with_profile(&self.cmd)
+            .apply_to_command(&mut command)?;
+
+        let mut child = command.group_spawn()?;
+
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            if self.prompt_delivery == PromptDelivery::Stdin {
+                stdin.write_all(combined_prompt.as_bytes()).await?;
+            }
+            stdin.shutdown().await?;
+        }
+
+        Ok(child.into())
+    }
+
+    fn create_plain_text_normalizer(index_provider: EntryIndexProvider) -> PlainTextLogProcessor {
+        PlainTextLogProcessor::builder()
+            .normalized_entry_producer(Box::new(|content: String| NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::AssistantMessage,
+                content,
+                metadata: None,
+            }))
+            .transform_lines(Box::new(|lines| {
+                lines.iter_mut().for_each(|line| {
+                    *line = strip_ansi_escapes::strip_str(&line);
+                })
+            }))
+            .index_provider(index_provider)
+            .build()
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for CustomAgent {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_custom(current_dir, prompt, vec![], env).await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let follow_up_args = self.follow_up_args(session_id)?;
+        self.spawn_custom(current_dir, prompt, follow_up_args, env)
+            .await
+    }
+
+    /// Maps stdout JSONL through `log_mapping`, or falls back to plain text assistant messages.
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, _worktree_path: &Path) {
+        let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
+        normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+        let session_matcher = self.session_id.as_ref().and_then(SessionIdMatcher::compile);
+        let log_mapping = self.log_mapping.clone();
+
+        tokio::spawn(async move {
+            let mut stdout_lines = msg_store.stdout_lines_stream();
+            let mut plain_text_processor = log_mapping
+                .is_none()
+                .then(|| Self::create_plain_text_normalizer(entry_index_provider.clone()));
+            let mut session_id_found = false;
+
+            while let Some(Ok(line)) = stdout_lines.next().await {
+                let json = serde_json::from_str::<Value>(line.trim()).ok();
+
+                if !session_id_found
+                    && let Some(matcher) = &session_matcher
+                    && let Some(session_id) = matcher.extract(&line, json.as_ref())
+                {
+                    msg_store.push_session_id(session_id);
+                    session_id_found = true;
+                }
+
+                if let Some(processor) = plain_text_processor.as_mut() {
+                    for patch in processor.process(line + "\n") {
+                        msg_store.push_patch(patch);
+                    }
+                    continue;
+                }
+
+                let (Some(rules), Some(json)) = (&log_mapping, json) else {
+                    continue;
+                };
+                if let Some(rule) = rules.iter().find(|rule| rule.matches(&json)) {
+                    msg_store.push_patch(ConversationPatch::add_normalized_entry(
+                        entry_index_provider.next(),
+                        rule.to_entry(&json),
+                    ));
+                }
+            }
+        });
+    }
+
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn mapping_rule_builds_command_entry() {
+        let rule = JsonlMappingRule {
+            match_pointer: "/type".to_string(),
+            match_value: "exec".to_string(),
+            entry_type: MappedEntryType::CommandRun,
+            content: Some("/payload/cmd".to_string()),
+            tool_name: None,
+            result: None,
+            status: None,
+        };
+        let line = json!({ "type": "exec", "payload": { "cmd": "cargo test" } });
+
+        assert!(rule.matches(&line));
+        assert!(!rule.matches(&json!({ "type": "message" })));
+
+        let entry = rule.to_entry(&line);
+        assert_eq!(entry.content, "cargo test");
+        match entry.entry_type {
+            NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type: ActionType::CommandRun { command, .. },
+                ..
+            } => {
+                assert_eq!(tool_name, "exec");
+                assert_eq!(command, "cargo test");
+            }
+            other => panic!("unexpected entry type: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn mapping_rule_derives_tool_status() {
+        let rule = JsonlMappingRule {
+            match_pointer: "/type".to_string(),
+            match_value: "exec".to_string(),
+            entry_type: MappedEntryType::CommandRun,
+            content: Some("/cmd".to_string()),
+            tool_name: None,
+            result: Some("/output".to_string()),
+            status: Some("/exit_code".to_string()),
+        };
+
+        let failed =
+            rule.to_entry(&json!({ "type": "exec", "cmd": "false", "exit_code": 1, "output": "" }));
+        match failed.entry_type {
+            NormalizedEntryType::ToolUse {
+                status: ToolStatus::Failed,
+                action_type:
+                    ActionType::CommandRun {
+                        result: Some(result),
+                        ..
+                    },
+                ..
+            } => assert!(matches!(
+                result.exit_status,
+                Some(CommandExitStatus::ExitCode { code: 1 })
+            )),
+            other => panic!("unexpected entry type: {other:?}"),
+        }
+
+        let succeeded = rule.to_entry(&json!({ "type": "exec", "cmd": "true", "exit_code": 0 }));
+        assert!(matches!(
+            succeeded.entry_type,
+            NormalizedEntryType::ToolUse {
+                status: ToolStatus::Success,
+                ..
+            }
+        ));
+
+        let running = rule.to_entry(&json!({ "type": "exec", "cmd": "sleep 1" }));
+        assert!(matches!(
+            running.entry_type,
+            NormalizedEntryType::ToolUse {
+                status: ToolStatus::Created,
+                ..
+            }
+        ));
+
+        let result_only = JsonlMappingRule {
+            status: None,
+            ..rule
+        };
+        assert!(matches!(
+            result_only.tool_status(&json!({ "type": "exec" })).0,
+            ToolStatus::Created
+        ));
+        assert!(matches!(
+            result_only
+                .tool_status(&json!({ "type": "exec", "output": "done" }))
+                .0,
+            ToolStatus::Success
+        ));
+        assert!(matches!(
+            result_only
+                .tool_status(&json!({ "type": "exec", "output": "x", "status": "error" }))
+                .0,
+            ToolStatus::Success
+        ));
+    }
+
+    #[test]
+    fn session_id_rules() {
+        let pointer = SessionIdMatcher::compile(&SessionIdRule::JsonPointer {
+            pointer: "/session".to_string(),
+        })
+        .unwrap();
+        let json = json!({ "session": "abc-123" });
+        assert_eq!(pointer.extract("", Some(&json)).as_deref(), Some("abc-123"));
+        assert_eq!(pointer.extract("not json", None), None);
+
+        let regex = SessionIdMatcher::compile(&SessionIdRule::Regex {
+            pattern: r"session: (\S+)".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            regex.extract("started session: s-42", None).as_deref(),
+            Some("s-42")
+        );
+    }
+
+    #[test]
+    fn follow_up_params_substitute_session_id() {
+        let agent: CustomAgent = serde_json::from_value(json!({
+            "command": "my-agent --json",
+            "follow_up_params": ["--resume", "{session_id}"]
+        }))
+        .unwrap();
+        assert_eq!(
+            agent.follow_up_args("s-1").unwrap(),
+            vec!["--resume".to_string(), "s-1".to_string()]
+        );
+
+        let no_follow_up: CustomAgent =
+            serde_json::from_value(json!({ "command": "my-agent" })).unwrap();
+        assert!(matches!(
+            no_follow_up.follow_up_args("s-1"),
+            Err(ExecutorError::FollowUpNotSupported(_))
+        ));
+    }
+}
@@ -126,7 +126,7 @@ async fn spawn_droid(
 
     env.clone()
         .with_profile(cmd_overrides)
-        .apply_to_command(&mut command);
+        .apply_to_command(&mut command)?;
 
     let mut child = command.group_spawn()?;
 
@@ -18,11 +18,12 @@ use crate::executors::qa_mock::QaMockExecutor;
 use crate::{
     actions::{ExecutorAction, review::RepoReviewContext},
     approvals::ExecutorApprovalService,
-    command::CommandBuildError,
+    command::{CmdOverrides, CommandBuildError},
     env::ExecutionEnv,
     executors::{
-        amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot, cursor::CursorAgent,
-        droid::Droid, gemini::Gemini, opencode::Opencode, qwen::QwenCode,
+        acp::Acp, amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot,
+        cursor::CursorAgent, custom::CustomAgent, droid::Droid, gemini::Gemini, opencode::Opencode,
+        qwen::QwenCode,
     },
     logs::utils::patch,
     mcp_config::McpConfig,
@@ -34,6 +35,7 @@ pub mod claude;
 pub mod codex;
 pub mod copilot;
 pub mod cursor;
+pub mod custom;
 pub mod droid;
 pub mod gemini;
 pub mod opencode;
@@ -85,6 +87,8 @@ pub enum ExecutorError {
     SetupHelperNotSupported,
     #[error("Auth required: {0}")]
     AuthRequired(String),
+    #[error("Could not set up the Linux sandbox: {0}")]
+    Sandbox(String),
 }
 
 #[enum_dispatch]
@@ -115,6 +119,8 @@ pub enum CodingAgent {
     QwenCode,
     Copilot,
     Droid,
+    CustomAgent,
+    Acp,
     #[cfg(feature = "qa-mode")]
     QaMock(QaMockExecutor),
 }
@@ -177,17 +183,40 @@ impl CodingAgent {
             | Self::Gemini(_)
             | Self::QwenCode(_)
             | Self::Droid(_)
-            | Self::Opencode(_) => vec![BaseAgentCapability::SessionFork],
+            | Self::Opencode(_)
+            | Self::Acp(_) => vec![BaseAgentCapability::SessionFork],
             Self::Codex(_) => vec![
                 BaseAgentCapability::SessionFork,
                 BaseAgentCapability::SetupHelper,
             ],
             Self::CursorAgent(_) => vec![BaseAgentCapability::SetupHelper],
-            Self::Copilot(_) => vec![],
+            Self::CustomAgent(agent) if agent.follow_up_params.is_some() => {
+                vec![BaseAgentCapability::SessionFork]
+            }
+            Self::Copilot(_) | Self::CustomAgent(_) => vec![],
             #[cfg(feature = "qa-mode")]
             Self::QaMock(_) => vec![], // QA mock doesn't need special capabilities
         }
     }
+
+    /// Command overrides and execution limits configured on the profile
+    pub fn cmd_overrides(&self) -> Option<&CmdOverrides> {
+        match self {
+            Self::ClaudeCode(agent) => Some(&agent.cmd),
+            Self::Amp(agent) => Some(&agent.cmd),
+            Self::Gemini(agent) => Some(&agent.cmd),
+            Self::Codex(agent) => Some(&agent.cmd),
+            Self::Opencode(agent) => Some(&agent.cmd),
+            Self::CursorAgent(agent) => Some(&agent.cmd),
+            Self::QwenCode(agent) => Some(&agent.cmd),
+            Self::Copilot(agent) => Some(&agent.cmd),
+            Self::Droid(agent) => Some(&agent.cmd),
+            Self::CustomAgent(agent) => Some(&agent.cmd),
+            Self::Acp(agent) => Some(&agent.cmd),
+            #[cfg(feature = "qa-mode")]
+            Self::QaMock(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -107,7 +107,7 @@ impl Opencode {
 
         env.clone()
             .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .apply_to_command(&mut command)?;
 
         let child = command.group_spawn()?;
 
@@ -13,8 +13,8 @@ use crate::{
     approvals::ToolCallMetadata,
     logs::{
         ActionType, CommandExitStatus, CommandRunResult, FileChange, NormalizedEntry,
-        NormalizedEntryError, NormalizedEntryType, TodoItem, TokenUsageInfo, ToolResult,
-        ToolStatus,
+        NormalizedEntryError, NormalizedEntryType, TodoItem, TokenUsage, TokenUsageInfo,
+        ToolResult, ToolStatus,
         stderr_processor::normalize_stderr_logs,
         utils::{
             EntryIndexProvider,
@@ -80,6 +80,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                             entry_type: NormalizedEntryType::TokenUsageInfo(TokenUsageInfo {
                                 total_tokens,
                                 model_context_window,
+                                usage: state.run_usage(),
                             }),
                             content: format!(
                                 "Tokens used: {} / Context window: {}",
@@ -163,6 +164,9 @@ struct LogState {
     todo_update_entry: Option<usize>,
     todo_update_fingerprint: Option<String>,
     retry_status_fingerprint: Option<String>,
+    /// Token counts of each assistant message; OpenCode reports them per message, not per run
+    message_usage: HashMap<String, TokenUsage>,
+    last_model: Option<String>,
 }
 
 impl LogState {
@@ -179,9 +183,57 @@ impl LogState {
             todo_update_entry: None,
             todo_update_fingerprint: None,
             retry_status_fingerprint: None,
+            message_usage: HashMap::new(),
+            last_model: None,
         }
     }
 
+    fn record_message_usage(&mut self, info: &MessageInfo) {
+        if info.role != MessageRole::Assistant {
+            return;
+        }
+        let Some(tokens) = &info.tokens else {
+            return;
+        };
+        if let Some(model) = info.model_id() {
+            self.last_model = Some(model.to_string());
+        }
+        let cache = tokens.cache.as_ref();
+        self.message_usage.insert(
+            info.id.clone(),
+            TokenUsage {
+                model: None,
+                input_tokens: tokens.input as u64,
+                // Reasoning tokens are billed as output
+                output_tokens: tokens.output as u64 + tokens.reasoning as u64,
+                cache_read_tokens: cache.map(|c| c.read as u64).unwrap_or(0),
+                cache_write_tokens: cache.map(|c| c.write as u64).unwrap_or(0),
+                cost_usd: info.cost,
+            },
+        );
+    }
+
+    /// Usage of all assistant messages of the run so far
+    fn run_usage(&self) -> Option<TokenUsage> {
+        if self.message_usage.is_empty() {
+            return None;
+        }
+        let mut total = TokenUsage {
+            model: self.last_model.clone(),
+            ..Default::default()
+        };
+        for usage in self.message_usage.values() {
+            total.input_tokens += usage.input_tokens;
+            total.output_tokens += usage.output_tokens;
+            total.cache_read_tokens += usage.cache_read_tokens;
+            total.cache_write_tokens += usage.cache_write_tokens;
+            if let Some(cost) = usage.cost_usd {
+                *total.cost_usd.get_or_insert(0.0) += cost;
+            }
+        }
+        Some(total)
+    }
+
     fn handle_sdk_event(&mut self, raw: &Value, worktree_path: &Path, msg_store: &Arc<MsgStore>) {
         let Some(event) = SdkEvent::parse(raw) else {
             let raw_text = raw.to_string();
@@ -197,6 +249,7 @@ impl LogState {
             SdkEvent::MessageUpdated(event) => {
                 let info = event.info;
                 self.maybe_emit_model_system_message(&info);
+                self.record_message_usage(&info);
                 self.message_roles.insert(info.id, info.role);
             }
             SdkEvent::MessagePartUpdated(event) => {
@@ -124,6 +124,9 @@ pub(super) struct MessageInfo {
     pub(super) model_id: Option<String>,
     #[serde(default)]
     pub(super) tokens: Option<MessageTokens>,
+    /// Cost of the message in USD, as computed by OpenCode
+    #[serde(default)]
+    pub(super) cost: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -132,6 +135,8 @@ pub(super) struct MessageTokens {
     pub(super) input: u32,
     #[serde(default, deserialize_with = "deserialize_f64_as_u32")]
     pub(super) output: u32,
+    #[serde(default, deserialize_with = "deserialize_f64_as_u32")]
+    pub(super) reasoning: u32,
     pub(super) cache: Option<MessageTokensCache>,
 }
 
@@ -139,6 +144,8 @@ pub(super) struct MessageTokens {
 pub(super) struct MessageTokensCache {
     #[serde(default, deserialize_with = "deserialize_f64_as_u32")]
     pub(super) read: u32,
+    #[serde(default, deserialize_with = "deserialize_f64_as_u32")]
+    pub(super) write: u32,
 }
 
 fn deserialize_f64_as_u32<'de, D>(deserializer: D) -> Result<u32, D::Error>
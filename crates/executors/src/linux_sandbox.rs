@@ -0,0 +1,325 @@
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use ts_rs::TS;
+use workspace_utils::path::expand_tilde;
+
+use crate::executors::{BaseCodingAgent, ExecutorError};
+
+/// Config and state directories an agent keeps under the home directory
+pub fn agent_home_paths(agent: BaseCodingAgent) -> &'static [&'static str] {
+    match agent {
+        BaseCodingAgent::ClaudeCode => &[".claude", ".claude.json"],
+        BaseCodingAgent::Codex => &[".codex"],
+        BaseCodingAgent::Gemini => &[".gemini"],
+        BaseCodingAgent::QwenCode => &[".qwen"],
+        BaseCodingAgent::Opencode => &[
+            ".config/opencode",
+            ".local/share/opencode",
+            ".local/state/opencode",
+            ".cache/opencode",
+        ],
+        BaseCodingAgent::CursorAgent => &[".cursor", ".config/cursor"],
+        BaseCodingAgent::Copilot => &[".copilot"],
+        BaseCodingAgent::Droid => &[".factory"],
+        BaseCodingAgent::Amp => &[".config/amp", ".local/share/amp", ".cache/amp"],
+        _ => &[],
+    }
+}
+
+/// Parts of the main repository's git directory that committing in a worktree writes to
+const GIT_COMMON_WRITABLE_PATHS: &[&str] = &["objects", "refs", "logs", "packed-refs"];
+
+/// Landlock sandbox for agent processes on Linux. Outside the workspace, the config directories
+/// of the agent, the object store and refs of the workspace repositories and `writable_paths`,
+/// the filesystem is read-only.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema, Default)]
+pub struct LinuxSandbox {
+    #[serde(default)]
+    #[schemars(
+        title = "Deny Network",
+        description = "Block outgoing TCP connections and listening sockets. Needs Linux 6.7 or later"
+    )]
+    pub deny_network: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Writable Paths",
+        description = "Additional files and directories the agent may write to, e.g. ~/.cargo"
+    )]
+    pub writable_paths: Option<Vec<String>>,
+}
+
+impl LinuxSandbox {
+    /// Paths the sandboxed process may write beneath. `workspace_root` is the workspace directory
+    /// and `repo_paths` its worktrees, whose git metadata lives in the main repositories. Only the
+    /// config directories of `agent` are writable in the home directory.
+    pub fn writable_paths(
+        &self,
+        workspace_root: &Path,
+        repo_paths: &[PathBuf],
+        agent: Option<BaseCodingAgent>,
+    ) -> Vec<PathBuf> {
+        let mut paths = vec![
+            workspace_root.to_path_buf(),
+            std::env::temp_dir(),
+            PathBuf::from("/dev"),
+            npm_cache_dir(),
+        ];
+        paths.extend(repo_paths.iter().flat_map(|repo| git_writable_paths(repo)));
+        if let (Some(home), Some(agent)) = (dirs::home_dir(), agent) {
+            paths.extend(agent_home_paths(agent).iter().map(|path| home.join(path)));
+        }
+        paths.extend(
+            self.writable_paths
+                .iter()
+                .flatten()
+                .map(|path| expand_tilde(path)),
+        );
+        paths
+    }
+
+    /// Restrict `command` once it has been spawned. Fails when the kernel has no Landlock support
+    /// or the ruleset cannot be built, rather than running the agent unsandboxed.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn apply_to_command(
+        &self,
+        command: &mut Command,
+        writable_paths: &[PathBuf],
+    ) -> Result<(), ExecutorError> {
+        use landlock::{
+            ABI, Access, AccessFs, AccessNet, CompatLevel, Compatible, Ruleset, RulesetAttr,
+            RulesetCreatedAttr, RulesetError, RulesetStatus, path_beneath_rules,
+        };
+
+        // npx, which most agents are launched through, caches packages outside the home
+        // directory so a sandboxed agent cannot change what unsandboxed runs execute
+        if !command
+            .as_std()
+            .get_envs()
+            .any(|(key, _)| key.eq_ignore_ascii_case("npm_config_cache"))
+        {
+            let cache = npm_cache_dir();
+            std::fs::create_dir_all(&cache).map_err(|e| {
+                ExecutorError::Sandbox(format!("cannot create {}: {e}", cache.display()))
+            })?;
+            command.env("npm_config_cache", cache);
+        }
+
+        let abi = ABI::V5;
+        let existing: Vec<&PathBuf> = writable_paths.iter().filter(|p| p.exists()).collect();
+        let ruleset = (|| -> Result<_, RulesetError> {
+            // Kernels without Landlock fail here instead of silently not enforcing the ruleset
+            let mut ruleset = Ruleset::default()
+                .set_compatibility(CompatLevel::HardRequirement)
+                .handle_access(AccessFs::from_write(ABI::V1))?
+                .set_compatibility(CompatLevel::BestEffort)
+                .handle_access(AccessFs::from_write(abi))?;
+            if self.deny_network {
+                // Refuse to run rather than silently leaving the network open on older kernels
+                ruleset = ruleset
+                    .set_compatibility(CompatLevel::HardRequirement)
+                    .handle_access(AccessNet::from_all(abi))?
+                    .set_compatibility(CompatLevel::BestEffort);
+            }
+            ruleset
+                .create()?
+                .add_rules(path_beneath_rules(existing, AccessFs::from_all(abi)))
+        })()
+        .map_err(|e| ExecutorError::Sandbox(e.to_string()))?;
+
+        // The ruleset is built before forking so the child only makes the restricting syscalls
+        let mut ruleset = Some(ruleset);
+        unsafe {
+            command.pre_exec(move || {
+                let ruleset = ruleset.take().ok_or(std::io::ErrorKind::PermissionDenied)?;
+                let status = ruleset
+                    .restrict_self()
+                    .map_err(|_| std::io::ErrorKind::PermissionDenied)?;
+                if status.ruleset == RulesetStatus::NotEnforced {
+                    return Err(std::io::ErrorKind::Unsupported.into());
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub(crate) fn apply_to_command(
+        &self,
+        _command: &mut Command,
+        _writable_paths: &[PathBuf],
+    ) -> Result<(), ExecutorError> {
+        tracing::warn!("The Linux sandbox is only available on Linux, running unsandboxed");
+        Ok(())
+    }
+}
+
+/// npm cache of sandboxed processes, kept apart from the one in the home directory
+fn npm_cache_dir() -> PathBuf {
+    workspace_utils::cache_dir().join("sandbox-npm")
+}
+
+/// Where committing in `worktree` writes in its main repository: the worktree's own git
+/// directory and the shared object store and refs. Hooks and config stay read-only, so the
+/// sandboxed process cannot make git run code outside the sandbox.
+pub fn git_writable_paths(worktree: &Path) -> Vec<PathBuf> {
+    let Some(gitdir) = worktree_git_dir(worktree) else {
+        return Vec::new();
+    };
+    // <repo>/.git/worktrees/<name>
+    let common_dir = gitdir
+        .parent()
+        .and_then(Path::parent)
+        .map(Path::to_path_buf);
+    let mut paths = vec![gitdir];
+    if let Some(common_dir) = common_dir {
+        paths.extend(
+            GIT_COMMON_WRITABLE_PATHS
+                .iter()
+                .map(|path| common_dir.join(path)),
+        );
+    }
+    paths
+}
+
+/// The directory in the main repository that `.git` of a worktree points to
+fn worktree_git_dir(worktree: &Path) -> Option<PathBuf> {
+    let contents = std::fs::read_to_string(worktree.join(".git")).ok()?;
+    let gitdir = PathBuf::from(contents.trim().strip_prefix("gitdir:")?.trim());
+    Some(if gitdir.is_absolute() {
+        gitdir
+    } else {
+        worktree.join(gitdir)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command as StdCommand;
+
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = StdCommand::new("git")
+            .current_dir(dir)
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    /// A repository with one commit and a worktree of it, as workspaces are set up
+    fn repo_with_worktree(root: &Path) -> (PathBuf, PathBuf) {
+        let repo = root.join("repo");
+        let worktree = root.join("workspace").join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q"]);
+        git(
+            &repo,
+            &[
+                "-c",
+                "user.name=vk",
+                "-c",
+                "user.email=vk@example.com",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        );
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "vk/test",
+                worktree.to_str().unwrap(),
+            ],
+        );
+        (repo, worktree)
+    }
+
+    #[test]
+    fn writable_paths_cover_workspace_agent_dirs_and_extra_paths() {
+        let sandbox = LinuxSandbox {
+            deny_network: false,
+            writable_paths: Some(vec!["/opt/cache".to_string()]),
+        };
+        let workspace = PathBuf::from("/tmp/vk/workspace");
+        let paths = sandbox.writable_paths(
+            &workspace,
+            &[workspace.join("missing-repo")],
+            Some(BaseCodingAgent::Codex),
+        );
+
+        assert_eq!(paths[0], workspace);
+        assert!(paths.contains(&PathBuf::from("/dev")));
+        assert!(paths.contains(&PathBuf::from("/opt/cache")));
+        if let Some(home) = dirs::home_dir() {
+            assert!(paths.contains(&home.join(".codex")));
+            assert!(!paths.contains(&home.join(".claude")));
+            assert!(!paths.contains(&home.join(".config")));
+        }
+    }
+
+    #[test]
+    fn worktrees_write_to_objects_and_refs_but_not_hooks() {
+        let root = tempfile::tempdir().unwrap();
+        let (repo, worktree) = repo_with_worktree(root.path());
+        let git_dir = repo.join(".git").canonicalize().unwrap();
+
+        let paths: Vec<PathBuf> = git_writable_paths(&worktree)
+            .iter()
+            .filter_map(|path| path.canonicalize().ok())
+            .collect();
+        assert!(paths.contains(&git_dir.join("objects")));
+        assert!(paths.contains(&git_dir.join("refs")));
+        assert!(paths.contains(&git_dir.join("worktrees").join("repo")));
+        assert!(
+            !paths
+                .iter()
+                .any(|path| git_dir.join("hooks").starts_with(path))
+        );
+        assert!(
+            !paths
+                .iter()
+                .any(|path| git_dir.join("config").starts_with(path))
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn sandboxed_process_cannot_write_git_hooks() {
+        let root = tempfile::tempdir().unwrap();
+        let (repo, worktree) = repo_with_worktree(root.path());
+        let hooks = repo.join(".git").join("hooks");
+        // The temp dir holding the repository is left out, unlike in `writable_paths`
+        let mut writable = vec![worktree.clone()];
+        writable.extend(git_writable_paths(&worktree));
+
+        let run = |script: String| {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(script).current_dir(&worktree);
+            LinuxSandbox::default()
+                .apply_to_command(&mut command, &writable)
+                .map(|()| command)
+        };
+
+        let Ok(mut command) = run(format!("echo ok > {}", worktree.join("file").display())) else {
+            eprintln!("Landlock is not available, skipping");
+            return;
+        };
+        assert!(command.status().await.unwrap().success());
+
+        let hook = hooks.join("pre-commit");
+        let mut command = run(format!("echo 'exit 0' > {}", hook.display())).unwrap();
+        assert!(!command.status().await.unwrap().success());
+        assert!(!hook.exists());
+    }
+}
@@ -0,0 +1,355 @@
+//! Record and replay of raw executor output.
+//!
+//! A fixture holds the raw stdout and stderr of an execution together with when each chunk
+//! arrived. Replaying it through an executor's `normalize_logs` reproduces log normalization
+//! offline, so normalization bugs can be debugged and locked in with golden tests.
+//!
+//! Fixtures are JSONL: a [`ReplayHeader`] line followed by one [`ReplayEvent`] per line.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use workspace_utils::{log_msg::LogMsg, msg_store::MsgStore};
+
+use crate::{
+    executors::{CodingAgent, StandardCodingAgentExecutor},
+    logs::{NormalizedEntry, utils::patch::extract_normalized_entry_from_patch},
+    profile::{ExecutorConfigs, ExecutorProfileId},
+};
+
+/// How long normalization may keep producing patches after the last raw chunk was replayed
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(5);
+const SETTLE_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("replay fixture has no header line")]
+    MissingHeader,
+    #[error("invalid replay fixture line {line}: {source}")]
+    InvalidLine {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplayHeader {
+    pub executor_profile_id: ExecutorProfileId,
+    /// Directory the agent ran in; normalizers use it to make paths relative
+    pub worktree_path: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplayEvent {
+    /// Milliseconds since the first recorded chunk
+    pub offset_ms: u64,
+    pub stream: ReplayStream,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayFixture {
+    pub header: ReplayHeader,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl ReplayFixture {
+    /// Build a fixture from timestamped raw logs. Anything but stdout and stderr is dropped, as
+    /// normalizers only read raw output.
+    pub fn record(
+        header: ReplayHeader,
+        logs: impl IntoIterator<Item = (DateTime<Utc>, LogMsg)>,
+    ) -> Self {
+        let mut start = None;
+        let events = logs
+            .into_iter()
+            .filter_map(|(at, msg)| {
+                let (stream, content) = match msg {
+                    LogMsg::Stdout(content) => (ReplayStream::Stdout, content),
+                    LogMsg::Stderr(content) => (ReplayStream::Stderr, content),
+                    _ => return None,
+                };
+                let start = *start.get_or_insert(at);
+                Some(ReplayEvent {
+                    offset_ms: (at - start).num_milliseconds().max(0) as u64,
+                    stream,
+                    content,
+                })
+            })
+            .collect();
+        Self { header, events }
+    }
+
+    pub fn to_jsonl(&self) -> Result<String, serde_json::Error> {
+        let mut out = serde_json::to_string(&self.header)?;
+        out.push('\n');
+        for event in &self.events {
+            out.push_str(&serde_json::to_string(event)?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    pub fn from_jsonl(input: &str) -> Result<Self, ReplayError> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let (idx, header) = lines.next().ok_or(ReplayError::MissingHeader)?;
+        let header = serde_json::from_str(header).map_err(|source| ReplayError::InvalidLine {
+            line: idx + 1,
+            source,
+        })?;
+        let events = lines
+            .map(|(idx, line)| {
+                serde_json::from_str(line).map_err(|source| ReplayError::InvalidLine {
+                    line: idx + 1,
+                    source,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { header, events })
+    }
+
+    pub async fn load(path: &Path) -> Result<Self, ReplayError> {
+        let input = tokio::fs::read_to_string(path).await?;
+        Self::from_jsonl(&input)
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        tokio::fs::write(path, self.to_jsonl()?).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayTiming {
+    /// Wait between chunks as long as the agent originally did
+    Original,
+    /// Push all chunks at once
+    Instant,
+}
+
+/// Feeds a recorded fixture back through an executor's log normalization
+pub struct ReplayExecutor {
+    agent: CodingAgent,
+    fixture: ReplayFixture,
+    timing: ReplayTiming,
+}
+
+impl ReplayExecutor {
+    pub fn new(agent: CodingAgent, fixture: ReplayFixture) -> Self {
+        Self {
+            agent,
+            fixture,
+            timing: ReplayTiming::Original,
+        }
+    }
+
+    /// Replay with the executor profile the fixture was recorded with, as configured in `configs`
+    pub fn from_fixture(configs: &ExecutorConfigs, fixture: ReplayFixture) -> Self {
+        let agent = configs.get_coding_agent_or_default(&fixture.header.executor_profile_id);
+        Self::new(agent, fixture)
+    }
+
+    pub fn with_timing(mut self, timing: ReplayTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Replay the fixture and return the store once normalization stopped producing patches
+    pub async fn run(&self) -> Arc<MsgStore> {
+        let msg_store = Arc::new(MsgStore::new());
+        self.agent
+            .normalize_logs(msg_store.clone(), &self.fixture.header.worktree_path);
+
+        let start = tokio::time::Instant::now();
+        for event in &self.fixture.events {
+            if self.timing == ReplayTiming::Original {
+                tokio::time::sleep_until(start + Duration::from_millis(event.offset_ms)).await;
+            }
+            match event.stream {
+                ReplayStream::Stdout => msg_store.push_stdout(event.content.clone()),
+                ReplayStream::Stderr => msg_store.push_stderr(event.content.clone()),
+            }
+        }
+        msg_store.push_finished();
+
+        wait_until_settled(&msg_store).await;
+        msg_store
+    }
+}
+
+async fn wait_until_settled(msg_store: &MsgStore) {
+    let deadline = tokio::time::Instant::now() + SETTLE_TIMEOUT;
+    let mut len = msg_store.get_history().len();
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(SETTLE_INTERVAL).await;
+        let next_len = msg_store.get_history().len();
+        if next_len == len {
+            return;
+        }
+        len = next_len;
+    }
+    tracing::warn!("Log normalization still running after replay settle timeout");
+}
+
+/// The conversation the normalizer produced, in entry order and without timestamps so it can be
+/// compared against a golden file
+pub fn normalized_entries(msg_store: &MsgStore) -> Vec<NormalizedEntry> {
+    indexed_normalized_entries(msg_store)
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect()
+}
+
+/// Like [`normalized_entries`], with the index each entry has in the conversation
+pub fn indexed_normalized_entries(msg_store: &MsgStore) -> Vec<(usize, NormalizedEntry)> {
+    let mut entries = BTreeMap::new();
+    for msg in msg_store.get_history() {
+        if let LogMsg::JsonPatch(patch) = msg
+            && let Some((idx, mut entry)) = extract_normalized_entry_from_patch(&patch)
+        {
+            entry.timestamp = None;
+            entries.insert(idx, entry);
+        }
+    }
+    entries.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{executors::BaseCodingAgent, logs::NormalizedEntryType};
+
+    const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/replay");
+
+    #[test]
+    fn fixture_roundtrips_through_jsonl() {
+        let at = |ms| Utc.timestamp_millis_opt(1_700_000_000_000 + ms).unwrap();
+        let fixture = ReplayFixture::record(
+            ReplayHeader {
+                executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::Codex),
+                worktree_path: PathBuf::from("/tmp/worktree"),
+            },
+            vec![
+                (at(0), LogMsg::Stdout("{\"a\":1}\n".to_string())),
+                (at(5), LogMsg::SessionId("ignored".to_string())),
+                (at(250), LogMsg::Stderr("warning".to_string())),
+            ],
+        );
+
+        assert_eq!(
+            fixture.events,
+            vec![
+                ReplayEvent {
+                    offset_ms: 0,
+                    stream: ReplayStream::Stdout,
+                    content: "{\"a\":1}\n".to_string(),
+                },
+                ReplayEvent {
+                    offset_ms: 250,
+                    stream: ReplayStream::Stderr,
+                    content: "warning".to_string(),
+                },
+            ]
+        );
+        let jsonl = fixture.to_jsonl().unwrap();
+        assert_eq!(ReplayFixture::from_jsonl(&jsonl).unwrap(), fixture);
+    }
+
+    #[tokio::test]
+    async fn replays_claude_fixture_through_normalizer() {
+        let fixture = ReplayFixture::load(&Path::new(FIXTURE_DIR).join("claude_code.jsonl"))
+            .await
+            .unwrap();
+        let msg_store = ReplayExecutor::from_fixture(&ExecutorConfigs::from_defaults(), fixture)
+            .with_timing(ReplayTiming::Instant)
+            .run()
+            .await;
+
+        let entries = normalized_entries(&msg_store);
+        assert!(entries.iter().any(|entry| matches!(
+            &entry.entry_type,
+            NormalizedEntryType::ToolUse { tool_name, .. } if tool_name == "Read"
+        )));
+        assert!(entries.iter().any(|entry| {
+            matches!(entry.entry_type, NormalizedEntryType::AssistantMessage)
+                && entry.content.contains("README.md")
+        }));
+    }
+
+    /// Compares every fixture with its committed `.golden.json` conversation. Run with
+    /// `UPDATE_GOLDEN=1` to rewrite the golden files after an intended normalizer change.
+    #[tokio::test]
+    async fn fixtures_match_golden_conversations() {
+        let update = std::env::var("UPDATE_GOLDEN").is_ok_and(|value| value == "1");
+        let configs = ExecutorConfigs::from_defaults();
+        let mut fixtures: Vec<_> = std::fs::read_dir(FIXTURE_DIR)
+            .unwrap()
+            .map(|dir_entry| dir_entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect();
+        fixtures.sort();
+        assert!(!fixtures.is_empty(), "no replay fixtures in {FIXTURE_DIR}");
+
+        for path in fixtures {
+            let fixture = ReplayFixture::load(&path).await.unwrap();
+            let msg_store = ReplayExecutor::from_fixture(&configs, fixture)
+                .with_timing(ReplayTiming::Instant)
+                .run()
+                .await;
+            // Metadata holds raw agent payloads, which would make the goldens unreadable
+            let entries: Vec<_> = normalized_entries(&msg_store)
+                .into_iter()
+                .map(|entry| NormalizedEntry {
+                    metadata: None,
+                    ..entry
+                })
+                .collect();
+            let actual = serde_json::to_value(&entries).unwrap();
+
+            let golden_path = path.with_extension("golden.json");
+            if update {
+                let json = serde_json::to_string_pretty(&actual).unwrap() + "\n";
+                std::fs::write(&golden_path, json).unwrap();
+                continue;
+            }
+            let expected = std::fs::read_to_string(&golden_path).unwrap_or_else(|e| {
+                panic!(
+                    "missing golden file {} ({e}), run with UPDATE_GOLDEN=1 to create it",
+                    golden_path.display()
+                )
+            });
+            let expected: serde_json::Value = serde_json::from_str(&expected).unwrap();
+            assert_eq!(
+                actual,
+                expected,
+                "normalized conversation of {} differs from {}",
+                path.display(),
+                golden_path.display()
+            );
+        }
+    }
+}
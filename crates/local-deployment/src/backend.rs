@@ -0,0 +1,167 @@
+//! Where the processes of executions run. [`LocalContainerService`] is generic over an
+//! [`ExecutionBackend`], so the same workspace handling runs processes on this machine, in OCI
+//! containers or on SSH hosts.
+//!
+//! [`LocalContainerService`]: crate::container::LocalContainerService
+
+use std::{path::Path, sync::Arc};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use db::models::repo::Repo;
+use executors::{actions::ExecutorAction, env::CommandWrapper};
+use services::services::container::ContainerError;
+use uuid::Uuid;
+
+use crate::{
+    oci::{self, CliRunner, OciBackend},
+    resources::ResourceLimits,
+    ssh::{self, SshBackend},
+};
+
+/// How the commands of an execution are run
+pub struct Placement {
+    pub wrapper: Arc<dyn CommandWrapper>,
+    /// The worktrees the commands change are on another machine and are copied back with
+    /// [`ExecutionBackend::sync`]
+    pub remote: bool,
+}
+
+/// Runs the processes of executions. Implemented by [`HostBackend`], [`OciBackend`],
+/// [`SshBackend`] and [`WorkspaceBackends`], which picks one of the others for each workspace.
+#[async_trait]
+pub trait ExecutionBackend: Clone + Send + Sync + 'static {
+    /// How to run the commands of an execution of a workspace, or None to run them on this
+    /// machine under `limits`. Backends that place an execution enforce `limits` themselves.
+    async fn place(
+        &self,
+        exec_id: Uuid,
+        workspace_dir: &Path,
+        branch: &str,
+        repos: &[Repo],
+        action: &ExecutorAction,
+        limits: Option<ResourceLimits>,
+    ) -> Result<Option<Placement>, ContainerError>;
+
+    /// Copy the changes of a remote execution into the local worktrees
+    async fn sync(&self, _exec_id: Uuid) {}
+
+    /// Stop the processes of an execution that killing the local child does not reach
+    async fn stop(&self, _exec_id: Uuid) {}
+
+    /// Clean up after an execution that exited or was stopped
+    async fn release(&self, _exec_id: Uuid) {}
+
+    /// Remove what the executions of a deleted workspace left outside its local worktrees
+    async fn remove_workspace(&self, _workspace_dir: &Path, _repos: &[Repo]) {}
+}
+
+/// Runs every process on this machine
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostBackend;
+
+#[async_trait]
+impl ExecutionBackend for HostBackend {
+    async fn place(
+        &self,
+        _exec_id: Uuid,
+        _workspace_dir: &Path,
+        _branch: &str,
+        _repos: &[Repo],
+        _action: &ExecutorAction,
+        _limits: Option<ResourceLimits>,
+    ) -> Result<Option<Placement>, ContainerError> {
+        Ok(None)
+    }
+}
+
+/// Runs workspaces with a repo that sets an SSH host on that host, those with a repo that sets
+/// a container image in a container, and the rest on this machine
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceBackends {
+    /// None without podman or docker
+    oci: Option<OciBackend>,
+    /// None without an ssh client
+    ssh: Option<SshBackend>,
+}
+
+impl WorkspaceBackends {
+    /// The container runtime and ssh client that are installed
+    pub async fn detect() -> Self {
+        Self {
+            oci: CliRunner::detect()
+                .await
+                .map(|runner| OciBackend::new(Arc::new(runner))),
+            ssh: SshBackend::detect().await,
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionBackend for WorkspaceBackends {
+    async fn place(
+        &self,
+        exec_id: Uuid,
+        workspace_dir: &Path,
+        branch: &str,
+        repos: &[Repo],
+        action: &ExecutorAction,
+        limits: Option<ResourceLimits>,
+    ) -> Result<Option<Placement>, ContainerError> {
+        if let Some(host) = ssh::host_for_repos(repos) {
+            let backend = self.ssh.as_ref().ok_or_else(|| {
+                ContainerError::Other(anyhow!(
+                    "A repository of this workspace runs on {host}, but no ssh client is installed"
+                ))
+            })?;
+            return backend
+                .place(exec_id, workspace_dir, branch, repos, action, limits)
+                .await;
+        }
+        if let Some(image) = oci::image_for_action(repos, action) {
+            let backend = self.oci.as_ref().ok_or_else(|| {
+                ContainerError::Other(anyhow!(
+                    "A repository of this workspace runs in container image {image}, but neither podman nor docker is installed"
+                ))
+            })?;
+            return backend
+                .place(exec_id, workspace_dir, branch, repos, action, limits)
+                .await;
+        }
+        Ok(None)
+    }
+
+    async fn sync(&self, exec_id: Uuid) {
+        if let Some(ssh) = &self.ssh {
+            ssh.sync(exec_id).await;
+        }
+    }
+
+    async fn stop(&self, exec_id: Uuid) {
+        if let Some(oci) = &self.oci {
+            oci.stop(exec_id).await;
+        }
+        // The exit monitor still copies the remote changes back before forgetting the execution
+        if let Some(ssh) = &self.ssh {
+            ssh.stop(exec_id).await;
+        }
+    }
+
+    async fn release(&self, exec_id: Uuid) {
+        if let Some(oci) = &self.oci {
+            oci.release(exec_id).await;
+        }
+        if let Some(ssh) = &self.ssh {
+            ssh.release(exec_id).await;
+        }
+    }
+
+    async fn remove_workspace(&self, workspace_dir: &Path, repos: &[Repo]) {
+        if let Some(oci) = &self.oci {
+            oci.remove_workspace(workspace_dir, repos).await;
+        }
+        if let Some(ssh) = &self.ssh {
+            ssh.remove_workspace(workspace_dir, repos).await;
+        }
+    }
+}
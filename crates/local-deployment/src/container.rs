@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io,
     path::{Path, PathBuf},
     sync::Arc,
@@ -14,9 +14,13 @@ use db::{
     models::{
         coding_agent_turn::CodingAgentTurn,
         execution_process::{
-            ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+            ExecutionContext, ExecutionProcess, ExecutionProcessCompletionReason,
+            ExecutionProcessRunReason, ExecutionProcessStatus,
         },
         execution_process_repo_state::ExecutionProcessRepoState,
+        execution_process_token_usage::{
+            ExecutionProcessTokenUsage, UpsertExecutionProcessTokenUsage,
+        },
         repo::Repo,
         scratch::{DraftFollowUpData, Scratch, ScratchType},
         session::{Session, SessionError},
@@ -33,22 +37,30 @@ use executors::{
         coding_agent_initial::CodingAgentInitialRequest,
     },
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
+    command::CmdOverrides,
     env::{ExecutionEnv, RepoContext},
     executors::{BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal, InterruptSender},
-    logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
+    logs::{NormalizedEntryType, TokenUsage, utils::patch::extract_normalized_entry_from_patch},
+    profile::{ExecutorConfigs, ExecutorProfileId},
+};
+use futures::{
+    FutureExt, StreamExt, TryStreamExt,
+    stream::{BoxStream, select},
 };
-use futures::{FutureExt, TryStreamExt, stream::select};
 use serde_json::json;
 use services::services::{
     analytics::AnalyticsContext,
     approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
-    config::Config,
+    budget::find_exhausted_budget,
+    config::{Config, ModelPrice},
     container::{ContainerError, ContainerRef, ContainerService},
     diff_stream::{self, DiffStreamHandle},
+    executor_fallback::fallback_reason_from_logs,
     git::{GitCli, GitService},
     image::ImageService,
     notification::NotificationService,
     queued_message::QueuedMessageService,
+    workspace_lock::WorkspaceLocks,
     workspace_manager::{RepoWorkspaceInput, WorkspaceManager},
 };
 use tokio::{sync::RwLock, task::JoinHandle};
@@ -60,10 +72,23 @@ use utils::{
 };
 use uuid::Uuid;
 
-use crate::{command, copy};
+use crate::{
+    backend::{ExecutionBackend, WorkspaceBackends},
+    command, copy,
+    oci::OciBackend,
+    resources::{self, AppliedLimits, ResourceLimits, UsageSampler},
+    ssh::SshBackend,
+};
+
+/// How often the CPU and memory use of running executions is sampled
+const RESOURCE_SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+/// How often the worktrees of executions running over SSH are copied back to this machine
+const REMOTE_SYNC_INTERVAL: Duration = Duration::from_secs(5);
 
+/// Runs the processes of workspaces on this machine, or wherever `B` places them. Worktrees,
+/// diffs and commits are always handled on this machine.
 #[derive(Clone)]
-pub struct LocalContainerService {
+pub struct LocalContainerService<B: ExecutionBackend = WorkspaceBackends> {
     db: DBService,
     child_store: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AsyncGroupChild>>>>>,
     interrupt_senders: Arc<RwLock<HashMap<Uuid, InterruptSender>>>,
@@ -75,9 +100,19 @@ pub struct LocalContainerService {
     approvals: Approvals,
     queued_message_service: QueuedMessageService,
     notification_service: NotificationService,
+    workspace_locks: WorkspaceLocks,
+    /// Where the processes of executions run
+    backend: B,
 }
 
-impl LocalContainerService {
+/// Runs every process in a container of the image its repos set, for servers that only run
+/// untrusted repositories
+pub type OciContainerService = LocalContainerService<OciBackend>;
+
+/// Runs every process on the SSH host its repos set, or the default host of the backend
+pub type SshContainerService = LocalContainerService<SshBackend>;
+
+impl<B: ExecutionBackend> LocalContainerService<B> {
     #[allow(clippy::too_many_arguments)]
     pub async fn new(
         db: DBService,
@@ -88,6 +123,7 @@ impl LocalContainerService {
         analytics: Option<AnalyticsContext>,
         approvals: Approvals,
         queued_message_service: QueuedMessageService,
+        backend: B,
     ) -> Self {
         let child_store = Arc::new(RwLock::new(HashMap::new()));
         let interrupt_senders = Arc::new(RwLock::new(HashMap::new()));
@@ -105,6 +141,8 @@ impl LocalContainerService {
             approvals,
             queued_message_service,
             notification_service,
+            workspace_locks: WorkspaceLocks::default(),
+            backend,
         };
 
         container.spawn_workspace_cleanup();
@@ -369,6 +407,8 @@ impl LocalContainerService {
         &self,
         exec_id: &Uuid,
         exit_signal: Option<ExecutorExitSignal>,
+        msg_store: Option<Arc<MsgStore>>,
+        limits: Option<&CmdOverrides>,
     ) -> JoinHandle<()> {
         let exec_id = *exec_id;
         let child_store = self.child_store.clone();
@@ -380,12 +420,23 @@ impl LocalContainerService {
 
         let mut process_exit_rx = self.spawn_os_exit_watcher(exec_id);
 
+        let max_runtime_secs = limits.and_then(|limits| limits.max_runtime_secs);
+        let max_tool_calls = limits.and_then(|limits| limits.max_tool_calls);
+        // Only the stream is kept so the MsgStore can be dropped once the execution ends
+        let log_stream = max_tool_calls
+            .and(msg_store)
+            .map(|store| store.history_plus_stream());
+
         tokio::spawn(async move {
             let mut exit_signal_future = exit_signal
                 .map(|rx| rx.boxed()) // wait for result
                 .unwrap_or_else(|| std::future::pending().boxed()); // no signal, stall forever
 
+            let mut limit_future =
+                wait_for_execution_limit(max_runtime_secs, max_tool_calls, log_stream).boxed();
+
             let status_result: std::io::Result<std::process::ExitStatus>;
+            let mut limit_reason = None;
 
             // Wait for process to exit, or exit signal from executor
             tokio::select! {
@@ -412,34 +463,97 @@ impl LocalContainerService {
                 exit_status_result = &mut process_exit_rx => {
                     status_result = exit_status_result.unwrap_or_else(|e| Err(std::io::Error::other(e)));
                 }
-            }
+                // Runtime or tool call limit of the executor profile exceeded
+                (reason, message) = &mut limit_future => {
+                    tracing::info!("Stopping execution {}: {}", exec_id, message);
+                    // Stderr is normalized into an error entry and persisted with the raw logs
+                    if let Some(store) = msg_stores.read().await.get(&exec_id) {
+                        store.push_stderr(message);
+                    }
 
-            let (exit_code, status) = match status_result {
-                Ok(exit_status) => {
-                    let code = exit_status.code().unwrap_or(-1) as i64;
-                    let status = if exit_status.success() {
-                        ExecutionProcessStatus::Completed
-                    } else {
-                        ExecutionProcessStatus::Failed
-                    };
-                    (Some(code), status)
+                    // Try graceful interrupt first, then kill the process group
+                    if let Some(interrupt_sender) = container.take_interrupt_sender(&exec_id).await {
+                        let _ = interrupt_sender.send(());
+                        if tokio::time::timeout(Duration::from_secs(5), &mut process_exit_rx)
+                            .await
+                            .is_err()
+                        {
+                            tracing::debug!("Graceful shutdown timed out for process {}, force killing", exec_id);
+                        }
+                    }
+                    if let Some(child_lock) = child_store.read().await.get(&exec_id).cloned() {
+                        let mut child = child_lock.write().await;
+                        if let Err(err) = command::kill_process_group(&mut child).await {
+                            tracing::error!("Failed to kill process group after execution limit: {} {}", exec_id, err);
+                        }
+                    }
+
+                    limit_reason = Some(reason);
+                    status_result = Ok(failure_exit_status());
                 }
-                Err(_) => (None, ExecutionProcessStatus::Failed),
-            };
+            }
+            drop(limit_future);
+
+            let (exit_code, status) = completion_status(&status_result, limit_reason.is_some());
 
-            if !ExecutionProcess::was_stopped(&db.pool, exec_id).await
-                && let Err(e) =
+            if !ExecutionProcess::was_stopped(&db.pool, exec_id).await {
+                if let Err(e) =
                     ExecutionProcess::update_completion(&db.pool, exec_id, status, exit_code).await
-            {
-                tracing::error!("Failed to update execution process completion: {}", e);
+                {
+                    tracing::error!("Failed to update execution process completion: {}", e);
+                } else if let Some(reason) = limit_reason
+                    && let Err(e) =
+                        ExecutionProcess::set_completion_reason(&db.pool, exec_id, reason).await
+                {
+                    tracing::error!("Failed to record execution completion reason: {}", e);
+                }
             }
 
+            // Bring the changes of an execution that ran on another machine into the local
+            // worktrees before they are committed
+            container.backend.sync(exec_id).await;
+
             if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
                 // Update executor session summary if available
                 if let Err(e) = container.update_executor_session_summary(&exec_id).await {
                     tracing::warn!("Failed to update executor session summary: {}", e);
                 }
 
+                // Retry coding agents that failed on auth or rate limits with a fallback profile
+                let fallback_reason = if ctx.execution_process.status
+                    == ExecutionProcessStatus::Failed
+                    && ctx.execution_process.run_reason == ExecutionProcessRunReason::CodingAgent
+                {
+                    msg_stores
+                        .read()
+                        .await
+                        .get(&exec_id)
+                        .and_then(|store| fallback_reason_from_logs(&store.get_history()))
+                } else {
+                    None
+                };
+                let fallback_started = match fallback_reason {
+                    Some(reason) => match container
+                        .try_start_fallback_execution(
+                            &ctx.workspace,
+                            &ctx.execution_process,
+                            reason,
+                        )
+                        .await
+                    {
+                        Ok(fallback_process) => fallback_process.is_some(),
+                        Err(e) => {
+                            tracing::error!(
+                                "Failed to start fallback executor for {}: {}",
+                                exec_id,
+                                e
+                            );
+                            false
+                        }
+                    },
+                    None => false,
+                };
+
                 let success = matches!(
                     ctx.execution_process.status,
                     ExecutionProcessStatus::Completed
@@ -494,7 +608,19 @@ impl LocalContainerService {
                     }
                 }
 
-                if container.should_finalize(&ctx) {
+                // The next stage of a project pipeline takes over the task once this one is
+                // done. A stage that fails to start fails the run, and the task is finalized.
+                let pipeline_advanced = if !fallback_started && container.should_finalize(&ctx) {
+                    container.advance_pipeline(&ctx).await.unwrap_or_else(|e| {
+                        tracing::error!("Failed to advance pipeline: {}", e);
+                        false
+                    })
+                } else {
+                    false
+                };
+
+                // A running fallback takes over the task, so it is not finalized yet
+                if !fallback_started && !pipeline_advanced && container.should_finalize(&ctx) {
                     // Only execute queued messages if the execution succeeded
                     // If it failed or was killed, just clear the queue and finalize
                     let should_execute_queued = !matches!(
@@ -565,6 +691,15 @@ impl LocalContainerService {
                         "exit_code": ctx.execution_process.exit_code,
                     })));
                 }
+
+                // Index the conversation for log search while its MsgStore is still around
+                if matches!(
+                    ctx.execution_process.run_reason,
+                    ExecutionProcessRunReason::CodingAgent
+                ) && let Err(e) = container.index_execution_logs(&exec_id).await
+                {
+                    tracing::warn!("Failed to index logs of execution {}: {}", exec_id, e);
+                }
             }
 
             // Now that commit/next-action/finalization steps for this process are complete,
@@ -587,6 +722,7 @@ impl LocalContainerService {
 
             // Cleanup child handle
             child_store.write().await.remove(&exec_id);
+            container.backend.release(exec_id).await;
         })
     }
 
@@ -717,6 +853,200 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Spawn a background task that streams samples of the CPU and memory use of an execution's
+    /// process group into the MsgStore while it runs, enforces a sampled memory limit, and
+    /// releases the limits and records the peaks once it ends.
+    fn spawn_resource_monitor(
+        &self,
+        exec_id: Uuid,
+        pgid: u32,
+        applied: Option<AppliedLimits>,
+    ) -> JoinHandle<()> {
+        let db = self.db.clone();
+        let msg_stores = self.msg_stores.clone();
+
+        tokio::spawn(async move {
+            let memory_limit = applied
+                .as_ref()
+                .and_then(AppliedLimits::sampled_memory_limit);
+            let mut sampler = UsageSampler::new(pgid);
+            let mut peak: Option<(u64, f64)> = None;
+            let mut interval = tokio::time::interval(RESOURCE_SAMPLE_INTERVAL);
+
+            loop {
+                interval.tick().await;
+                let usage = sampler.sample().await;
+                // The store is removed once the execution has finished
+                let stores = msg_stores.read().await;
+                let Some(store) = stores.get(&exec_id) else {
+                    break;
+                };
+                let Some(usage) = usage else {
+                    continue;
+                };
+                store.push_resource_usage(usage);
+                let (rss, cpu) = peak.unwrap_or_default();
+                peak = Some((rss.max(usage.rss_bytes), cpu.max(usage.cpu_percent)));
+
+                if let Some(limit) = memory_limit
+                    && usage.rss_bytes > limit
+                {
+                    store.push_stderr(format!(
+                        "Memory limit of {} MB exceeded, stopping the process",
+                        limit / 1024 / 1024
+                    ));
+                    resources::kill_group(pgid);
+                }
+            }
+
+            if let Some(applied) = applied {
+                resources::release_limits(applied).await;
+            }
+            if let Some((rss, cpu)) = peak
+                && let Err(e) = ExecutionProcess::update_peak_resource_usage(
+                    &db.pool,
+                    exec_id,
+                    i64::try_from(rss).unwrap_or(i64::MAX),
+                    cpu,
+                )
+                .await
+            {
+                tracing::warn!("Failed to record peak resource usage of {}: {}", exec_id, e);
+            }
+        })
+    }
+
+    /// Spawn a background task that copies the remote worktrees of an execution running on
+    /// another machine into the local ones while it runs, so the workspace diff follows its
+    /// changes
+    fn spawn_remote_sync(&self, exec_id: Uuid) -> JoinHandle<()> {
+        let msg_stores = self.msg_stores.clone();
+        let backend = self.backend.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(REMOTE_SYNC_INTERVAL);
+            // The first tick completes immediately, before there is anything to copy
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                // The store is removed once the execution has finished
+                if !msg_stores.read().await.contains_key(&exec_id) {
+                    break;
+                }
+                backend.sync(exec_id).await;
+            }
+        })
+    }
+
+    /// Persist the cumulative token usage a coding agent reported for an execution
+    async fn record_token_usage(
+        &self,
+        exec_id: Uuid,
+        executor: BaseCodingAgent,
+        usage: &TokenUsage,
+    ) -> Result<(), sqlx::Error> {
+        ExecutionProcessTokenUsage::upsert(
+            &self.db.pool,
+            exec_id,
+            &UpsertExecutionProcessTokenUsage {
+                executor: executor.to_string(),
+                model: usage.model.clone(),
+                input_tokens: usage.input_tokens as i64,
+                output_tokens: usage.output_tokens as i64,
+                cache_read_tokens: usage.cache_read_tokens as i64,
+                cache_write_tokens: usage.cache_write_tokens as i64,
+                reported_cost_usd: usage.cost_usd,
+            },
+        )
+        .await
+    }
+
+    /// Spawn a background task that persists the token usage reported by a coding agent
+    /// and stops the execution once the task or project budget is exhausted.
+    fn spawn_token_usage_monitor(
+        &self,
+        exec_id: Uuid,
+        task_id: Uuid,
+        project_id: Uuid,
+        executor: BaseCodingAgent,
+        msg_store: Arc<MsgStore>,
+    ) -> JoinHandle<()> {
+        let container = self.clone();
+        // Only the stream is kept so the MsgStore can be dropped once the execution ends
+        let mut stream = msg_store.history_plus_stream();
+        drop(msg_store);
+
+        tokio::spawn(async move {
+            let mut last_usage: Option<TokenUsage> = None;
+
+            while let Some(Ok(msg)) = stream.next().await {
+                let LogMsg::JsonPatch(patch) = msg else {
+                    continue;
+                };
+                let Some(usage) =
+                    extract_normalized_entry_from_patch(&patch).and_then(|(_, entry)| match entry
+                        .entry_type
+                    {
+                        NormalizedEntryType::TokenUsageInfo(info) => info.usage,
+                        _ => None,
+                    })
+                else {
+                    continue;
+                };
+                if last_usage.as_ref() == Some(&usage) {
+                    continue;
+                }
+
+                if let Err(e) = container
+                    .record_token_usage(exec_id, executor, &usage)
+                    .await
+                {
+                    tracing::warn!("Failed to record token usage for {}: {}", exec_id, e);
+                    continue;
+                }
+                last_usage = Some(usage);
+
+                let pool = &container.db.pool;
+                let prices = container.config.read().await.model_prices.clone();
+                let reason = match find_exhausted_budget(pool, &prices, task_id, project_id).await {
+                    Ok(Some(reason)) => reason,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        tracing::warn!("Failed to check budgets for {}: {}", exec_id, e);
+                        continue;
+                    }
+                };
+
+                tracing::info!("Stopping execution {}: {}", exec_id, reason);
+                // Stderr is normalized into an error entry and persisted with the raw logs
+                if let Some(store) = container.msg_stores.read().await.get(&exec_id) {
+                    store.push_stderr(reason);
+                }
+                match ExecutionProcess::find_by_id(pool, exec_id).await {
+                    Ok(Some(process)) if process.status == ExecutionProcessStatus::Running => {
+                        if let Err(e) = container
+                            .stop_execution(&process, ExecutionProcessStatus::Killed)
+                            .await
+                        {
+                            tracing::error!("Failed to stop execution {}: {}", exec_id, e);
+                        } else if let Err(e) = ExecutionProcess::set_completion_reason(
+                            pool,
+                            exec_id,
+                            ExecutionProcessCompletionReason::BudgetExceeded,
+                        )
+                        .await
+                        {
+                            tracing::error!("Failed to record execution completion reason: {}", e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Failed to load execution {}: {}", exec_id, e),
+                }
+                break;
+            }
+        })
+    }
+
     /// Copy project files and images to the workspace.
     /// Skips files/images that already exist (fast no-op if all exist).
     async fn copy_files_and_images(
@@ -890,6 +1220,79 @@ impl LocalContainerService {
     }
 }
 
+/// Resolves with the completion reason and a user facing message once the execution has run
+/// longer than `max_runtime_secs` or made more than `max_tool_calls` tool calls.
+async fn wait_for_execution_limit(
+    max_runtime_secs: Option<u32>,
+    max_tool_calls: Option<u32>,
+    log_stream: Option<BoxStream<'static, Result<LogMsg, io::Error>>>,
+) -> (ExecutionProcessCompletionReason, String) {
+    let runtime_exceeded = async {
+        match max_runtime_secs {
+            Some(secs) => tokio::time::sleep(Duration::from_secs(secs.into())).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let tool_calls_exceeded = async {
+        let (Some(max_tool_calls), Some(mut log_stream)) = (max_tool_calls, log_stream) else {
+            return std::future::pending().await;
+        };
+        // Tool calls are updated in place, so count the distinct entry indices
+        let mut tool_call_indices = HashSet::new();
+        while let Some(Ok(msg)) = log_stream.next().await {
+            if let LogMsg::JsonPatch(patch) = msg
+                && let Some((index, entry)) = extract_normalized_entry_from_patch(&patch)
+                && matches!(entry.entry_type, NormalizedEntryType::ToolUse { .. })
+                && tool_call_indices.insert(index)
+                && tool_call_indices.len() > max_tool_calls as usize
+            {
+                return;
+            }
+        }
+        // The execution ended before reaching the limit
+        std::future::pending().await
+    };
+
+    tokio::select! {
+        _ = runtime_exceeded => (
+            ExecutionProcessCompletionReason::Timeout,
+            format!(
+                "Execution stopped: exceeded the maximum runtime of {} seconds",
+                max_runtime_secs.unwrap_or_default()
+            ),
+        ),
+        _ = tool_calls_exceeded => (
+            ExecutionProcessCompletionReason::ToolCallLimit,
+            format!(
+                "Execution stopped: exceeded the limit of {} tool calls",
+                max_tool_calls.unwrap_or_default()
+            ),
+        ),
+    }
+}
+
+/// Exit code and status recorded for an execution. Executions stopped for exceeding a limit
+/// count as killed, whatever their exit status.
+fn completion_status(
+    status_result: &std::io::Result<std::process::ExitStatus>,
+    stopped_by_limit: bool,
+) -> (Option<i64>, ExecutionProcessStatus) {
+    match status_result {
+        _ if stopped_by_limit => (None, ExecutionProcessStatus::Killed),
+        Ok(exit_status) => {
+            let code = exit_status.code().unwrap_or(-1) as i64;
+            let status = if exit_status.success() {
+                ExecutionProcessStatus::Completed
+            } else {
+                ExecutionProcessStatus::Failed
+            };
+            (Some(code), status)
+        }
+        Err(_) => (None, ExecutionProcessStatus::Failed),
+    }
+}
+
 fn failure_exit_status() -> std::process::ExitStatus {
     #[cfg(unix)]
     {
@@ -904,7 +1307,7 @@ fn failure_exit_status() -> std::process::ExitStatus {
 }
 
 #[async_trait]
-impl ContainerService for LocalContainerService {
+impl<B: ExecutionBackend> ContainerService for LocalContainerService<B> {
     fn msg_stores(&self) -> &Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>> {
         &self.msg_stores
     }
@@ -921,10 +1324,22 @@ impl ContainerService for LocalContainerService {
         &self.notification_service
     }
 
+    fn workspace_locks(&self) -> &WorkspaceLocks {
+        &self.workspace_locks
+    }
+
     async fn git_branch_prefix(&self) -> String {
         self.config.read().await.git_branch_prefix.clone()
     }
 
+    async fn model_prices(&self) -> Vec<ModelPrice> {
+        self.config.read().await.model_prices.clone()
+    }
+
+    async fn executor_fallbacks(&self) -> Vec<ExecutorProfileId> {
+        self.config.read().await.executor_fallbacks.clone()
+    }
+
     fn workspace_to_current_dir(&self, workspace: &Workspace) -> PathBuf {
         PathBuf::from(workspace.container_ref.clone().unwrap_or_default())
     }
@@ -935,8 +1350,7 @@ impl ContainerService for LocalContainerService {
             .await?
             .ok_or(sqlx::Error::RowNotFound)?;
 
-        let workspace_dir_name =
-            LocalContainerService::dir_name_from_workspace(&workspace.id, &task.title);
+        let workspace_dir_name = Self::dir_name_from_workspace(&workspace.id, &task.title);
         let workspace_dir = WorkspaceManager::get_workspace_base_dir().join(&workspace_dir_name);
 
         let workspace_repos =
@@ -992,6 +1406,13 @@ impl ContainerService for LocalContainerService {
 
     async fn delete(&self, workspace: &Workspace) -> Result<(), ContainerError> {
         self.try_stop(workspace, true).await;
+        if let Some(container_ref) = &workspace.container_ref {
+            let repos =
+                WorkspaceRepo::find_repos_for_workspace(&self.db.pool, workspace.id).await?;
+            self.backend
+                .remove_workspace(Path::new(container_ref), &repos)
+                .await;
+        }
         Self::cleanup_workspace(&self.db, workspace).await;
         Ok(())
     }
@@ -1017,8 +1438,7 @@ impl ContainerService for LocalContainerService {
                 .parent_task(&self.db.pool)
                 .await?
                 .ok_or(sqlx::Error::RowNotFound)?;
-            let workspace_dir_name =
-                LocalContainerService::dir_name_from_workspace(&workspace.id, &task.title);
+            let workspace_dir_name = Self::dir_name_from_workspace(&workspace.id, &task.title);
             WorkspaceManager::get_workspace_base_dir().join(&workspace_dir_name)
         };
 
@@ -1088,7 +1508,8 @@ impl ContainerService for LocalContainerService {
                     | BaseCodingAgent::ClaudeCode
                     | BaseCodingAgent::Gemini
                     | BaseCodingAgent::QwenCode
-                    | BaseCodingAgent::Opencode,
+                    | BaseCodingAgent::Opencode
+                    | BaseCodingAgent::Acp,
                 ) => ExecutorApprovalBridge::new(
                     self.approvals.clone(),
                     self.db.clone(),
@@ -1104,6 +1525,7 @@ impl ContainerService for LocalContainerService {
 
         let commit_reminder = self.config.read().await.commit_reminder;
         let mut env = ExecutionEnv::new(repo_context, commit_reminder);
+        env.executor = executor_action.base_executor();
 
         // Load task and project context for environment variables
         let task = workspace
@@ -1123,20 +1545,84 @@ impl ContainerService for LocalContainerService {
         env.insert("VK_WORKSPACE_ID", workspace.id.to_string());
         env.insert("VK_WORKSPACE_BRANCH", &workspace.branch);
 
+        // Runtime and tool call limits of the executor profile
+        let limits = executor_action
+            .executor_profile_id()
+            .and_then(|profile_id| ExecutorConfigs::get_cached().get_coding_agent(profile_id));
+        // Scripts and dev servers run under the resource limits of the profile their session uses
+        let resource_limits = match &limits {
+            Some(agent) => agent
+                .cmd_overrides()
+                .and_then(ResourceLimits::from_overrides),
+            None => ExecutionProcess::latest_executor_profile_for_session(
+                &self.db.pool,
+                execution_process.session_id,
+            )
+            .await
+            .ok()
+            .flatten()
+            .and_then(|profile_id| ExecutorConfigs::get_cached().get_coding_agent(&profile_id))
+            .and_then(|agent| {
+                agent
+                    .cmd_overrides()
+                    .and_then(ResourceLimits::from_overrides)
+            }),
+        };
+
+        // The backend may run the process in a container or on another machine, which then
+        // enforces the resource limits
+        let placement = self
+            .backend
+            .place(
+                execution_process.id,
+                &current_dir,
+                &workspace.branch,
+                &repos,
+                executor_action,
+                resource_limits,
+            )
+            .await?;
+        let placed = placement.is_some();
+        let mut remote = false;
+        if let Some(placement) = placement {
+            env.command_wrapper = Some(placement.wrapper);
+            remote = placement.remote;
+        }
+
+        // Limits are set up before spawning so the process runs under them from the start
+        let applied_limits = match resource_limits {
+            Some(limits) if !placed => {
+                resources::prepare_limits(execution_process.id, &limits).await
+            }
+            _ => None,
+        };
+        env.spawn_hook = applied_limits.as_ref().and_then(AppliedLimits::spawn_hook);
+
         // Create the child and stream, add to execution tracker with timeout
-        let mut spawned = tokio::time::timeout(
+        let spawned = match tokio::time::timeout(
             Duration::from_secs(30),
             executor_action.spawn(&current_dir, approvals_service, &env),
         )
         .await
-        .map_err(|_| {
-            ContainerError::Other(anyhow!(
+        {
+            Ok(spawned) => spawned.map_err(ContainerError::from),
+            Err(_) => Err(ContainerError::Other(anyhow!(
                 "Timeout: process took more than 30 seconds to start"
-            ))
-        })??;
+            ))),
+        };
+        let mut spawned = match spawned {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                if let Some(applied) = applied_limits {
+                    resources::release_limits(applied).await;
+                }
+                return Err(e);
+            }
+        };
 
         self.track_child_msgs_in_store(execution_process.id, &mut spawned.child)
             .await;
+        let pgid = spawned.child.inner().id();
 
         self.add_child_to_store(execution_process.id, spawned.child)
             .await;
@@ -1147,8 +1633,44 @@ impl ContainerService for LocalContainerService {
                 .await;
         }
 
-        // Spawn unified exit monitor: watches OS exit and optional executor signal
-        let _hn = self.spawn_exit_monitor(&execution_process.id, spawned.exit_signal);
+        // Spawn unified exit monitor: watches OS exit, optional executor signal and the
+        // runtime/tool call limits of the executor profile
+        let _hn = self.spawn_exit_monitor(
+            &execution_process.id,
+            spawned.exit_signal,
+            self.get_msg_store_by_id(&execution_process.id).await,
+            limits.as_ref().and_then(|agent| agent.cmd_overrides()),
+        );
+
+        // The local process of a placed execution is only the client of the container runtime
+        // or ssh, so its usage says nothing about the execution
+        match pgid {
+            Some(pgid) if !placed => {
+                self.spawn_resource_monitor(execution_process.id, pgid, applied_limits);
+            }
+            _ => {
+                if let Some(applied) = applied_limits {
+                    resources::release_limits(applied).await;
+                }
+            }
+        }
+
+        if remote && execution_process.run_reason != ExecutionProcessRunReason::DevServer {
+            self.spawn_remote_sync(execution_process.id);
+        }
+
+        // Track token usage of coding agents and enforce budgets
+        if let Some(executor) = executor_action.base_executor()
+            && let Some(msg_store) = self.get_msg_store_by_id(&execution_process.id).await
+        {
+            self.spawn_token_usage_monitor(
+                execution_process.id,
+                task.id,
+                project.id,
+                executor,
+                msg_store,
+            );
+        }
 
         Ok(())
     }
@@ -1216,6 +1738,7 @@ impl ContainerService for LocalContainerService {
             }
         }
         self.remove_child_from_store(&execution_process.id).await;
+        self.backend.stop(execution_process.id).await;
 
         // Mark the process finished in the MsgStore
         if let Some(msg) = self.msg_stores.write().await.remove(&execution_process.id) {
@@ -1413,3 +1936,134 @@ fn success_exit_status() -> std::process::ExitStatus {
         ExitStatusExt::from_raw(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::{
+        ActionType, NormalizedEntry, NormalizedEntryType, ToolStatus, utils::ConversationPatch,
+    };
+    use futures::StreamExt;
+
+    use super::*;
+
+    fn tool_use(index: usize, status: ToolStatus) -> Result<LogMsg, io::Error> {
+        let entry = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "Bash".to_string(),
+                action_type: ActionType::CommandRun {
+                    command: "cargo test".to_string(),
+                    result: None,
+                },
+                status,
+            },
+            content: "cargo test".to_string(),
+            metadata: None,
+        };
+        Ok(LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(
+            index, entry,
+        )))
+    }
+
+    fn assistant_message(index: usize) -> Result<LogMsg, io::Error> {
+        let entry = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: "Running the tests".to_string(),
+            metadata: None,
+        };
+        Ok(LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(
+            index, entry,
+        )))
+    }
+
+    /// Log stream that stays open after the given messages, like a running execution
+    fn open_stream(
+        msgs: Vec<Result<LogMsg, io::Error>>,
+    ) -> Option<BoxStream<'static, Result<LogMsg, io::Error>>> {
+        Some(
+            futures::stream::iter(msgs)
+                .chain(futures::stream::pending())
+                .boxed(),
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn runtime_limit_times_out() {
+        let start = tokio::time::Instant::now();
+        let (reason, message) = wait_for_execution_limit(Some(30), None, None).await;
+        assert_eq!(reason, ExecutionProcessCompletionReason::Timeout);
+        assert_eq!(start.elapsed(), Duration::from_secs(30));
+        assert!(message.contains("30 seconds"));
+    }
+
+    #[tokio::test]
+    async fn tool_call_limit_counts_distinct_entries() {
+        let stream = open_stream(vec![
+            tool_use(0, ToolStatus::Created),
+            // Status updates replace the same entry and are not new tool calls
+            tool_use(0, ToolStatus::Success),
+            assistant_message(1),
+            tool_use(2, ToolStatus::Created),
+            tool_use(3, ToolStatus::Created),
+        ]);
+        let (reason, message) = wait_for_execution_limit(None, Some(2), stream).await;
+        assert_eq!(reason, ExecutionProcessCompletionReason::ToolCallLimit);
+        assert!(message.contains("2 tool calls"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limits_release_executions_that_stay_below_them() {
+        // No limits configured
+        let unlimited = wait_for_execution_limit(None, None, open_stream(vec![]));
+        assert!(
+            tokio::time::timeout(Duration::from_secs(3600), unlimited)
+                .await
+                .is_err()
+        );
+
+        // The execution ended with fewer tool calls than allowed
+        let finished = futures::stream::iter(vec![tool_use(0, ToolStatus::Success)]).boxed();
+        let below_limit = wait_for_execution_limit(None, Some(1), Some(finished));
+        assert!(
+            tokio::time::timeout(Duration::from_secs(3600), below_limit)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn first_exceeded_limit_wins() {
+        let stream = open_stream(vec![
+            tool_use(0, ToolStatus::Created),
+            tool_use(1, ToolStatus::Created),
+        ]);
+        let (reason, _) = wait_for_execution_limit(Some(60), Some(1), stream).await;
+        assert_eq!(reason, ExecutionProcessCompletionReason::ToolCallLimit);
+
+        let stream = open_stream(vec![tool_use(0, ToolStatus::Created)]);
+        let (reason, _) = wait_for_execution_limit(Some(60), Some(1), stream).await;
+        assert_eq!(reason, ExecutionProcessCompletionReason::Timeout);
+    }
+
+    #[test]
+    fn limit_stops_are_recorded_as_killed() {
+        assert_eq!(
+            completion_status(&Ok(success_exit_status()), false),
+            (Some(0), ExecutionProcessStatus::Completed)
+        );
+        assert_eq!(
+            completion_status(&Ok(failure_exit_status()), false),
+            (Some(1), ExecutionProcessStatus::Failed)
+        );
+        assert_eq!(
+            completion_status(&Err(io::Error::other("lost")), false),
+            (None, ExecutionProcessStatus::Failed)
+        );
+        // The interrupted agent may still exit cleanly
+        assert_eq!(
+            completion_status(&Ok(success_exit_status()), true),
+            (None, ExecutionProcessStatus::Killed)
+        );
+    }
+}
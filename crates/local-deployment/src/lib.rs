@@ -30,11 +30,15 @@ use utils::{
 };
 use uuid::Uuid;
 
-use crate::{container::LocalContainerService, pty::PtyService};
+use crate::{backend::WorkspaceBackends, container::LocalContainerService, pty::PtyService};
+pub mod backend;
 mod command;
 pub mod container;
 mod copy;
+pub mod oci;
 pub mod pty;
+mod resources;
+pub mod ssh;
 
 #[derive(Clone)]
 pub struct LocalDeployment {
@@ -179,6 +183,7 @@ impl Deployment for LocalDeployment {
             analytics_ctx,
             approvals.clone(),
             queued_message_service.clone(),
+            WorkspaceBackends::detect().await,
         )
         .await;
 
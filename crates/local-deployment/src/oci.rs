@@ -0,0 +1,915 @@
+//! Running executions inside OCI containers through the podman or docker CLI
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use db::models::repo::Repo;
+use executors::{
+    actions::{ExecutorAction, ExecutorActionType, script::ScriptContext},
+    env::CommandWrapper,
+    executors::BaseCodingAgent,
+    linux_sandbox::{agent_home_paths, git_writable_paths},
+};
+use services::services::container::ContainerError;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::{
+    backend::{ExecutionBackend, Placement},
+    resources::ResourceLimits,
+};
+
+/// Names a container runtime CLI to use instead of looking for podman, then docker
+const RUNTIME_ENV: &str = "VK_CONTAINER_RUNTIME";
+
+/// Variables of the server's environment that hold the API keys and endpoints of coding agents
+/// and git hosts. They are passed into containers unless the process sets them itself.
+pub const CREDENTIAL_ENV_VARS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_AUTH_TOKEN",
+    "ANTHROPIC_BASE_URL",
+    "CLAUDE_CODE_OAUTH_TOKEN",
+    "OPENAI_API_KEY",
+    "OPENAI_BASE_URL",
+    "GEMINI_API_KEY",
+    "GOOGLE_API_KEY",
+    "DASHSCOPE_API_KEY",
+    "OPENROUTER_API_KEY",
+    "CURSOR_API_KEY",
+    "FACTORY_API_KEY",
+    "AMP_API_KEY",
+    "GH_TOKEN",
+    "GITHUB_TOKEN",
+];
+
+/// A host path mounted into a container
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mount {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    pub read_only: bool,
+}
+
+impl Mount {
+    /// `path` mounted at the same path, so paths in logs, diffs and git worktree metadata stay
+    /// valid
+    pub fn same_path(path: PathBuf, read_only: bool) -> Self {
+        Self {
+            source: path.clone(),
+            target: path,
+            read_only,
+        }
+    }
+}
+
+/// One process to run in a new container
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerRunSpec {
+    pub name: String,
+    pub image: String,
+    /// Mounted in order, so paths beneath a read-only mount can be mounted writable after it
+    pub mounts: Vec<Mount>,
+    pub working_dir: Option<PathBuf>,
+    /// Passed by name only and set in the environment of the runtime CLI, so values such as
+    /// credentials never show up in its command line
+    pub env: Vec<(String, String)>,
+    pub program: String,
+    pub args: Vec<String>,
+    /// Keep stdin attached, for agents that are driven through it
+    pub interactive: bool,
+    /// Port published on the host's loopback interface at the same number, so dev servers can
+    /// be reached on the port they print
+    pub published_port: Option<u16>,
+    pub limits: Option<ResourceLimits>,
+}
+
+/// Starts and removes containers. Implemented by [`CliRunner`] and by fakes in tests.
+#[async_trait]
+pub trait ContainerRunner: Debug + Send + Sync {
+    /// Command that runs `spec` in a new container, which is removed once the command exits
+    fn run_command(&self, spec: &ContainerRunSpec) -> Command;
+
+    /// Remove a container, stopping it first if it is still running
+    async fn remove(&self, name: &str) -> std::io::Result<()>;
+}
+
+/// Runs containers through the podman or docker CLI
+#[derive(Debug, Clone)]
+pub struct CliRunner {
+    program: PathBuf,
+}
+
+impl CliRunner {
+    pub fn new(program: PathBuf) -> Self {
+        Self { program }
+    }
+
+    /// The runtime named by `VK_CONTAINER_RUNTIME`, otherwise podman or docker, whichever is
+    /// installed
+    pub async fn detect() -> Option<Self> {
+        let candidates = match std::env::var(RUNTIME_ENV) {
+            Ok(runtime) => vec![runtime],
+            Err(_) => vec!["podman".to_string(), "docker".to_string()],
+        };
+        for candidate in candidates {
+            if let Some(program) = utils::shell::resolve_executable_path(&candidate).await {
+                return Some(Self::new(program));
+            }
+        }
+        None
+    }
+
+    fn is_podman(&self) -> bool {
+        self.program
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().contains("podman"))
+    }
+
+    fn run_args(&self, spec: &ContainerRunSpec) -> Vec<String> {
+        let mut args = vec![
+            "run".to_string(),
+            "--rm".to_string(),
+            "--init".to_string(),
+            "--name".to_string(),
+            spec.name.clone(),
+        ];
+        if spec.interactive {
+            args.push("--interactive".to_string());
+        }
+        if let Some(port) = spec.published_port {
+            args.push(format!("--publish=127.0.0.1:{port}:{port}"));
+        }
+        // Files the process writes to the worktree stay owned by the user
+        if self.is_podman() {
+            args.push("--userns=keep-id".to_string());
+        } else {
+            #[cfg(unix)]
+            args.extend([
+                "--user".to_string(),
+                format!(
+                    "{}:{}",
+                    nix::unistd::getuid().as_raw(),
+                    nix::unistd::getgid().as_raw()
+                ),
+            ]);
+        }
+        if let Some(limits) = &spec.limits {
+            if let Some(memory_mb) = limits.memory_mb {
+                args.push(format!("--memory={memory_mb}m"));
+            }
+            if let Some(cpu_percent) = limits.cpu_percent {
+                args.push(format!("--cpus={}", f64::from(cpu_percent) / 100.0));
+            }
+            if let Some(pids) = limits.pids {
+                args.push(format!("--pids-limit={pids}"));
+            }
+        }
+        for mount in &spec.mounts {
+            args.push(format!(
+                "--volume={}:{}{}",
+                mount.source.display(),
+                mount.target.display(),
+                if mount.read_only { ":ro" } else { "" }
+            ));
+        }
+        if let Some(dir) = &spec.working_dir {
+            args.push(format!("--workdir={}", dir.display()));
+        }
+        for (key, _) in &spec.env {
+            args.push(format!("--env={key}"));
+        }
+        args.push(spec.image.clone());
+        args.push(spec.program.clone());
+        args.extend(spec.args.iter().cloned());
+        args
+    }
+}
+
+#[async_trait]
+impl ContainerRunner for CliRunner {
+    fn run_command(&self, spec: &ContainerRunSpec) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(self.run_args(spec))
+            .envs(spec.env.iter().map(|(key, value)| (key, value)));
+        command
+    }
+
+    async fn remove(&self, name: &str) -> std::io::Result<()> {
+        let output = Command::new(&self.program)
+            .args(["rm", "--force", name])
+            .stdin(Stdio::null())
+            .output()
+            .await?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(std::io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ))
+        }
+    }
+}
+
+/// Image an execution runs in: that of the repo a script runs for, otherwise that of the first
+/// repo of the workspace that sets one. Once any repo sets an image, every process of the
+/// workspace runs in a container.
+pub fn image_for_action<'a>(repos: &'a [Repo], action: &ExecutorAction) -> Option<&'a str> {
+    let script_repo = match action.typ() {
+        ExecutorActionType::ScriptRequest(script) => script.working_dir.as_deref(),
+        _ => None,
+    };
+    script_repo
+        .and_then(|name| repos.iter().find(|repo| repo.name == name))
+        .and_then(|repo| repo.container_image.as_deref())
+        .or_else(|| {
+            repos
+                .iter()
+                .find_map(|repo| repo.container_image.as_deref())
+        })
+}
+
+/// Names of the containers started for each execution. An execution spawns a container for
+/// every command it runs, such as the follow-ups of an agent.
+type ExecutionContainers = Arc<Mutex<HashMap<Uuid, Vec<String>>>>;
+
+/// Runs the processes of workspaces in containers of the image their repos set
+#[derive(Debug, Clone)]
+pub struct OciBackend {
+    runner: Arc<dyn ContainerRunner>,
+    containers: ExecutionContainers,
+    /// Home directory of the server's user, whose agent config directories are copied in
+    home: Option<PathBuf>,
+    /// Where the copies of the agent config directories of each workspace are kept
+    homes_dir: PathBuf,
+    /// Credentials of the server's environment, from [`CREDENTIAL_ENV_VARS`]
+    inherited_env: Vec<(String, String)>,
+}
+
+impl OciBackend {
+    pub fn new(runner: Arc<dyn ContainerRunner>) -> Self {
+        Self {
+            runner,
+            containers: Arc::new(Mutex::new(HashMap::new())),
+            home: dirs::home_dir(),
+            homes_dir: utils::cache_dir().join("container-homes"),
+            inherited_env: CREDENTIAL_ENV_VARS
+                .iter()
+                .filter_map(|key| Some((key.to_string(), std::env::var(key).ok()?)))
+                .collect(),
+        }
+    }
+
+    /// Directory that holds the copies of the agent config directories of a workspace
+    fn staged_home(&self, workspace_dir: &Path) -> Option<PathBuf> {
+        Some(self.homes_dir.join(workspace_dir.file_name()?))
+    }
+
+    /// Copy the config directories of `agent` for a workspace, once, so its containers find
+    /// the credentials and settings of this machine without being able to change them. Later
+    /// executions of the workspace keep what earlier ones wrote, such as the sessions
+    /// follow-ups resume.
+    async fn stage_agent_home(&self, workspace_dir: &Path, agent: BaseCodingAgent) {
+        let (Some(home), Some(staged)) = (self.home.clone(), self.staged_home(workspace_dir))
+        else {
+            return;
+        };
+        let copied = tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+            create_private_dir(&staged)?;
+            for path in agent_home_paths(agent) {
+                let (source, target) = (home.join(path), staged.join(path));
+                if !source.exists() || target.exists() {
+                    continue;
+                }
+                if let Err(e) = copy_recursively(&source, &target) {
+                    // Copied again by the next execution
+                    let _ = if source.is_dir() {
+                        std::fs::remove_dir_all(&target)
+                    } else {
+                        std::fs::remove_file(&target)
+                    };
+                    return Err(e);
+                }
+            }
+            Ok(())
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        if let Err(e) = copied {
+            tracing::warn!(
+                "Failed to copy the {} config for its container: {}",
+                agent,
+                e
+            );
+        }
+    }
+
+    /// Wrapper that runs the commands of an execution in containers of `image`, with the
+    /// workspace, the git directories of its repos and the copied config directories of its
+    /// agent mounted
+    pub fn command_wrapper(
+        &self,
+        exec_id: Uuid,
+        image: &str,
+        action: &ExecutorAction,
+        workspace_root: &Path,
+        repos: &[Repo],
+        limits: Option<ResourceLimits>,
+    ) -> Arc<dyn CommandWrapper> {
+        let mut mounts = vec![Mount::same_path(workspace_root.to_path_buf(), false)];
+        // Worktrees keep their git metadata in the main repository, which stays read-only
+        // apart from what committing in the worktree writes, so hooks and config cannot be
+        // changed
+        for repo in repos {
+            let git_dir = repo.path.join(".git");
+            if !git_dir.is_dir() {
+                continue;
+            }
+            mounts.push(Mount::same_path(git_dir, true));
+            mounts.extend(
+                git_writable_paths(&workspace_root.join(&repo.name))
+                    .into_iter()
+                    .filter(|path| path.exists())
+                    .map(|path| Mount::same_path(path, false)),
+            );
+        }
+        // Agents signed in on this machine find copies of their credentials and settings at
+        // the same paths, as HOME is passed in too
+        if let (Some(home), Some(staged), Some(agent)) = (
+            &self.home,
+            self.staged_home(workspace_root),
+            action.base_executor(),
+        ) {
+            mounts.extend(
+                agent_home_paths(agent)
+                    .iter()
+                    .filter(|path| staged.join(path).exists())
+                    .map(|path| Mount {
+                        source: staged.join(path),
+                        target: home.join(path),
+                        read_only: false,
+                    }),
+            );
+        }
+        let mut env = self.inherited_env.clone();
+        if let Some(home) = &self.home {
+            env.push(("HOME".to_string(), home.to_string_lossy().into_owned()));
+        }
+        let (interactive, dev_server) = match action.typ() {
+            ExecutorActionType::ScriptRequest(script) => {
+                (false, script.context == ScriptContext::DevServer)
+            }
+            _ => (true, false),
+        };
+
+        Arc::new(ContainerCommand {
+            runner: self.runner.clone(),
+            exec_id,
+            containers: self.containers.clone(),
+            image: image.to_string(),
+            mounts,
+            env,
+            interactive,
+            dev_server,
+            limits,
+        })
+    }
+
+    /// Remove the containers of an execution. Containers remove themselves when their process
+    /// exits, this covers processes that were killed.
+    async fn remove_containers(&self, exec_id: Uuid) {
+        let names = self
+            .containers
+            .lock()
+            .unwrap()
+            .remove(&exec_id)
+            .unwrap_or_default();
+        for name in names {
+            if let Err(e) = self.runner.remove(&name).await {
+                tracing::debug!("Container {} was not removed: {}", name, e);
+            }
+        }
+    }
+}
+
+/// Create a directory only the user can enter
+fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(path)
+}
+
+/// Copy a file, or a directory with everything in it. Symlinks are copied as links and other
+/// special files are skipped. Blocking.
+fn copy_recursively(source: &Path, target: &Path) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(source)?;
+    if metadata.is_dir() {
+        std::fs::create_dir_all(target)?;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &target.join(entry.file_name()))?;
+        }
+    } else if metadata.is_symlink() {
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(std::fs::read_link(source)?, target)?;
+    } else if metadata.is_file() {
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Keeps the permissions, so credential files stay private
+        std::fs::copy(source, target)?;
+    }
+    Ok(())
+}
+
+/// Runs every process in a container, so workspaces whose repos set no image cannot start
+#[async_trait]
+impl ExecutionBackend for OciBackend {
+    async fn place(
+        &self,
+        exec_id: Uuid,
+        workspace_dir: &Path,
+        _branch: &str,
+        repos: &[Repo],
+        action: &ExecutorAction,
+        limits: Option<ResourceLimits>,
+    ) -> Result<Option<Placement>, ContainerError> {
+        let image = image_for_action(repos, action).ok_or_else(|| {
+            ContainerError::Other(anyhow!(
+                "No repository of this workspace sets a container image to run it in"
+            ))
+        })?;
+        if let Some(agent) = action.base_executor() {
+            self.stage_agent_home(workspace_dir, agent).await;
+        }
+        Ok(Some(Placement {
+            wrapper: self.command_wrapper(exec_id, image, action, workspace_dir, repos, limits),
+            remote: false,
+        }))
+    }
+
+    async fn stop(&self, exec_id: Uuid) {
+        self.remove_containers(exec_id).await;
+    }
+
+    async fn release(&self, exec_id: Uuid) {
+        self.remove_containers(exec_id).await;
+    }
+
+    async fn remove_workspace(&self, workspace_dir: &Path, _repos: &[Repo]) {
+        let Some(staged) = self.staged_home(workspace_dir) else {
+            return;
+        };
+        match tokio::fs::remove_dir_all(&staged).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!(
+                "Failed to remove the container home {}: {}",
+                staged.display(),
+                e
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ContainerCommand {
+    runner: Arc<dyn ContainerRunner>,
+    exec_id: Uuid,
+    containers: ExecutionContainers,
+    image: String,
+    mounts: Vec<Mount>,
+    /// Variables passed in unless the command sets them
+    env: Vec<(String, String)>,
+    interactive: bool,
+    /// Publishes the port the process listens on
+    dev_server: bool,
+    limits: Option<ResourceLimits>,
+}
+
+impl ContainerCommand {
+    /// Name of a new container of the execution, which is recorded so it can be removed
+    fn next_name(&self) -> String {
+        let mut containers = self.containers.lock().unwrap();
+        let names = containers.entry(self.exec_id).or_default();
+        let name = format!("vibe-kanban-{}-{}", self.exec_id, names.len());
+        names.push(name.clone());
+        name
+    }
+}
+
+/// The port set in `PORT`, otherwise a free port of the host that `PORT` is set to. `HOST` is
+/// set too, as loopback inside the container is not reachable through the published port.
+fn dev_server_port(env: &mut Vec<(String, String)>) -> Option<u16> {
+    let port = match env.iter().find(|(key, _)| key == "PORT") {
+        Some((_, port)) => port.parse().ok()?,
+        None => {
+            let port = match std::net::TcpListener::bind(("127.0.0.1", 0))
+                .and_then(|listener| listener.local_addr())
+            {
+                Ok(addr) => addr.port(),
+                Err(e) => {
+                    tracing::warn!("No free port for the dev server: {}", e);
+                    return None;
+                }
+            };
+            env.push(("PORT".to_string(), port.to_string()));
+            port
+        }
+    };
+    if !env.iter().any(|(key, _)| key == "HOST") {
+        env.push(("HOST".to_string(), "0.0.0.0".to_string()));
+    }
+    Some(port)
+}
+
+impl CommandWrapper for ContainerCommand {
+    fn wrap(&self, command: &Command) -> Command {
+        let command = command.as_std();
+        let program = Path::new(command.get_program());
+        // Executables resolved on the host are looked up on the image's PATH instead
+        let program = if program.is_absolute() {
+            program.file_name().unwrap_or(program.as_os_str())
+        } else {
+            program.as_os_str()
+        };
+        // Only variables set for the process and credentials are passed, not the rest of the
+        // server's environment
+        let mut env: Vec<(String, String)> = command
+            .get_envs()
+            .filter_map(|(key, value)| {
+                Some((
+                    key.to_string_lossy().into_owned(),
+                    value?.to_string_lossy().into_owned(),
+                ))
+            })
+            .collect();
+        for (key, value) in &self.env {
+            if !env.iter().any(|(set, _)| set == key) {
+                env.push((key.clone(), value.clone()));
+            }
+        }
+        let published_port = if self.dev_server {
+            dev_server_port(&mut env)
+        } else {
+            None
+        };
+        let spec = ContainerRunSpec {
+            name: self.next_name(),
+            image: self.image.clone(),
+            mounts: self.mounts.clone(),
+            working_dir: command.get_current_dir().map(Path::to_path_buf),
+            env,
+            program: program.to_string_lossy().into_owned(),
+            args: command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            interactive: self.interactive,
+            published_port,
+            limits: self.limits,
+        };
+
+        let mut wrapped = self.runner.run_command(&spec);
+        wrapped
+            .kill_on_drop(true)
+            .stdin(if self.interactive {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        wrapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command as StdCommand;
+
+    use executors::{
+        actions::{
+            coding_agent_initial::CodingAgentInitialRequest,
+            script::{ScriptRequest, ScriptRequestLanguage},
+        },
+        profile::ExecutorProfileId,
+    };
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct FakeRunner {
+        runs: Mutex<Vec<ContainerRunSpec>>,
+        removed: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ContainerRunner for FakeRunner {
+        fn run_command(&self, spec: &ContainerRunSpec) -> Command {
+            self.runs.lock().unwrap().push(spec.clone());
+            Command::new("true")
+        }
+
+        async fn remove(&self, name: &str) -> std::io::Result<()> {
+            self.removed.lock().unwrap().push(name.to_string());
+            Ok(())
+        }
+    }
+
+    fn repo(name: &str, container_image: Option<&str>) -> Repo {
+        Repo {
+            id: Uuid::new_v4(),
+            path: PathBuf::from(format!("/repos/{name}")),
+            name: name.to_string(),
+            display_name: name.to_string(),
+            setup_script: None,
+            cleanup_script: None,
+            copy_files: None,
+            parallel_setup_script: false,
+            dev_server_script: None,
+            default_target_branch: None,
+            container_image: container_image.map(str::to_string),
+            ssh_host: None,
+            git_host_provider: None,
+            auto_rebase: Default::default(),
+            created_at: Default::default(),
+            updated_at: Default::default(),
+        }
+    }
+
+    fn script(working_dir: Option<&str>, context: ScriptContext) -> ExecutorAction {
+        ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "npm test".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context,
+                working_dir: working_dir.map(str::to_string),
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn picks_image_of_script_repo_then_first_repo() {
+        let repos = vec![
+            repo("api", None),
+            repo("web", Some("node:22")),
+            repo("ml", Some("python:3.12")),
+        ];
+        let setup = |dir| script(dir, ScriptContext::SetupScript);
+
+        assert_eq!(
+            image_for_action(&repos, &setup(Some("ml"))),
+            Some("python:3.12")
+        );
+        assert_eq!(
+            image_for_action(&repos, &setup(Some("api"))),
+            Some("node:22")
+        );
+        assert_eq!(image_for_action(&repos, &setup(None)), Some("node:22"));
+        assert_eq!(image_for_action(&repos[..1], &setup(None)), None);
+    }
+
+    #[tokio::test]
+    async fn runs_wrapped_commands_in_containers_and_removes_them_when_released() {
+        let runner = Arc::new(FakeRunner::default());
+        let homes = tempfile::tempdir().unwrap();
+        let backend = OciBackend {
+            home: Some(PathBuf::from("/home/me")),
+            homes_dir: homes.path().to_path_buf(),
+            inherited_env: vec![
+                ("ANTHROPIC_API_KEY".to_string(), "sk-ant".to_string()),
+                ("GH_TOKEN".to_string(), "ghp-server".to_string()),
+            ],
+            ..OciBackend::new(runner.clone())
+        };
+        let exec_id = Uuid::new_v4();
+        let workspace = PathBuf::from("/workspaces/task");
+        let repos = [repo("web", Some("node:22"))];
+        let placement = backend
+            .place(
+                exec_id,
+                &workspace,
+                "vk/task",
+                &repos,
+                &script(Some("web"), ScriptContext::DevServer),
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!placement.remote);
+
+        let mut command = Command::new("/usr/local/bin/npm");
+        command
+            .args(["run", "dev"])
+            .current_dir(workspace.join("web"))
+            .env("VK_TASK_ID", "42")
+            .env("GH_TOKEN", "ghp-profile");
+        placement.wrapper.wrap(&command);
+        placement.wrapper.wrap(&command);
+
+        let runs = runner.runs.lock().unwrap().clone();
+        // Dev servers get a free port, published at the same number
+        let port = runs[0].published_port.unwrap();
+        let env = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            runs[0],
+            ContainerRunSpec {
+                name: format!("vibe-kanban-{exec_id}-0"),
+                image: "node:22".to_string(),
+                // The fake repos have no git directory on disk and scripts mount no agent
+                // config directories
+                mounts: vec![Mount::same_path(workspace.clone(), false)],
+                working_dir: Some(workspace.join("web")),
+                env: env(&[
+                    ("VK_TASK_ID", "42"),
+                    ("GH_TOKEN", "ghp-profile"),
+                    ("ANTHROPIC_API_KEY", "sk-ant"),
+                    ("HOME", "/home/me"),
+                    ("PORT", &port.to_string()),
+                    ("HOST", "0.0.0.0"),
+                ]),
+                program: "npm".to_string(),
+                args: vec!["run".to_string(), "dev".to_string()],
+                interactive: false,
+                published_port: Some(port),
+                limits: None,
+            }
+        );
+        assert_eq!(runs[1].name, format!("vibe-kanban-{exec_id}-1"));
+
+        backend.release(exec_id).await;
+        backend.release(exec_id).await;
+        assert_eq!(
+            *runner.removed.lock().unwrap(),
+            vec![
+                format!("vibe-kanban-{exec_id}-0"),
+                format!("vibe-kanban-{exec_id}-1")
+            ]
+        );
+
+        // Workspaces without an image are not run outside a container
+        assert!(
+            backend
+                .place(
+                    Uuid::new_v4(),
+                    &workspace,
+                    "vk/task",
+                    &[repo("api", None)],
+                    &script(None, ScriptContext::SetupScript),
+                    None,
+                )
+                .await
+                .is_err()
+        );
+
+        let cli = CliRunner::new(PathBuf::from("/usr/bin/podman"));
+        let args = cli.run_args(&runs[0]);
+        assert_eq!(
+            &args[..5],
+            ["run", "--rm", "--init", "--name", &runs[0].name]
+        );
+        assert!(args.contains(&"--volume=/workspaces/task:/workspaces/task".to_string()));
+        assert!(args.contains(&format!("--publish=127.0.0.1:{port}:{port}")));
+        assert!(!args.iter().any(|arg| arg == "--network=host"));
+        // Values are set in the environment of the CLI, not on its command line
+        assert!(args.contains(&"--env=GH_TOKEN".to_string()));
+        assert!(!args.iter().any(|arg| arg.contains("ghp-profile")));
+        let command = cli.run_command(&runs[0]);
+        assert!(command.as_std().get_envs().any(|(key, value)| {
+            key == "GH_TOKEN" && value == Some(std::ffi::OsStr::new("ghp-profile"))
+        }));
+        assert!(args.ends_with(&[
+            "node:22".to_string(),
+            "npm".to_string(),
+            "run".to_string(),
+            "dev".to_string()
+        ]));
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = StdCommand::new("git")
+            .current_dir(dir)
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[tokio::test]
+    async fn mounts_git_read_only_and_copies_of_agent_config() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path().canonicalize().unwrap();
+        let mut web = repo("web", Some("node:22"));
+        web.path = root.join("web");
+        let workspace = root.join("workspaces").join("task");
+        std::fs::create_dir_all(&web.path).unwrap();
+        git(&web.path, &["init", "-q"]);
+        git(
+            &web.path,
+            &[
+                "-c",
+                "user.name=vk",
+                "-c",
+                "user.email=vk@example.com",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        );
+        git(
+            &web.path,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "vk/task",
+                workspace.join("web").to_str().unwrap(),
+            ],
+        );
+        let home = root.join("home");
+        std::fs::create_dir_all(home.join(".codex")).unwrap();
+        std::fs::write(home.join(".codex").join("auth.json"), "{}").unwrap();
+
+        let runner = Arc::new(FakeRunner::default());
+        let backend = OciBackend {
+            home: Some(home.clone()),
+            homes_dir: root.join("homes"),
+            inherited_env: Vec::new(),
+            ..OciBackend::new(runner.clone())
+        };
+        let agent = ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: "fix it".to_string(),
+                executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::Codex),
+                working_dir: None,
+            }),
+            None,
+        );
+        let placement = backend
+            .place(
+                Uuid::new_v4(),
+                &workspace,
+                "vk/task",
+                &[web.clone()],
+                &agent,
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        placement.wrapper.wrap(&Command::new("codex"));
+
+        let git_dir = web.path.join(".git");
+        let staged = root.join("homes").join("task").join(".codex");
+        let mounts = runner.runs.lock().unwrap()[0].mounts.clone();
+        assert_eq!(mounts[0], Mount::same_path(workspace.clone(), false));
+        assert_eq!(mounts[1], Mount::same_path(git_dir.clone(), true));
+        for writable in [
+            git_dir.join("worktrees").join("web"),
+            git_dir.join("objects"),
+            git_dir.join("refs"),
+        ] {
+            assert!(mounts.contains(&Mount::same_path(writable, false)));
+        }
+        assert!(
+            !mounts
+                .iter()
+                .any(|mount| !mount.read_only && git_dir.join("hooks").starts_with(&mount.source))
+        );
+        assert_eq!(
+            mounts.last(),
+            Some(&Mount {
+                source: staged.clone(),
+                target: home.join(".codex"),
+                read_only: false,
+            })
+        );
+        assert_eq!(
+            std::fs::read_to_string(staged.join("auth.json")).unwrap(),
+            "{}"
+        );
+
+        backend.remove_workspace(&workspace, &[web]).await;
+        assert!(!staged.exists());
+    }
+}
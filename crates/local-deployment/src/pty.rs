@@ -1,7 +1,7 @@
 use std::{
     collections::HashMap,
     io::{Read, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
     thread,
 };
@@ -50,10 +50,43 @@ impl PtyService {
         working_dir: PathBuf,
         cols: u16,
         rows: u16,
+    ) -> Result<(Uuid, mpsc::UnboundedReceiver<Vec<u8>>), PtyError> {
+        let shell = get_interactive_shell().await;
+        let mut cmd = CommandBuilder::new(&shell);
+        cmd.cwd(&working_dir);
+        let shell_name = shell
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string();
+        self.spawn_session(cmd, shell_name, cols, rows).await
+    }
+
+    /// Open a login shell on an SSH host in `remote_dir`, which may be relative to the remote
+    /// home. The prompt is left to the remote shell configuration.
+    pub async fn create_ssh_session(
+        &self,
+        host: &str,
+        remote_dir: &Path,
+        cols: u16,
+        rows: u16,
+    ) -> Result<(Uuid, mpsc::UnboundedReceiver<Vec<u8>>), PtyError> {
+        let dir = remote_dir.to_string_lossy().replace('\'', r"'\''");
+        let mut cmd = CommandBuilder::new("ssh");
+        cmd.args(["-t", "--", host]);
+        cmd.arg(format!(r#"cd '{dir}' && exec "${{SHELL:-sh}}" -l"#));
+        self.spawn_session(cmd, String::new(), cols, rows).await
+    }
+
+    async fn spawn_session(
+        &self,
+        mut cmd: CommandBuilder,
+        shell_name: String,
+        cols: u16,
+        rows: u16,
     ) -> Result<(Uuid, mpsc::UnboundedReceiver<Vec<u8>>), PtyError> {
         let session_id = Uuid::new_v4();
         let (output_tx, output_rx) = mpsc::unbounded_channel();
-        let shell = get_interactive_shell().await;
 
         let result = tokio::task::spawn_blocking(move || {
             let pty_system = NativePtySystem::default();
@@ -67,18 +100,15 @@ impl PtyService {
                 })
                 .map_err(|e| PtyError::CreateFailed(e.to_string()))?;
 
-            let mut cmd = CommandBuilder::new(&shell);
-            cmd.cwd(&working_dir);
-
             // Configure shell-specific options
-            let shell_name = shell.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let shell_name = shell_name.as_str();
 
             if shell_name == "powershell.exe" || shell_name == "pwsh.exe" {
                 // PowerShell: use -NoLogo for cleaner startup
                 cmd.arg("-NoLogo");
             } else if shell_name == "cmd.exe" {
                 // cmd.exe: no special args needed
-            } else {
+            } else if !shell_name.is_empty() {
                 // Unix shells
                 cmd.env("VIBE_KANBAN_TERMINAL", "1");
 
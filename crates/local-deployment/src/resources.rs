@@ -0,0 +1,594 @@
+//! Resource limits and usage sampling for the process groups of executions
+
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::{path::PathBuf, sync::Arc, time::Instant};
+
+#[cfg(target_os = "linux")]
+use executors::executors::ExecutorError;
+use executors::{command::CmdOverrides, env::SpawnHook};
+#[cfg(target_os = "linux")]
+use tokio::{process::Command, sync::OnceCell};
+use utils::log_msg::ResourceUsage;
+use uuid::Uuid;
+
+#[cfg(target_os = "linux")]
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+#[cfg(target_os = "linux")]
+const CGROUP_CONTROLLERS: &[&str] = &["memory", "cpu", "pids"];
+/// Leaf of the delegated cgroup the server moves itself into
+#[cfg(target_os = "linux")]
+const SERVER_CGROUP: &str = "vibe-kanban-server";
+#[cfg(any(target_os = "linux", test))]
+const CPU_PERIOD_USECS: u64 = 100_000;
+
+/// CPU, memory and process limits of an executor profile
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceLimits {
+    pub memory_mb: Option<u32>,
+    pub cpu_percent: Option<u32>,
+    pub pids: Option<u32>,
+}
+
+impl ResourceLimits {
+    /// The limits set in `cmd`, or None when it sets none
+    pub fn from_overrides(cmd: &CmdOverrides) -> Option<Self> {
+        let limits = Self {
+            memory_mb: cmd.max_memory_mb,
+            cpu_percent: cmd.max_cpu_percent,
+            pids: cmd.max_pids,
+        };
+        (limits != Self::default()).then_some(limits)
+    }
+
+    #[cfg(any(target_os = "linux", test))]
+    fn memory_bytes(&self) -> Option<u64> {
+        self.memory_mb.map(|mb| u64::from(mb) * 1024 * 1024)
+    }
+
+    /// Interface files of a cgroup v2 group and the values that apply these limits
+    #[cfg(any(target_os = "linux", test))]
+    fn cgroup_files(&self) -> Vec<(&'static str, String)> {
+        let mut files = Vec::new();
+        if let Some(bytes) = self.memory_bytes() {
+            files.push(("memory.max", bytes.to_string()));
+        }
+        if let Some(percent) = self.cpu_percent {
+            // The kernel rejects quotas below 1ms
+            let quota = (u64::from(percent) * CPU_PERIOD_USECS / 100).max(1000);
+            files.push(("cpu.max", format!("{quota} {CPU_PERIOD_USECS}")));
+        }
+        if let Some(pids) = self.pids {
+            files.push(("pids.max", pids.to_string()));
+        }
+        files
+    }
+}
+
+/// Per-process rlimits applied when there is no cgroup to enforce the limits of a group
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rlimits {
+    /// `RLIMIT_DATA`, the writable memory of each process
+    data_bytes: Option<u64>,
+    /// `RLIMIT_NPROC`, which counts every process of the user, so it is the limit of the
+    /// execution on top of the processes the user already runs
+    processes: Option<u64>,
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl Rlimits {
+    /// The rlimits for `limits`, given how many processes the user runs. A CPU percentage has
+    /// no rlimit: `RLIMIT_CPU` caps total CPU time, which would end long running agents.
+    fn for_limits(limits: &ResourceLimits, user_processes: u64) -> Self {
+        Self {
+            data_bytes: limits.memory_bytes(),
+            processes: limits
+                .pids
+                .map(|pids| user_processes.saturating_add(u64::from(pids))),
+        }
+    }
+}
+
+/// How the limits of an execution are enforced
+#[derive(Debug, Clone)]
+pub enum AppliedLimits {
+    /// A cgroup created for the execution, which its process joins before it runs and which is
+    /// removed once it ends
+    Cgroup {
+        dir: PathBuf,
+        /// `cgroup.procs` of the cgroup, opened ahead so joining it does not block
+        procs: Arc<std::fs::File>,
+    },
+    /// Without a delegated cgroup, each process gets rlimits and the usage sampler stops the
+    /// process group once its combined resident memory goes over the limit
+    Rlimits(Rlimits),
+}
+
+impl AppliedLimits {
+    /// Hook that confines the process before it runs, None when nothing has to happen
+    pub fn spawn_hook(&self) -> Option<Arc<dyn SpawnHook>> {
+        match self {
+            #[cfg(target_os = "linux")]
+            AppliedLimits::Cgroup { procs, .. } => Some(Arc::new(JoinCgroup(procs.clone()))),
+            #[cfg(target_os = "linux")]
+            AppliedLimits::Rlimits(rlimits) => Some(Arc::new(SetRlimits(*rlimits))),
+            #[cfg(not(target_os = "linux"))]
+            _ => None,
+        }
+    }
+
+    /// Memory limit the usage sampler has to enforce
+    pub fn sampled_memory_limit(&self) -> Option<u64> {
+        match self {
+            AppliedLimits::Rlimits(rlimits) => rlimits.data_bytes,
+            AppliedLimits::Cgroup { .. } => None,
+        }
+    }
+}
+
+/// Set up `limits` for an execution that is about to be spawned. A child cgroup is used when
+/// the server runs in a cgroup v2 group delegated to it; otherwise each process gets rlimits
+/// and the memory of the whole group is enforced by sampling.
+#[cfg(target_os = "linux")]
+pub async fn prepare_limits(exec_id: Uuid, limits: &ResourceLimits) -> Option<AppliedLimits> {
+    match create_cgroup(exec_id, limits).await {
+        Ok(applied) => return Some(applied),
+        Err(e) => tracing::debug!("cgroup v2 limits unavailable: {}", e),
+    }
+
+    if limits.cpu_percent.is_some() {
+        tracing::warn!(
+            "The CPU limit of execution {} needs a delegated cgroup v2 group and is not applied",
+            exec_id
+        );
+    }
+    let user_processes = if limits.pids.is_some() {
+        tokio::task::spawn_blocking(count_user_processes)
+            .await
+            .unwrap_or_default()
+    } else {
+        0
+    };
+    let rlimits = Rlimits::for_limits(limits, user_processes);
+    (rlimits != Rlimits::default()).then_some(AppliedLimits::Rlimits(rlimits))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub async fn prepare_limits(exec_id: Uuid, _limits: &ResourceLimits) -> Option<AppliedLimits> {
+    tracing::warn!(
+        "Resource limits are only available on Linux, execution {} runs unlimited",
+        exec_id
+    );
+    None
+}
+
+/// Moves the spawned process into a cgroup before it runs, so every process it starts is
+/// limited from the beginning
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+struct JoinCgroup(Arc<std::fs::File>);
+
+#[cfg(target_os = "linux")]
+impl SpawnHook for JoinCgroup {
+    fn apply(&self, command: &mut Command) -> Result<(), ExecutorError> {
+        use std::os::fd::AsRawFd;
+
+        let procs = self.0.clone();
+        // SAFETY: the closure only calls write(2), which is async-signal-safe
+        unsafe {
+            command.pre_exec(move || {
+                // Writing 0 moves the writing process
+                if libc::write(procs.as_raw_fd(), b"0".as_ptr().cast(), 1) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Lowers the rlimits of the spawned process before it runs. They are inherited by every
+/// process it starts and cannot be raised again.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+struct SetRlimits(Rlimits);
+
+#[cfg(target_os = "linux")]
+impl SpawnHook for SetRlimits {
+    fn apply(&self, command: &mut Command) -> Result<(), ExecutorError> {
+        let mut rlimits = Vec::new();
+        for (resource, limit) in [
+            (libc::RLIMIT_DATA, self.0.data_bytes),
+            (libc::RLIMIT_NPROC, self.0.processes),
+        ] {
+            let Some(limit) = limit else {
+                continue;
+            };
+            // Raising the hard limit is not allowed, so it is lowered only
+            let mut current = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            // SAFETY: getrlimit only writes to the rlimit it is given
+            if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+                return Err(ExecutorError::Io(std::io::Error::last_os_error()));
+            }
+            let limit = (limit as libc::rlim_t).min(current.rlim_max);
+            rlimits.push((
+                resource,
+                libc::rlimit {
+                    rlim_cur: limit,
+                    rlim_max: limit,
+                },
+            ));
+        }
+
+        // SAFETY: the closure only calls setrlimit(2), a plain system call
+        unsafe {
+            command.pre_exec(move || {
+                for (resource, limit) in &rlimits {
+                    if libc::setrlimit(*resource, limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Number of processes of the user the server runs as, read from /proc. Blocking.
+#[cfg(target_os = "linux")]
+fn count_user_processes() -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    // SAFETY: getuid has no preconditions
+    let uid = unsafe { libc::getuid() };
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.parse::<u32>().is_ok())
+                && entry.metadata().is_ok_and(|metadata| metadata.uid() == uid)
+        })
+        .count() as u64
+}
+
+/// The cgroup execution cgroups are created in, set up on first use
+#[cfg(target_os = "linux")]
+async fn cgroup_parent() -> std::io::Result<&'static Path> {
+    static PARENT: OnceCell<Result<PathBuf, String>> = OnceCell::const_new();
+    PARENT
+        .get_or_init(|| async { setup_cgroup_parent().await.map_err(|e| e.to_string()) })
+        .await
+        .as_deref()
+        .map_err(|e| std::io::Error::other(e.to_string()))
+}
+
+/// The cgroup v2 group a process is in
+#[cfg(target_os = "linux")]
+async fn cgroup_of(pid: &str) -> std::io::Result<PathBuf> {
+    // The unified hierarchy is the "0::" entry
+    let contents = tokio::fs::read_to_string(format!("/proc/{pid}/cgroup")).await?;
+    let relative = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .ok_or_else(|| std::io::Error::other("cgroup v2 is not mounted"))?
+        .trim()
+        .trim_start_matches('/');
+    Ok(Path::new(CGROUP_ROOT).join(relative))
+}
+
+/// Use the cgroup the server runs in when it is delegated to the server, e.g. when it is
+/// started with `systemd-run --user --scope -p Delegate=yes` or from a unit with
+/// `Delegate=yes`. cgroup v2 only enables controllers for the children of a group without
+/// processes of its own, so the server moves itself into a leaf. Groups that hold other
+/// processes are left alone.
+#[cfg(target_os = "linux")]
+async fn setup_cgroup_parent() -> std::io::Result<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut parent = cgroup_of("self").await?;
+    // Set up by an earlier run of the server in the same group
+    if parent.file_name().is_some_and(|name| name == SERVER_CGROUP) {
+        parent.pop();
+    }
+
+    let subtree_control = parent.join("cgroup.subtree_control");
+    let enabled = tokio::fs::read_to_string(&subtree_control).await?;
+    let missing: Vec<&str> = CGROUP_CONTROLLERS
+        .iter()
+        .copied()
+        .filter(|controller| !enabled.split_whitespace().any(|e| e == *controller))
+        .collect();
+    if missing.is_empty() {
+        return Ok(parent);
+    }
+
+    // systemd hands delegated groups over to the user they are delegated to
+    // SAFETY: geteuid has no preconditions
+    let uid = unsafe { libc::geteuid() };
+    if tokio::fs::metadata(&parent).await?.uid() != uid {
+        return Err(std::io::Error::other(format!(
+            "{} is not delegated to the server",
+            parent.display()
+        )));
+    }
+    let own_pid = std::process::id().to_string();
+    let procs = tokio::fs::read_to_string(parent.join("cgroup.procs")).await?;
+    if procs.lines().any(|pid| pid != own_pid) {
+        return Err(std::io::Error::other(format!(
+            "{} has processes other than the server",
+            parent.display()
+        )));
+    }
+
+    if !procs.trim().is_empty() {
+        let leaf = parent.join(SERVER_CGROUP);
+        match tokio::fs::create_dir(&leaf).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+        tokio::fs::write(leaf.join("cgroup.procs"), &own_pid).await?;
+    }
+    let enable: Vec<String> = missing.iter().map(|c| format!("+{c}")).collect();
+    tokio::fs::write(&subtree_control, enable.join(" ")).await?;
+    Ok(parent)
+}
+
+/// Create a cgroup for the execution beneath the delegated one
+#[cfg(target_os = "linux")]
+async fn create_cgroup(exec_id: Uuid, limits: &ResourceLimits) -> std::io::Result<AppliedLimits> {
+    let dir = cgroup_parent()
+        .await?
+        .join(format!("vibe-kanban-{exec_id}"));
+    tokio::fs::create_dir(&dir).await?;
+    let setup = async {
+        for (file, value) in limits.cgroup_files() {
+            tokio::fs::write(dir.join(file), value).await?;
+        }
+        tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(dir.join("cgroup.procs"))
+            .await
+    };
+    match setup.await {
+        Ok(procs) => Ok(AppliedLimits::Cgroup {
+            dir,
+            procs: Arc::new(procs.into_std().await),
+        }),
+        Err(e) => {
+            let _ = tokio::fs::remove_dir(&dir).await;
+            Err(e)
+        }
+    }
+}
+
+/// Undo what [`prepare_limits`] set up once the execution has ended
+pub async fn release_limits(applied: AppliedLimits) {
+    let AppliedLimits::Cgroup { dir, procs } = applied else {
+        return;
+    };
+    drop(procs);
+    // Killed processes can take a moment to leave the cgroup
+    for _ in 0..10 {
+        match tokio::fs::remove_dir(&dir).await {
+            Ok(()) => return,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(200)).await,
+        }
+    }
+    tracing::warn!("Failed to remove cgroup {}", dir.display());
+}
+
+/// Kill every process in the group `pgid`, for limits enforced by sampling
+#[cfg(target_os = "linux")]
+pub fn kill_group(pgid: u32) {
+    // SAFETY: killpg has no memory safety preconditions
+    if unsafe { libc::killpg(pgid as libc::pid_t, libc::SIGKILL) } != 0 {
+        tracing::warn!(
+            "Failed to kill process group {}: {}",
+            pgid,
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn kill_group(_pgid: u32) {}
+
+/// Fields of /proc/<pid>/stat that resource sampling needs
+#[derive(Debug, PartialEq)]
+struct ProcStat {
+    pgrp: u32,
+    /// User plus system time, in clock ticks
+    cpu_ticks: u64,
+    rss_pages: u64,
+}
+
+fn parse_proc_stat(contents: &str) -> Option<ProcStat> {
+    // The command name may contain spaces and parentheses, so fields are counted from the
+    // last ')'. The first field after it is the state, field 3 in proc(5).
+    let fields: Vec<&str> = contents
+        .get(contents.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(ProcStat {
+        pgrp: fields.get(2)?.parse().ok()?,
+        cpu_ticks: utime + stime,
+        rss_pages: fields.get(21)?.parse::<i64>().ok()?.max(0) as u64,
+    })
+}
+
+/// The pids and stats of every process in the group `pgid`, none where there is no procfs.
+/// Reads all of /proc, so async callers run it on a blocking thread.
+fn group_stats(pgid: u32) -> Vec<(u32, ProcStat)> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let stat = parse_proc_stat(&std::fs::read_to_string(entry.path().join("stat")).ok()?)?;
+            (stat.pgrp == pgid).then_some((pid, stat))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn sysconf(name: libc::c_int) -> Option<u64> {
+    // SAFETY: sysconf has no preconditions
+    let value = unsafe { libc::sysconf(name) };
+    u64::try_from(value).ok().filter(|value| *value > 0)
+}
+
+/// Samples the CPU and memory use of a process group from /proc
+pub struct UsageSampler {
+    pgid: u32,
+    ticks_per_sec: u64,
+    page_size: u64,
+    last: Option<(Instant, u64)>,
+}
+
+impl UsageSampler {
+    pub fn new(pgid: u32) -> Self {
+        #[cfg(target_os = "linux")]
+        let (ticks_per_sec, page_size) = (
+            sysconf(libc::_SC_CLK_TCK).unwrap_or(100),
+            sysconf(libc::_SC_PAGESIZE).unwrap_or(4096),
+        );
+        #[cfg(not(target_os = "linux"))]
+        let (ticks_per_sec, page_size) = (100, 4096);
+
+        Self {
+            pgid,
+            ticks_per_sec,
+            page_size,
+            last: None,
+        }
+    }
+
+    /// Current usage of the group, or None once none of its processes are left. CPU use is
+    /// measured since the previous sample and is 0 on the first.
+    pub async fn sample(&mut self) -> Option<ResourceUsage> {
+        let pgid = self.pgid;
+        let stats = tokio::task::spawn_blocking(move || group_stats(pgid))
+            .await
+            .unwrap_or_default();
+        if stats.is_empty() {
+            return None;
+        }
+
+        let ticks: u64 = stats.iter().map(|(_, stat)| stat.cpu_ticks).sum();
+        let rss_pages: u64 = stats.iter().map(|(_, stat)| stat.rss_pages).sum();
+        let now = Instant::now();
+        let cpu_percent = match self.last.replace((now, ticks)) {
+            Some((at, last_ticks)) => {
+                let elapsed = now.duration_since(at).as_secs_f64();
+                // Time of processes that exited since the last sample is no longer counted
+                let busy = ticks.saturating_sub(last_ticks) as f64 / self.ticks_per_sec as f64;
+                if elapsed > 0.0 {
+                    busy / elapsed * 100.0
+                } else {
+                    0.0
+                }
+            }
+            None => 0.0,
+        };
+
+        Some(ResourceUsage {
+            cpu_percent,
+            rss_bytes: rss_pages * self.page_size,
+            process_count: stats.len() as u32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proc_stat_with_parentheses_in_command_name() {
+        let stat = "4242 (node (worker) x) S 4200 4242 4242 0 -1 4194560 1500 0 0 0 \
+                    730 120 0 0 20 0 11 0 98765 1234567890 5120 18446744073709551615 1 1 0 0";
+        assert_eq!(
+            parse_proc_stat(stat),
+            Some(ProcStat {
+                pgrp: 4242,
+                cpu_ticks: 850,
+                rss_pages: 5120,
+            })
+        );
+        assert_eq!(parse_proc_stat("4242 (node) S 4200"), None);
+    }
+
+    #[test]
+    fn limits_map_to_cgroup_interface_files() {
+        let limits = ResourceLimits {
+            memory_mb: Some(512),
+            cpu_percent: Some(150),
+            pids: None,
+        };
+        assert_eq!(
+            limits.cgroup_files(),
+            vec![
+                ("memory.max", "536870912".to_string()),
+                ("cpu.max", "150000 100000".to_string()),
+            ]
+        );
+
+        // Quotas below 1ms are raised to it, and every limit gets its file
+        let limits = ResourceLimits {
+            memory_mb: None,
+            cpu_percent: Some(0),
+            pids: Some(64),
+        };
+        assert_eq!(
+            limits.cgroup_files(),
+            vec![
+                ("cpu.max", "1000 100000".to_string()),
+                ("pids.max", "64".to_string()),
+            ]
+        );
+        assert!(ResourceLimits::default().cgroup_files().is_empty());
+    }
+
+    #[test]
+    fn limits_map_to_rlimits_without_cgroups() {
+        let limits = ResourceLimits {
+            memory_mb: Some(512),
+            cpu_percent: Some(150),
+            pids: Some(64),
+        };
+        // The process limit comes on top of the processes the user already runs
+        assert_eq!(
+            Rlimits::for_limits(&limits, 200),
+            Rlimits {
+                data_bytes: Some(536870912),
+                processes: Some(264),
+            }
+        );
+        assert_eq!(
+            Rlimits::for_limits(
+                &ResourceLimits {
+                    cpu_percent: Some(100),
+                    ..Default::default()
+                },
+                200
+            ),
+            Rlimits::default()
+        );
+    }
+}
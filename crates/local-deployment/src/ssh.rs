@@ -0,0 +1,1029 @@
+//! Running workspaces on a remote machine over SSH. The host gets a bare repository per repo and
+//! a worktree per workspace. The local worktrees mirror the remote ones, so diffs, commits,
+//! merges and pull requests keep working on this machine.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::{Output, Stdio},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use db::models::repo::Repo;
+use executors::{
+    actions::{ExecutorAction, ExecutorActionType},
+    env::CommandWrapper,
+};
+use services::services::container::ContainerError;
+use tokio::{
+    process::Command,
+    sync::{Mutex, RwLock},
+};
+use uuid::Uuid;
+
+use crate::{
+    backend::{ExecutionBackend, Placement},
+    resources::ResourceLimits,
+};
+
+/// Directory beneath the remote home that holds the repositories and worktrees
+const REMOTE_DIR: &str = ".vibe-kanban";
+/// Namespace of the refs commits are moved between the local and remote repositories with
+const REF_PREFIX: &str = "refs/vibe-kanban";
+
+/// Creates the bare repository remote worktrees are added to. $1: git dir
+const INIT_SCRIPT: &str = r#"git init -q --bare "$1""#;
+
+/// Moves a remote worktree to the commit pushed to a ref, keeping its uncommitted changes, or
+/// adds the worktree. Worktrees that already contain the commit, such as those of a workspace
+/// with another execution running, are left alone. $1: git dir, $2: worktree, $3: branch, $4: ref
+const CHECKOUT_SCRIPT: &str = r#"set -e
+if [ ! -e "$2" ]; then
+    git -C "$1" worktree prune
+    mkdir -p "$(dirname "$2")"
+    git --git-dir="$1" worktree add -q -B "$3" "$2" "$4"
+elif git -C "$2" merge-base --is-ancestor "$4" HEAD; then
+    :
+else
+    git -C "$2" add -A
+    if git -C "$2" diff --cached --quiet "$4"; then
+        git -C "$2" reset -q "$4"
+    else
+        git -C "$2" reset -q
+        git -C "$2" reset -q --keep "$4"
+    fi
+fi"#;
+
+/// Commits the working tree of a remote worktree on top of its HEAD, without moving the branch
+/// or touching the index, and prints the HEAD and tree. $1: worktree, $2: ref to point at the
+/// commit
+const SNAPSHOT_SCRIPT: &str = r#"set -e
+cd "$1"
+index=$(mktemp)
+trap 'rm -f "$index"' EXIT
+# Starting from a copy of the index saves hashing unchanged files
+cp "$(git rev-parse --git-path index)" "$index" 2>/dev/null || rm -f "$index"
+export GIT_INDEX_FILE="$index"
+git add -A
+tree=$(git write-tree)
+export GIT_AUTHOR_NAME="Vibe Kanban" GIT_AUTHOR_EMAIL=noreply@vibekanban.com
+export GIT_COMMITTER_NAME="Vibe Kanban" GIT_COMMITTER_EMAIL=noreply@vibekanban.com
+git update-ref "$2" "$(git commit-tree "$tree" -p HEAD -m snapshot)"
+echo "$(git rev-parse HEAD) $tree""#;
+
+/// Identity of the snapshot commits the changes of a worktree are copied with
+const SNAPSHOT_IDENTITY: &[(&str, &str)] = &[
+    ("GIT_AUTHOR_NAME", "Vibe Kanban"),
+    ("GIT_AUTHOR_EMAIL", "noreply@vibekanban.com"),
+    ("GIT_COMMITTER_NAME", "Vibe Kanban"),
+    ("GIT_COMMITTER_EMAIL", "noreply@vibekanban.com"),
+];
+
+/// Kills the process group an execution recorded the leader of. $1: pid file
+const KILL_SCRIPT: &str = r#"[ -f "$1" ] || exit 0
+pid=$(cat "$1")
+rm -f "$1"
+kill -TERM -- "-$pid" 2>/dev/null || kill -TERM "$pid" 2>/dev/null || true"#;
+
+/// Variable the environment of a remote command is handed to [`LOCAL_SCRIPT`] in
+const ENV_VAR: &str = "VK_SSH_ENV";
+
+/// Writes the environment of a remote command to the stdin of ssh ahead of the command's own
+/// input, keeping the values off the command lines of both machines. $@: ssh and its arguments
+const LOCAL_SCRIPT: &str = r#"vars=$VK_SSH_ENV
+unset VK_SSH_ENV
+{ printf '%s' "$vars"; exec cat; } | "$@""#;
+
+/// Reads the environment [`LOCAL_SCRIPT`] writes, lines of shell prefixed with `+` up to a `.`
+/// line, and exports it. `read` takes stdin a byte at a time, so the rest is left to the command.
+const READ_ENV_SCRIPT: &str = r#"vk_env= && while IFS= read -r vk_line && [ "$vk_line" != . ]; do vk_env="$vk_env${vk_line#+}
+"; done && eval "$vk_env""#;
+
+/// Removes a remote workspace. $1: workspace dir, then the git dirs of its repos
+const REMOVE_SCRIPT: &str = r#"rm -rf "$1"
+shift
+for git_dir in "$@"; do
+    [ -d "$git_dir" ] && git -C "$git_dir" worktree prune
+done
+exit 0"#;
+
+/// SSH host the processes of a workspace run on: that of the first repo that sets one. Every
+/// repo of such a workspace is checked out on that host.
+pub fn host_for_repos(repos: &[Repo]) -> Option<&str> {
+    repos.iter().find_map(|repo| repo.ssh_host.as_deref())
+}
+
+/// Directory of a workspace on its SSH host, relative to the remote home
+pub fn remote_workspace_dir(workspace_dir: &Path) -> PathBuf {
+    let name = workspace_dir
+        .file_name()
+        .unwrap_or(workspace_dir.as_os_str());
+    Path::new(REMOTE_DIR).join("worktrees").join(name)
+}
+
+fn remote_git_dir(repo: &Repo) -> PathBuf {
+    Path::new(REMOTE_DIR)
+        .join("repos")
+        .join(format!("{}.git", repo.id))
+}
+
+/// Git URL of a repository on `host`, in scp syntax so paths relative to the remote home and
+/// hosts from ~/.ssh/config work
+fn remote_url(host: &str, git_dir: &Path) -> String {
+    format!("{host}:{}", git_dir.display())
+}
+
+/// Quote `value` as a single word for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_./=:@,+%".contains(&b));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+fn output_text(program: &str, output: Output) -> anyhow::Result<String> {
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(anyhow!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RemoteRepo {
+    local_worktree: PathBuf,
+    /// Paths on the host, relative to the remote home
+    git_dir: PathBuf,
+    worktree: PathBuf,
+}
+
+/// A workspace checked out on an SSH host
+#[derive(Debug, Clone)]
+pub struct RemoteWorkspace {
+    host: String,
+    home: PathBuf,
+    branch: String,
+    local_root: PathBuf,
+    /// Absolute path of the workspace directory on the host
+    remote_root: PathBuf,
+    repos: Vec<RemoteRepo>,
+}
+
+impl RemoteWorkspace {
+    fn pid_file(&self, exec_id: Uuid) -> PathBuf {
+        self.home
+            .join(REMOTE_DIR)
+            .join("pids")
+            .join(exec_id.to_string())
+    }
+}
+
+/// Runs the processes of workspaces on the SSH host their repos set
+#[derive(Debug, Clone)]
+pub struct SshBackend {
+    program: PathBuf,
+    /// Host of workspaces whose repos set none
+    default_host: Option<String>,
+    homes: Arc<RwLock<HashMap<String, PathBuf>>>,
+    /// Executions that were started over SSH
+    executions: Arc<RwLock<HashMap<Uuid, RemoteWorkspace>>>,
+    /// HEAD and tree of the last snapshot copied into each local worktree
+    snapshots: Arc<RwLock<HashMap<PathBuf, String>>>,
+    /// Syncs rewrite the local worktrees, so only one runs at a time
+    sync_lock: Arc<Mutex<()>>,
+}
+
+impl SshBackend {
+    pub fn new(program: PathBuf) -> Self {
+        Self {
+            program,
+            default_host: None,
+            homes: Arc::new(RwLock::new(HashMap::new())),
+            executions: Arc::new(RwLock::new(HashMap::new())),
+            snapshots: Arc::new(RwLock::new(HashMap::new())),
+            sync_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Run workspaces whose repos set no SSH host on `host` too
+    pub fn with_default_host(mut self, host: impl Into<String>) -> Self {
+        self.default_host = Some(host.into());
+        self
+    }
+
+    /// The ssh client on the PATH, if there is one
+    pub async fn detect() -> Option<Self> {
+        utils::shell::resolve_executable_path("ssh")
+            .await
+            .map(Self::new)
+    }
+
+    fn host_for<'a>(&'a self, repos: &'a [Repo]) -> Option<&'a str> {
+        host_for_repos(repos).or(self.default_host.as_deref())
+    }
+
+    /// Arguments of `ssh` that run a command on `host`. Batch mode makes ssh fail rather than
+    /// prompt for passwords or host keys nobody can answer.
+    fn ssh_args(host: &str, stdin: bool) -> Vec<String> {
+        let mut args = vec![
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+            "-T".to_string(),
+        ];
+        if !stdin {
+            args.push("-n".to_string());
+        }
+        args.extend(["--".to_string(), host.to_string()]);
+        args
+    }
+
+    /// Run a shell script on `host` with positional arguments and return its output
+    async fn run_script(&self, host: &str, script: &str, args: &[&str]) -> anyhow::Result<String> {
+        let mut remote = format!("sh -c {} sh", shell_quote(script));
+        for arg in args {
+            remote.push(' ');
+            remote.push_str(&shell_quote(arg));
+        }
+        let output = Command::new(&self.program)
+            .args(Self::ssh_args(host, false))
+            .arg(remote)
+            .stdin(Stdio::null())
+            .output()
+            .await?;
+        output_text("ssh", output)
+    }
+
+    /// Run git in a local worktree, reaching remotes through the same ssh client
+    async fn git(&self, dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+        self.git_with_env(dir, args, &[]).await
+    }
+
+    async fn git_with_env(
+        &self,
+        dir: &Path,
+        args: &[&str],
+        envs: &[(&str, &str)],
+    ) -> anyhow::Result<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .env(
+                "GIT_SSH_COMMAND",
+                format!(
+                    "{} -o BatchMode=yes",
+                    shell_quote(&self.program.to_string_lossy())
+                ),
+            )
+            .envs(envs.iter().copied())
+            .stdin(Stdio::null())
+            .output()
+            .await?;
+        output_text("git", output)
+    }
+
+    /// Tree of the working tree of a local worktree, hashed into a temporary index so the
+    /// worktree's own index is left alone
+    async fn working_tree(&self, worktree: &Path) -> anyhow::Result<String> {
+        let index = std::env::temp_dir().join(format!("vibe-kanban-index-{}", Uuid::new_v4()));
+        let own_index = self
+            .git(worktree, &["rev-parse", "--git-path", "index"])
+            .await?;
+        // Starting from a copy of the index saves hashing unchanged files
+        let _ = tokio::fs::copy(worktree.join(own_index), &index).await;
+        let index_path = index.to_string_lossy();
+        let envs = [("GIT_INDEX_FILE", &*index_path)];
+        let tree = match self.git_with_env(worktree, &["add", "-A"], &envs).await {
+            Ok(_) => self.git_with_env(worktree, &["write-tree"], &envs).await,
+            Err(e) => Err(e),
+        };
+        let _ = tokio::fs::remove_file(&index).await;
+        tree
+    }
+
+    async fn home(&self, host: &str) -> anyhow::Result<PathBuf> {
+        if let Some(home) = self.homes.read().await.get(host) {
+            return Ok(home.clone());
+        }
+        let home = PathBuf::from(self.run_script(host, r#"printf %s "$HOME""#, &[]).await?);
+        self.homes
+            .write()
+            .await
+            .insert(host.to_string(), home.clone());
+        Ok(home)
+    }
+
+    /// Check out a workspace on `host`, moving its remote worktrees to the commits of the local
+    /// ones. Uncommitted changes of the remote worktrees are kept.
+    pub async fn prepare(
+        &self,
+        host: &str,
+        workspace_dir: &Path,
+        repos: &[Repo],
+        branch: &str,
+    ) -> anyhow::Result<RemoteWorkspace> {
+        let home = self.home(host).await?;
+        let root = remote_workspace_dir(workspace_dir);
+        let sync_ref = format!("{REF_PREFIX}/sync/{branch}");
+
+        let _guard = self.sync_lock.lock().await;
+        let mut remote_repos = Vec::with_capacity(repos.len());
+        for repo in repos {
+            let remote = RemoteRepo {
+                local_worktree: workspace_dir.join(&repo.name),
+                git_dir: remote_git_dir(repo),
+                worktree: root.join(&repo.name),
+            };
+            let git_dir = remote.git_dir.to_string_lossy();
+            self.run_script(host, INIT_SCRIPT, &[&git_dir]).await?;
+            // A branch checked out in a worktree cannot be pushed to, so commits go to a ref of
+            // their own that the worktree is then reset to
+            self.git(
+                &remote.local_worktree,
+                &[
+                    "push",
+                    "-q",
+                    "--force",
+                    &remote_url(host, &remote.git_dir),
+                    &format!("HEAD:{sync_ref}"),
+                ],
+            )
+            .await?;
+            self.run_script(
+                host,
+                CHECKOUT_SCRIPT,
+                &[
+                    &git_dir,
+                    &remote.worktree.to_string_lossy(),
+                    branch,
+                    &sync_ref,
+                ],
+            )
+            .await?;
+            remote_repos.push(remote);
+        }
+
+        Ok(RemoteWorkspace {
+            host: host.to_string(),
+            remote_root: home.join(&root),
+            home,
+            branch: branch.to_string(),
+            local_root: workspace_dir.to_path_buf(),
+            repos: remote_repos,
+        })
+    }
+
+    /// Wrapper that runs the commands of an execution on the host of `remote`
+    pub async fn command_wrapper(
+        &self,
+        exec_id: Uuid,
+        remote: RemoteWorkspace,
+        action: &ExecutorAction,
+    ) -> Arc<dyn CommandWrapper> {
+        let wrapper = SshCommand {
+            program: self.program.clone(),
+            host: remote.host.clone(),
+            local_root: remote.local_root.clone(),
+            remote_root: remote.remote_root.clone(),
+            pid_file: remote.pid_file(exec_id),
+            interactive: !matches!(action.typ(), ExecutorActionType::ScriptRequest(_)),
+        };
+        self.executions.write().await.insert(exec_id, remote);
+        Arc::new(wrapper)
+    }
+
+    async fn pull_repo(
+        &self,
+        host: &str,
+        repo: &RemoteRepo,
+        snapshot_ref: &str,
+    ) -> anyhow::Result<()> {
+        let state = self
+            .run_script(
+                host,
+                SNAPSHOT_SCRIPT,
+                &[&repo.worktree.to_string_lossy(), snapshot_ref],
+            )
+            .await?;
+        let synced = self
+            .snapshots
+            .read()
+            .await
+            .get(&repo.local_worktree)
+            .cloned();
+        if synced.as_ref() == Some(&state) {
+            return Ok(());
+        }
+
+        // The local worktree may only hold the changes copied from the host last time, which
+        // the new ones replace. Anything else was changed on this machine and is kept.
+        let worktree = &repo.local_worktree;
+        let tree = self.working_tree(worktree).await?;
+        let head_tree = self.git(worktree, &["rev-parse", "HEAD^{tree}"]).await?;
+        let synced_tree = synced
+            .as_deref()
+            .and_then(|state| state.split_once(' '))
+            .map(|(_, tree)| tree);
+        if tree != head_tree && synced_tree != Some(tree.as_str()) {
+            return Err(anyhow!(
+                "it has changes that were not made on {host}; commit or discard them to resume \
+                 syncing"
+            ));
+        }
+
+        self.git(
+            worktree,
+            &[
+                "fetch",
+                "-q",
+                "--no-tags",
+                &remote_url(host, &repo.git_dir),
+                snapshot_ref,
+            ],
+        )
+        .await?;
+        // Commits made here since the last run, such as those of finished executions, are
+        // pushed before the next one instead of being overwritten
+        if self
+            .git(
+                worktree,
+                &["merge-base", "--is-ancestor", "HEAD", "FETCH_HEAD~1"],
+            )
+            .await
+            .is_err()
+        {
+            tracing::debug!(
+                "Not syncing {} from {}: it has commits the host lacks",
+                worktree.display(),
+                host
+            );
+            return Ok(());
+        }
+        // Commit the changes copied last time so the worktree is clean, move to the snapshot,
+        // then step back to its parent, the remote HEAD, leaving the snapshot as uncommitted
+        // changes. --keep refuses to touch files changed since the check above.
+        if tree != head_tree {
+            let commit = self
+                .git_with_env(
+                    worktree,
+                    &["commit-tree", &tree, "-p", "HEAD", "-m", "snapshot"],
+                    SNAPSHOT_IDENTITY,
+                )
+                .await?;
+            self.git(worktree, &["reset", "-q", "--soft", &commit])
+                .await?;
+            self.git(worktree, &["reset", "-q"]).await?;
+        }
+        self.git(worktree, &["reset", "-q", "--keep", "FETCH_HEAD"])
+            .await?;
+        self.git(worktree, &["reset", "-q", "HEAD~1"]).await?;
+
+        self.snapshots.write().await.insert(worktree.clone(), state);
+        Ok(())
+    }
+}
+
+/// Runs every process on an SSH host: the one the workspace's repos set, otherwise the default
+/// host
+#[async_trait]
+impl ExecutionBackend for SshBackend {
+    async fn place(
+        &self,
+        exec_id: Uuid,
+        workspace_dir: &Path,
+        branch: &str,
+        repos: &[Repo],
+        action: &ExecutorAction,
+        _limits: Option<ResourceLimits>,
+    ) -> Result<Option<Placement>, ContainerError> {
+        let host = self.host_for(repos).ok_or_else(|| {
+            ContainerError::Other(anyhow!(
+                "No SSH host is set for the repositories of this workspace"
+            ))
+        })?;
+        let remote = self
+            .prepare(host, workspace_dir, repos, branch)
+            .await
+            .map_err(|e| {
+                ContainerError::Other(anyhow!("Failed to check out workspace on {host}: {e}"))
+            })?;
+        Ok(Some(Placement {
+            wrapper: self.command_wrapper(exec_id, remote, action).await,
+            remote: true,
+        }))
+    }
+
+    /// Copy the remote worktrees of an execution into the local ones: commits as commits and
+    /// the rest of the working tree as uncommitted changes
+    async fn sync(&self, exec_id: Uuid) {
+        let Some(remote) = self.executions.read().await.get(&exec_id).cloned() else {
+            return;
+        };
+        let _guard = self.sync_lock.lock().await;
+        let snapshot_ref = format!("{REF_PREFIX}/snapshot/{}", remote.branch);
+        for repo in &remote.repos {
+            if let Err(e) = self.pull_repo(&remote.host, repo, &snapshot_ref).await {
+                tracing::warn!(
+                    "Failed to sync {} from {}: {}",
+                    repo.local_worktree.display(),
+                    remote.host,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Stop the remote processes of an execution. They outlive the local ssh client, as
+    /// sessions without a terminal get no hangup when it is killed.
+    async fn stop(&self, exec_id: Uuid) {
+        let Some(remote) = self.executions.read().await.get(&exec_id).cloned() else {
+            return;
+        };
+        let pid_file = remote.pid_file(exec_id);
+        if let Err(e) = self
+            .run_script(&remote.host, KILL_SCRIPT, &[&pid_file.to_string_lossy()])
+            .await
+        {
+            tracing::debug!(
+                "Failed to stop execution {} on {}: {}",
+                exec_id,
+                remote.host,
+                e
+            );
+        }
+    }
+
+    /// Stop the remote processes of an execution, if it ran over SSH, and forget it
+    async fn release(&self, exec_id: Uuid) {
+        self.stop(exec_id).await;
+        self.executions.write().await.remove(&exec_id);
+    }
+
+    /// Remove the remote worktrees of a workspace
+    async fn remove_workspace(&self, workspace_dir: &Path, repos: &[Repo]) {
+        let Some(host) = self.host_for(repos) else {
+            return;
+        };
+        let root = remote_workspace_dir(workspace_dir);
+        let git_dirs: Vec<String> = repos
+            .iter()
+            .map(|repo| remote_git_dir(repo).to_string_lossy().into_owned())
+            .collect();
+        let mut args = vec![root.to_string_lossy().into_owned()];
+        args.extend(git_dirs);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        if let Err(e) = self.run_script(host, REMOVE_SCRIPT, &args).await {
+            tracing::warn!("Failed to remove {} on {}: {}", root.display(), host, e);
+        }
+        let mut snapshots = self.snapshots.write().await;
+        snapshots.retain(|worktree, _| !worktree.starts_with(workspace_dir));
+    }
+}
+
+#[derive(Debug)]
+struct SshCommand {
+    program: PathBuf,
+    host: String,
+    local_root: PathBuf,
+    remote_root: PathBuf,
+    pid_file: PathBuf,
+    /// Keep stdin attached, for agents that are driven through it
+    interactive: bool,
+}
+
+impl SshCommand {
+    /// `value` with the local workspace directory replaced by the remote one, if it is a path
+    /// inside the workspace
+    fn remote_path(&self, value: &str) -> String {
+        match Path::new(value).strip_prefix(&self.local_root) {
+            Ok(rest) if rest.as_os_str().is_empty() => {
+                self.remote_root.to_string_lossy().into_owned()
+            }
+            Ok(rest) => self.remote_root.join(rest).to_string_lossy().into_owned(),
+            Err(_) => value.to_string(),
+        }
+    }
+
+    /// Shell command that records the pid of the session, which leads the process group of
+    /// the command, reads the environment from stdin, then runs the command in place of the
+    /// shell
+    fn remote_command(&self, command: &std::process::Command) -> String {
+        let dir = command
+            .get_current_dir()
+            .map(|dir| self.remote_path(&dir.to_string_lossy()))
+            .unwrap_or_else(|| self.remote_root.to_string_lossy().into_owned());
+        let program = Path::new(command.get_program());
+        // Executables resolved on this machine are looked up on the host's PATH instead
+        let program = if program.is_absolute() {
+            program.file_name().unwrap_or(program.as_os_str())
+        } else {
+            program.as_os_str()
+        };
+
+        let mut words = vec!["exec".to_string(), shell_quote(&program.to_string_lossy())];
+        words.extend(
+            command
+                .get_args()
+                .map(|arg| shell_quote(&self.remote_path(&arg.to_string_lossy()))),
+        );
+
+        let pid_dir = self.pid_file.parent().unwrap_or(Path::new("."));
+        format!(
+            "mkdir -p {} && echo $$ > {} && cd {} && {READ_ENV_SCRIPT} && {}",
+            shell_quote(&pid_dir.to_string_lossy()),
+            shell_quote(&self.pid_file.to_string_lossy()),
+            shell_quote(&dir),
+            words.join(" ")
+        )
+    }
+
+    /// Environment of the command as [`READ_ENV_SCRIPT`] reads it. Only variables set for the
+    /// process are passed, not the server's environment.
+    fn remote_env(&self, command: &std::process::Command) -> String {
+        let mut env = String::new();
+        for (key, value) in command.get_envs() {
+            let Some(value) = value else {
+                continue;
+            };
+            let value = self.remote_path(&value.to_string_lossy());
+            let export = format!(
+                "export {}",
+                shell_quote(&format!("{}={value}", key.to_string_lossy()))
+            );
+            // Values may span lines, each of which is marked as part of the environment
+            for line in export.split('\n') {
+                env.push('+');
+                env.push_str(line);
+                env.push('\n');
+            }
+        }
+        env.push_str(".\n");
+        env
+    }
+}
+
+impl CommandWrapper for SshCommand {
+    fn wrap(&self, command: &Command) -> Command {
+        let mut wrapped = Command::new("sh");
+        wrapped
+            .args(["-c", LOCAL_SCRIPT, "sh"])
+            .arg(&self.program)
+            .args(SshBackend::ssh_args(&self.host, true))
+            .arg(self.remote_command(command.as_std()))
+            .env(ENV_VAR, self.remote_env(command.as_std()))
+            .kill_on_drop(true)
+            .stdin(if self.interactive {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        wrapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::actions::script::{ScriptContext, ScriptRequest, ScriptRequestLanguage};
+
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// A repo with a commit, and a workspace with a worktree of it on `vk/test`
+    fn workspace(dir: &Path) -> (PathBuf, PathBuf, PathBuf, [Repo; 1]) {
+        let repo_path = dir.join("repo");
+        std::fs::create_dir(&repo_path).unwrap();
+        git(&repo_path, &["init", "-q", "-b", "main"]);
+        std::fs::write(repo_path.join("README.md"), "hello\n").unwrap();
+        git(&repo_path, &["add", "-A"]);
+        git(&repo_path, &["commit", "-q", "-m", "initial"]);
+        let workspace_dir = dir.join(format!("vk-test-{}", Uuid::new_v4()));
+        let worktree = workspace_dir.join("app");
+        git(
+            &repo_path,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "vk/test",
+                &worktree.to_string_lossy(),
+            ],
+        );
+        let repos = [Repo {
+            id: Uuid::new_v4(),
+            path: repo_path.clone(),
+            name: "app".to_string(),
+            display_name: "app".to_string(),
+            setup_script: None,
+            cleanup_script: None,
+            copy_files: None,
+            parallel_setup_script: false,
+            dev_server_script: None,
+            default_target_branch: None,
+            container_image: None,
+            ssh_host: None,
+            git_host_provider: None,
+            auto_rebase: Default::default(),
+            created_at: Default::default(),
+            updated_at: Default::default(),
+        }];
+        (repo_path, workspace_dir, worktree, repos)
+    }
+
+    fn script_action() -> ExecutorAction {
+        ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "true".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+                working_dir: None,
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn runs_command_in_remote_worktree_with_quoted_arguments() {
+        assert_eq!(shell_quote("npm"), "npm");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(""), "''");
+
+        let wrapper = SshCommand {
+            program: PathBuf::from("/usr/bin/ssh"),
+            host: "builder".to_string(),
+            local_root: PathBuf::from("/Users/me/vk/1a2b-task"),
+            remote_root: PathBuf::from("/home/me/.vibe-kanban/worktrees/1a2b-task"),
+            pid_file: PathBuf::from("/home/me/.vibe-kanban/pids/42"),
+            interactive: false,
+        };
+        let mut command = Command::new("/usr/local/bin/npx");
+        command
+            .args([
+                "-y",
+                "claude",
+                "--add-dir",
+                "/Users/me/vk/1a2b-task/web",
+                "--add-dir",
+                "/Users/me/vk/1a2b-task-old/web",
+            ])
+            .current_dir("/Users/me/vk/1a2b-task/api")
+            .env("VK_PROJECT_NAME", "My app")
+            .env("GH_TOKEN", "secret\nvalue");
+
+        let wrapped = wrapper.wrap(&command);
+        let args: Vec<String> = wrapped
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            args,
+            vec![
+                "-c".to_string(),
+                LOCAL_SCRIPT.to_string(),
+                "sh".to_string(),
+                "/usr/bin/ssh".to_string(),
+                "-o".to_string(),
+                "BatchMode=yes".to_string(),
+                "-T".to_string(),
+                "--".to_string(),
+                "builder".to_string(),
+                format!(
+                    "mkdir -p /home/me/.vibe-kanban/pids && echo $$ > \
+                     /home/me/.vibe-kanban/pids/42 && cd \
+                     /home/me/.vibe-kanban/worktrees/1a2b-task/api && {READ_ENV_SCRIPT} && exec \
+                     npx -y claude --add-dir /home/me/.vibe-kanban/worktrees/1a2b-task/web \
+                     --add-dir /Users/me/vk/1a2b-task-old/web"
+                ),
+            ]
+        );
+        // Values travel over stdin, not the command line
+        let env: Vec<_> = wrapped.as_std().get_envs().collect();
+        assert_eq!(env.len(), 1);
+        assert_eq!(env[0].0, ENV_VAR);
+        let env = env[0].1.unwrap().to_string_lossy();
+        assert!(env.starts_with('+'));
+        assert!(env.contains("+export 'GH_TOKEN=secret\n+value'\n"));
+        assert!(env.contains("+export 'VK_PROJECT_NAME=My app'\n"));
+        assert!(env.ends_with("\n.\n"));
+        assert_eq!(
+            remote_workspace_dir(&wrapper.local_root),
+            PathBuf::from(".vibe-kanban/worktrees/1a2b-task")
+        );
+    }
+
+    /// Stands in for ssh: runs the remote command on this machine, in `home`
+    fn fake_ssh(dir: &Path, home: &Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::create_dir_all(home).unwrap();
+        let bin = dir.join("bin");
+        std::fs::create_dir(&bin).unwrap();
+        // Named ssh, so git passes it the arguments of OpenSSH
+        let program = bin.join("ssh");
+        std::fs::write(
+            &program,
+            format!(
+                r#"#!/bin/sh
+while [ $# -gt 0 ]; do
+    case "$1" in
+        --) shift; break ;;
+        -o|-p|-l|-i|-F) shift 2 ;;
+        -*) shift ;;
+        *) break ;;
+    esac
+done
+shift
+export HOME={home}
+cd "$HOME" || exit 255
+exec sh -c "$*"
+"#,
+                home = shell_quote(&home.to_string_lossy())
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        program
+    }
+
+    #[tokio::test]
+    async fn checks_out_on_host_passes_env_over_stdin_and_stops_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("remote");
+        let backend = SshBackend::new(fake_ssh(dir.path(), &home)).with_default_host("builder");
+        let (_, workspace_dir, worktree, repos) = workspace(dir.path());
+        let place = |exec_id| {
+            let backend = backend.clone();
+            let workspace_dir = workspace_dir.clone();
+            let repos = repos.clone();
+            async move {
+                let placement = backend
+                    .place(
+                        exec_id,
+                        &workspace_dir,
+                        "vk/test",
+                        &repos,
+                        &script_action(),
+                        None,
+                    )
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert!(placement.remote);
+                placement.wrapper
+            }
+        };
+
+        // The commits of the local worktree are pushed to a bare repo and checked out
+        let exec_id = Uuid::new_v4();
+        let wrapper = place(exec_id).await;
+        let git_dir = home.join(remote_git_dir(&repos[0]));
+        assert_eq!(
+            git(&git_dir, &["rev-parse", "--is-bare-repository"]),
+            "true"
+        );
+        assert_eq!(
+            git(
+                &git_dir,
+                &["rev-parse", &format!("{REF_PREFIX}/sync/vk/test")]
+            ),
+            git(&worktree, &["rev-parse", "HEAD"])
+        );
+        let remote_worktree = home.join(remote_workspace_dir(&workspace_dir)).join("app");
+        assert!(remote_worktree.join("README.md").exists());
+
+        // Variables arrive intact, and what the command writes is synced back
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", r#"printf '%s' "$VK_SECRET" > secret.txt"#])
+            .current_dir(&worktree)
+            .env("VK_SECRET", "it's\n  a secret");
+        let output = wrapper.wrap(&command).output().await.unwrap();
+        assert!(output.status.success(), "{output:?}");
+        backend.sync(exec_id).await;
+        backend.release(exec_id).await;
+        assert_eq!(
+            std::fs::read_to_string(worktree.join("secret.txt")).unwrap(),
+            "it's\n  a secret"
+        );
+
+        // Stopping kills the process recorded in the pid file
+        let exec_id = Uuid::new_v4();
+        let wrapper = place(exec_id).await;
+        let mut command = Command::new("sleep");
+        command.arg("60").current_dir(&worktree);
+        let mut child = wrapper.wrap(&command).spawn().unwrap();
+        let pid_file = home.join(REMOTE_DIR).join("pids").join(exec_id.to_string());
+        for _ in 0..100 {
+            if pid_file.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert!(pid_file.exists());
+        backend.stop(exec_id).await;
+        let status = tokio::time::timeout(std::time::Duration::from_secs(10), child.wait())
+            .await
+            .expect("process was not stopped")
+            .unwrap();
+        assert!(!status.success());
+        assert!(!pid_file.exists());
+        backend.release(exec_id).await;
+
+        backend.remove_workspace(&workspace_dir, &repos).await;
+        assert!(!remote_worktree.exists());
+    }
+
+    /// Needs an sshd to run against: set `VK_TEST_SSH_HOST` to a host, such as `localhost`,
+    /// that accepts logins without a password prompt and has git installed
+    #[tokio::test]
+    #[ignore = "needs an SSH host in VK_TEST_SSH_HOST"]
+    async fn runs_on_host_and_syncs_changes_back_without_overwriting_local_ones() {
+        let Ok(host) = std::env::var("VK_TEST_SSH_HOST") else {
+            return;
+        };
+        let backend = SshBackend::detect()
+            .await
+            .expect("no ssh client installed")
+            .with_default_host(host);
+
+        let dir = tempfile::tempdir().unwrap();
+        let (repo_path, workspace_dir, worktree, repos) = workspace(dir.path());
+
+        let run = |script: &'static str| {
+            let backend = backend.clone();
+            let workspace_dir = workspace_dir.clone();
+            let worktree = worktree.clone();
+            let repos = repos.clone();
+            async move {
+                let exec_id = Uuid::new_v4();
+                let placement = backend
+                    .place(
+                        exec_id,
+                        &workspace_dir,
+                        "vk/test",
+                        &repos,
+                        &script_action(),
+                        None,
+                    )
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert!(placement.remote);
+                let mut command = Command::new("sh");
+                command.args(["-c", script]).current_dir(&worktree);
+                let output = placement.wrapper.wrap(&command).output().await.unwrap();
+                assert!(output.status.success());
+                backend.sync(exec_id).await;
+                backend.release(exec_id).await;
+            }
+        };
+
+        // Changes made on the host arrive as uncommitted changes, replacing those copied before
+        run("echo one > remote.txt").await;
+        run("echo two > remote.txt && rm README.md").await;
+        assert_eq!(
+            std::fs::read_to_string(worktree.join("remote.txt")).unwrap(),
+            "two\n"
+        );
+        assert!(!worktree.join("README.md").exists());
+        assert_eq!(
+            git(&worktree, &["rev-parse", "HEAD"]),
+            git(&repo_path, &["rev-parse", "main"])
+        );
+
+        // Changes made on this machine since are not overwritten
+        std::fs::write(worktree.join("local.txt"), "mine\n").unwrap();
+        run("echo three > remote.txt").await;
+        assert_eq!(
+            std::fs::read_to_string(worktree.join("remote.txt")).unwrap(),
+            "two\n"
+        );
+        assert!(worktree.join("local.txt").exists());
+
+        backend.remove_workspace(&workspace_dir, &repos).await;
+    }
+}
@@ -18,6 +18,7 @@ fn generate_types_content() -> String {
         db::models::project::SearchMatchType::decl(),
         db::models::repo::Repo::decl(),
         db::models::repo::UpdateRepo::decl(),
+        db::models::repo::AutoRebasePolicy::decl(),
         db::models::project_repo::ProjectRepo::decl(),
         db::models::project_repo::CreateProjectRepo::decl(),
         db::models::workspace_repo::WorkspaceRepo::decl(),
@@ -46,11 +47,31 @@ fn generate_types_content() -> String {
         db::models::image::CreateImage::decl(),
         db::models::workspace::Workspace::decl(),
         db::models::workspace::WorkspaceWithStatus::decl(),
+        db::models::attempt_group::AttemptGroup::decl(),
         db::models::session::Session::decl(),
+        db::models::session::ExecutorFallback::decl(),
+        db::models::session::ExecutorFallbackReason::decl(),
         db::models::execution_process::ExecutionProcess::decl(),
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
+        db::models::execution_process::ExecutionProcessCompletionReason::decl(),
         db::models::execution_process_repo_state::ExecutionProcessRepoState::decl(),
+        db::models::budget::Budget::decl(),
+        db::models::budget::SetBudget::decl(),
+        db::models::approval_policy::ProjectApprovalPolicy::decl(),
+        db::models::approval_policy::SetApprovalPolicy::decl(),
+        db::models::pipeline::PipelineStage::decl(),
+        db::models::pipeline::ProjectPipeline::decl(),
+        db::models::pipeline::SetProjectPipeline::decl(),
+        db::models::pipeline::PipelineRunStatus::decl(),
+        db::models::pipeline::PipelineRun::decl(),
+        db::models::approval::Approval::decl(),
+        db::models::approval::ApprovalOutcome::decl(),
+        db::models::approval::ApprovalDecidedBy::decl(),
+        db::models::approval::ApprovalQuery::decl(),
+        db::models::execution_log_search::LogSearchEntryKind::decl(),
+        db::models::execution_log_search::LogSearchHit::decl(),
+        db::models::execution_log_search::LogSearchQuery::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
@@ -59,8 +80,12 @@ fn generate_types_content() -> String {
         utils::approvals::ApprovalStatus::decl(),
         utils::approvals::CreateApprovalRequest::decl(),
         utils::approvals::ApprovalResponse::decl(),
+        utils::approvals::ApprovalScope::decl(),
+        utils::approvals::ApprovalMatcher::decl(),
+        utils::approvals::RememberApproval::decl(),
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
+        utils::log_msg::ResourceUsage::decl(),
         utils::response::ApiResponse::<()>::decl(),
         utils::api::oauth::LoginStatus::decl(),
         utils::api::oauth::ProfileResponse::decl(),
@@ -114,10 +139,15 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::RenameBranchResponse::decl(),
         server::routes::sessions::review::StartReviewRequest::decl(),
         server::routes::sessions::review::ReviewError::decl(),
+        server::routes::sessions::fork::ForkSessionRequest::decl(),
+        server::routes::sessions::fork::RepoForkComparison::decl(),
+        server::routes::sessions::fork::ForkComparison::decl(),
         server::routes::task_attempts::OpenEditorRequest::decl(),
         server::routes::task_attempts::OpenEditorResponse::decl(),
         server::routes::tasks::CreateAndStartTaskRequest::decl(),
         server::routes::task_attempts::pr::CreatePrApiRequest::decl(),
+        server::routes::task_attempts::pr::CreateStackedPrsRequest::decl(),
+        server::routes::task_attempts::pr::StackedPr::decl(),
         server::routes::images::ImageResponse::decl(),
         server::routes::images::ImageMetadata::decl(),
         server::routes::task_attempts::CreateTaskAttemptBody::decl(),
@@ -144,6 +174,13 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::workspace_summary::WorkspaceSummary::decl(),
         server::routes::task_attempts::workspace_summary::WorkspaceSummaryResponse::decl(),
         server::routes::task_attempts::workspace_summary::DiffStats::decl(),
+        server::routes::task_attempts::best_of_n::CreateAttemptGroupBody::decl(),
+        server::routes::task_attempts::best_of_n::AttemptGroupWithWorkspaces::decl(),
+        server::routes::task_attempts::best_of_n::ScriptRunResult::decl(),
+        server::routes::task_attempts::best_of_n::AttemptComparison::decl(),
+        server::routes::task_attempts::best_of_n::AttemptGroupComparison::decl(),
+        server::routes::task_attempts::best_of_n::PickAttemptWinnerRequest::decl(),
+        server::routes::task_attempts::pipeline::ApprovePipelinePlanRequest::decl(),
         services::services::filesystem::DirectoryEntry::decl(),
         services::services::filesystem::DirectoryListResponse::decl(),
         services::services::file_search::SearchMode::decl(),
@@ -158,6 +195,11 @@ fn generate_types_content() -> String {
         services::services::config::UiLanguage::decl(),
         services::services::config::ShowcaseState::decl(),
         services::services::config::SendMessageShortcut::decl(),
+        services::services::config::ModelPrice::decl(),
+        services::services::config::LogRetentionConfig::decl(),
+        services::services::config::GitHostConfig::decl(),
+        services::services::token_usage::TokenUsageReport::decl(),
+        services::services::token_usage::TokenUsageBreakdown::decl(),
         services::services::git::GitBranch::decl(),
         services::services::queued_message::QueuedMessage::decl(),
         services::services::queued_message::QueueStatus::decl(),
@@ -173,6 +215,9 @@ fn generate_types_content() -> String {
         executors::executors::SlashCommandDescription::decl(),
         executors::executors::AvailabilityInfo::decl(),
         executors::command::CommandBuilder::decl(),
+        executors::approvals::policy::ApprovalDecision::decl(),
+        executors::approvals::policy::ApprovalRule::decl(),
+        executors::linux_sandbox::LinuxSandbox::decl(),
         executors::profile::ExecutorProfileId::decl(),
         executors::profile::ExecutorConfig::decl(),
         executors::profile::ExecutorConfigs::decl(),
@@ -193,6 +238,12 @@ fn generate_types_content() -> String {
         executors::executors::droid::Droid::decl(),
         executors::executors::droid::Autonomy::decl(),
         executors::executors::droid::ReasoningEffortLevel::decl(),
+        executors::executors::custom::CustomAgent::decl(),
+        executors::executors::custom::PromptDelivery::decl(),
+        executors::executors::custom::SessionIdRule::decl(),
+        executors::executors::custom::MappedEntryType::decl(),
+        executors::executors::custom::JsonlMappingRule::decl(),
+        executors::executors::acp::Acp::decl(),
         executors::executors::AppendPrompt::decl(),
         executors::actions::coding_agent_initial::CodingAgentInitialRequest::decl(),
         executors::actions::coding_agent_follow_up::CodingAgentFollowUpRequest::decl(),
@@ -203,6 +254,7 @@ fn generate_types_content() -> String {
         executors::logs::NormalizedEntry::decl(),
         executors::logs::NormalizedEntryType::decl(),
         executors::logs::TokenUsageInfo::decl(),
+        executors::logs::TokenUsage::decl(),
         executors::logs::FileChange::decl(),
         executors::logs::ActionType::decl(),
         executors::logs::TodoItem::decl(),
@@ -297,6 +349,14 @@ fn generate_schemas() -> Result<HashMap<&'static str, String>, serde_json::Error
             "droid",
             generate_json_schema::<executors::executors::droid::Droid>()?,
         ),
+        (
+            "custom_agent",
+            generate_json_schema::<executors::executors::custom::CustomAgent>()?,
+        ),
+        (
+            "acp",
+            generate_json_schema::<executors::executors::acp::Acp>()?,
+        ),
     ]);
     println!(
         "✅ JSON schemas generated. {} schemas created.",
@@ -126,9 +126,15 @@ impl IntoResponse for ApiError {
             },
             ApiError::GitHost(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHostError"),
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
-            ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
+            ApiError::Container(err) => match err {
+                ContainerError::BudgetExceeded(_) => (StatusCode::FORBIDDEN, "BudgetExceeded"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
+            },
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
             ApiError::CommandBuilder(_) => (StatusCode::INTERNAL_SERVER_ERROR, "CommandBuildError"),
+            ApiError::Database(sqlx::Error::RowNotFound) => {
+                (StatusCode::NOT_FOUND, "DatabaseError")
+            }
             ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DatabaseError"),
             ApiError::Worktree(_) => (StatusCode::INTERNAL_SERVER_ERROR, "WorktreeError"),
             ApiError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ConfigError"),
@@ -265,6 +271,7 @@ impl IntoResponse for ApiError {
             ApiError::BadRequest(msg) => msg.clone(),
             ApiError::Conflict(msg) => msg.clone(),
             ApiError::Forbidden(msg) => msg.clone(),
+            ApiError::Container(ContainerError::BudgetExceeded(reason)) => reason.clone(),
             _ => format!("{}: {}", error_type, self),
         };
         let response = ApiResponse::<()>::error(&error_message);
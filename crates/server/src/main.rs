@@ -68,9 +68,22 @@ async fn main() -> Result<(), VibeKanbanError> {
         .await
         .map_err(DeploymentError::from)?;
     deployment.spawn_pr_monitor_service().await;
+    deployment.spawn_log_compactor_service().await;
+    deployment.spawn_auto_rebase_service().await;
     deployment
         .track_if_analytics_allowed("session_start", serde_json::json!({}))
         .await;
+    // Index logs of executions that finished before log search existed
+    let deployment_for_log_search = deployment.clone();
+    tokio::spawn(async move {
+        if let Err(e) = deployment_for_log_search
+            .container()
+            .backfill_log_search_index()
+            .await
+        {
+            tracing::warn!("Failed to backfill log search index: {}", e);
+        }
+    });
     // Pre-warm file search cache for most active projects
     let deployment_for_cache = deployment.clone();
     tokio::spawn(async move {
@@ -1,17 +1,38 @@
 use axum::{
     Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json as ResponseJson,
-    routing::post,
+    routing::{get, post},
 };
+use db::models::approval::{Approval, ApprovalQuery};
 use deployment::Deployment;
+use sqlx::Error as SqlxError;
 use utils::{
     approvals::{ApprovalResponse, ApprovalStatus},
     response::ApiResponse,
 };
 
-use crate::DeploymentImpl;
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Audit trail of approval requests, newest first
+pub async fn get_approvals(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ApprovalQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<Approval>>>, ApiError> {
+    let approvals = Approval::search(&deployment.db().pool, &query).await?;
+    Ok(ResponseJson(ApiResponse::success(approvals)))
+}
+
+pub async fn get_approval(
+    State(deployment): State<DeploymentImpl>,
+    Path(id): Path<String>,
+) -> Result<ResponseJson<ApiResponse<Approval>>, ApiError> {
+    let approval = Approval::find_by_id(&deployment.db().pool, &id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    Ok(ResponseJson(ApiResponse::success(approval)))
+}
 
 pub async fn respond_to_approval(
     State(deployment): State<DeploymentImpl>,
@@ -19,8 +40,19 @@ pub async fn respond_to_approval(
     ResponseJson(request): ResponseJson<ApprovalResponse>,
 ) -> Result<ResponseJson<ApiResponse<ApprovalStatus>>, StatusCode> {
     let service = deployment.approvals();
+    let responder = deployment
+        .config()
+        .read()
+        .await
+        .github
+        .username
+        .clone()
+        .unwrap_or_else(|| deployment.user_id().to_string());
 
-    match service.respond(&deployment.db().pool, &id, request).await {
+    match service
+        .respond(&deployment.db().pool, &id, request, &responder)
+        .await
+    {
         Ok((status, context)) => {
             deployment
                 .track_if_analytics_allowed(
@@ -44,5 +76,8 @@ pub async fn respond_to_approval(
 }
 
 pub fn router() -> Router<DeploymentImpl> {
-    Router::new().route("/approvals/{id}/respond", post(respond_to_approval))
+    Router::new()
+        .route("/approvals", get(get_approvals))
+        .route("/approvals/{id}", get(get_approval))
+        .route("/approvals/{id}/respond", post(respond_to_approval))
 }
@@ -5,22 +5,31 @@ use axum::{
         Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
+    http::header,
     middleware::from_fn_with_state,
     response::{IntoResponse, Json as ResponseJson},
     routing::{get, post},
 };
 use db::models::{
+    execution_log_search::{ExecutionLogSearch, LogSearchHit, LogSearchQuery},
     execution_process::{ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus},
     execution_process_repo_state::ExecutionProcessRepoState,
+    session::Session,
 };
 use deployment::Deployment;
+use executors::executors::ExecutorError;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::Deserialize;
 use services::services::container::ContainerService;
 use utils::{log_msg::LogMsg, response::ApiResponse};
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_execution_process_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_execution_process_middleware,
+    routes::sessions::transcript::{TranscriptQuery, transcript_response},
+};
 
 #[derive(Debug, Deserialize)]
 pub struct SessionExecutionProcessQuery {
@@ -166,6 +175,38 @@ async fn handle_normalized_logs_ws(
     Ok(())
 }
 
+/// Stream the CPU and memory samples of a running execution's process group
+pub async fn stream_resource_usage_ws(
+    ws: WebSocketUpgrade,
+    State(deployment): State<DeploymentImpl>,
+    Path(exec_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let store = deployment
+        .container()
+        .get_msg_store_by_id(&exec_id)
+        .await
+        .ok_or_else(|| {
+            ApiError::ExecutionProcess(ExecutionProcessError::ExecutionProcessNotFound)
+        })?;
+
+    let stream = store
+        .history_plus_stream()
+        .filter(|msg| {
+            futures_util::future::ready(matches!(
+                msg,
+                Ok(LogMsg::ResourceUsage(_) | LogMsg::Finished)
+            ))
+        })
+        .err_into::<anyhow::Error>()
+        .boxed();
+
+    Ok(ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_normalized_logs_ws(socket, stream).await {
+            tracing::warn!("resource usage WS closed: {}", e);
+        }
+    }))
+}
+
 pub async fn stop_execution_process(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(deployment): State<DeploymentImpl>,
@@ -243,6 +284,60 @@ pub async fn get_execution_process_repo_states(
     Ok(ResponseJson(ApiResponse::success(repo_states)))
 }
 
+/// Download the raw output of a coding agent execution as a JSONL replay fixture
+pub async fn get_execution_process_replay_fixture(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<impl IntoResponse, ApiError> {
+    let fixture = deployment
+        .container()
+        .record_replay_fixture(&execution_process)
+        .await?
+        .ok_or_else(|| {
+            ApiError::BadRequest("Execution process did not run a coding agent".to_string())
+        })?;
+    let body = fixture.to_jsonl().map_err(ExecutorError::from)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"replay-{}.jsonl\"",
+                    execution_process.id
+                ),
+            ),
+        ],
+        body,
+    ))
+}
+
+/// Download the transcript of a single coding agent execution
+pub async fn get_execution_process_transcript(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TranscriptQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let session = Session::find_by_id(&deployment.db().pool, execution_process.session_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Session not found".to_string()))?;
+    let transcript = deployment
+        .container()
+        .build_transcript(&session, std::slice::from_ref(&execution_process))
+        .await?;
+    transcript_response(&transcript, query.format, execution_process.id)
+}
+
+/// Full-text search over the normalized logs of coding agent executions
+pub async fn search_execution_logs(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<LogSearchHit>>>, ApiError> {
+    let hits = ExecutionLogSearch::search(&deployment.db().pool, &query).await?;
+    Ok(ResponseJson(ApiResponse::success(hits)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspace_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
@@ -250,12 +345,16 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/repo-states", get(get_execution_process_repo_states))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
+        .route("/resource-usage/ws", get(stream_resource_usage_ws))
+        .route("/replay-fixture", get(get_execution_process_replay_fixture))
+        .route("/transcript", get(get_execution_process_transcript))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_execution_process_middleware,
         ));
 
     let workspaces_router = Router::new()
+        .route("/search", get(search_execution_logs))
         .route(
             "/stream/session/ws",
             get(stream_execution_processes_by_session_ws),
@@ -13,6 +13,8 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
+    approval_policy::{ProjectApprovalPolicy, SetApprovalPolicy},
+    pipeline::{ProjectPipeline, SetProjectPipeline, validate_pipeline_stages},
     project::{CreateProject, Project, ProjectError, SearchResult, UpdateProject},
     project_repo::{CreateProjectRepo, ProjectRepo},
     repo::Repo,
@@ -568,6 +570,81 @@ pub async fn get_project_repository(
     }
 }
 
+pub async fn get_project_approval_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectApprovalPolicy>>>, ApiError> {
+    let policy = ProjectApprovalPolicy::find_for_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+pub async fn set_project_approval_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetApprovalPolicy>,
+) -> Result<ResponseJson<ApiResponse<ProjectApprovalPolicy>>, ApiError> {
+    let policy =
+        ProjectApprovalPolicy::set_for_project(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_approval_policy_set",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "rule_count": payload.rules.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+pub async fn delete_project_approval_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectApprovalPolicy::delete_for_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_project_pipeline(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectPipeline>>>, ApiError> {
+    let pipeline = ProjectPipeline::find_for_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(pipeline)))
+}
+
+pub async fn set_project_pipeline(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetProjectPipeline>,
+) -> Result<ResponseJson<ApiResponse<ProjectPipeline>>, ApiError> {
+    validate_pipeline_stages(&payload.stages).map_err(ApiError::BadRequest)?;
+    let pipeline =
+        ProjectPipeline::set_for_project(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_pipeline_set",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "stage_count": payload.stages.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(pipeline)))
+}
+
+pub async fn delete_project_pipeline(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectPipeline::delete_for_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let project_id_router = Router::new()
         .route(
@@ -586,6 +663,18 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             "/repositories",
             get(get_project_repositories).post(add_project_repository),
         )
+        .route(
+            "/approval-policy",
+            get(get_project_approval_policy)
+                .put(set_project_approval_policy)
+                .delete(delete_project_approval_policy),
+        )
+        .route(
+            "/pipeline",
+            get(get_project_pipeline)
+                .put(set_project_pipeline)
+                .delete(delete_project_pipeline),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
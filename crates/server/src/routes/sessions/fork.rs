@@ -0,0 +1,265 @@
+use std::path::PathBuf;
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    coding_agent_turn::CodingAgentTurn,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    execution_process_repo_state::ExecutionProcessRepoState,
+    session::Session,
+    workspace::{CreateWorkspace, Workspace, WorkspaceError},
+    workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
+};
+use deployment::Deployment;
+use executors::{executors::BaseAgentCapability, profile::ExecutorConfigs};
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::ContainerService, git::DiffTarget, workspace_manager::WorkspaceManager,
+};
+use ts_rs::TS;
+use utils::{diff::Diff, response::ApiResponse};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ForkSessionRequest {
+    /// The coding agent turn to fork after
+    pub execution_process_id: Uuid,
+}
+
+/// Changes of a forked session's branch relative to the branch of the session it was forked from
+#[derive(Debug, Serialize, TS)]
+pub struct RepoForkComparison {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub original_branch: String,
+    pub fork_branch: String,
+    pub diffs: Vec<Diff>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ForkComparison {
+    pub original_session_id: Uuid,
+    pub original_workspace_id: Uuid,
+    pub repos: Vec<RepoForkComparison>,
+}
+
+/// Branch a new session off an earlier coding agent turn. The fork gets its own workspace whose
+/// worktrees start at the commits the turn ended on, and its first follow-up resumes a fork of
+/// the agent conversation at that turn. The original session and workspace are left untouched.
+pub async fn fork_session(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ForkSessionRequest>,
+) -> Result<ResponseJson<ApiResponse<Session>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let process = ExecutionProcess::find_by_id(pool, payload.execution_process_id)
+        .await?
+        .ok_or(ApiError::BadRequest("Process not found".to_string()))?;
+    if process.session_id != session.id {
+        return Err(ApiError::BadRequest(
+            "Process does not belong to this session".to_string(),
+        ));
+    }
+    if process.run_reason != ExecutionProcessRunReason::CodingAgent || process.dropped {
+        return Err(ApiError::BadRequest(
+            "Only coding agent turns in the session history can be forked".to_string(),
+        ));
+    }
+    if process.status == ExecutionProcessStatus::Running {
+        return Err(ApiError::Conflict(
+            "Cannot fork a turn that is still running".to_string(),
+        ));
+    }
+
+    let executor_profile_id = process
+        .executor_action()
+        .ok()
+        .and_then(|action| action.executor_profile_id())
+        .cloned()
+        .ok_or(ApiError::BadRequest(
+            "Turn has no coding agent executor".to_string(),
+        ))?;
+    let coding_agent =
+        ExecutorConfigs::get_cached().get_coding_agent_or_default(&executor_profile_id);
+    if !coding_agent
+        .capabilities()
+        .contains(&BaseAgentCapability::SessionFork)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "{} does not support forking sessions",
+            executor_profile_id.executor
+        )));
+    }
+
+    let has_agent_session = CodingAgentTurn::find_by_execution_process_id(pool, process.id)
+        .await?
+        .is_some_and(|turn| turn.agent_session_id.is_some());
+    if !has_agent_session {
+        return Err(ApiError::BadRequest(
+            "Turn has no agent session to fork from".to_string(),
+        ));
+    }
+
+    let workspace = Workspace::find_by_id(pool, session.workspace_id)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::ValidationError(
+            "Workspace not found".to_string(),
+        )))?;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    let fork_workspace_id = Uuid::new_v4();
+    let branch = deployment
+        .container()
+        .git_branch_from_workspace(&fork_workspace_id, &task.title)
+        .await;
+    let fork_workspace = Workspace::create(
+        pool,
+        &CreateWorkspace {
+            branch,
+            agent_working_dir: workspace.agent_working_dir.clone(),
+        },
+        fork_workspace_id,
+        task.id,
+    )
+    .await?;
+
+    let fork =
+        match setup_fork_workspace(&deployment, &session, &process, &workspace, &fork_workspace)
+            .await
+        {
+            Ok(fork) => fork,
+            Err(e) => {
+                // Don't leave a half set up workspace and its worktrees behind
+                WorkspaceManager::discard_workspace(pool, fork_workspace.id).await;
+                return Err(e);
+            }
+        };
+
+    deployment
+        .track_if_analytics_allowed(
+            "session_forked",
+            serde_json::json!({
+                "session_id": session.id.to_string(),
+                "fork_session_id": fork.id.to_string(),
+                "executor": &executor_profile_id.executor,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(fork)))
+}
+
+/// Give the fork workspace the repos of the original one with worktrees at the commits the
+/// turn ended on, then create the forked session in it
+async fn setup_fork_workspace(
+    deployment: &DeploymentImpl,
+    session: &Session,
+    process: &ExecutionProcess,
+    workspace: &Workspace,
+    fork_workspace: &Workspace,
+) -> Result<Session, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace_repos: Vec<CreateWorkspaceRepo> =
+        WorkspaceRepo::find_by_workspace_id(pool, workspace.id)
+            .await?
+            .into_iter()
+            .map(|wr| CreateWorkspaceRepo {
+                repo_id: wr.repo_id,
+                target_branch: wr.target_branch,
+            })
+            .collect();
+    WorkspaceRepo::create_many(pool, fork_workspace.id, &workspace_repos).await?;
+
+    // Move each fresh worktree to the commit the turn ended on
+    let container_ref = deployment.container().create(fork_workspace).await?;
+    let workspace_dir = PathBuf::from(container_ref);
+    let repo_states =
+        ExecutionProcessRepoState::find_by_execution_process_id(pool, process.id).await?;
+    for repo in WorkspaceRepo::find_repos_for_workspace(pool, fork_workspace.id).await? {
+        let Some(commit) = repo_states
+            .iter()
+            .find(|state| state.repo_id == repo.id)
+            .and_then(|state| {
+                state
+                    .after_head_commit
+                    .clone()
+                    .or_else(|| state.before_head_commit.clone())
+            })
+        else {
+            continue;
+        };
+        deployment.git().reset_worktree_to_commit(
+            &workspace_dir.join(&repo.name),
+            &commit,
+            true,
+        )?;
+    }
+
+    Ok(Session::create_fork(pool, session, process.id, Uuid::new_v4(), fork_workspace.id).await?)
+}
+
+/// Compare the committed changes of a forked session with the session it was forked from
+pub async fn compare_fork(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ForkComparison>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let original = match session.forked_from_session_id {
+        Some(id) => Session::find_by_id(pool, id).await?,
+        None => return Err(ApiError::BadRequest("Session is not a fork".to_string())),
+    }
+    .ok_or(ApiError::BadRequest(
+        "Original session not found".to_string(),
+    ))?;
+    let fork_workspace = Workspace::find_by_id(pool, session.workspace_id)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::ValidationError(
+            "Workspace not found".to_string(),
+        )))?;
+    let original_workspace = Workspace::find_by_id(pool, original.workspace_id)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::ValidationError(
+            "Original workspace not found".to_string(),
+        )))?;
+
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, fork_workspace.id).await?;
+    let original_branch = original_workspace.branch.clone();
+    let fork_branch = fork_workspace.branch.clone();
+    let git = deployment.git().clone();
+    let comparisons = tokio::task::spawn_blocking(move || {
+        repos
+            .into_iter()
+            .map(|repo| {
+                let diffs = git.get_diffs(
+                    DiffTarget::Branch {
+                        repo_path: &repo.path,
+                        branch_name: &fork_branch,
+                        base_branch: &original_branch,
+                    },
+                    None,
+                )?;
+                Ok(RepoForkComparison {
+                    repo_id: repo.id,
+                    repo_name: repo.name,
+                    original_branch: original_branch.clone(),
+                    fork_branch: fork_branch.clone(),
+                    diffs,
+                })
+            })
+            .collect::<Result<Vec<_>, ApiError>>()
+    })
+    .await
+    .map_err(std::io::Error::from)??;
+
+    Ok(ResponseJson(ApiResponse::success(ForkComparison {
+        original_session_id: original.id,
+        original_workspace_id: original_workspace.id,
+        repos: comparisons,
+    })))
+}
@@ -1,5 +1,7 @@
+pub mod fork;
 pub mod queue;
 pub mod review;
+pub mod transcript;
 
 use axum::{
     Extension, Json, Router,
@@ -9,6 +11,7 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
+    coding_agent_turn::CodingAgentTurn,
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
     scratch::{Scratch, ScratchType},
     session::{CreateSession, Session, SessionError},
@@ -174,8 +177,17 @@ pub async fn follow_up(
         let _ = ExecutionProcess::drop_at_and_after(pool, process.session_id, proc_id).await?;
     }
 
+    // A fork without turns of its own continues the agent session of the turn it forked from
     let latest_agent_session_id =
-        ExecutionProcess::find_latest_coding_agent_turn_session_id(pool, session.id).await?;
+        match ExecutionProcess::find_latest_coding_agent_turn_session_id(pool, session.id).await? {
+            Some(agent_session_id) => Some(agent_session_id),
+            None => match session.forked_from_execution_process_id {
+                Some(process_id) => CodingAgentTurn::find_by_execution_process_id(pool, process_id)
+                    .await?
+                    .and_then(|turn| turn.agent_session_id),
+                None => None,
+            },
+        };
 
     let prompt = payload.prompt;
 
@@ -236,6 +248,9 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/", get(get_session))
         .route("/follow-up", post(follow_up))
         .route("/review", post(review::start_review))
+        .route("/transcript", get(transcript::get_session_transcript))
+        .route("/fork", post(fork::fork_session))
+        .route("/fork-compare", get(fork::compare_fork))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_session_middleware,
@@ -0,0 +1,60 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use db::models::{execution_process::ExecutionProcess, session::Session};
+use deployment::Deployment;
+use executors::executors::ExecutorError;
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    transcript::{Transcript, TranscriptFormat},
+};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptQuery {
+    #[serde(default)]
+    pub format: TranscriptFormat,
+}
+
+/// Render a transcript as a download named after the session or execution process it covers
+pub fn transcript_response(
+    transcript: &Transcript,
+    format: TranscriptFormat,
+    id: Uuid,
+) -> Result<Response, ApiError> {
+    let body = transcript.render(format).map_err(ExecutorError::from)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"transcript-{id}.{}\"",
+                    format.extension()
+                ),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+pub async fn get_session_transcript(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TranscriptQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let processes =
+        ExecutionProcess::find_by_session_id(&deployment.db().pool, session.id, false).await?;
+    let transcript = deployment
+        .container()
+        .build_transcript(&session, &processes)
+        .await?;
+    transcript_response(&transcript, query.format, session.id)
+}
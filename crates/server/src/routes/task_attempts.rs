@@ -1,13 +1,15 @@
+pub mod best_of_n;
 pub mod codex_setup;
 pub mod cursor_setup;
 pub mod gh_cli_setup;
 pub mod images;
+pub mod pipeline;
 pub mod pr;
 pub mod util;
 pub mod workspace_summary;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
@@ -178,24 +180,44 @@ pub async fn create_task_attempt(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateTaskAttemptBody>,
 ) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
-    let executor_profile_id = payload.executor_profile_id.clone();
-
     if payload.repos.is_empty() {
         return Err(ApiError::BadRequest(
             "At least one repository is required".to_string(),
         ));
     }
 
-    let pool = &deployment.db().pool;
     let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
         .await?
         .ok_or(SqlxError::RowNotFound)?;
 
+    let workspace = start_task_attempt(
+        &deployment,
+        &task,
+        &payload.repos,
+        &payload.executor_profile_id,
+    )
+    .await?;
+
+    tracing::info!("Created attempt for task {}", task.id);
+
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
+/// Create a workspace for `task` on `repos` and start the coding agent in it. A failure to
+/// start the agent is logged and leaves the workspace in place.
+pub(crate) async fn start_task_attempt(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    repos: &[WorkspaceRepoInput],
+    executor_profile_id: &ExecutorProfileId,
+) -> Result<Workspace, ApiError> {
+    let pool = &deployment.db().pool;
+
     // Compute agent_working_dir based on repo count:
     // - Single repo: use repo name as working dir (agent runs in repo directory)
     // - Multiple repos: use None (agent runs in workspace root)
-    let agent_working_dir = if payload.repos.len() == 1 {
-        let repo = Repo::find_by_id(pool, payload.repos[0].repo_id)
+    let agent_working_dir = if repos.len() == 1 {
+        let repo = Repo::find_by_id(pool, repos[0].repo_id)
             .await?
             .ok_or(RepoError::NotFound)?;
         Some(repo.name)
@@ -216,12 +238,11 @@ pub async fn create_task_attempt(
             agent_working_dir,
         },
         attempt_id,
-        payload.task_id,
+        task.id,
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
+    let workspace_repos: Vec<CreateWorkspaceRepo> = repos
         .iter()
         .map(|r| CreateWorkspaceRepo {
             repo_id: r.repo_id,
@@ -246,14 +267,12 @@ pub async fn create_task_attempt(
                 "variant": &executor_profile_id.variant,
                 "executor": &executor_profile_id.executor,
                 "workspace_id": workspace.id.to_string(),
-                "repository_count": payload.repos.len(),
+                "repository_count": repos.len(),
             }),
         )
         .await;
 
-    tracing::info!("Created attempt for task {}", task.id);
-
-    Ok(ResponseJson(ApiResponse::success(workspace)))
+    Ok(workspace)
 }
 
 #[axum::debug_handler]
@@ -423,12 +442,39 @@ pub async fn merge_task_attempt(
     State(deployment): State<DeploymentImpl>,
     Json(request): Json<MergeTaskAttemptRequest>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let task = workspace
+        .parent_task(&deployment.db().pool)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::TaskNotFound))?;
+
+    merge_workspace_repo(&deployment, &workspace, &task, request.repo_id).await?;
+    complete_merged_workspace(&deployment, &workspace, &task).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_merged",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Merge the workspace branch of one repo into its target branch and record the merge
+pub(crate) async fn merge_workspace_repo(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    task: &Task,
+    repo_id: Uuid,
+) -> Result<(), ApiError> {
     let pool = &deployment.db().pool;
 
-    let workspace_repo =
-        WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
-            .await?
-            .ok_or(RepoError::NotFound)?;
+    let workspace_repo = WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
 
     let repo = Repo::find_by_id(pool, workspace_repo.repo_id)
         .await?
@@ -447,15 +493,11 @@ pub async fn merge_task_attempt(
 
     let container_ref = deployment
         .container()
-        .ensure_container_exists(&workspace)
+        .ensure_container_exists(workspace)
         .await?;
     let workspace_path = Path::new(&container_ref);
     let worktree_path = workspace_path.join(repo.name);
 
-    let task = workspace
-        .parent_task(pool)
-        .await?
-        .ok_or(ApiError::Workspace(WorkspaceError::TaskNotFound))?;
     let task_uuid_str = task.id.to_string();
     let first_uuid_section = task_uuid_str.split('-').next().unwrap_or(&task_uuid_str);
 
@@ -485,6 +527,19 @@ pub async fn merge_task_attempt(
         &merge_commit_id,
     )
     .await?;
+
+    Ok(())
+}
+
+/// Mark the task of a merged workspace done, archive the workspace unless it is pinned and
+/// stop its dev servers
+pub(crate) async fn complete_merged_workspace(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    task: &Task,
+) -> Result<(), ApiError> {
+    let pool = &deployment.db().pool;
+
     Task::update_status(pool, task.id, TaskStatus::Done).await?;
     if !workspace.pinned {
         Workspace::set_archived(pool, workspace.id, true).await?;
@@ -515,17 +570,7 @@ pub async fn merge_task_attempt(
         }
     }
 
-    deployment
-        .track_if_analytics_allowed(
-            "task_attempt_merged",
-            serde_json::json!({
-                "task_id": task.id.to_string(),
-                "workspace_id": workspace.id.to_string(),
-            }),
-        )
-        .await;
-
-    Ok(ResponseJson(ApiResponse::success(())))
+    Ok(())
 }
 
 pub async fn push_task_attempt_branch(
@@ -701,6 +746,8 @@ pub struct BranchStatus {
     pub conflicted_files: Vec<String>,
     /// True if the target branch is a remote branch (merging not allowed, must use PR)
     pub is_target_remote: bool,
+    /// True if an automatic rebase onto the target branch hit conflicts and was aborted
+    pub auto_rebase_conflict: bool,
 }
 
 #[derive(Debug, Clone, Serialize, TS)]
@@ -723,6 +770,11 @@ pub async fn get_task_attempt_branch_status(
         .iter()
         .map(|wr| (wr.repo_id, wr.target_branch.clone()))
         .collect();
+    let auto_rebase_conflicts: HashSet<_> = workspace_repos
+        .iter()
+        .filter(|wr| wr.auto_rebase_conflict_oid.is_some())
+        .map(|wr| wr.repo_id)
+        .collect();
 
     let container_ref = deployment
         .container()
@@ -850,6 +902,7 @@ pub async fn get_task_attempt_branch_status(
                 conflict_op,
                 conflicted_files,
                 is_target_remote: target_branch_type == BranchType::Remote,
+                auto_rebase_conflict: auto_rebase_conflicts.contains(&repo.id),
             },
         });
     }
@@ -1173,6 +1226,10 @@ pub async fn rebase_task_attempt(
         };
     }
 
+    if workspace_repo.auto_rebase_conflict_oid.is_some() {
+        WorkspaceRepo::set_auto_rebase_conflict(pool, workspace.id, payload.repo_id, None).await?;
+    }
+
     deployment
         .track_if_analytics_allowed(
             "task_attempt_rebased",
@@ -1774,6 +1831,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/rebase", post(rebase_task_attempt))
         .route("/conflicts/abort", post(abort_conflicts_task_attempt))
         .route("/pr", post(pr::create_pr))
+        .route("/pr/stack", post(pr::create_stacked_prs))
         .route("/pr/attach", post(pr::attach_existing_pr))
         .route("/pr/comments", get(pr::get_pr_comments))
         .route("/open-editor", post(open_task_attempt_in_editor))
@@ -1785,6 +1843,9 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/search", get(search_workspace_files))
         .route("/first-message", get(get_first_user_message))
         .route("/mark-seen", put(mark_seen))
+        .route("/pipeline", get(pipeline::get_pipeline_run))
+        .route("/pipeline/approve", post(pipeline::approve_pipeline_plan))
+        .route("/pipeline/reject", post(pipeline::reject_pipeline_plan))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_workspace_middleware,
@@ -1795,6 +1856,19 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/count", get(get_workspace_count))
         .route("/stream/ws", get(stream_workspaces_ws))
         .route("/summary", post(workspace_summary::get_workspace_summaries))
+        .route(
+            "/groups",
+            get(best_of_n::get_attempt_groups).post(best_of_n::create_attempt_group),
+        )
+        .route("/groups/{group_id}", get(best_of_n::get_attempt_group))
+        .route(
+            "/groups/{group_id}/compare",
+            get(best_of_n::compare_attempt_group),
+        )
+        .route(
+            "/groups/{group_id}/pick-winner",
+            post(best_of_n::pick_attempt_winner),
+        )
         .nest("/{id}", task_attempt_id_router)
         .nest("/{id}/images", images::router(deployment));
 
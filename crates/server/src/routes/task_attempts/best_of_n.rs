@@ -0,0 +1,336 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    attempt_group::AttemptGroup,
+    coding_agent_turn::CodingAgentTurn,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    execution_process_token_usage::{ExecutionProcessTokenUsage, TokenUsageScope},
+    merge::Merge,
+    session::Session,
+    task::Task,
+    workspace::{Workspace, WorkspaceError},
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use services::services::{container::ContainerService, token_usage::TokenUsageReport};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    routes::{
+        task_attempts::{
+            WorkspaceRepoInput, complete_merged_workspace, merge_workspace_repo,
+            start_task_attempt,
+            workspace_summary::{DiffStats, compute_workspace_diff_stats},
+        },
+        token_usage::build_report,
+    },
+};
+
+const MAX_ATTEMPTS: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct CreateAttemptGroupBody {
+    pub task_id: Uuid,
+    pub repos: Vec<WorkspaceRepoInput>,
+    /// Each profile is started `attempts_per_profile` times, in its own workspace
+    pub executor_profile_ids: Vec<ExecutorProfileId>,
+    pub attempts_per_profile: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptGroupWithWorkspaces {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub group: AttemptGroup,
+    pub workspaces: Vec<Workspace>,
+}
+
+/// Outcome of the latest cleanup script run, which is where repos run their tests and checks
+/// after each coding agent turn
+#[derive(Debug, Serialize, TS)]
+pub struct ScriptRunResult {
+    pub execution_process_id: Uuid,
+    pub status: ExecutionProcessStatus,
+    pub exit_code: Option<i64>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptComparison {
+    pub workspace: Workspace,
+    pub executor_profile_id: Option<ExecutorProfileId>,
+    /// Status of the latest coding agent turn
+    pub agent_status: Option<ExecutionProcessStatus>,
+    /// None when the workspace has no worktree
+    pub diff_stats: Option<DiffStats>,
+    pub test_run: Option<ScriptRunResult>,
+    pub token_usage: TokenUsageReport,
+    /// Final assistant message of the latest turn
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptGroupComparison {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub group: AttemptGroup,
+    pub attempts: Vec<AttemptComparison>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttemptGroupQuery {
+    pub task_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct PickAttemptWinnerRequest {
+    pub workspace_id: Uuid,
+}
+
+async fn find_group(deployment: &DeploymentImpl, id: Uuid) -> Result<AttemptGroup, ApiError> {
+    AttemptGroup::find_by_id(&deployment.db().pool, id)
+        .await?
+        .ok_or(ApiError::BadRequest("Attempt group not found".to_string()))
+}
+
+async fn group_workspaces(
+    deployment: &DeploymentImpl,
+    group: &AttemptGroup,
+) -> Result<Vec<Workspace>, ApiError> {
+    let pool = &deployment.db().pool;
+    let mut workspaces = Vec::new();
+    for id in AttemptGroup::workspace_ids(pool, group.id).await? {
+        if let Some(workspace) = Workspace::find_by_id(pool, id).await? {
+            workspaces.push(workspace);
+        }
+    }
+    Ok(workspaces)
+}
+
+/// Start the same task several times in separate workspaces, on different executor profiles or
+/// repeatedly on one
+pub async fn create_attempt_group(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateAttemptGroupBody>,
+) -> Result<ResponseJson<ApiResponse<AttemptGroupWithWorkspaces>>, ApiError> {
+    if payload.repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one repository is required".to_string(),
+        ));
+    }
+    let attempts_per_profile = payload.attempts_per_profile.unwrap_or(1) as usize;
+    let attempt_count = payload.executor_profile_ids.len() * attempts_per_profile;
+    if !(2..=MAX_ATTEMPTS).contains(&attempt_count) {
+        return Err(ApiError::BadRequest(format!(
+            "Between 2 and {MAX_ATTEMPTS} attempts can be started at once"
+        )));
+    }
+
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, payload.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    // The group exists before any attempt starts, so a failure part way through leaves the
+    // attempts that did start in it
+    let group = AttemptGroup::create(pool, Uuid::new_v4(), task.id).await?;
+    let mut workspaces = Vec::with_capacity(attempt_count);
+    for executor_profile_id in &payload.executor_profile_ids {
+        for _ in 0..attempts_per_profile {
+            let workspace =
+                start_task_attempt(&deployment, &task, &payload.repos, executor_profile_id).await?;
+            AttemptGroup::add_workspace(pool, group.id, workspace.id).await?;
+            workspaces.push(workspace);
+        }
+    }
+
+    tracing::info!(
+        "Started {} attempts for task {} in group {}",
+        attempt_count,
+        task.id,
+        group.id
+    );
+
+    Ok(ResponseJson(ApiResponse::success(
+        AttemptGroupWithWorkspaces { group, workspaces },
+    )))
+}
+
+pub async fn get_attempt_groups(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<AttemptGroupQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AttemptGroupWithWorkspaces>>>, ApiError> {
+    let mut groups = Vec::new();
+    for group in AttemptGroup::find_by_task_id(&deployment.db().pool, query.task_id).await? {
+        let workspaces = group_workspaces(&deployment, &group).await?;
+        groups.push(AttemptGroupWithWorkspaces { group, workspaces });
+    }
+    Ok(ResponseJson(ApiResponse::success(groups)))
+}
+
+pub async fn get_attempt_group(
+    State(deployment): State<DeploymentImpl>,
+    Path(group_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<AttemptGroupWithWorkspaces>>, ApiError> {
+    let group = find_group(&deployment, group_id).await?;
+    let workspaces = group_workspaces(&deployment, &group).await?;
+    Ok(ResponseJson(ApiResponse::success(
+        AttemptGroupWithWorkspaces { group, workspaces },
+    )))
+}
+
+/// Side-by-side results of every attempt in a group
+pub async fn compare_attempt_group(
+    State(deployment): State<DeploymentImpl>,
+    Path(group_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<AttemptGroupComparison>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let group = find_group(&deployment, group_id).await?;
+
+    let mut attempts = Vec::new();
+    for workspace in group_workspaces(&deployment, &group).await? {
+        let executor_profile_id =
+            match Session::find_latest_by_workspace_id(pool, workspace.id).await? {
+                Some(session) => {
+                    ExecutionProcess::latest_executor_profile_for_session(pool, session.id).await?
+                }
+                None => None,
+            };
+        let agent_status = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            pool,
+            workspace.id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?
+        .map(|process| process.status);
+        let test_run = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            pool,
+            workspace.id,
+            &ExecutionProcessRunReason::CleanupScript,
+        )
+        .await?
+        .map(|process| ScriptRunResult {
+            execution_process_id: process.id,
+            status: process.status,
+            exit_code: process.exit_code,
+        });
+        let diff_stats = if workspace.container_ref.is_some() {
+            compute_workspace_diff_stats(&deployment, &workspace)
+                .await
+                .ok()
+        } else {
+            None
+        };
+        let totals =
+            ExecutionProcessTokenUsage::totals(pool, TokenUsageScope::Workspace(workspace.id))
+                .await?;
+        let token_usage = build_report(&deployment, totals).await;
+        let summary =
+            CodingAgentTurn::find_latest_summary_by_workspace_id(pool, workspace.id).await?;
+
+        attempts.push(AttemptComparison {
+            workspace,
+            executor_profile_id,
+            agent_status,
+            diff_stats,
+            test_run,
+            token_usage,
+            summary,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(AttemptGroupComparison {
+        group,
+        attempts,
+    })))
+}
+
+/// Merge every repo of the winning workspace that is not merged yet and complete its task
+async fn merge_winner(
+    deployment: &DeploymentImpl,
+    winner: &Workspace,
+    task: &Task,
+) -> Result<(), ApiError> {
+    let pool = &deployment.db().pool;
+    for workspace_repo in WorkspaceRepo::find_by_workspace_id(pool, winner.id).await? {
+        if !Merge::find_by_workspace_and_repo_id(pool, winner.id, workspace_repo.repo_id)
+            .await?
+            .is_empty()
+        {
+            continue;
+        }
+        merge_workspace_repo(deployment, winner, task, workspace_repo.repo_id).await?;
+    }
+    complete_merged_workspace(deployment, winner, task).await
+}
+
+/// Merge the winning attempt into the target branches and archive the other attempts
+pub async fn pick_attempt_winner(
+    State(deployment): State<DeploymentImpl>,
+    Path(group_id): Path<Uuid>,
+    Json(payload): Json<PickAttemptWinnerRequest>,
+) -> Result<ResponseJson<ApiResponse<AttemptGroupWithWorkspaces>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let group = find_group(&deployment, group_id).await?;
+
+    let workspaces = group_workspaces(&deployment, &group).await?;
+    let winner = workspaces
+        .iter()
+        .find(|w| w.id == payload.workspace_id)
+        .ok_or(ApiError::BadRequest(
+            "Workspace is not part of this attempt group".to_string(),
+        ))?;
+    let task = winner
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::TaskNotFound))?;
+
+    // The winner is recorded before merging, so picking the same workspace again resumes a pick
+    // that failed part way through
+    if !AttemptGroup::set_winner(pool, group.id, winner.id).await? {
+        return Err(ApiError::Conflict(
+            "A winner has already been picked for this group".to_string(),
+        ));
+    }
+    if let Err(e) = merge_winner(&deployment, winner, &task).await {
+        // Nothing was merged, so another workspace may still be picked
+        if Merge::find_by_workspace_id(pool, winner.id)
+            .await?
+            .is_empty()
+        {
+            AttemptGroup::clear_winner(pool, group.id, winner.id).await?;
+        }
+        return Err(e);
+    }
+
+    for workspace in workspaces.iter().filter(|w| w.id != winner.id) {
+        deployment.container().try_stop(workspace, true).await;
+        Workspace::set_archived(pool, workspace.id, true).await?;
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "attempt_group_winner_picked",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "workspace_id": winner.id.to_string(),
+                "attempt_count": workspaces.len(),
+            }),
+        )
+        .await;
+
+    let group = find_group(&deployment, group_id).await?;
+    let workspaces = group_workspaces(&deployment, &group).await?;
+    Ok(ResponseJson(ApiResponse::success(
+        AttemptGroupWithWorkspaces { group, workspaces },
+    )))
+}
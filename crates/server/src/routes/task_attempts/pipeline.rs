@@ -0,0 +1,119 @@
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    pipeline::{PipelineRun, PipelineRunStatus},
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::container::ContainerService;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ApprovePipelinePlanRequest {
+    /// Replaces the plan passed on to the next stages, for edits made during review
+    pub plan: Option<String>,
+}
+
+async fn find_run(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+) -> Result<PipelineRun, ApiError> {
+    PipelineRun::find_latest_by_workspace_id(&deployment.db().pool, workspace.id)
+        .await?
+        .ok_or(ApiError::BadRequest("Pipeline run not found".to_string()))
+}
+
+fn awaiting_approval_conflict() -> ApiError {
+    ApiError::Conflict("Pipeline is not waiting for plan approval".to_string())
+}
+
+async fn find_run_awaiting_approval(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+) -> Result<PipelineRun, ApiError> {
+    let run = find_run(deployment, workspace).await?;
+    if run.status != PipelineRunStatus::AwaitingApproval {
+        return Err(awaiting_approval_conflict());
+    }
+    Ok(run)
+}
+
+pub async fn get_pipeline_run(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<PipelineRun>>>, ApiError> {
+    let run = PipelineRun::find_latest_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(run)))
+}
+
+/// Approve the plan of a pipeline waiting at a plan approval stage and start the stage after it
+pub async fn approve_pipeline_plan(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ApprovePipelinePlanRequest>,
+) -> Result<ResponseJson<ApiResponse<PipelineRun>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let mut run = find_run_awaiting_approval(&deployment, &workspace).await?;
+
+    // Only one approval, or a rejection, gets past the approval stage
+    if !PipelineRun::transition_status(
+        pool,
+        run.id,
+        PipelineRunStatus::AwaitingApproval,
+        PipelineRunStatus::Running,
+    )
+    .await?
+    {
+        return Err(awaiting_approval_conflict());
+    }
+
+    if let Some(plan) = payload.plan.filter(|plan| !plan.trim().is_empty()) {
+        PipelineRun::set_plan(pool, run.id, &plan).await?;
+        run.plan = Some(plan);
+    }
+
+    let has_plan = run.plan.is_some();
+    let next_stage = usize::try_from(run.current_stage + 1).unwrap_or_default();
+    deployment
+        .container()
+        .start_pipeline_stage_or_fail(&workspace, &run, next_stage)
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "pipeline_plan_approved",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "has_plan": has_plan,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(
+        find_run(&deployment, &workspace).await?,
+    )))
+}
+
+/// Reject the plan of a pipeline waiting at a plan approval stage, which ends the run
+pub async fn reject_pipeline_plan(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<PipelineRun>>, ApiError> {
+    let run = find_run_awaiting_approval(&deployment, &workspace).await?;
+    if !PipelineRun::transition_status(
+        &deployment.db().pool,
+        run.id,
+        PipelineRunStatus::AwaitingApproval,
+        PipelineRunStatus::Cancelled,
+    )
+    .await?
+    {
+        return Err(awaiting_approval_conflict());
+    }
+    Ok(ResponseJson(ApiResponse::success(
+        find_run(&deployment, &workspace).await?,
+    )))
+}
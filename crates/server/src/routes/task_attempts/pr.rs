@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{collections::VecDeque, path::PathBuf};
 
 use axum::{
     Extension, Json,
@@ -7,7 +7,7 @@ use axum::{
 };
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
-    merge::{Merge, MergeStatus},
+    merge::{Merge, MergeStatus, PullRequestInfo},
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
     task::{Task, TaskStatus},
@@ -44,6 +44,18 @@ pub struct CreatePrApiRequest {
     pub auto_generate_description: bool,
 }
 
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct CreateStackedPrsRequest {
+    pub repo_id: Uuid,
+    pub draft: Option<bool>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct StackedPr {
+    pub workspace_id: Uuid,
+    pub pr_url: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[ts(tag = "type", rename_all = "snake_case")]
@@ -99,7 +111,7 @@ Analyze the changes in this branch and write:
    - Any important implementation details
    - At the end, include a note: "This PR was written using [Vibe Kanban](https://vibekanban.com)"
 
-Use the appropriate CLI tool to update the PR (gh pr edit for GitHub, az repos pr update for Azure DevOps)."#;
+Use the appropriate CLI tool to update the PR (gh pr edit for GitHub, az repos pr update for Azure DevOps, glab mr update for GitLab)."#;
 
 async fn trigger_pr_description_follow_up(
     deployment: &DeploymentImpl,
@@ -192,32 +204,23 @@ async fn trigger_pr_description_follow_up(
     Ok(())
 }
 
-pub async fn create_pr(
-    Extension(workspace): Extension<Workspace>,
-    State(deployment): State<DeploymentImpl>,
-    Json(request): Json<CreatePrApiRequest>,
-) -> Result<ResponseJson<ApiResponse<String, PrError>>, ApiError> {
+/// Push the workspace branch and open a PR for it against `target_branch`, recording it
+/// on the workspace. Failures the user can act on are returned as a [`PrError`].
+async fn open_pr(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    repo: &Repo,
+    target_branch: &str,
+    title: String,
+    body: Option<String>,
+    draft: Option<bool>,
+) -> Result<Result<PullRequestInfo, PrError>, ApiError> {
     let pool = &deployment.db().pool;
-
-    let workspace_repo =
-        WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
-            .await?
-            .ok_or(RepoError::NotFound)?;
-
-    let repo = Repo::find_by_id(pool, workspace_repo.repo_id)
-        .await?
-        .ok_or(RepoError::NotFound)?;
-
     let repo_path = repo.path.clone();
-    let target_branch = if let Some(branch) = request.target_branch {
-        branch
-    } else {
-        workspace_repo.target_branch.clone()
-    };
 
     let container_ref = deployment
         .container()
-        .ensure_container_exists(&workspace)
+        .ensure_container_exists(workspace)
         .await?;
     let workspace_path = PathBuf::from(&container_ref);
     let worktree_path = workspace_path.join(&repo.name);
@@ -228,14 +231,14 @@ pub async fn create_pr(
     // Try to get the remote from the branch name (works for remote-tracking branches like "upstream/main").
     // Fall back to push_remote if the branch doesn't exist locally or isn't a remote-tracking branch.
     let (target_remote, base_branch) =
-        match git.get_remote_name_from_branch_name(&repo_path, &target_branch) {
+        match git.get_remote_name_from_branch_name(&repo_path, target_branch) {
             Ok(remote) => {
                 let branch = target_branch
                     .strip_prefix(&format!("{remote}/"))
-                    .unwrap_or(&target_branch);
+                    .unwrap_or(target_branch);
                 (remote, branch.to_string())
             }
-            Err(_) => (push_remote.clone(), target_branch.clone()),
+            Err(_) => (push_remote.clone(), target_branch.to_string()),
         };
 
     let push_remote_url = git.get_remote_url(&repo_path, &push_remote)?;
@@ -243,21 +246,15 @@ pub async fn create_pr(
 
     match git.check_remote_branch_exists(&repo_path, &target_remote_url, &base_branch) {
         Ok(false) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::TargetBranchNotFound {
-                    branch: target_branch.clone(),
-                },
-            )));
+            return Ok(Err(PrError::TargetBranchNotFound {
+                branch: target_branch.to_string(),
+            }));
         }
         Err(GitServiceError::GitCLI(GitCliError::AuthFailed(_))) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::GitCliNotLoggedIn,
-            )));
+            return Ok(Err(PrError::GitCliNotLoggedIn));
         }
         Err(GitServiceError::GitCLI(GitCliError::NotAvailable)) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::GitCliNotInstalled,
-            )));
+            return Ok(Err(PrError::GitCliNotInstalled));
         }
         Err(e) => return Err(ApiError::GitService(e)),
         Ok(true) => {}
@@ -267,30 +264,23 @@ pub async fn create_pr(
         tracing::error!("Failed to push branch to remote: {}", e);
         match e {
             GitServiceError::GitCLI(GitCliError::AuthFailed(_)) => {
-                return Ok(ResponseJson(ApiResponse::error_with_data(
-                    PrError::GitCliNotLoggedIn,
-                )));
+                return Ok(Err(PrError::GitCliNotLoggedIn));
             }
             GitServiceError::GitCLI(GitCliError::NotAvailable) => {
-                return Ok(ResponseJson(ApiResponse::error_with_data(
-                    PrError::GitCliNotInstalled,
-                )));
+                return Ok(Err(PrError::GitCliNotInstalled));
             }
             _ => return Err(ApiError::GitService(e)),
         }
     }
 
-    let git_host = match git_host::GitHostService::from_url(&target_remote_url) {
+    let git_hosts = deployment.config().read().await.git_hosts.clone();
+    let git_host = match git_host::GitHostService::for_repo(repo, &target_remote_url, &git_hosts) {
         Ok(host) => host,
         Err(GitHostError::UnsupportedProvider) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::UnsupportedProvider,
-            )));
+            return Ok(Err(PrError::UnsupportedProvider));
         }
         Err(GitHostError::CliNotInstalled { provider }) => {
-            return Ok(ResponseJson(ApiResponse::error_with_data(
-                PrError::CliNotInstalled { provider },
-            )));
+            return Ok(Err(PrError::CliNotInstalled { provider }));
         }
         Err(e) => return Err(ApiError::GitHost(e)),
     };
@@ -299,11 +289,11 @@ pub async fn create_pr(
 
     // Create the PR
     let pr_request = CreatePrRequest {
-        title: request.title.clone(),
-        body: request.body.clone(),
+        title,
+        body,
         head_branch: workspace.branch.clone(),
         base_branch: base_branch.clone(),
-        draft: request.draft,
+        draft,
         head_repo_url: Some(push_remote_url),
     };
 
@@ -316,7 +306,7 @@ pub async fn create_pr(
             if let Err(e) = Merge::create_pr(
                 pool,
                 workspace.id,
-                workspace_repo.repo_id,
+                repo.id,
                 &base_branch,
                 pr_info.number,
                 &pr_info.url,
@@ -326,11 +316,6 @@ pub async fn create_pr(
                 tracing::error!("Failed to update workspace PR status: {}", e);
             }
 
-            // Auto-open PR in browser
-            if let Err(e) = utils::browser::open_browser(&pr_info.url).await {
-                tracing::warn!("Failed to open PR in browser: {}", e);
-            }
-
             deployment
                 .track_if_analytics_allowed(
                     "pr_created",
@@ -341,24 +326,7 @@ pub async fn create_pr(
                 )
                 .await;
 
-            // Trigger auto-description follow-up if enabled
-            if request.auto_generate_description
-                && let Err(e) = trigger_pr_description_follow_up(
-                    &deployment,
-                    &workspace,
-                    pr_info.number,
-                    &pr_info.url,
-                )
-                .await
-            {
-                tracing::warn!(
-                    "Failed to trigger PR description follow-up for attempt {}: {}",
-                    workspace.id,
-                    e
-                );
-            }
-
-            Ok(ResponseJson(ApiResponse::success(pr_info.url)))
+            Ok(Ok(pr_info))
         }
         Err(e) => {
             tracing::error!(
@@ -368,20 +336,145 @@ pub async fn create_pr(
                 e
             );
             match &e {
-                GitHostError::CliNotInstalled { provider } => Ok(ResponseJson(
-                    ApiResponse::error_with_data(PrError::CliNotInstalled {
-                        provider: *provider,
-                    }),
-                )),
-                GitHostError::AuthFailed(_) => Ok(ResponseJson(ApiResponse::error_with_data(
-                    PrError::CliNotLoggedIn { provider },
-                ))),
+                GitHostError::CliNotInstalled { provider } => Ok(Err(PrError::CliNotInstalled {
+                    provider: *provider,
+                })),
+                GitHostError::AuthFailed(_) => Ok(Err(PrError::CliNotLoggedIn { provider })),
                 _ => Err(ApiError::GitHost(e)),
             }
         }
     }
 }
 
+pub async fn create_pr(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<CreatePrApiRequest>,
+) -> Result<ResponseJson<ApiResponse<String, PrError>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace_repo =
+        WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+
+    let repo = Repo::find_by_id(pool, workspace_repo.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    let target_branch = if let Some(branch) = request.target_branch {
+        branch
+    } else {
+        workspace_repo.target_branch.clone()
+    };
+
+    let pr_info = match open_pr(
+        &deployment,
+        &workspace,
+        &repo,
+        &target_branch,
+        request.title.clone(),
+        request.body.clone(),
+        request.draft,
+    )
+    .await?
+    {
+        Ok(pr_info) => pr_info,
+        Err(e) => return Ok(ResponseJson(ApiResponse::error_with_data(e))),
+    };
+
+    // Auto-open PR in browser
+    if let Err(e) = utils::browser::open_browser(&pr_info.url).await {
+        tracing::warn!("Failed to open PR in browser: {}", e);
+    }
+
+    // Trigger auto-description follow-up if enabled
+    if request.auto_generate_description
+        && let Err(e) =
+            trigger_pr_description_follow_up(&deployment, &workspace, pr_info.number, &pr_info.url)
+                .await
+    {
+        tracing::warn!(
+            "Failed to trigger PR description follow-up for attempt {}: {}",
+            workspace.id,
+            e
+        );
+    }
+
+    Ok(ResponseJson(ApiResponse::success(pr_info.url)))
+}
+
+/// Open PRs for a workspace and every workspace stacked on it, where each subtask's PR
+/// targets the branch of the workspace it was created from. Workspaces that already have
+/// an open PR keep it.
+pub async fn create_stacked_prs(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<CreateStackedPrsRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<StackedPr>, PrError>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace_repo =
+        WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+
+    let repo = Repo::find_by_id(pool, workspace_repo.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    // Parents are opened before their children so each base branch is pushed first
+    let mut pending = VecDeque::from([(workspace, workspace_repo.target_branch)]);
+    let mut stacked = Vec::new();
+    while let Some((workspace, target_branch)) = pending.pop_front() {
+        let open_pr_url = Merge::find_by_workspace_and_repo_id(pool, workspace.id, repo.id)
+            .await?
+            .into_iter()
+            .find_map(|merge| match merge {
+                Merge::Pr(pr) if matches!(pr.pr_info.status, MergeStatus::Open) => {
+                    Some(pr.pr_info.url)
+                }
+                _ => None,
+            });
+
+        let pr_url = match open_pr_url {
+            Some(url) => url,
+            None => {
+                let task = workspace
+                    .parent_task(pool)
+                    .await?
+                    .ok_or(ApiError::Workspace(WorkspaceError::TaskNotFound))?;
+                match open_pr(
+                    &deployment,
+                    &workspace,
+                    &repo,
+                    &target_branch,
+                    task.title,
+                    task.description,
+                    request.draft,
+                )
+                .await?
+                {
+                    Ok(pr_info) => pr_info.url,
+                    Err(e) => return Ok(ResponseJson(ApiResponse::error_with_data(e))),
+                }
+            }
+        };
+        stacked.push(StackedPr {
+            workspace_id: workspace.id,
+            pr_url,
+        });
+
+        for child in
+            Workspace::find_stacked_children(pool, workspace.id, repo.id, &workspace.branch).await?
+        {
+            pending.push_back((child, workspace.branch.clone()));
+        }
+    }
+
+    Ok(ResponseJson(ApiResponse::success(stacked)))
+}
+
 pub async fn attach_existing_pr(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
@@ -420,7 +513,8 @@ pub async fn attach_existing_pr(
         &git.resolve_remote_name_for_branch(&repo.path, &workspace_repo.target_branch)?,
     )?;
 
-    let git_host = match git_host::GitHostService::from_url(&remote_url) {
+    let git_hosts = deployment.config().read().await.git_hosts.clone();
+    let git_host = match git_host::GitHostService::for_repo(&repo, &remote_url, &git_hosts) {
         Ok(host) => host,
         Err(GitHostError::UnsupportedProvider) => {
             return Ok(ResponseJson(ApiResponse::error_with_data(
@@ -540,7 +634,8 @@ pub async fn get_pr_comments(
         &git.resolve_remote_name_for_branch(&repo.path, &workspace_repo.target_branch)?,
     )?;
 
-    let git_host = match git_host::GitHostService::from_url(&remote_url) {
+    let git_hosts = deployment.config().read().await.git_hosts.clone();
+    let git_host = match git_host::GitHostService::for_repo(&repo, &remote_url, &git_hosts) {
         Ok(host) => host,
         Err(GitHostError::CliNotInstalled { provider }) => {
             return Ok(ResponseJson(ApiResponse::error_with_data(
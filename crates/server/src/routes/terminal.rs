@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use axum::{
     Router,
@@ -13,6 +13,7 @@ use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 use db::models::{workspace::Workspace, workspace_repo::WorkspaceRepo};
 use deployment::Deployment;
 use futures_util::{SinkExt, StreamExt};
+use local_deployment::ssh;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -70,40 +71,65 @@ pub async fn terminal_ws(
     }
 
     let mut working_dir = base_dir.clone();
-    match WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, query.workspace_id).await {
-        Ok(repos) if repos.len() == 1 => {
-            let repo_dir = base_dir.join(&repos[0].name);
-            if repo_dir.exists() {
-                working_dir = repo_dir;
-            }
-        }
-        Ok(_) => {}
-        Err(e) => {
+    let repos = WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, query.workspace_id)
+        .await
+        .unwrap_or_else(|e| {
             tracing::warn!(
                 "Failed to resolve repos for workspace {}: {}",
                 attempt.id,
                 e
             );
+            Vec::new()
+        });
+    if repos.len() == 1 {
+        let repo_dir = base_dir.join(&repos[0].name);
+        if repo_dir.exists() {
+            working_dir = repo_dir;
         }
     }
 
+    // Workspaces that run over SSH get a shell in their worktrees on the host
+    let target = match ssh::host_for_repos(&repos) {
+        Some(host) => TerminalTarget::Ssh {
+            host: host.to_string(),
+            remote_dir: ssh::remote_workspace_dir(&base_dir)
+                .join(working_dir.strip_prefix(&base_dir).unwrap_or(Path::new(""))),
+        },
+        None => TerminalTarget::Local(working_dir),
+    };
+
     Ok(ws.on_upgrade(move |socket| {
-        handle_terminal_ws(socket, deployment, working_dir, query.cols, query.rows)
+        handle_terminal_ws(socket, deployment, target, query.cols, query.rows)
     }))
 }
 
+enum TerminalTarget {
+    Local(PathBuf),
+    Ssh { host: String, remote_dir: PathBuf },
+}
+
 async fn handle_terminal_ws(
     socket: WebSocket,
     deployment: DeploymentImpl,
-    working_dir: PathBuf,
+    target: TerminalTarget,
     cols: u16,
     rows: u16,
 ) {
-    let (session_id, mut output_rx) = match deployment
-        .pty()
-        .create_session(working_dir, cols, rows)
-        .await
-    {
+    let session = match target {
+        TerminalTarget::Local(working_dir) => {
+            deployment
+                .pty()
+                .create_session(working_dir, cols, rows)
+                .await
+        }
+        TerminalTarget::Ssh { host, remote_dir } => {
+            deployment
+                .pty()
+                .create_ssh_session(&host, &remote_dir, cols, rows)
+                .await
+        }
+    };
+    let (session_id, mut output_rx) = match session {
         Ok(result) => result,
         Err(e) => {
             tracing::error!("Failed to create PTY session: {}", e);
@@ -0,0 +1,203 @@
+use axum::{
+    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
+    response::Json as ResponseJson, routing::get,
+};
+use db::models::{
+    budget::{Budget, SetBudget},
+    execution_process_token_usage::{
+        ExecutionProcessTokenUsage, TokenUsageScope, TokenUsageTotals,
+    },
+    project::Project,
+    session::Session,
+    task::Task,
+};
+use deployment::Deployment;
+use services::services::token_usage::TokenUsageReport;
+use utils::response::ApiResponse;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{load_project_middleware, load_session_middleware, load_task_middleware},
+};
+
+pub(crate) async fn build_report(
+    deployment: &DeploymentImpl,
+    totals: Vec<TokenUsageTotals>,
+) -> TokenUsageReport {
+    let prices = deployment.config().read().await.model_prices.clone();
+    TokenUsageReport::from_totals(totals, &prices)
+}
+
+pub async fn get_session_token_usage(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TokenUsageReport>>, ApiError> {
+    let totals = ExecutionProcessTokenUsage::totals(
+        &deployment.db().pool,
+        TokenUsageScope::Session(session.id),
+    )
+    .await?;
+    let report = build_report(&deployment, totals).await;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub async fn get_task_token_usage(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TokenUsageReport>>, ApiError> {
+    let totals =
+        ExecutionProcessTokenUsage::totals(&deployment.db().pool, TokenUsageScope::Task(task.id))
+            .await?;
+    let report = build_report(&deployment, totals).await;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub async fn get_project_token_usage(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TokenUsageReport>>, ApiError> {
+    let totals = ExecutionProcessTokenUsage::totals(
+        &deployment.db().pool,
+        TokenUsageScope::Project(project.id),
+    )
+    .await?;
+    let report = build_report(&deployment, totals).await;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Usage across all projects; the breakdown is grouped per executor and model
+pub async fn get_token_usage(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TokenUsageReport>>, ApiError> {
+    let totals =
+        ExecutionProcessTokenUsage::totals(&deployment.db().pool, TokenUsageScope::All).await?;
+    let report = build_report(&deployment, totals).await;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub async fn get_project_budget(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<Budget>>>, ApiError> {
+    let budget = Budget::find_for_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+pub async fn set_project_budget(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetBudget>,
+) -> Result<ResponseJson<ApiResponse<Budget>>, ApiError> {
+    validate_budget(&payload)?;
+    let budget = Budget::set_for_project(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_budget_set",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+pub async fn delete_project_budget(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    Budget::delete_for_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_task_budget(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<Budget>>>, ApiError> {
+    let budget = Budget::find_for_task(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+pub async fn set_task_budget(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetBudget>,
+) -> Result<ResponseJson<ApiResponse<Budget>>, ApiError> {
+    validate_budget(&payload)?;
+    let budget = Budget::set_for_task(&deployment.db().pool, task.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_budget_set",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": task.project_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+pub async fn delete_task_budget(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    Budget::delete_for_task(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+fn validate_budget(payload: &SetBudget) -> Result<(), ApiError> {
+    if payload.max_tokens.is_some_and(|max| max < 0)
+        || payload
+            .max_cost_usd
+            .is_some_and(|max| !max.is_finite() || max < 0.0)
+    {
+        return Err(ApiError::BadRequest(
+            "Budget limits must be non-negative".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let session_router = Router::new()
+        .route("/", get(get_session_token_usage))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_session_middleware,
+        ));
+
+    let task_router = Router::new()
+        .route("/", get(get_task_token_usage))
+        .route(
+            "/budget",
+            get(get_task_budget)
+                .put(set_task_budget)
+                .delete(delete_task_budget),
+        )
+        .layer(from_fn_with_state(deployment.clone(), load_task_middleware));
+
+    let project_router = Router::new()
+        .route("/", get(get_project_token_usage))
+        .route(
+            "/budget",
+            get(get_project_budget)
+                .put(set_project_budget)
+                .delete(delete_project_budget),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_project_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_token_usage))
+        .nest("/sessions/{session_id}", session_router)
+        .nest("/tasks/{task_id}", task_router)
+        .nest("/projects/{project_id}", project_router);
+
+    Router::new().nest("/token-usage", inner)
+}
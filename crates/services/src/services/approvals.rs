@@ -8,13 +8,18 @@ use std::{
 
 use dashmap::DashMap;
 use db::models::{
+    approval::{Approval, ApprovalDecidedBy},
     execution_process::ExecutionProcess,
+    remembered_approval::{CreateRememberedApproval, RememberedApproval},
     task::{Task, TaskStatus},
 };
 use executors::{
-    approvals::ToolCallMetadata,
+    approvals::{
+        ToolCallMetadata,
+        policy::{ApprovalDecision, ApprovalRule, RuleRegex},
+    },
     logs::{
-        NormalizedEntry, NormalizedEntryType, ToolStatus,
+        ActionType, NormalizedEntry, NormalizedEntryType, ToolStatus,
         utils::patch::{ConversationPatch, extract_normalized_entry_from_patch},
     },
 };
@@ -23,7 +28,9 @@ use sqlx::{Error as SqlxError, SqlitePool};
 use thiserror::Error;
 use tokio::sync::{RwLock, oneshot};
 use utils::{
-    approvals::{ApprovalRequest, ApprovalResponse, ApprovalStatus},
+    approvals::{
+        ApprovalMatcher, ApprovalRequest, ApprovalResponse, ApprovalStatus, RememberApproval,
+    },
     log_msg::LogMsg,
     msg_store::MsgStore,
 };
@@ -35,11 +42,44 @@ struct PendingApproval {
     entry: NormalizedEntry,
     execution_process_id: Uuid,
     tool_name: String,
+    tool_input: serde_json::Value,
+    requested_at: chrono::DateTime<chrono::Utc>,
     response_tx: oneshot::Sender<ApprovalStatus>,
 }
 
 type ApprovalWaiter = Shared<BoxFuture<'static, ApprovalStatus>>;
 
+/// Whether a remembered decision applies to a tool call
+fn remembered_decision_matches(
+    decision: &RememberedApproval,
+    tool_name: &str,
+    tool_input: &serde_json::Value,
+    action: Option<&ActionType>,
+) -> bool {
+    match &decision.matcher.0 {
+        ApprovalMatcher::ExactInput => {
+            decision.tool_name == tool_name && &decision.tool_input.0 == tool_input
+        }
+        ApprovalMatcher::Tool => decision.tool_name == tool_name,
+        // Reuse the approval rule matching so a remembered prefix never approves a
+        // command chained onto an approved one
+        ApprovalMatcher::CommandPrefix { prefix } => ApprovalRule {
+            decision: match decision.status.0 {
+                ApprovalStatus::Approved => ApprovalDecision::Allow,
+                _ => ApprovalDecision::Deny,
+            },
+            tool: None,
+            command: Some(
+                RuleRegex::new(&format!(r"^{}(\s|$)", regex::escape(prefix.trim())))
+                    .expect("Escaped command prefix should be a valid regex"),
+            ),
+            path: None,
+            reason: None,
+        }
+        .matches(tool_name, action),
+    }
+}
+
 #[derive(Debug)]
 pub struct ToolContext {
     pub tool_name: String,
@@ -110,6 +150,8 @@ impl Approvals {
                         entry: matching_tool,
                         execution_process_id: request.execution_process_id,
                         tool_name: request.tool_name.clone(),
+                        tool_input: request.tool_input.clone(),
+                        requested_at: request.created_at,
                         response_tx: tx,
                     },
                 );
@@ -137,17 +179,31 @@ impl Approvals {
         Ok((request, waiter))
     }
 
-    #[tracing::instrument(skip(self, id, req))]
+    #[tracing::instrument(skip(self, id, req, responder))]
     pub async fn respond(
         &self,
         pool: &SqlitePool,
         id: &str,
         req: ApprovalResponse,
+        responder: &str,
     ) -> Result<(ApprovalStatus, ToolContext), ApprovalError> {
         if let Some((_, p)) = self.pending.remove(id) {
             self.completed.insert(id.to_string(), req.status.clone());
             let _ = p.response_tx.send(req.status.clone());
 
+            if let Err(e) = Approval::record_response(
+                pool,
+                id,
+                &req.status,
+                ApprovalDecidedBy::User,
+                Some(responder),
+                p.requested_at,
+            )
+            .await
+            {
+                tracing::error!("Failed to record response to approval '{}': {}", id, e);
+            }
+
             if let Some(store) = self.msg_store_by_id(&p.execution_process_id).await {
                 let status = ToolStatus::from_approval_status(&req.status).ok_or(
                     ApprovalError::Custom(anyhow::anyhow!("Invalid approval status")),
@@ -165,6 +221,11 @@ impl Approvals {
                 );
             }
 
+            if let Some(remember) = &req.remember {
+                self.remember_decision(pool, &p, &req.status, remember, responder)
+                    .await;
+            }
+
             let tool_ctx = ToolContext {
                 tool_name: p.tool_name,
                 execution_process_id: p.execution_process_id,
@@ -193,6 +254,64 @@ impl Approvals {
         }
     }
 
+    async fn remember_decision(
+        &self,
+        pool: &SqlitePool,
+        pending: &PendingApproval,
+        status: &ApprovalStatus,
+        remember: &RememberApproval,
+        responder: &str,
+    ) {
+        if !matches!(
+            status,
+            ApprovalStatus::Approved | ApprovalStatus::Denied { .. }
+        ) {
+            return;
+        }
+        let ctx = match ExecutionProcess::load_context(pool, pending.execution_process_id).await {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load context to remember approval decision: {}",
+                    e
+                );
+                return;
+            }
+        };
+        let data = CreateRememberedApproval {
+            scope: remember.scope,
+            session_id: ctx.session.id,
+            workspace_id: ctx.workspace.id,
+            tool_name: pending.tool_name.clone(),
+            tool_input: pending.tool_input.clone(),
+            matcher: remember.matcher.clone(),
+            status: status.clone(),
+            responder: responder.to_string(),
+        };
+        if let Err(e) = RememberedApproval::create(pool, &data, Uuid::new_v4()).await {
+            tracing::error!("Failed to remember approval decision: {}", e);
+        }
+    }
+
+    /// The latest remembered decision for a tool call, checking the session before the
+    /// workspace. Returns the decision and who made it.
+    pub async fn find_remembered_decision(
+        &self,
+        pool: &SqlitePool,
+        session_id: Uuid,
+        workspace_id: Uuid,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+        action: Option<&ActionType>,
+    ) -> Result<Option<(ApprovalStatus, String)>, SqlxError> {
+        let decisions =
+            RememberedApproval::find_for_session(pool, session_id, workspace_id).await?;
+        Ok(decisions
+            .into_iter()
+            .find(|decision| remembered_decision_matches(decision, tool_name, tool_input, action))
+            .map(|decision| (decision.status.0, decision.responder)))
+    }
+
     #[tracing::instrument(skip(self, id, timeout_at, waiter))]
     fn spawn_timeout_watcher(
         &self,
@@ -256,6 +375,38 @@ impl Approvals {
         });
     }
 
+    /// Action of the tool use entry a tool call id belongs to, if it has been logged yet
+    pub async fn find_tool_action(
+        &self,
+        execution_process_id: &Uuid,
+        tool_call_id: &str,
+    ) -> Option<ActionType> {
+        let store = self.msg_store_by_id(execution_process_id).await?;
+        let (_, entry) = find_matching_tool_use(store, tool_call_id)?;
+        match entry.entry_type {
+            NormalizedEntryType::ToolUse { action_type, .. } => Some(action_type),
+            _ => None,
+        }
+    }
+
+    /// Update the tool use entry of a tool call that was decided without a human
+    pub async fn set_tool_status(
+        &self,
+        execution_process_id: &Uuid,
+        tool_call_id: &str,
+        status: &ApprovalStatus,
+    ) {
+        let Some(store) = self.msg_store_by_id(execution_process_id).await else {
+            return;
+        };
+        if let Some((idx, entry)) = find_matching_tool_use(store.clone(), tool_call_id)
+            && let Some(status) = ToolStatus::from_approval_status(status)
+            && let Some(updated_entry) = entry.with_tool_status(status)
+        {
+            store.push_patch(ConversationPatch::replace(idx, updated_entry));
+        }
+    }
+
     async fn msg_store_by_id(&self, execution_process_id: &Uuid) -> Option<Arc<MsgStore>> {
         let map = self.msg_stores.read().await;
         map.get(execution_process_id).cloned()
@@ -422,4 +573,35 @@ mod tests {
             "Should not match different tool ids"
         );
     }
+
+    #[test]
+    fn test_remembered_command_prefix_matching() {
+        let remembered = RememberedApproval {
+            id: Uuid::new_v4(),
+            session_id: Some(Uuid::new_v4()),
+            workspace_id: None,
+            tool_name: "Bash".to_string(),
+            tool_input: sqlx::types::Json(serde_json::json!({ "command": "npm test" })),
+            matcher: sqlx::types::Json(ApprovalMatcher::CommandPrefix {
+                prefix: "npm test".to_string(),
+            }),
+            status: sqlx::types::Json(ApprovalStatus::Approved),
+            responder: "user".to_string(),
+            created_at: chrono::Utc::now(),
+        };
+        let matches = |input: &serde_json::Value, action: Option<&ActionType>| {
+            remembered_decision_matches(&remembered, "Bash", input, action)
+        };
+        let run = |command: &str| ActionType::CommandRun {
+            command: command.to_string(),
+            result: None,
+        };
+        let input = serde_json::json!({});
+
+        assert!(matches(&input, Some(&run("npm test"))));
+        assert!(matches(&input, Some(&run("npm test -- --watch=false"))));
+        assert!(!matches(&input, Some(&run("npm testing"))));
+        assert!(!matches(&input, Some(&run("npm test && rm -rf /"))));
+        assert!(!matches(&input, None));
+    }
 }
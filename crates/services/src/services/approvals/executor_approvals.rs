@@ -1,8 +1,22 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use db::{self, DBService, models::execution_process::ExecutionProcess};
-use executors::approvals::{ExecutorApprovalError, ExecutorApprovalService};
+use db::{
+    self, DBService,
+    models::{
+        approval::{Approval, ApprovalDecidedBy},
+        approval_policy::ProjectApprovalPolicy,
+        execution_process::{ExecutionContext, ExecutionProcess},
+    },
+};
+use executors::{
+    approvals::{
+        ExecutorApprovalError, ExecutorApprovalService,
+        policy::{ApprovalDecision, ApprovalRule, find_matching_rule},
+    },
+    logs::ActionType,
+    profile::ExecutorConfigs,
+};
 use serde_json::Value;
 use utils::approvals::{ApprovalRequest, ApprovalStatus, CreateApprovalRequest};
 use uuid::Uuid;
@@ -30,6 +44,140 @@ impl ExecutorApprovalBridge {
             execution_process_id,
         })
     }
+
+    /// The first approval rule of the project, then of the executor profile, that matches a
+    /// tool call, along with where it came from
+    async fn find_approval_rule(
+        &self,
+        ctx: &ExecutionContext,
+        tool_name: &str,
+        action: Option<&ActionType>,
+    ) -> Option<(ApprovalRule, &'static str)> {
+        let project_rules =
+            match ProjectApprovalPolicy::find_for_project(&self.db.pool, ctx.project.id).await {
+                Ok(policy) => policy.map(|policy| policy.rules.0).unwrap_or_default(),
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to load approval policy for project {}: {}",
+                        ctx.project.id,
+                        e
+                    );
+                    Vec::new()
+                }
+            };
+        if let Some(rule) = find_matching_rule(&project_rules, tool_name, action) {
+            return Some((rule.clone(), "project approval policy"));
+        }
+
+        let profile_rules = ctx
+            .execution_process
+            .executor_action()
+            .ok()
+            .and_then(|action| action.executor_profile_id())
+            .and_then(|profile_id| ExecutorConfigs::get_cached().get_coding_agent(profile_id))
+            .and_then(|agent| agent.cmd_overrides()?.approval_rules.clone())
+            .unwrap_or_default();
+        find_matching_rule(&profile_rules, tool_name, action)
+            .map(|rule| (rule.clone(), "executor profile approval rules"))
+    }
+
+    /// Decide a tool call without asking the user, from the approval rules or else from a
+    /// decision remembered for the session or workspace. Returns the decision, how it was made
+    /// and by whom, or `None` when the user has to decide.
+    async fn decide_without_user(
+        &self,
+        request: &ApprovalRequest,
+    ) -> Option<(ApprovalStatus, ApprovalDecidedBy, String)> {
+        let ctx = ExecutionProcess::load_context(&self.db.pool, self.execution_process_id)
+            .await
+            .ok()?;
+        let action = self
+            .approvals
+            .find_tool_action(&self.execution_process_id, &request.tool_call_id)
+            .await;
+
+        let (status, decided_by, responder) = match self
+            .find_approval_rule(&ctx, &request.tool_name, action.as_ref())
+            .await
+        {
+            Some((rule, source)) => {
+                let status = match rule.decision {
+                    ApprovalDecision::Ask => return None,
+                    ApprovalDecision::Allow => ApprovalStatus::Approved,
+                    ApprovalDecision::Deny => ApprovalStatus::Denied {
+                        reason: Some(rule.reason.unwrap_or_else(|| {
+                            format!("Tool '{}' is denied by an approval rule", request.tool_name)
+                        })),
+                    },
+                };
+                (status, ApprovalDecidedBy::Rule, source.to_string())
+            }
+            None => {
+                let remembered = self
+                    .approvals
+                    .find_remembered_decision(
+                        &self.db.pool,
+                        ctx.session.id,
+                        ctx.workspace.id,
+                        &request.tool_name,
+                        &request.tool_input,
+                        action.as_ref(),
+                    )
+                    .await;
+                let (status, responder) = match remembered {
+                    Ok(remembered) => remembered?,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to load remembered approval decisions for session {}: {}",
+                            ctx.session.id,
+                            e
+                        );
+                        return None;
+                    }
+                };
+                (status, ApprovalDecidedBy::Remembered, responder)
+            }
+        };
+
+        tracing::debug!(
+            "Tool call '{}' ({}) for execution process {} decided by {:?} ({}): {:?}",
+            request.tool_call_id,
+            request.tool_name,
+            self.execution_process_id,
+            decided_by,
+            responder,
+            status
+        );
+        self.approvals
+            .set_tool_status(&self.execution_process_id, &request.tool_call_id, &status)
+            .await;
+        Some((status, decided_by, responder))
+    }
+
+    async fn record_response(
+        &self,
+        request: &ApprovalRequest,
+        status: &ApprovalStatus,
+        decided_by: ApprovalDecidedBy,
+        responder: Option<&str>,
+    ) {
+        if let Err(e) = Approval::record_response(
+            &self.db.pool,
+            &request.id,
+            status,
+            decided_by,
+            responder,
+            request.created_at,
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to record response to approval '{}': {}",
+                request.id,
+                e
+            );
+        }
+    }
 }
 
 #[async_trait]
@@ -40,8 +188,6 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
         tool_input: Value,
         tool_call_id: &str,
     ) -> Result<ApprovalStatus, ExecutorApprovalError> {
-        super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
-
         let request = ApprovalRequest::from_create(
             CreateApprovalRequest {
                 tool_name: tool_name.to_string(),
@@ -50,8 +196,19 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
             },
             self.execution_process_id,
         );
+        if let Err(e) = Approval::create(&self.db.pool, &request).await {
+            tracing::error!("Failed to record approval request '{}': {}", request.id, e);
+        }
+
+        if let Some((status, decided_by, responder)) = self.decide_without_user(&request).await {
+            self.record_response(&request, &status, decided_by, Some(&responder))
+                .await;
+            return Ok(status);
+        }
 
-        let (_, waiter) = self
+        super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
+
+        let (request, waiter) = self
             .approvals
             .create_with_waiter(request)
             .await
@@ -76,6 +233,10 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
                 "approval finished in pending state",
             ));
         }
+        if matches!(status, ApprovalStatus::TimedOut) {
+            self.record_response(&request, &status, ApprovalDecidedBy::Timeout, None)
+                .await;
+        }
 
         Ok(status)
     }
@@ -0,0 +1,278 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use db::{
+    DBService,
+    models::{
+        execution_process::ExecutionProcess,
+        repo::{AutoRebasePolicy, Repo},
+        workspace_repo::{AutoRebaseCandidate, WorkspaceRepo},
+    },
+};
+use git2::BranchType;
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+use crate::services::{
+    git::{GitService, GitServiceError},
+    workspace_lock::WorkspaceLocks,
+};
+
+/// How often a remote target branch is fetched to notice it moved
+const FETCH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Error)]
+pub enum AutoRebaseError {
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+}
+
+/// Service that rebases workspaces onto their target branch when it moves, following the
+/// auto-rebase policy of each repo
+pub struct AutoRebaseService {
+    db: DBService,
+    git: GitService,
+    poll_interval: Duration,
+    /// Shared with process start, so a rebase never races a new process
+    workspace_locks: WorkspaceLocks,
+    /// When each remote target branch of each repo was last fetched
+    last_fetches: Mutex<HashMap<(PathBuf, String), Instant>>,
+}
+
+impl AutoRebaseService {
+    pub async fn spawn(
+        db: DBService,
+        workspace_locks: WorkspaceLocks,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            git: GitService::new(),
+            poll_interval: Duration::from_secs(60),
+            workspace_locks,
+            last_fetches: Mutex::new(HashMap::new()),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting auto-rebase service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check_workspaces().await {
+                error!("Error checking workspaces for auto-rebase: {}", e);
+            }
+        }
+    }
+
+    /// Rebase every workspace repo that fell behind its target branch
+    async fn check_workspaces(&self) -> Result<(), SqlxError> {
+        let candidates = WorkspaceRepo::find_auto_rebase_candidates(&self.db.pool).await?;
+        if candidates.is_empty() {
+            return Ok(());
+        }
+
+        debug!(
+            "Checking {} workspace repos for auto-rebase",
+            candidates.len()
+        );
+
+        let mut repos: HashMap<_, Option<Repo>> = HashMap::new();
+        for candidate in candidates {
+            let repo = match repos.entry(candidate.repo_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    entry.insert(Repo::find_by_id(&self.db.pool, candidate.repo_id).await?)
+                }
+            };
+            let Some(repo) = repo else {
+                continue;
+            };
+            self.fetch_remote_target(repo, &candidate.target_branch);
+            if let Err(e) =
+                auto_rebase_workspace(&self.db, &self.git, &self.workspace_locks, &candidate, repo)
+                    .await
+            {
+                error!(
+                    "Error auto-rebasing workspace {} onto {}: {}",
+                    candidate.workspace_id, candidate.target_branch, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fetch a remote target branch, at most once per [`FETCH_INTERVAL`], so moves on the
+    /// remote are noticed without fetching on every tick
+    fn fetch_remote_target(&self, repo: &Repo, target_branch: &str) {
+        {
+            let mut last_fetches = self.last_fetches.lock().unwrap();
+            let key = (repo.path.clone(), target_branch.to_string());
+            if last_fetches
+                .get(&key)
+                .is_some_and(|fetched_at| fetched_at.elapsed() < FETCH_INTERVAL)
+            {
+                return;
+            }
+            last_fetches.insert(key, Instant::now());
+        }
+
+        if !matches!(
+            self.git.find_branch_type(&repo.path, target_branch),
+            Ok(BranchType::Remote)
+        ) {
+            return;
+        }
+        if let Err(e) = self.git.fetch_remote_branch(&repo.path, target_branch) {
+            // Compared with the last fetched state until a later fetch succeeds
+            warn!(
+                "Could not fetch {} of {} for auto-rebase: {}",
+                target_branch, repo.name, e
+            );
+        }
+    }
+}
+
+/// What [`auto_rebase_workspace`] did with a workspace repo
+#[derive(Debug, PartialEq, Eq)]
+pub enum AutoRebaseOutcome {
+    Rebased,
+    /// Not behind its target branch
+    UpToDate,
+    /// Running under `idle_only`, starting a process, dirty or mid-rebase; retried on a
+    /// later tick
+    Busy,
+    /// The rebase hit conflicts and was aborted; the workspace is flagged until it is rebased
+    Conflicts,
+    /// Has no worktree or target branch, or already conflicted with the current target commit
+    Skipped,
+}
+
+/// Rebase a workspace repo onto its target branch if it fell behind, following the repo's
+/// auto-rebase policy. Remote target branches are compared as last fetched.
+pub async fn auto_rebase_workspace(
+    db: &DBService,
+    git: &GitService,
+    workspace_locks: &WorkspaceLocks,
+    candidate: &AutoRebaseCandidate,
+    repo: &Repo,
+) -> Result<AutoRebaseOutcome, AutoRebaseError> {
+    let pool = &db.pool;
+    let target_branch = &candidate.target_branch;
+
+    // Worktrees removed by cleanup are recreated when the workspace is used again
+    let worktree_path = Path::new(&candidate.container_ref).join(&repo.name);
+    if !worktree_path.exists() || !git.check_branch_exists(&repo.path, target_branch)? {
+        return Ok(AutoRebaseOutcome::Skipped);
+    }
+
+    // A conflicting rebase is only retried once the target branch moves again
+    let target_oid = git.get_branch_oid(&repo.path, target_branch)?;
+    if candidate.auto_rebase_conflict_oid.as_deref() == Some(target_oid.as_str()) {
+        return Ok(AutoRebaseOutcome::Skipped);
+    }
+
+    let (_, commits_behind) = match git.find_branch_type(&repo.path, target_branch)? {
+        BranchType::Local => git.get_branch_status(&repo.path, &candidate.branch, target_branch)?,
+        BranchType::Remote => {
+            git.get_remote_branch_status(&repo.path, &candidate.branch, Some(target_branch))?
+        }
+    };
+    if commits_behind == 0 {
+        // The conflicts were resolved by rebasing some other way
+        if candidate.auto_rebase_conflict_oid.is_some() {
+            WorkspaceRepo::set_auto_rebase_conflict(
+                pool,
+                candidate.workspace_id,
+                candidate.repo_id,
+                None,
+            )
+            .await?;
+        }
+        return Ok(AutoRebaseOutcome::UpToDate);
+    }
+
+    // Held until the rebase is done, so no process starts between the check and the rebase
+    let Some(_workspace_lock) = workspace_locks.try_lock(candidate.workspace_id) else {
+        return Ok(AutoRebaseOutcome::Busy);
+    };
+    if candidate.auto_rebase == AutoRebasePolicy::IdleOnly
+        && ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+            pool,
+            candidate.workspace_id,
+        )
+        .await?
+    {
+        debug!(
+            "Workspace {} is running, not rebasing it onto {}",
+            candidate.workspace_id, target_branch
+        );
+        return Ok(AutoRebaseOutcome::Busy);
+    }
+
+    match git.rebase_branch(
+        &repo.path,
+        &worktree_path,
+        target_branch,
+        target_branch,
+        &candidate.branch,
+    ) {
+        Ok(_) => {
+            info!(
+                "Rebased workspace {} onto {} ({} new commits)",
+                candidate.workspace_id, target_branch, commits_behind
+            );
+            if candidate.auto_rebase_conflict_oid.is_some() {
+                WorkspaceRepo::set_auto_rebase_conflict(
+                    pool,
+                    candidate.workspace_id,
+                    candidate.repo_id,
+                    None,
+                )
+                .await?;
+            }
+            Ok(AutoRebaseOutcome::Rebased)
+        }
+        Err(GitServiceError::MergeConflicts { message, .. }) => {
+            // Leave the branch as it was and flag the workspace for a manual rebase
+            git.abort_conflicts(&worktree_path)?;
+            WorkspaceRepo::set_auto_rebase_conflict(
+                pool,
+                candidate.workspace_id,
+                candidate.repo_id,
+                Some(&target_oid),
+            )
+            .await?;
+            warn!(
+                "Could not auto-rebase workspace {}: {}",
+                candidate.workspace_id, message
+            );
+            Ok(AutoRebaseOutcome::Conflicts)
+        }
+        // Retried on a later tick, once changes are committed or the rebase is finished
+        Err(e @ (GitServiceError::WorktreeDirty(..) | GitServiceError::RebaseInProgress)) => {
+            debug!(
+                "Not rebasing workspace {} onto {}: {}",
+                candidate.workspace_id, target_branch, e
+            );
+            Ok(AutoRebaseOutcome::Busy)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,184 @@
+use db::models::{
+    budget::Budget,
+    execution_process_token_usage::{ExecutionProcessTokenUsage, TokenUsageScope},
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::services::{config::ModelPrice, token_usage::TokenUsageReport};
+
+/// Check the task budget, then the project budget, against the usage recorded so far.
+/// Returns a user facing explanation for the first budget that is exhausted.
+pub async fn find_exhausted_budget(
+    pool: &SqlitePool,
+    prices: &[ModelPrice],
+    task_id: Uuid,
+    project_id: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    if let Some(budget) = Budget::find_for_task(pool, task_id).await? {
+        let totals =
+            ExecutionProcessTokenUsage::totals(pool, TokenUsageScope::Task(task_id)).await?;
+        let report = TokenUsageReport::from_totals(totals, prices);
+        if let Some(reason) = exhausted_reason("Task", &budget, &report) {
+            return Ok(Some(reason));
+        }
+    }
+
+    if let Some(budget) = Budget::find_for_project(pool, project_id).await? {
+        let totals =
+            ExecutionProcessTokenUsage::totals(pool, TokenUsageScope::Project(project_id)).await?;
+        let report = TokenUsageReport::from_totals(totals, prices);
+        if let Some(reason) = exhausted_reason("Project", &budget, &report) {
+            return Ok(Some(reason));
+        }
+    }
+
+    Ok(None)
+}
+
+fn exhausted_reason(scope: &str, budget: &Budget, report: &TokenUsageReport) -> Option<String> {
+    if let Some(max_cost_usd) = budget.max_cost_usd
+        && report.estimated_cost_usd >= max_cost_usd
+    {
+        return Some(format!(
+            "{scope} spend budget exhausted: ${:.2} spent of ${max_cost_usd:.2}. Raise the budget to continue.",
+            report.estimated_cost_usd
+        ));
+    }
+
+    if let Some(max_tokens) = budget.max_tokens
+        && report.total_tokens() >= max_tokens
+    {
+        return Some(format!(
+            "{scope} token budget exhausted: {} tokens used of {max_tokens}. Raise the budget to continue.",
+            report.total_tokens()
+        ));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::{
+        DBService,
+        models::{
+            budget::SetBudget,
+            execution_process::{ExecutionProcess, ExecutionProcessCompletionReason},
+            execution_process_token_usage::UpsertExecutionProcessTokenUsage,
+        },
+        test_utils::{add_task, create_coding_agent_process, create_task_fixture},
+    };
+
+    use super::*;
+
+    fn report(input_tokens: i64, estimated_cost_usd: f64) -> TokenUsageReport {
+        TokenUsageReport {
+            input_tokens,
+            estimated_cost_usd,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unlimited_budget_is_never_exhausted() {
+        let budget = Budget {
+            max_tokens: None,
+            max_cost_usd: None,
+            updated_at: Utc::now(),
+        };
+        assert!(exhausted_reason("Task", &budget, &report(i64::MAX / 2, 1e9)).is_none());
+    }
+
+    #[test]
+    fn reaching_either_limit_exhausts_budget() {
+        let budget = Budget {
+            max_tokens: Some(1_000),
+            max_cost_usd: Some(5.0),
+            updated_at: Utc::now(),
+        };
+        assert!(exhausted_reason("Task", &budget, &report(999, 4.99)).is_none());
+
+        let reason = exhausted_reason("Project", &budget, &report(10, 5.0)).unwrap();
+        assert!(reason.starts_with("Project spend budget exhausted"));
+
+        let reason = exhausted_reason("Task", &budget, &report(1_000, 0.0)).unwrap();
+        assert!(reason.starts_with("Task token budget exhausted"));
+    }
+
+    #[tokio::test]
+    async fn recorded_usage_exhausts_task_then_project_budget() {
+        let db = DBService::new_in_memory().await;
+        let pool = &db.pool;
+        let fixture = create_task_fixture(&db, "Budgeted").await;
+        let other = add_task(&db, fixture.project.clone(), "Sibling").await;
+        let process = create_coding_agent_process(&db, fixture.session.id).await;
+        ExecutionProcessTokenUsage::upsert(
+            pool,
+            process.id,
+            &UpsertExecutionProcessTokenUsage {
+                executor: "CLAUDE_CODE".to_string(),
+                model: Some("claude-sonnet-4-5".to_string()),
+                input_tokens: 800,
+                output_tokens: 400,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reported_cost_usd: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let limit = |max_tokens| SetBudget {
+            max_tokens: Some(max_tokens),
+            max_cost_usd: None,
+        };
+        Budget::set_for_task(pool, fixture.task.id, &limit(10_000))
+            .await
+            .unwrap();
+        Budget::set_for_project(pool, fixture.project.id, &limit(1_000))
+            .await
+            .unwrap();
+
+        // The task budget has room left, the project budget does not
+        let reason = find_exhausted_budget(pool, &[], fixture.task.id, fixture.project.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(reason.starts_with("Project token budget exhausted"));
+        // Usage of one task counts against the whole project
+        assert!(
+            find_exhausted_budget(pool, &[], other.task.id, fixture.project.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        Budget::set_for_task(pool, fixture.task.id, &limit(1_200))
+            .await
+            .unwrap();
+        let reason = find_exhausted_budget(pool, &[], fixture.task.id, fixture.project.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(reason.starts_with("Task token budget exhausted"));
+
+        // The stop is recorded on the process, apart from manual stops and execution limits
+        ExecutionProcess::set_completion_reason(
+            pool,
+            process.id,
+            ExecutionProcessCompletionReason::BudgetExceeded,
+        )
+        .await
+        .unwrap();
+        let process = ExecutionProcess::find_by_id(pool, process.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            process.completion_reason,
+            Some(ExecutionProcessCompletionReason::BudgetExceeded)
+        );
+    }
+}
@@ -27,6 +27,9 @@ pub type GitHubConfig = versions::v8::GitHubConfig;
 pub type UiLanguage = versions::v8::UiLanguage;
 pub type ShowcaseState = versions::v8::ShowcaseState;
 pub type SendMessageShortcut = versions::v8::SendMessageShortcut;
+pub type ModelPrice = versions::v8::ModelPrice;
+pub type LogRetentionConfig = versions::v8::LogRetentionConfig;
+pub type GitHostConfig = versions::v8::GitHostConfig;
 
 /// Will always return config, trying old schemas or eventually returning default
 pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
@@ -1,4 +1,5 @@
 use anyhow::Error;
+use db::models::repo::ProviderKind;
 use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
@@ -17,6 +18,69 @@ fn default_pr_auto_description_enabled() -> bool {
     true
 }
 
+fn model_price(
+    model: &str,
+    input_per_mtok: f64,
+    output_per_mtok: f64,
+    cache_read_per_mtok: f64,
+    cache_write_per_mtok: f64,
+) -> ModelPrice {
+    ModelPrice {
+        model: model.to_string(),
+        input_per_mtok,
+        output_per_mtok,
+        cache_read_per_mtok,
+        cache_write_per_mtok,
+    }
+}
+
+fn default_model_prices() -> Vec<ModelPrice> {
+    vec![
+        model_price("claude-opus-4-5", 5.0, 25.0, 0.5, 6.25),
+        model_price("claude-opus-4", 15.0, 75.0, 1.5, 18.75),
+        model_price("claude-sonnet-4", 3.0, 15.0, 0.3, 3.75),
+        model_price("claude-haiku-4", 1.0, 5.0, 0.1, 1.25),
+        model_price("gpt-5", 1.25, 10.0, 0.125, 0.0),
+        model_price("gpt-5-mini", 0.25, 2.0, 0.025, 0.0),
+    ]
+}
+
+/// Price of a model in USD per million tokens, used to estimate spend.
+/// `model` is matched as a prefix of the reported model name; the longest match wins.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq)]
+pub struct ModelPrice {
+    pub model: String,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    pub cache_read_per_mtok: f64,
+    pub cache_write_per_mtok: f64,
+}
+
+/// Limits on the stored logs of finished executions. Logs beyond any limit are deleted by the
+/// background log compactor; unset limits keep logs forever.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct LogRetentionConfig {
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    /// Counted as stored, so compressed logs count with their compressed size
+    #[serde(default)]
+    pub max_bytes_per_project: Option<u64>,
+    #[serde(default)]
+    pub keep_last_processes_per_project: Option<u32>,
+}
+
+/// Git hosting service on a custom domain, used when the provider cannot be detected from
+/// the remote URL.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct GitHostConfig {
+    /// Host name as it appears in remote and pull request URLs, e.g. `github.corp.example`
+    pub host: String,
+    pub provider: ProviderKind,
+    /// API endpoint to use instead of the one derived from `host`
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
 pub enum SendMessageShortcut {
     #[default]
@@ -56,6 +120,16 @@ pub struct Config {
     pub commit_reminder: bool,
     #[serde(default)]
     pub send_message_shortcut: SendMessageShortcut,
+    #[serde(default = "default_model_prices")]
+    pub model_prices: Vec<ModelPrice>,
+    /// Profiles to retry a coding agent with, in order, when it fails with missing auth,
+    /// a missing executable or a rate limit
+    #[serde(default)]
+    pub executor_fallbacks: Vec<ExecutorProfileId>,
+    #[serde(default)]
+    pub log_retention: LogRetentionConfig,
+    #[serde(default)]
+    pub git_hosts: Vec<GitHostConfig>,
 }
 
 impl Config {
@@ -85,6 +159,10 @@ impl Config {
             beta_workspaces_invitation_sent: false,
             commit_reminder: false,
             send_message_shortcut: SendMessageShortcut::default(),
+            model_prices: default_model_prices(),
+            executor_fallbacks: Vec::new(),
+            log_retention: LogRetentionConfig::default(),
+            git_hosts: Vec::new(),
         }
     }
 
@@ -139,6 +217,10 @@ impl Default for Config {
             beta_workspaces_invitation_sent: false,
             commit_reminder: false,
             send_message_shortcut: SendMessageShortcut::default(),
+            model_prices: default_model_prices(),
+            executor_fallbacks: Vec::new(),
+            log_retention: LogRetentionConfig::default(),
+            git_hosts: Vec::new(),
         }
     }
 }
@@ -6,10 +6,12 @@ use std::{
 
 use anyhow::{Error as AnyhowError, anyhow};
 use async_trait::async_trait;
+use chrono::Utc;
 use db::{
     DBService,
     models::{
         coding_agent_turn::{CodingAgentTurn, CreateCodingAgentTurn},
+        execution_log_search::ExecutionLogSearch,
         execution_process::{
             CreateExecutionProcess, ExecutionContext, ExecutionProcess, ExecutionProcessError,
             ExecutionProcessRunReason, ExecutionProcessStatus,
@@ -18,8 +20,9 @@ use db::{
         execution_process_repo_state::{
             CreateExecutionProcessRepoState, ExecutionProcessRepoState,
         },
+        pipeline::{PipelineRun, PipelineRunStatus, PipelineStage, ProjectPipeline},
         repo::Repo,
-        session::{CreateSession, Session, SessionError},
+        session::{CreateSession, ExecutorFallback, ExecutorFallbackReason, Session, SessionError},
         task::{Task, TaskStatus},
         workspace::{Workspace, WorkspaceError},
         workspace_repo::WorkspaceRepo,
@@ -33,11 +36,13 @@ use executors::{
     actions::{
         ExecutorAction, ExecutorActionType,
         coding_agent_initial::CodingAgentInitialRequest,
+        review::{RepoReviewContext, ReviewRequest},
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
-    executors::{ExecutorError, StandardCodingAgentExecutor},
+    executors::{ExecutorError, StandardCodingAgentExecutor, build_review_prompt},
     logs::{NormalizedEntry, NormalizedEntryError, NormalizedEntryType, utils::ConversationPatch},
     profile::ExecutorProfileId,
+    replay::{self, ReplayExecutor, ReplayFixture, ReplayHeader, ReplayTiming},
 };
 use futures::{StreamExt, future, stream::BoxStream};
 use json_patch::Patch;
@@ -52,8 +57,15 @@ use utils::{
 use uuid::Uuid;
 
 use crate::services::{
-    git::{GitService, GitServiceError},
+    budget::find_exhausted_budget,
+    config::ModelPrice,
+    executor_fallback::{fallback_reason_for_error, next_fallback_profile},
+    git::{DiffTarget, GitService, GitServiceError},
+    log_search::search_entries,
     notification::NotificationService,
+    pipeline::{plan_from_entries, render_stage_prompt, review_prompt_with_plan},
+    transcript::{Transcript, TranscriptFileChange, TranscriptTurn, indexed_entries_from_history},
+    workspace_lock::WorkspaceLocks,
     workspace_manager::WorkspaceError as WorkspaceManagerError,
     worktree_manager::WorktreeError,
 };
@@ -81,6 +93,8 @@ pub enum ContainerError {
     Io(#[from] std::io::Error),
     #[error("Failed to kill process: {0}")]
     KillFailed(std::io::Error),
+    #[error("{0}")]
+    BudgetExceeded(String),
     #[error(transparent)]
     Other(#[from] AnyhowError), // Catches any unclassified errors
 }
@@ -95,6 +109,9 @@ pub trait ContainerService {
 
     fn notification_service(&self) -> &NotificationService;
 
+    /// Held while an execution starts, so background rebases never race a new process
+    fn workspace_locks(&self) -> &WorkspaceLocks;
+
     fn workspace_to_current_dir(&self, workspace: &Workspace) -> PathBuf;
 
     async fn available_agent_slash_commands(
@@ -588,6 +605,90 @@ pub trait ContainerService {
 
     async fn git_branch_prefix(&self) -> String;
 
+    async fn model_prices(&self) -> Vec<ModelPrice>;
+
+    async fn executor_fallbacks(&self) -> Vec<ExecutorProfileId>;
+
+    /// Retry a coding agent execution that failed for `reason` with the next untried profile of
+    /// the configured fallback chain. The retry runs in the same workspace, so it starts from the
+    /// current worktree state. Profiles that cannot be spawned either are skipped until the chain
+    /// runs out. Returns the new execution process if a fallback was started.
+    async fn try_start_fallback_execution(
+        &self,
+        workspace: &Workspace,
+        failed_process: &ExecutionProcess,
+        reason: ExecutorFallbackReason,
+    ) -> Result<Option<ExecutionProcess>, ContainerError> {
+        let pool = &self.db().pool;
+        let chain = self.executor_fallbacks().await;
+        let task = workspace
+            .parent_task(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+
+        let mut failed_process = failed_process.clone();
+        let mut reason = reason;
+        // Every attempt is recorded on the session, so each profile of the chain runs at most once
+        for _ in 0..chain.len() {
+            let action = failed_process.executor_action()?;
+            let Some(current) = action.executor_profile_id() else {
+                return Ok(None);
+            };
+            let Some(session) = Session::find_by_id(pool, failed_process.session_id).await? else {
+                return Ok(None);
+            };
+            let Some(next) = next_fallback_profile(&chain, current, &session.executor_fallbacks.0)
+            else {
+                return Ok(None);
+            };
+            let Some(fallback_action) =
+                action.with_fallback_profile(next.clone(), &task.to_prompt())
+            else {
+                return Ok(None);
+            };
+
+            tracing::info!(
+                "Execution {} failed ({:?}) with {}, falling back to {}",
+                failed_process.id,
+                reason,
+                current,
+                next
+            );
+            let fallback = ExecutorFallback {
+                from: current.clone(),
+                to: next,
+                reason,
+                execution_process_id: failed_process.id,
+                created_at: Utc::now(),
+            };
+            Session::record_executor_fallback(pool, session.id, &fallback).await?;
+
+            let (execution_process, start_error) = self
+                .create_and_start_execution(
+                    workspace,
+                    &session,
+                    &fallback_action,
+                    &ExecutionProcessRunReason::CodingAgent,
+                )
+                .await?;
+            let Some(start_error) = start_error else {
+                return Ok(Some(execution_process));
+            };
+            let next_reason = match &start_error {
+                ContainerError::ExecutorError(executor_error) => {
+                    fallback_reason_for_error(executor_error)
+                }
+                _ => None,
+            };
+            let Some(next_reason) = next_reason else {
+                return Err(start_error);
+            };
+            failed_process = execution_process;
+            reason = next_reason;
+        }
+        Ok(None)
+    }
+
     async fn git_branch_from_workspace(&self, workspace_id: &Uuid, task_title: &str) -> String {
         let task_title_id = git_branch_id(task_title);
         let prefix = self.git_branch_prefix().await;
@@ -650,6 +751,54 @@ pub trait ContainerService {
         }
     }
 
+    /// Raw output of a coding agent execution as a replay fixture, so its log normalization can
+    /// be reproduced offline. Returns `None` for executions that did not run a coding agent.
+    async fn record_replay_fixture(
+        &self,
+        process: &ExecutionProcess,
+    ) -> Result<Option<ReplayFixture>, ContainerError> {
+        let Some((workspace, _)) = process
+            .parent_workspace_and_session(&self.db().pool)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let current_dir = self.workspace_to_current_dir(&workspace);
+        let (executor_profile_id, worktree_path) = match process.executor_action()?.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => (
+                request.executor_profile_id.clone(),
+                request.effective_dir(&current_dir),
+            ),
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => (
+                request.executor_profile_id.clone(),
+                request.effective_dir(&current_dir),
+            ),
+            ExecutorActionType::ReviewRequest(request) => {
+                (request.executor_profile_id.clone(), current_dir)
+            }
+            _ => return Ok(None),
+        };
+
+        let records =
+            ExecutionProcessLogs::find_by_execution_id(&self.db().pool, process.id).await?;
+        let mut logs = Vec::new();
+        for record in &records {
+            for line in record.logs.lines().filter(|line| !line.trim().is_empty()) {
+                let msg: LogMsg =
+                    serde_json::from_str(line).map_err(|e| ContainerError::Other(e.into()))?;
+                logs.push((record.inserted_at, msg));
+            }
+        }
+
+        Ok(Some(ReplayFixture::record(
+            ReplayHeader {
+                executor_profile_id,
+                worktree_path,
+            },
+            logs,
+        )))
+    }
+
     async fn stream_normalized_logs(
         &self,
         id: &Uuid,
@@ -822,6 +971,178 @@ pub trait ContainerService {
         }
     }
 
+    /// Normalized conversation of an execution process, as far as it has run
+    async fn normalized_entries(&self, id: &Uuid) -> Vec<NormalizedEntry> {
+        self.indexed_normalized_entries(id)
+            .await
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+
+    /// Like [`Self::normalized_entries`], with the index each entry has in the conversation
+    async fn indexed_normalized_entries(&self, id: &Uuid) -> Vec<(usize, NormalizedEntry)> {
+        // A live store never finishes streaming while the process runs, so read its snapshot
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            return indexed_entries_from_history(&store.get_history());
+        }
+        let Some(stream) = self.stream_normalized_logs(id).await else {
+            return Vec::new();
+        };
+        let history: Vec<LogMsg> = stream
+            .take_while(|msg| future::ready(!matches!(msg, Ok(LogMsg::Finished))))
+            .filter_map(|msg| future::ready(msg.ok()))
+            .collect()
+            .await;
+        indexed_entries_from_history(&history)
+    }
+
+    /// Files the workspace changed compared to the target branch of each repo. Workspaces
+    /// without a worktree have nothing to summarise.
+    async fn workspace_file_changes(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<Vec<TranscriptFileChange>, ContainerError> {
+        let Some(container_ref) = &workspace.container_ref else {
+            return Ok(Vec::new());
+        };
+        let workspace_root = PathBuf::from(container_ref);
+        if !workspace_root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let repos = WorkspaceRepo::find_repos_with_target_branch_for_workspace(
+            &self.db().pool,
+            workspace.id,
+        )
+        .await?;
+        let mut files = Vec::new();
+        for repo in repos {
+            let git = self.git().clone();
+            let worktree_path = workspace_root.join(&repo.repo.name);
+            let branch = workspace.branch.clone();
+            let diffs = tokio::task::spawn_blocking(move || {
+                let base_commit =
+                    git.get_base_commit(&repo.repo.path, &branch, &repo.target_branch)?;
+                let diffs = git.get_diffs(
+                    DiffTarget::Worktree {
+                        worktree_path: &worktree_path,
+                        base_commit: &base_commit,
+                    },
+                    None,
+                )?;
+                Ok::<_, GitServiceError>(TranscriptFileChange::from_diffs(&repo.repo.name, &diffs))
+            })
+            .await
+            .map_err(|e| ContainerError::Other(anyhow!("Failed to diff workspace: {e}")))?;
+            match diffs {
+                Ok(diffs) => files.extend(diffs),
+                Err(e) => tracing::warn!("Skipping transcript diff summary for repo: {e}"),
+            }
+        }
+        Ok(files)
+    }
+
+    /// Transcript of the coding agent executions among `processes`, which must belong to
+    /// `session`, followed by a summary of the workspace diff
+    async fn build_transcript(
+        &self,
+        session: &Session,
+        processes: &[ExecutionProcess],
+    ) -> Result<Transcript, ContainerError> {
+        let pool = &self.db().pool;
+        let workspace = Workspace::find_by_id(pool, session.workspace_id)
+            .await?
+            .ok_or(WorkspaceError::ValidationError(
+                "Workspace not found".to_string(),
+            ))?;
+        let title = Task::find_by_id(pool, workspace.task_id)
+            .await?
+            .map(|task| task.title)
+            .or_else(|| workspace.name.clone())
+            .unwrap_or_else(|| format!("Session {}", session.id));
+
+        let mut turns = Vec::new();
+        for process in processes
+            .iter()
+            .filter(|p| p.run_reason == ExecutionProcessRunReason::CodingAgent)
+        {
+            let prompt = CodingAgentTurn::find_by_execution_process_id(pool, process.id)
+                .await?
+                .and_then(|turn| turn.prompt);
+            let executor = process
+                .executor_action()
+                .ok()
+                .and_then(|action| action.executor_profile_id())
+                .map(ToString::to_string);
+            turns.push(TranscriptTurn {
+                execution_process_id: process.id,
+                executor,
+                prompt,
+                started_at: process.started_at,
+                entries: self.normalized_entries(&process.id).await,
+            });
+        }
+
+        Ok(Transcript {
+            title,
+            session_id: session.id,
+            exported_at: Utc::now(),
+            turns,
+            files: self.workspace_file_changes(&workspace).await?,
+        })
+    }
+
+    /// Add the normalized conversation of a coding agent execution to the log search index
+    async fn index_execution_logs(
+        &self,
+        execution_process_id: &Uuid,
+    ) -> Result<(), ContainerError> {
+        let entries = search_entries(&self.indexed_normalized_entries(execution_process_id).await);
+        ExecutionLogSearch::index_execution(&self.db().pool, *execution_process_id, &entries)
+            .await?;
+        Ok(())
+    }
+
+    /// Index coding agent executions that finished before log search existed. Their raw logs
+    /// are replayed through the normalizer, so no worktree has to be recreated.
+    async fn backfill_log_search_index(&self) -> Result<(), ContainerError> {
+        const BATCH_SIZE: i64 = 50;
+        let pool = &self.db().pool;
+        loop {
+            let ids = ExecutionLogSearch::find_unindexed_execution_ids(pool, BATCH_SIZE).await?;
+            if ids.is_empty() {
+                return Ok(());
+            }
+            for id in ids {
+                let entries = match ExecutionProcess::find_by_id(pool, id).await? {
+                    Some(process) => match self.record_replay_fixture(&process).await {
+                        Ok(Some(fixture)) => {
+                            let configs = ExecutorConfigs::get_cached();
+                            let msg_store = ReplayExecutor::from_fixture(&configs, fixture)
+                                .with_timing(ReplayTiming::Instant)
+                                .run()
+                                .await;
+                            search_entries(&replay::indexed_normalized_entries(&msg_store))
+                        }
+                        Ok(None) => Vec::new(),
+                        Err(e) => {
+                            tracing::warn!("Failed to replay logs of execution {}: {}", id, e);
+                            Vec::new()
+                        }
+                    },
+                    None => Vec::new(),
+                };
+                // Executions that cannot be replayed or indexed are still marked, so they are not
+                // retried on every boot
+                if let Err(e) = ExecutionLogSearch::index_execution(pool, id, &entries).await {
+                    tracing::warn!("Failed to index logs of execution {}: {}", id, e);
+                    ExecutionLogSearch::mark_indexed(pool, id).await?;
+                }
+            }
+        }
+    }
+
     fn spawn_stream_raw_logs_to_db(&self, execution_id: &Uuid) -> JoinHandle<()> {
         let execution_id = *execution_id;
         let msg_stores = self.msg_stores().clone();
@@ -889,7 +1210,7 @@ pub trait ContainerService {
                         LogMsg::Finished => {
                             break;
                         }
-                        LogMsg::JsonPatch(_) | LogMsg::Ready => continue,
+                        LogMsg::JsonPatch(_) | LogMsg::Ready | LogMsg::ResourceUsage(_) => continue,
                     }
                 }
             }
@@ -901,39 +1222,83 @@ pub trait ContainerService {
         workspace: &Workspace,
         executor_profile_id: ExecutorProfileId,
     ) -> Result<ExecutionProcess, ContainerError> {
-        // Create container
-        self.create(workspace).await?;
-
         // Get parent task
         let task = workspace
             .parent_task(&self.db().pool)
             .await?
             .ok_or(SqlxError::RowNotFound)?;
 
+        // Refuse new attempts before creating any worktree once the budget is used up
+        if let Some(reason) = find_exhausted_budget(
+            &self.db().pool,
+            &self.model_prices().await,
+            task.id,
+            task.project_id,
+        )
+        .await?
+        {
+            return Err(ContainerError::BudgetExceeded(reason));
+        }
+
+        // Create container
+        self.create(workspace).await?;
+
         let repos = WorkspaceRepo::find_repos_for_workspace(&self.db().pool, workspace.id).await?;
 
         let workspace = Workspace::find_by_id(&self.db().pool, workspace.id)
             .await?
             .ok_or(SqlxError::RowNotFound)?;
 
+        // A project pipeline replaces the first turn with its first agent stage
+        let pipeline = ProjectPipeline::find_for_project(&self.db().pool, task.project_id).await?;
+        let (agent_profile_id, prompt) = match pipeline
+            .as_ref()
+            .and_then(|pipeline| pipeline.stages.0.first())
+        {
+            Some(PipelineStage::Agent {
+                executor_profile_id: stage_profile_id,
+                prompt,
+            }) => (
+                stage_profile_id
+                    .clone()
+                    .unwrap_or_else(|| executor_profile_id.clone()),
+                render_stage_prompt(prompt.as_deref(), &task.to_prompt(), None),
+            ),
+            _ => (executor_profile_id.clone(), task.to_prompt()),
+        };
+
         // Create a session for this workspace
         let session = Session::create(
             &self.db().pool,
             &CreateSession {
-                executor: Some(executor_profile_id.executor.to_string()),
+                executor: Some(agent_profile_id.executor.to_string()),
             },
             Uuid::new_v4(),
             workspace.id,
         )
         .await?;
 
-        let prompt = task.to_prompt();
+        if let Some(pipeline) = &pipeline {
+            PipelineRun::create(
+                &self.db().pool,
+                workspace.id,
+                session.id,
+                &pipeline.stages.0,
+                &executor_profile_id,
+            )
+            .await?;
+        }
 
         let repos_with_setup: Vec<_> = repos.iter().filter(|r| r.setup_script.is_some()).collect();
 
         let all_parallel = repos_with_setup.iter().all(|r| r.parallel_setup_script);
 
-        let cleanup_action = self.cleanup_actions_for_repos(&repos);
+        // Pipelines run their own script stages instead of the cleanup scripts
+        let cleanup_action = if pipeline.is_some() {
+            None
+        } else {
+            self.cleanup_actions_for_repos(&repos)
+        };
 
         let working_dir = workspace
             .agent_working_dir
@@ -944,7 +1309,7 @@ pub trait ContainerService {
         let coding_action = ExecutorAction::new(
             ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
                 prompt,
-                executor_profile_id: executor_profile_id.clone(),
+                executor_profile_id: agent_profile_id,
                 working_dir,
             }),
             cleanup_action.map(Box::new),
@@ -988,6 +1353,233 @@ pub trait ContainerService {
         Ok(execution_process)
     }
 
+    /// Start stage `index` of a pipeline run, skipping script stages with nothing to run. Returns
+    /// None when the run stops to wait for plan approval or has no stages left.
+    async fn start_pipeline_stage(
+        &self,
+        workspace: &Workspace,
+        run: &PipelineRun,
+        index: usize,
+    ) -> Result<Option<ExecutionProcess>, ContainerError> {
+        let pool = &self.db().pool;
+        let task = workspace
+            .parent_task(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let plan = run.plan.as_deref();
+        let working_dir = workspace
+            .agent_working_dir
+            .as_ref()
+            .filter(|dir| !dir.is_empty())
+            .cloned();
+
+        for (stage_index, stage) in run.stages.0.iter().enumerate().skip(index) {
+            let stage_index = stage_index as i64;
+            let (session, action, run_reason) = match stage {
+                PipelineStage::PlanApproval => {
+                    PipelineRun::update_progress(
+                        pool,
+                        run.id,
+                        stage_index,
+                        run.session_id,
+                        PipelineRunStatus::AwaitingApproval,
+                    )
+                    .await?;
+                    return Ok(None);
+                }
+                PipelineStage::Agent {
+                    executor_profile_id,
+                    prompt,
+                } => {
+                    let executor_profile_id = executor_profile_id
+                        .clone()
+                        .unwrap_or_else(|| run.default_executor_profile_id.0.clone());
+                    let session = Session::create(
+                        pool,
+                        &CreateSession {
+                            executor: Some(executor_profile_id.executor.to_string()),
+                        },
+                        Uuid::new_v4(),
+                        workspace.id,
+                    )
+                    .await?;
+                    let action = ExecutorAction::new(
+                        ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                            prompt: render_stage_prompt(prompt.as_deref(), &task.to_prompt(), plan),
+                            executor_profile_id,
+                            working_dir: working_dir.clone(),
+                        }),
+                        None,
+                    );
+                    (session, action, ExecutionProcessRunReason::CodingAgent)
+                }
+                PipelineStage::Review {
+                    executor_profile_id,
+                    additional_prompt,
+                } => {
+                    let executor_profile_id = executor_profile_id
+                        .clone()
+                        .unwrap_or_else(|| run.default_executor_profile_id.0.clone());
+                    let session = Session::create(
+                        pool,
+                        &CreateSession {
+                            executor: Some(executor_profile_id.executor.to_string()),
+                        },
+                        Uuid::new_v4(),
+                        workspace.id,
+                    )
+                    .await?;
+
+                    // Review everything the earlier stages committed on the workspace branch
+                    let workspace_path =
+                        PathBuf::from(self.ensure_container_exists(workspace).await?);
+                    let mut context = Vec::new();
+                    for repo in WorkspaceRepo::find_repos_with_target_branch_for_workspace(
+                        pool,
+                        workspace.id,
+                    )
+                    .await?
+                    {
+                        if let Ok(base_commit) = self.git().get_fork_point(
+                            &workspace_path.join(&repo.repo.name),
+                            &repo.target_branch,
+                            &workspace.branch,
+                        ) {
+                            context.push(RepoReviewContext {
+                                repo_id: repo.repo.id,
+                                repo_name: repo.repo.display_name,
+                                base_commit,
+                            });
+                        }
+                    }
+                    let context = (!context.is_empty()).then_some(context);
+                    let additional_prompt =
+                        review_prompt_with_plan(additional_prompt.as_deref(), plan);
+                    let action = ExecutorAction::new(
+                        ExecutorActionType::ReviewRequest(ReviewRequest {
+                            executor_profile_id,
+                            prompt: build_review_prompt(
+                                context.as_deref(),
+                                additional_prompt.as_deref(),
+                            ),
+                            context,
+                            session_id: None,
+                            working_dir: working_dir.clone(),
+                        }),
+                        None,
+                    );
+                    (session, action, ExecutionProcessRunReason::CodingAgent)
+                }
+                PipelineStage::Script { script } => {
+                    let action = match script {
+                        Some(script) => Some(ExecutorAction::new(
+                            ExecutorActionType::ScriptRequest(ScriptRequest {
+                                script: script.clone(),
+                                language: ScriptRequestLanguage::Bash,
+                                context: ScriptContext::CleanupScript,
+                                working_dir: working_dir.clone(),
+                            }),
+                            None,
+                        )),
+                        None => self.cleanup_actions_for_repos(
+                            &WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?,
+                        ),
+                    };
+                    let Some(action) = action else {
+                        continue;
+                    };
+                    // Scripts run in the session of the stage before them
+                    let session = Session::find_by_id(pool, run.session_id)
+                        .await?
+                        .ok_or(SqlxError::RowNotFound)?;
+                    (session, action, ExecutionProcessRunReason::CleanupScript)
+                }
+            };
+
+            PipelineRun::update_progress(
+                pool,
+                run.id,
+                stage_index,
+                session.id,
+                PipelineRunStatus::Running,
+            )
+            .await?;
+            let execution_process = self
+                .start_execution(workspace, &session, &action, &run_reason)
+                .await?;
+            return Ok(Some(execution_process));
+        }
+
+        PipelineRun::update_progress(
+            pool,
+            run.id,
+            run.stages.0.len() as i64,
+            run.session_id,
+            PipelineRunStatus::Completed,
+        )
+        .await?;
+        Ok(None)
+    }
+
+    /// Move the pipeline run of a finished stage on to its next stage, passing on the plan the
+    /// first agent stage produced. Returns whether a next stage was started.
+    async fn advance_pipeline(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError> {
+        let pool = &self.db().pool;
+        let Some(mut run) =
+            PipelineRun::find_latest_by_workspace_id(pool, ctx.workspace.id).await?
+        else {
+            return Ok(false);
+        };
+        // Only the process of the current stage moves the run along
+        if run.status != PipelineRunStatus::Running
+            || run.session_id != ctx.session.id
+            || matches!(
+                ctx.execution_process.run_reason,
+                ExecutionProcessRunReason::DevServer
+            )
+        {
+            return Ok(false);
+        }
+
+        if ctx.execution_process.status != ExecutionProcessStatus::Completed {
+            PipelineRun::update_status(pool, run.id, PipelineRunStatus::Failed).await?;
+            return Ok(false);
+        }
+
+        if run.plan.is_none()
+            && matches!(run.current(), Some(PipelineStage::Agent { .. }))
+            && let Some(plan) =
+                plan_from_entries(&self.normalized_entries(&ctx.execution_process.id).await)
+        {
+            if let Err(e) = PipelineRun::set_plan(pool, run.id, &plan).await {
+                PipelineRun::update_status(pool, run.id, PipelineRunStatus::Failed).await?;
+                return Err(e.into());
+            }
+            run.plan = Some(plan);
+        }
+
+        let next_stage = usize::try_from(run.current_stage + 1).unwrap_or_default();
+        Ok(self
+            .start_pipeline_stage_or_fail(&ctx.workspace, &run, next_stage)
+            .await?
+            .is_some())
+    }
+
+    /// Like `start_pipeline_stage`, but marks the run failed when the stage cannot be started so
+    /// it does not stay running without a process
+    async fn start_pipeline_stage_or_fail(
+        &self,
+        workspace: &Workspace,
+        run: &PipelineRun,
+        index: usize,
+    ) -> Result<Option<ExecutionProcess>, ContainerError> {
+        let started = self.start_pipeline_stage(workspace, run, index).await;
+        if started.is_err() {
+            PipelineRun::update_status(&self.db().pool, run.id, PipelineRunStatus::Failed).await?;
+        }
+        started
+    }
+
     async fn start_execution(
         &self,
         workspace: &Workspace,
@@ -995,11 +1587,64 @@ pub trait ContainerService {
         executor_action: &ExecutorAction,
         run_reason: &ExecutionProcessRunReason,
     ) -> Result<ExecutionProcess, ContainerError> {
+        let (execution_process, start_error) = self
+            .create_and_start_execution(workspace, session, executor_action, run_reason)
+            .await?;
+        let Some(start_error) = start_error else {
+            return Ok(execution_process);
+        };
+
+        // Retry with the next fallback profile when the agent cannot run at all
+        if let ContainerError::ExecutorError(executor_error) = &start_error
+            && let Some(reason) = fallback_reason_for_error(executor_error)
+        {
+            match self
+                .try_start_fallback_execution(workspace, &execution_process, reason)
+                .await
+            {
+                Ok(Some(fallback_process)) => return Ok(fallback_process),
+                Ok(None) => {}
+                Err(e) => tracing::error!(
+                    "Failed to start fallback executor for {}: {}",
+                    execution_process.id,
+                    e
+                ),
+            }
+        }
+        Err(start_error)
+    }
+
+    /// Create the execution process for `executor_action` and spawn it. When spawning fails
+    /// the process is marked as failed and returned together with the start error.
+    async fn create_and_start_execution(
+        &self,
+        workspace: &Workspace,
+        session: &Session,
+        executor_action: &ExecutorAction,
+        run_reason: &ExecutionProcessRunReason,
+    ) -> Result<(ExecutionProcess, Option<ContainerError>), ContainerError> {
+        // Released once the process is marked running, which background rebases check for
+        let _workspace_lock = self.workspace_locks().lock(workspace.id).await;
+
         // Update task status to InProgress when starting an execution
         let task = workspace
             .parent_task(&self.db().pool)
             .await?
             .ok_or(SqlxError::RowNotFound)?;
+
+        // Refuse new coding agent runs once the task or project budget is used up
+        if run_reason == &ExecutionProcessRunReason::CodingAgent
+            && let Some(reason) = find_exhausted_budget(
+                &self.db().pool,
+                &self.model_prices().await,
+                task.id,
+                task.project_id,
+            )
+            .await?
+        {
+            return Err(ContainerError::BudgetExceeded(reason));
+        }
+
         if task.status != TaskStatus::InProgress
             && run_reason != &ExecutionProcessRunReason::DevServer
         {
@@ -1130,7 +1775,8 @@ pub trait ContainerService {
                     .await;
                 }
             };
-            return Err(start_error);
+
+            return Ok((execution_process, Some(start_error)));
         }
 
         // Start processing normalised logs for executor requests and follow ups
@@ -1174,7 +1820,7 @@ pub trait ContainerService {
         }
 
         self.spawn_stream_raw_logs_to_db(&execution_process.id);
-        Ok(execution_process)
+        Ok((execution_process, None))
     }
 
     async fn try_start_next_action(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
@@ -0,0 +1,240 @@
+use std::sync::LazyLock;
+
+use db::models::session::{ExecutorFallback, ExecutorFallbackReason};
+use executors::{
+    executors::ExecutorError,
+    logs::{
+        NormalizedEntryError, NormalizedEntryType,
+        utils::patch::extract_normalized_entry_from_patch,
+    },
+    profile::ExecutorProfileId,
+};
+use regex::Regex;
+use utils::log_msg::LogMsg;
+
+static RATE_LIMIT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\brate[ _-]?limit(?:ed|[ _-]error|\s+(?:exceeded|reached|hit))|too many requests|usage limit (?:reached|exceeded)|quota (?:exceeded|exhausted)|(?:status|error|code)\W{0,3}429\b",
+    )
+    .expect("valid regex")
+});
+
+/// Fallback reason for an error returned while spawning a coding agent
+pub fn fallback_reason_for_error(error: &ExecutorError) -> Option<ExecutorFallbackReason> {
+    match error {
+        ExecutorError::AuthRequired(_) => Some(ExecutorFallbackReason::AuthRequired),
+        ExecutorError::ExecutableNotFound { .. } => {
+            Some(ExecutorFallbackReason::ExecutableNotFound)
+        }
+        _ => None,
+    }
+}
+
+/// Look at how a failed coding agent execution ended for an auth or rate limit failure.
+/// Only structured error entries count, and a rate limit only when it is the agent's final
+/// error: errors the agent recovered from and limits mentioned in its output are ignored.
+/// Raw stderr is used as the final error when the executor produced no error entries.
+pub fn fallback_reason_from_logs(history: &[LogMsg]) -> Option<ExecutorFallbackReason> {
+    let mut final_error: Option<(usize, String)> = None;
+    let mut has_error_entries = false;
+    for msg in history {
+        let LogMsg::JsonPatch(patch) = msg else {
+            continue;
+        };
+        let Some((index, entry)) = extract_normalized_entry_from_patch(patch) else {
+            continue;
+        };
+        match entry.entry_type {
+            NormalizedEntryType::ErrorMessage {
+                error_type: NormalizedEntryError::SetupRequired,
+            } => return Some(ExecutorFallbackReason::AuthRequired),
+            NormalizedEntryType::ErrorMessage { .. } => {
+                has_error_entries = true;
+                final_error = Some((index, entry.content));
+            }
+            // The agent carried on after the error
+            _ if final_error
+                .as_ref()
+                .is_some_and(|(error_index, _)| index > *error_index) =>
+            {
+                final_error = None
+            }
+            _ => {}
+        }
+    }
+
+    let final_error = match final_error {
+        Some((_, content)) => content,
+        None if has_error_entries => return None,
+        None => history.iter().rev().find_map(|msg| match msg {
+            LogMsg::Stderr(line) if !line.trim().is_empty() => Some(line.clone()),
+            _ => None,
+        })?,
+    };
+    RATE_LIMIT
+        .is_match(&final_error)
+        .then_some(ExecutorFallbackReason::RateLimited)
+}
+
+/// The first profile of `chain` the session has not run yet. Profiles that already failed
+/// are never retried so a chain cannot loop.
+pub fn next_fallback_profile(
+    chain: &[ExecutorProfileId],
+    current: &ExecutorProfileId,
+    previous: &[ExecutorFallback],
+) -> Option<ExecutorProfileId> {
+    chain
+        .iter()
+        .find(|candidate| {
+            *candidate != current
+                && !previous
+                    .iter()
+                    .any(|fallback| &fallback.from == *candidate || &fallback.to == *candidate)
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use executors::{
+        executors::BaseCodingAgent,
+        logs::{NormalizedEntry, utils::ConversationPatch},
+    };
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn entry(index: usize, entry_type: NormalizedEntryType, content: &str) -> LogMsg {
+        LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(
+            index,
+            NormalizedEntry {
+                timestamp: None,
+                entry_type,
+                content: content.to_string(),
+                metadata: None,
+            },
+        ))
+    }
+
+    fn error_entry(error_type: NormalizedEntryError, content: &str) -> LogMsg {
+        entry(0, NormalizedEntryType::ErrorMessage { error_type }, content)
+    }
+
+    #[test]
+    fn detects_rate_limits_and_missing_auth() {
+        assert_eq!(
+            fallback_reason_from_logs(&[LogMsg::Stderr(
+                "API Error: 429 Too Many Requests".to_string()
+            )]),
+            Some(ExecutorFallbackReason::RateLimited)
+        );
+        assert_eq!(
+            fallback_reason_from_logs(&[error_entry(
+                NormalizedEntryError::Other,
+                "Claude AI usage limit reached"
+            )]),
+            Some(ExecutorFallbackReason::RateLimited)
+        );
+        assert_eq!(
+            fallback_reason_from_logs(&[error_entry(
+                NormalizedEntryError::SetupRequired,
+                "Please log in"
+            )]),
+            Some(ExecutorFallbackReason::AuthRequired)
+        );
+        assert_eq!(
+            fallback_reason_from_logs(&[
+                LogMsg::Stderr("compilation failed".to_string()),
+                error_entry(NormalizedEntryError::Other, "tests failed"),
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn ignores_rate_limits_that_are_not_the_final_error() {
+        let error = |index, content| {
+            entry(
+                index,
+                NormalizedEntryType::ErrorMessage {
+                    error_type: NormalizedEntryError::Other,
+                },
+                content,
+            )
+        };
+
+        // Mentioned by the agent rather than reported as an error
+        assert_eq!(
+            fallback_reason_from_logs(&[
+                entry(
+                    0,
+                    NormalizedEntryType::AssistantMessage,
+                    "Added a rate limit of 429 requests to the API client"
+                ),
+                error(1, "tests failed"),
+            ]),
+            None
+        );
+        // Retried by the agent, which then failed for another reason
+        assert_eq!(
+            fallback_reason_from_logs(&[
+                error(0, "Rate limited, retrying in 5s"),
+                entry(1, NormalizedEntryType::AssistantMessage, "Done"),
+                error(2, "tests failed"),
+            ]),
+            None
+        );
+        // Recovered from entirely, the raw stderr behind the entry is not looked at again
+        assert_eq!(
+            fallback_reason_from_logs(&[
+                LogMsg::Stderr("API Error: 429 Too Many Requests".to_string()),
+                error(0, "API Error: 429 Too Many Requests"),
+                entry(1, NormalizedEntryType::AssistantMessage, "Done"),
+            ]),
+            None
+        );
+        // Early stderr noise before an unrelated crash
+        assert_eq!(
+            fallback_reason_from_logs(&[
+                LogMsg::Stderr("warning: status 429, backing off".to_string()),
+                LogMsg::Stderr("panic: index out of range".to_string()),
+            ]),
+            None
+        );
+        // Code that happens to contain the words
+        assert_eq!(
+            fallback_reason_from_logs(&[error(
+                0,
+                "error[E0425]: cannot find value `rate_limit` in this scope (line 4290)"
+            )]),
+            None
+        );
+    }
+
+    #[test]
+    fn next_profile_skips_profiles_already_tried() {
+        let claude = ExecutorProfileId::new(BaseCodingAgent::ClaudeCode);
+        let codex = ExecutorProfileId::new(BaseCodingAgent::Codex);
+        let opencode = ExecutorProfileId::new(BaseCodingAgent::Opencode);
+        let chain = vec![claude.clone(), codex.clone(), opencode.clone()];
+
+        assert_eq!(
+            next_fallback_profile(&chain, &claude, &[]),
+            Some(codex.clone())
+        );
+
+        let previous = vec![ExecutorFallback {
+            from: claude.clone(),
+            to: codex.clone(),
+            reason: ExecutorFallbackReason::RateLimited,
+            execution_process_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+        }];
+        assert_eq!(
+            next_fallback_profile(&chain, &codex, &previous),
+            Some(opencode.clone())
+        );
+        assert_eq!(next_fallback_profile(&chain[..2], &codex, &previous), None);
+    }
+}
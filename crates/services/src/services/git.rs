@@ -1606,6 +1606,17 @@ impl GitService {
             })
     }
 
+    /// Update a remote-tracking branch such as `origin/main` from its remote
+    pub fn fetch_remote_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let branch_ref = Self::find_branch(&repo, branch_name)?.into_reference();
+        self.fetch_branch_from_remote(&repo, &branch_ref)
+    }
+
     pub fn get_remote_url(
         &self,
         repo_path: &Path,
@@ -5,6 +5,7 @@
 
 use std::{
     ffi::{OsStr, OsString},
+    io::Write,
     path::Path,
     process::Command,
 };
@@ -12,6 +13,7 @@ use std::{
 use chrono::{DateTime, Utc};
 use db::models::merge::{MergeStatus, PullRequestInfo};
 use serde::Deserialize;
+use tempfile::NamedTempFile;
 use thiserror::Error;
 use utils::shell::resolve_executable_path_blocking;
 
@@ -393,6 +395,71 @@ impl AzCli {
         Self::parse_pr_threads(&raw)
     }
 
+    /// Change the target branch of a pull request.
+    ///
+    /// `az repos pr update` cannot retarget a PR, so this goes through the REST API.
+    pub fn update_pr_target(
+        &self,
+        organization_url: &str,
+        project_id: &str,
+        repo_id: &str,
+        pr_id: i64,
+        target_branch: &str,
+    ) -> Result<(), AzCliError> {
+        let mut body_file = NamedTempFile::new()
+            .map_err(|e| AzCliError::CommandFailed(format!("Failed to create temp file: {e}")))?;
+        let body = Self::update_pr_target_body(target_branch);
+        body_file
+            .write_all(body.to_string().as_bytes())
+            .map_err(|e| AzCliError::CommandFailed(format!("Failed to write body: {e}")))?;
+
+        let args = Self::update_pr_target_args(
+            organization_url,
+            project_id,
+            repo_id,
+            pr_id,
+            body_file.path(),
+        );
+        self.run(args, None)?;
+        Ok(())
+    }
+
+    fn update_pr_target_body(target_branch: &str) -> serde_json::Value {
+        serde_json::json!({ "targetRefName": format!("refs/heads/{target_branch}") })
+    }
+
+    /// Arguments of `az devops invoke` that PATCH a pull request with the body in `body_path`.
+    fn update_pr_target_args(
+        organization_url: &str,
+        project_id: &str,
+        repo_id: &str,
+        pr_id: i64,
+        body_path: &Path,
+    ) -> Vec<OsString> {
+        let mut args: Vec<OsString> = Vec::with_capacity(20);
+        args.push(OsString::from("devops"));
+        args.push(OsString::from("invoke"));
+        args.push(OsString::from("--area"));
+        args.push(OsString::from("git"));
+        args.push(OsString::from("--resource"));
+        args.push(OsString::from("pullRequests"));
+        args.push(OsString::from("--route-parameters"));
+        args.push(OsString::from(format!("project={}", project_id)));
+        args.push(OsString::from(format!("repositoryId={}", repo_id)));
+        args.push(OsString::from(format!("pullRequestId={}", pr_id)));
+        args.push(OsString::from("--http-method"));
+        args.push(OsString::from("PATCH"));
+        args.push(OsString::from("--in-file"));
+        args.push(body_path.as_os_str().to_os_string());
+        args.push(OsString::from("--organization"));
+        args.push(OsString::from(organization_url));
+        args.push(OsString::from("--api-version"));
+        args.push(OsString::from("7.0"));
+        args.push(OsString::from("--output"));
+        args.push(OsString::from("json"));
+        args
+    }
+
     /// Parse PR URL to extract organization and PR ID.
     ///
     /// Only extracts the minimal info needed for `az repos pr show`.
@@ -589,6 +656,46 @@ mod tests {
         assert!(AzCli::parse_pr_url("https://dev.azure.com/myorg/myproject/_git/myrepo").is_none());
     }
 
+    #[test]
+    fn test_update_pr_target_args() {
+        assert_eq!(
+            AzCli::update_pr_target_body("main"),
+            serde_json::json!({ "targetRefName": "refs/heads/main" })
+        );
+        let args = AzCli::update_pr_target_args(
+            "https://dev.azure.com/myorg",
+            "proj-id",
+            "repo-id",
+            42,
+            Path::new("/tmp/body.json"),
+        );
+        assert_eq!(
+            args,
+            [
+                "devops",
+                "invoke",
+                "--area",
+                "git",
+                "--resource",
+                "pullRequests",
+                "--route-parameters",
+                "project=proj-id",
+                "repositoryId=repo-id",
+                "pullRequestId=42",
+                "--http-method",
+                "PATCH",
+                "--in-file",
+                "/tmp/body.json",
+                "--organization",
+                "https://dev.azure.com/myorg",
+                "--api-version",
+                "7.0",
+                "--output",
+                "json"
+            ]
+        );
+    }
+
     #[test]
     fn test_map_azure_status() {
         assert!(matches!(
@@ -246,6 +246,52 @@ impl GitHostProvider for AzureDevOpsProvider {
         .await
     }
 
+    async fn update_pr_base(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        pr_number: i64,
+        base_branch: &str,
+    ) -> Result<(), GitHostError> {
+        let repo_info = self.get_repo_info(repo_path, remote_url).await?;
+        let base = base_branch.to_string();
+
+        (|| async {
+            let cli = self.az_cli.clone();
+            let organization_url = repo_info.organization_url.clone();
+            let project_id = repo_info.project_id.clone();
+            let repo_id = repo_info.repo_id.clone();
+            let base = base.clone();
+
+            task::spawn_blocking(move || {
+                cli.update_pr_target(&organization_url, &project_id, &repo_id, pr_number, &base)
+            })
+            .await
+            .map_err(|err| {
+                GitHostError::PullRequest(format!(
+                    "Failed to execute Azure CLI for updating PR: {err}"
+                ))
+            })?
+            .map_err(GitHostError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHostError| e.should_retry())
+        .notify(|err: &GitHostError, dur: Duration| {
+            tracing::warn!(
+                "Azure DevOps API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
     fn provider_kind(&self) -> ProviderKind {
         ProviderKind::AzureDevOps
     }
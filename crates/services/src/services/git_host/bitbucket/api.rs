@@ -0,0 +1,351 @@
+//! Types and helpers for the Bitbucket Cloud REST API (`/2.0`).
+
+use chrono::{DateTime, Utc};
+use db::models::merge::{MergeStatus, PullRequestInfo};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::services::git_host::{gitlab::parse_remote_url, types::UnifiedPrComment};
+
+pub const API_BASE: &str = "https://api.bitbucket.org/2.0";
+
+/// Environment variables checked for an access token, in order. App passwords are not read
+/// from the environment; they are used through the credentials git stores for the host.
+pub const TOKEN_VARS: &[&str] = &["BITBUCKET_TOKEN"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitbucketRepoInfo {
+    pub host: String,
+    pub workspace: String,
+    pub repo_slug: String,
+}
+
+impl BitbucketRepoInfo {
+    /// Path of the repository relative to the API base.
+    pub fn api_path(&self) -> String {
+        format!("repositories/{}/{}", self.workspace, self.repo_slug)
+    }
+}
+
+/// A page of results; `next` is the absolute URL of the following page.
+#[derive(Deserialize)]
+pub struct Paginated<T> {
+    pub values: Vec<T>,
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BitbucketPullRequest {
+    id: i64,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    reviewers: Vec<BitbucketUser>,
+    #[serde(default)]
+    close_source_branch: bool,
+    state: String,
+    links: BitbucketLinks,
+    merge_commit: Option<BitbucketCommit>,
+    updated_on: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct BitbucketLinks {
+    html: Option<BitbucketLink>,
+}
+
+#[derive(Deserialize)]
+struct BitbucketLink {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct BitbucketCommit {
+    hash: String,
+}
+
+#[derive(Deserialize)]
+pub struct BitbucketComment {
+    id: i64,
+    content: BitbucketContent,
+    user: Option<BitbucketUser>,
+    created_on: DateTime<Utc>,
+    links: Option<BitbucketLinks>,
+    inline: Option<BitbucketInline>,
+    #[serde(default)]
+    deleted: bool,
+}
+
+#[derive(Deserialize)]
+struct BitbucketContent {
+    #[serde(default)]
+    raw: String,
+}
+
+#[derive(Deserialize)]
+struct BitbucketUser {
+    uuid: Option<String>,
+    display_name: Option<String>,
+    nickname: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BitbucketInline {
+    path: String,
+    /// Line in the new file
+    to: Option<i64>,
+    /// Line in the old file
+    from: Option<i64>,
+}
+
+impl BitbucketPullRequest {
+    /// Body of the PUT that moves the PR onto `base_branch`. Updates replace the PR, so the
+    /// fields it would otherwise drop are sent back as fetched.
+    pub fn retarget_body(&self, base_branch: &str) -> Value {
+        let reviewers: Vec<Value> = self
+            .reviewers
+            .iter()
+            .filter_map(|reviewer| reviewer.uuid.as_ref())
+            .map(|uuid| json!({ "uuid": uuid }))
+            .collect();
+        json!({
+            "title": self.title,
+            "description": self.description,
+            "reviewers": reviewers,
+            "close_source_branch": self.close_source_branch,
+            "destination": { "branch": { "name": base_branch } },
+        })
+    }
+}
+
+impl From<BitbucketPullRequest> for PullRequestInfo {
+    fn from(pr: BitbucketPullRequest) -> Self {
+        let status = map_bitbucket_state(&pr.state);
+        // Bitbucket has no merge timestamp; a merged PR is last updated by the merge
+        let merged_at = if matches!(status, MergeStatus::Merged) {
+            pr.updated_on
+        } else {
+            None
+        };
+        PullRequestInfo {
+            number: pr.id,
+            url: pr.links.html.map(|l| l.href).unwrap_or_default(),
+            status,
+            merged_at,
+            merge_commit_sha: pr.merge_commit.map(|c| c.hash),
+        }
+    }
+}
+
+/// Map Bitbucket PR state to MergeStatus
+fn map_bitbucket_state(state: &str) -> MergeStatus {
+    match state.to_uppercase().as_str() {
+        "OPEN" => MergeStatus::Open,
+        "MERGED" => MergeStatus::Merged,
+        "DECLINED" | "SUPERSEDED" => MergeStatus::Closed,
+        _ => MergeStatus::Unknown,
+    }
+}
+
+pub fn parse_repo_url(url: &str) -> Option<BitbucketRepoInfo> {
+    let location = parse_remote_url(url)?;
+    let (workspace, repo_slug) = location.path.split_once('/')?;
+    if repo_slug.contains('/') {
+        return None;
+    }
+    Some(BitbucketRepoInfo {
+        host: location.host,
+        workspace: workspace.to_string(),
+        repo_slug: repo_slug.to_string(),
+    })
+}
+
+/// Parse a PR URL into its repository and ID.
+///
+/// Format: `https://bitbucket.org/{workspace}/{repo}/pull-requests/{id}`
+pub fn parse_pr_url(url: &str) -> Option<(BitbucketRepoInfo, i64)> {
+    let (repo_url, rest) = url.split_once("/pull-requests/")?;
+    let id = rest
+        .split(['/', '?', '#'])
+        .next()
+        .and_then(|s| s.parse().ok())?;
+    Some((parse_repo_url(repo_url)?, id))
+}
+
+/// Query selecting PRs whose source branch is `branch`.
+pub fn source_branch_query(branch: &str) -> String {
+    format!(
+        "source.branch.name=\"{}\"",
+        branch.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// Convert PR comments to unified comments, oldest first.
+pub fn to_unified_comments(comments: Vec<BitbucketComment>) -> Vec<UnifiedPrComment> {
+    let mut unified: Vec<UnifiedPrComment> = comments
+        .into_iter()
+        .filter(|c| !c.deleted)
+        .map(|c| {
+            let author = c
+                .user
+                .and_then(|u| u.nickname.or(u.display_name))
+                .unwrap_or_else(|| "unknown".to_string());
+            let url = c.links.and_then(|l| l.html).map(|l| l.href);
+            match c.inline {
+                Some(inline) => {
+                    let (line, side) = match (inline.to, inline.from) {
+                        (Some(line), _) => (Some(line), Some("RIGHT".to_string())),
+                        (None, Some(line)) => (Some(line), Some("LEFT".to_string())),
+                        (None, None) => (None, None),
+                    };
+                    UnifiedPrComment::Review {
+                        id: c.id,
+                        author,
+                        author_association: None,
+                        body: c.content.raw,
+                        created_at: c.created_on,
+                        url,
+                        path: inline.path,
+                        line,
+                        side,
+                        diff_hunk: None,
+                    }
+                }
+                None => UnifiedPrComment::General {
+                    id: c.id.to_string(),
+                    author,
+                    author_association: None,
+                    body: c.content.raw,
+                    created_at: c.created_on,
+                    url,
+                },
+            }
+        })
+        .collect();
+
+    unified.sort_by_key(|c| c.created_at());
+    unified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repo_url() {
+        let info = parse_repo_url("git@bitbucket.org:workspace/repo.git").unwrap();
+        assert_eq!(info.api_path(), "repositories/workspace/repo");
+
+        let info = parse_repo_url("https://alice@bitbucket.org/workspace/repo.git").unwrap();
+        assert_eq!(info.host, "bitbucket.org");
+        assert_eq!(info.repo_slug, "repo");
+    }
+
+    #[test]
+    fn test_parse_pr_url() {
+        let (info, id) =
+            parse_pr_url("https://bitbucket.org/workspace/repo/pull-requests/8/overview").unwrap();
+        assert_eq!(info.workspace, "workspace");
+        assert_eq!(id, 8);
+
+        assert!(parse_pr_url("https://bitbucket.org/workspace/repo").is_none());
+    }
+
+    #[test]
+    fn test_source_branch_query() {
+        assert_eq!(
+            source_branch_query("vk/feature"),
+            r#"source.branch.name="vk/feature""#
+        );
+        assert_eq!(
+            source_branch_query(r#"a"b"#),
+            r#"source.branch.name="a\"b""#
+        );
+    }
+
+    #[test]
+    fn test_pull_request_status() {
+        let pr: BitbucketPullRequest = serde_json::from_str(
+            r#"{
+                "id": 8,
+                "state": "MERGED",
+                "links": {"html": {"href": "https://bitbucket.org/workspace/repo/pull-requests/8"}},
+                "merge_commit": {"hash": "abc123"},
+                "updated_on": "2026-01-10T12:00:00.123456+00:00"
+            }"#,
+        )
+        .unwrap();
+        let info = PullRequestInfo::from(pr);
+        assert!(matches!(info.status, MergeStatus::Merged));
+        assert!(info.merged_at.is_some());
+        assert_eq!(info.merge_commit_sha.as_deref(), Some("abc123"));
+
+        assert!(matches!(
+            map_bitbucket_state("DECLINED"),
+            MergeStatus::Closed
+        ));
+        assert!(matches!(map_bitbucket_state("OPEN"), MergeStatus::Open));
+    }
+
+    #[test]
+    fn test_retarget_body_keeps_fetched_fields() {
+        let pr: BitbucketPullRequest = serde_json::from_str(
+            r#"{
+                "id": 9,
+                "title": "Add search",
+                "description": "Stacked on #8",
+                "reviewers": [{"uuid": "{1111}", "display_name": "Bob"}],
+                "close_source_branch": true,
+                "state": "OPEN",
+                "links": {"html": null},
+                "destination": {"branch": {"name": "vk/parent"}}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            pr.retarget_body("main"),
+            json!({
+                "title": "Add search",
+                "description": "Stacked on #8",
+                "reviewers": [{"uuid": "{1111}"}],
+                "close_source_branch": true,
+                "destination": {"branch": {"name": "main"}},
+            })
+        );
+    }
+
+    #[test]
+    fn test_to_unified_comments() {
+        let comments: Vec<BitbucketComment> = serde_json::from_str(
+            r#"[
+                {"id": 2, "content": {"raw": "Fix this"}, "user": {"display_name": "Bob"},
+                 "created_on": "2026-01-10T12:01:00+00:00",
+                 "inline": {"path": "src/lib.rs", "to": 4, "from": null}},
+                {"id": 1, "content": {"raw": "Nice"}, "user": {"nickname": "alice"},
+                 "created_on": "2026-01-10T12:00:00+00:00"},
+                {"id": 3, "content": {"raw": ""}, "created_on": "2026-01-10T12:02:00+00:00",
+                 "deleted": true}
+            ]"#,
+        )
+        .unwrap();
+
+        let unified = to_unified_comments(comments);
+        assert_eq!(unified.len(), 2);
+        assert!(matches!(
+            &unified[0],
+            UnifiedPrComment::General { author, .. } if author == "alice"
+        ));
+        match &unified[1] {
+            UnifiedPrComment::Review {
+                path, line, side, ..
+            } => {
+                assert_eq!(path, "src/lib.rs");
+                assert_eq!(*line, Some(4));
+                assert_eq!(side.as_deref(), Some("RIGHT"));
+            }
+            other => panic!("expected review comment, got {other:?}"),
+        }
+    }
+}
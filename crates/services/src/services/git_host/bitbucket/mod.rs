@@ -0,0 +1,198 @@
+//! Bitbucket Cloud hosting service implementation.
+
+mod api;
+
+use std::path::Path;
+
+use api::{BitbucketComment, BitbucketPullRequest, BitbucketRepoInfo, Paginated};
+use async_trait::async_trait;
+use db::models::merge::PullRequestInfo;
+use reqwest::Client;
+use serde_json::json;
+use tracing::info;
+
+use super::{
+    GitHostProvider,
+    rest::{Credentials, RestClient},
+    types::{CreatePrRequest, GitHostError, ProviderKind, UnifiedPrComment},
+};
+
+/// Upper bound on pages followed for list endpoints.
+const MAX_PAGES: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct BitbucketProvider {
+    http: Client,
+    /// API endpoint overriding the one derived from remote URLs
+    api_base_url: Option<String>,
+}
+
+impl BitbucketProvider {
+    pub fn new(api_base_url: Option<String>) -> Result<Self, GitHostError> {
+        Ok(Self {
+            http: Client::new(),
+            api_base_url,
+        })
+    }
+
+    fn get_repo_info(url: &str) -> Result<BitbucketRepoInfo, GitHostError> {
+        api::parse_repo_url(url).ok_or_else(|| {
+            GitHostError::Repository(format!("Could not parse Bitbucket repository URL: {url}"))
+        })
+    }
+
+    async fn client(&self, repo: &BitbucketRepoInfo) -> RestClient {
+        let credentials = Credentials::resolve(&repo.host, api::TOKEN_VARS).await;
+        RestClient::new(
+            self.http.clone(),
+            self.api_base_url.as_deref().unwrap_or(api::API_BASE),
+            credentials,
+            ProviderKind::Bitbucket,
+        )
+    }
+
+    /// Fetch every page of a list endpoint, up to [`MAX_PAGES`].
+    async fn get_all<T>(
+        client: &RestClient,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>, GitHostError>
+    where
+        T: serde::de::DeserializeOwned + Send,
+    {
+        let mut page: Paginated<T> = client.get(path, query).await?;
+        let mut values = std::mem::take(&mut page.values);
+        for _ in 1..MAX_PAGES {
+            let Some(next) = page.next.take() else {
+                break;
+            };
+            // The next link already carries the query
+            page = client.get(&next, &[]).await?;
+            values.append(&mut page.values);
+        }
+        Ok(values)
+    }
+}
+
+#[async_trait]
+impl GitHostProvider for BitbucketProvider {
+    async fn create_pr(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        request: &CreatePrRequest,
+    ) -> Result<PullRequestInfo, GitHostError> {
+        let target = Self::get_repo_info(remote_url)?;
+
+        let mut source = json!({ "branch": { "name": request.head_branch } });
+        if let Some(head_url) = &request.head_repo_url {
+            let head_repo = Self::get_repo_info(head_url)?;
+            if head_repo != target {
+                source["repository"] = json!({
+                    "full_name": format!("{}/{}", head_repo.workspace, head_repo.repo_slug)
+                });
+            }
+        }
+
+        let client = self.client(&target).await;
+        let pr: BitbucketPullRequest = client
+            .post(
+                &format!("{}/pullrequests", target.api_path()),
+                &json!({
+                    "title": request.title,
+                    "description": request.body.as_deref().unwrap_or(""),
+                    "source": source,
+                    "destination": { "branch": { "name": request.base_branch } },
+                    "draft": request.draft.unwrap_or(false),
+                }),
+            )
+            .await?;
+        let pr_info = PullRequestInfo::from(pr);
+
+        info!(
+            "Created Bitbucket PR #{} for branch {}",
+            pr_info.number, request.head_branch
+        );
+
+        Ok(pr_info)
+    }
+
+    async fn get_pr_status(&self, pr_url: &str) -> Result<PullRequestInfo, GitHostError> {
+        let (repo, id) = api::parse_pr_url(pr_url).ok_or_else(|| {
+            GitHostError::PullRequest(format!("Could not parse Bitbucket PR URL: {pr_url}"))
+        })?;
+
+        let client = self.client(&repo).await;
+        let pr: BitbucketPullRequest = client
+            .get(&format!("{}/pullrequests/{id}", repo.api_path()), &[])
+            .await?;
+        Ok(pr.into())
+    }
+
+    async fn list_prs_for_branch(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        branch_name: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitHostError> {
+        let repo = Self::get_repo_info(remote_url)?;
+        let client = self.client(&repo).await;
+        let query = api::source_branch_query(branch_name);
+
+        // Without explicit states only open PRs are returned
+        let prs: Vec<BitbucketPullRequest> = Self::get_all(
+            &client,
+            &format!("{}/pullrequests", repo.api_path()),
+            &[
+                ("q", query.as_str()),
+                ("state", "OPEN"),
+                ("state", "MERGED"),
+                ("state", "DECLINED"),
+                ("state", "SUPERSEDED"),
+                ("pagelen", "50"),
+            ],
+        )
+        .await?;
+
+        Ok(prs.into_iter().map(PullRequestInfo::from).collect())
+    }
+
+    async fn get_pr_comments(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        pr_number: i64,
+    ) -> Result<Vec<UnifiedPrComment>, GitHostError> {
+        let repo = Self::get_repo_info(remote_url)?;
+        let client = self.client(&repo).await;
+
+        let comments: Vec<BitbucketComment> = Self::get_all(
+            &client,
+            &format!("{}/pullrequests/{pr_number}/comments", repo.api_path()),
+            &[("pagelen", "100")],
+        )
+        .await?;
+
+        Ok(api::to_unified_comments(comments))
+    }
+
+    async fn update_pr_base(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        pr_number: i64,
+        base_branch: &str,
+    ) -> Result<(), GitHostError> {
+        let repo = Self::get_repo_info(remote_url)?;
+        let client = self.client(&repo).await;
+        let path = format!("{}/pullrequests/{pr_number}", repo.api_path());
+
+        let pr: BitbucketPullRequest = client.get(&path, &[]).await?;
+        let _: BitbucketPullRequest = client.put(&path, &pr.retarget_body(base_branch)).await?;
+        Ok(())
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::Bitbucket
+    }
+}
@@ -1,6 +1,7 @@
 //! Git hosting provider detection from repository URLs.
 
-use super::types::ProviderKind;
+use super::{gitlab::parse_remote_url, types::ProviderKind};
+use crate::services::config::GitHostConfig;
 
 /// Detect the git hosting provider from a remote URL.
 ///
@@ -8,6 +9,11 @@ use super::types::ProviderKind;
 /// - GitHub.com: `https://github.com/owner/repo` or `git@github.com:owner/repo.git`
 /// - GitHub Enterprise: URLs containing `github.` (e.g., `https://github.company.com/owner/repo`)
 /// - Azure DevOps: `https://dev.azure.com/org/project/_git/repo` or legacy `https://org.visualstudio.com/...`
+/// - GitLab: `https://gitlab.com/group/repo` or self-hosted URLs containing `gitlab.`
+/// - Gitea/Forgejo: `https://codeberg.org/owner/repo` or URLs containing `gitea.` or `forgejo.`
+/// - Bitbucket Cloud: `https://bitbucket.org/workspace/repo`
+///
+/// Other custom domains are ambiguous and need the provider selected on the repo.
 pub fn detect_provider_from_url(url: &str) -> ProviderKind {
     let url_lower = url.to_lowercase();
 
@@ -33,15 +39,63 @@ pub fn detect_provider_from_url(url: &str) -> ProviderKind {
         return ProviderKind::GitHub;
     }
 
+    // /-/merge_requests/ is unique to GitLab MR URLs, including custom domains
+    if url_lower.contains("gitlab.") || url_lower.contains("/-/merge_requests/") {
+        return ProviderKind::GitLab;
+    }
+
+    if url_lower.contains("codeberg.org")
+        || url_lower.contains("gitea.")
+        || url_lower.contains("forgejo.")
+    {
+        return ProviderKind::Gitea;
+    }
+
+    if url_lower.contains("bitbucket.org") {
+        return ProviderKind::Bitbucket;
+    }
+
     ProviderKind::Unknown
 }
 
+/// Find the configured host serving a remote or PR URL.
+pub fn find_host_config<'a>(url: &str, hosts: &'a [GitHostConfig]) -> Option<&'a GitHostConfig> {
+    let location = parse_remote_url(url)?;
+    hosts
+        .iter()
+        .find(|config| config.host.trim().eq_ignore_ascii_case(&location.host))
+}
+
+/// Provider serving `url` and the configured host it is on. The provider `selected` on the repo
+/// takes precedence over the one the host is mapped to, which takes precedence over detection
+/// from the URL.
+pub fn resolve_provider<'a>(
+    selected: Option<ProviderKind>,
+    url: &str,
+    hosts: &'a [GitHostConfig],
+) -> (ProviderKind, Option<&'a GitHostConfig>) {
+    let host = find_host_config(url, hosts);
+    let provider = selected
+        .or(host.map(|h| h.provider))
+        .unwrap_or_else(|| detect_provider_from_url(url));
+    (provider, host)
+}
+
+/// Host of an API base URL such as `https://github.corp.example/api/v3`.
+pub fn url_host(url: &str) -> Option<&str> {
+    let rest = url.trim().split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split('/').next()?;
+    let host = authority.rsplit('@').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
 /// Detect the git hosting provider from a PR URL.
 ///
 /// Supports:
 /// - GitHub: `https://github.com/owner/repo/pull/123`
 /// - GitHub Enterprise: `https://github.company.com/owner/repo/pull/123`
 /// - Azure DevOps: `https://dev.azure.com/org/project/_git/repo/pullrequest/123`
+/// - GitLab: `https://gitlab.com/group/repo/-/merge_requests/123`
 #[cfg(test)]
 fn detect_provider_from_pr_url(pr_url: &str) -> ProviderKind {
     let url_lower = pr_url.to_lowercase();
@@ -137,17 +191,126 @@ mod tests {
     }
 
     #[test]
-    fn test_unknown_provider() {
+    fn test_gitlab() {
         assert_eq!(
             detect_provider_from_url("https://gitlab.com/owner/repo"),
-            ProviderKind::Unknown
+            ProviderKind::GitLab
+        );
+        assert_eq!(
+            detect_provider_from_url("git@gitlab.company.com:group/sub/repo.git"),
+            ProviderKind::GitLab
         );
+    }
+
+    #[test]
+    fn test_gitea() {
+        assert_eq!(
+            detect_provider_from_url("https://codeberg.org/owner/repo.git"),
+            ProviderKind::Gitea
+        );
+        assert_eq!(
+            detect_provider_from_url("git@forgejo.company.com:owner/repo.git"),
+            ProviderKind::Gitea
+        );
+    }
+
+    #[test]
+    fn test_bitbucket() {
+        assert_eq!(
+            detect_provider_from_url("https://bitbucket.org/workspace/repo"),
+            ProviderKind::Bitbucket
+        );
+        assert_eq!(
+            detect_provider_from_url("git@bitbucket.org:workspace/repo.git"),
+            ProviderKind::Bitbucket
+        );
+    }
+
+    #[test]
+    fn test_unknown_provider() {
         assert_eq!(
-            detect_provider_from_url("https://bitbucket.org/owner/repo"),
+            detect_provider_from_url("https://git.company.com/owner/repo"),
             ProviderKind::Unknown
         );
     }
 
+    #[test]
+    fn test_find_host_config() {
+        let hosts = vec![GitHostConfig {
+            host: "github.corp.example".to_string(),
+            provider: ProviderKind::GitHub,
+            api_base_url: None,
+        }];
+        let found = |url: &str| find_host_config(url, &hosts).map(|h| h.provider);
+
+        assert_eq!(
+            found("git@GitHub.corp.example:team/repo.git"),
+            Some(ProviderKind::GitHub)
+        );
+        assert_eq!(
+            found("https://github.corp.example/team/repo/pull/7"),
+            Some(ProviderKind::GitHub)
+        );
+        assert_eq!(found("https://git.corp.example/team/repo"), None);
+    }
+
+    #[test]
+    fn test_resolve_provider_precedence() {
+        let hosts = vec![GitHostConfig {
+            host: "git.corp.example".to_string(),
+            provider: ProviderKind::Gitea,
+            api_base_url: Some("https://git.corp.example/gitea/api/v1".to_string()),
+        }];
+        let mapped = "git@git.corp.example:team/repo.git";
+        let resolved = |selected, url| {
+            let (provider, host) = resolve_provider(selected, url, &hosts);
+            (provider, host.map(|h| h.host.as_str()))
+        };
+
+        // The provider selected on the repo wins, still using the configured host
+        assert_eq!(
+            resolved(Some(ProviderKind::GitLab), mapped),
+            (ProviderKind::GitLab, Some("git.corp.example"))
+        );
+        // Then the provider the host is mapped to, even where the URL says otherwise
+        assert_eq!(
+            resolved(None, mapped),
+            (ProviderKind::Gitea, Some("git.corp.example"))
+        );
+        assert_eq!(
+            resolve_provider(
+                None,
+                "https://gitlab.corp.example/team/repo",
+                &[GitHostConfig {
+                    host: "gitlab.corp.example".to_string(),
+                    provider: ProviderKind::GitHub,
+                    api_base_url: None,
+                }],
+            )
+            .0,
+            ProviderKind::GitHub
+        );
+        // Then detection from the URL
+        assert_eq!(
+            resolved(None, "https://gitlab.com/team/repo"),
+            (ProviderKind::GitLab, None)
+        );
+        assert_eq!(
+            resolved(None, "https://git.other.example/team/repo"),
+            (ProviderKind::Unknown, None)
+        );
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://github.corp.example/api/v3"),
+            Some("github.corp.example")
+        );
+        assert_eq!(url_host("gitlab.corp.example"), Some("gitlab.corp.example"));
+        assert_eq!(url_host("https://"), None);
+    }
+
     #[test]
     fn test_pr_url_github() {
         assert_eq!(
@@ -175,4 +338,16 @@ mod tests {
             ProviderKind::AzureDevOps
         );
     }
+
+    #[test]
+    fn test_pr_url_gitlab() {
+        assert_eq!(
+            detect_provider_from_pr_url("https://gitlab.com/group/repo/-/merge_requests/12"),
+            ProviderKind::GitLab
+        );
+        assert_eq!(
+            detect_provider_from_pr_url("https://git.company.com/group/repo/-/merge_requests/34"),
+            ProviderKind::GitLab
+        );
+    }
 }
@@ -0,0 +1,346 @@
+//! Types and helpers for the Gitea/Forgejo REST API (`/api/v1`).
+//!
+//! Forgejo is a Gitea fork and keeps its API compatible, so both are served by
+//! this module.
+
+use chrono::{DateTime, Utc};
+use db::models::merge::{MergeStatus, PullRequestInfo};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::services::git_host::{gitlab::parse_remote_url, types::UnifiedPrComment};
+
+/// Environment variables checked for an access token, in order.
+pub const TOKEN_VARS: &[&str] = &["GITEA_TOKEN", "FORGEJO_TOKEN"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GiteaRepoInfo {
+    /// Web root of the instance, including any sub-path it is served under.
+    pub base_url: String,
+    pub host: String,
+    pub owner: String,
+    pub repo: String,
+}
+
+impl GiteaRepoInfo {
+    pub fn api_base(&self) -> String {
+        format!("{}/api/v1", self.base_url)
+    }
+
+    /// Path of the repository relative to the API base.
+    pub fn api_path(&self) -> String {
+        format!("repos/{}/{}", self.owner, self.repo)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GiteaPullRequest {
+    number: i64,
+    html_url: String,
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    merged_at: Option<DateTime<Utc>>,
+    merge_commit_sha: Option<String>,
+    head: Option<GiteaBranch>,
+}
+
+#[derive(Deserialize)]
+struct GiteaBranch {
+    #[serde(rename = "ref")]
+    ref_name: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+pub struct GiteaComment {
+    id: i64,
+    body: String,
+    user: Option<GiteaUser>,
+    created_at: DateTime<Utc>,
+    html_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct GiteaReview {
+    pub id: i64,
+    #[serde(default)]
+    body: String,
+    user: Option<GiteaUser>,
+    submitted_at: Option<DateTime<Utc>>,
+    html_url: Option<String>,
+    #[serde(default)]
+    pub comments_count: i64,
+}
+
+#[derive(Deserialize)]
+pub struct GiteaReviewComment {
+    id: i64,
+    body: String,
+    user: Option<GiteaUser>,
+    created_at: DateTime<Utc>,
+    html_url: Option<String>,
+    path: String,
+    /// Line in the new file, `0` when the comment is on a removed line
+    #[serde(default)]
+    position: i64,
+    /// Line in the old file, `0` when the comment is on an added line
+    #[serde(default)]
+    original_position: i64,
+    diff_hunk: Option<String>,
+}
+
+/// Items of list endpoints, told apart by their ID when paging through them.
+pub trait Identified {
+    fn id(&self) -> i64;
+}
+
+impl Identified for GiteaComment {
+    fn id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl Identified for GiteaReview {
+    fn id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl Identified for GiteaReviewComment {
+    fn id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl GiteaPullRequest {
+    pub fn head_ref(&self) -> Option<&str> {
+        self.head.as_ref().map(|head| head.ref_name.as_str())
+    }
+}
+
+impl From<GiteaPullRequest> for PullRequestInfo {
+    fn from(pr: GiteaPullRequest) -> Self {
+        let status = if pr.merged {
+            MergeStatus::Merged
+        } else {
+            map_gitea_state(&pr.state)
+        };
+        PullRequestInfo {
+            number: pr.number,
+            url: pr.html_url,
+            status,
+            merged_at: pr.merged_at,
+            merge_commit_sha: pr.merge_commit_sha,
+        }
+    }
+}
+
+/// Map Gitea PR state to MergeStatus. Merged PRs are reported as `closed`
+/// with `merged` set, which callers check first.
+fn map_gitea_state(state: &str) -> MergeStatus {
+    match state.to_lowercase().as_str() {
+        "open" => MergeStatus::Open,
+        "closed" => MergeStatus::Closed,
+        _ => MergeStatus::Unknown,
+    }
+}
+
+fn login(user: Option<GiteaUser>) -> String {
+    user.map(|u| u.login)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Parse a remote URL, treating the last two path segments as owner and repo
+/// and anything before them as the sub-path the instance is served under.
+pub fn parse_repo_url(url: &str) -> Option<GiteaRepoInfo> {
+    let location = parse_remote_url(url)?;
+    let scheme = if url.trim().starts_with("http://") {
+        "http"
+    } else {
+        "https"
+    };
+
+    let mut segments: Vec<&str> = location.path.split('/').collect();
+    let repo = segments.pop()?.to_string();
+    let owner = segments.pop()?.to_string();
+    let base_url = if segments.is_empty() {
+        format!("{scheme}://{}", location.host)
+    } else {
+        format!("{scheme}://{}/{}", location.host, segments.join("/"))
+    };
+
+    Some(GiteaRepoInfo {
+        base_url,
+        host: location.host,
+        owner,
+        repo,
+    })
+}
+
+/// Parse a PR URL into its repository and number.
+///
+/// Format: `https://{host}/{owner}/{repo}/pulls/{number}`
+pub fn parse_pr_url(url: &str) -> Option<(GiteaRepoInfo, i64)> {
+    let (repo_url, rest) = url.split_once("/pulls/")?;
+    let number = rest
+        .split(['/', '?', '#'])
+        .next()
+        .and_then(|s| s.parse().ok())?;
+    Some((parse_repo_url(repo_url)?, number))
+}
+
+/// Body of the PATCH that moves a PR onto `base_branch`; edits leave other fields as they are.
+pub fn retarget_body(base_branch: &str) -> Value {
+    json!({ "base": base_branch })
+}
+
+/// Merge issue comments and review comments into unified comments, oldest first.
+pub fn to_unified_comments(
+    comments: Vec<GiteaComment>,
+    reviews: Vec<(GiteaReview, Vec<GiteaReviewComment>)>,
+) -> Vec<UnifiedPrComment> {
+    let mut unified: Vec<UnifiedPrComment> = comments
+        .into_iter()
+        .map(|c| UnifiedPrComment::General {
+            id: c.id.to_string(),
+            author: login(c.user),
+            author_association: None,
+            body: c.body,
+            created_at: c.created_at,
+            url: c.html_url,
+        })
+        .collect();
+
+    for (review, review_comments) in reviews {
+        // The review summary is a general comment when the reviewer wrote one
+        if !review.body.trim().is_empty() {
+            unified.push(UnifiedPrComment::General {
+                id: format!("review-{}", review.id),
+                author: login(review.user),
+                author_association: None,
+                body: review.body,
+                created_at: review.submitted_at.unwrap_or_else(Utc::now),
+                url: review.html_url,
+            });
+        }
+
+        unified.extend(review_comments.into_iter().map(|c| {
+            let (line, side) = match (c.position, c.original_position) {
+                (new, _) if new > 0 => (Some(new), Some("RIGHT".to_string())),
+                (_, old) if old > 0 => (Some(old), Some("LEFT".to_string())),
+                _ => (None, None),
+            };
+            UnifiedPrComment::Review {
+                id: c.id,
+                author: login(c.user),
+                author_association: None,
+                body: c.body,
+                created_at: c.created_at,
+                url: c.html_url,
+                path: c.path,
+                line,
+                side,
+                diff_hunk: c.diff_hunk,
+            }
+        }));
+    }
+
+    unified.sort_by_key(|c| c.created_at());
+    unified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repo_url() {
+        let info = parse_repo_url("https://codeberg.org/owner/repo.git").unwrap();
+        assert_eq!(info.base_url, "https://codeberg.org");
+        assert_eq!(info.api_base(), "https://codeberg.org/api/v1");
+        assert_eq!(info.api_path(), "repos/owner/repo");
+
+        let info = parse_repo_url("http://git.lan/forgejo/owner/repo").unwrap();
+        assert_eq!(info.base_url, "http://git.lan/forgejo");
+        assert_eq!(info.owner, "owner");
+
+        let info = parse_repo_url("git@git.company.com:owner/repo.git").unwrap();
+        assert_eq!(info.base_url, "https://git.company.com");
+        assert_eq!(info.host, "git.company.com");
+    }
+
+    #[test]
+    fn test_retarget_body() {
+        assert_eq!(retarget_body("main"), json!({ "base": "main" }));
+    }
+
+    #[test]
+    fn test_parse_pr_url() {
+        let (info, number) = parse_pr_url("https://codeberg.org/owner/repo/pulls/17").unwrap();
+        assert_eq!(info.repo, "repo");
+        assert_eq!(number, 17);
+
+        assert!(parse_pr_url("https://github.com/owner/repo/pull/17").is_none());
+    }
+
+    #[test]
+    fn test_pull_request_status() {
+        let pr: GiteaPullRequest = serde_json::from_str(
+            r#"{
+                "number": 3,
+                "html_url": "https://codeberg.org/owner/repo/pulls/3",
+                "state": "closed",
+                "merged": true,
+                "merged_at": "2026-01-10T12:00:00Z",
+                "merge_commit_sha": "abc123",
+                "head": {"ref": "vk/feature"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(pr.head_ref(), Some("vk/feature"));
+        let info = PullRequestInfo::from(pr);
+        assert!(matches!(info.status, MergeStatus::Merged));
+        assert_eq!(info.merge_commit_sha.as_deref(), Some("abc123"));
+
+        assert!(matches!(map_gitea_state("open"), MergeStatus::Open));
+        assert!(matches!(map_gitea_state("closed"), MergeStatus::Closed));
+    }
+
+    #[test]
+    fn test_to_unified_comments() {
+        let comments: Vec<GiteaComment> = serde_json::from_str(
+            r#"[{"id": 1, "body": "Thanks", "user": {"login": "alice"},
+                 "created_at": "2026-01-10T12:00:00Z", "html_url": null}]"#,
+        )
+        .unwrap();
+        let review: GiteaReview = serde_json::from_str(
+            r#"{"id": 9, "body": "", "user": {"login": "bob"},
+                "submitted_at": "2026-01-10T12:05:00Z", "comments_count": 1}"#,
+        )
+        .unwrap();
+        let review_comments: Vec<GiteaReviewComment> = serde_json::from_str(
+            r#"[{"id": 2, "body": "Nit", "user": {"login": "bob"},
+                 "created_at": "2026-01-10T12:04:00Z", "path": "src/main.rs",
+                 "position": 0, "original_position": 7, "diff_hunk": "@@ -7 +6 @@"}]"#,
+        )
+        .unwrap();
+
+        let unified = to_unified_comments(comments, vec![(review, review_comments)]);
+        assert_eq!(unified.len(), 2);
+        match &unified[1] {
+            UnifiedPrComment::Review {
+                path, line, side, ..
+            } => {
+                assert_eq!(path, "src/main.rs");
+                assert_eq!(*line, Some(7));
+                assert_eq!(side.as_deref(), Some("LEFT"));
+            }
+            other => panic!("expected review comment, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Gitea and Forgejo hosting service implementation.
+
+mod api;
+
+use std::{collections::HashSet, path::Path};
+
+use api::{
+    GiteaComment, GiteaPullRequest, GiteaRepoInfo, GiteaReview, GiteaReviewComment, Identified,
+};
+use async_trait::async_trait;
+use db::models::merge::PullRequestInfo;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use tracing::info;
+
+use super::{
+    GitHostProvider,
+    rest::{Credentials, RestClient},
+    types::{CreatePrRequest, GitHostError, ProviderKind, UnifiedPrComment},
+};
+
+/// Page size for list endpoints, the default maximum of Gitea instances.
+const PAGE_LIMIT: usize = 50;
+/// Upper bound on pages scanned when looking up PRs for a branch.
+const MAX_PAGES: usize = 10;
+/// Upper bound on pages of comments and reviews fetched for a PR.
+const MAX_COMMENT_PAGES: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct GiteaProvider {
+    http: Client,
+    /// API endpoint overriding the one derived from remote URLs
+    api_base_url: Option<String>,
+}
+
+impl GiteaProvider {
+    pub fn new(api_base_url: Option<String>) -> Result<Self, GitHostError> {
+        Ok(Self {
+            http: Client::new(),
+            api_base_url,
+        })
+    }
+
+    /// Fetch every page of a list endpoint. Instances whose endpoint ignores `page` return the
+    /// same items again, which ends the scan.
+    async fn get_all<T>(client: &RestClient, path: &str) -> Result<Vec<T>, GitHostError>
+    where
+        T: DeserializeOwned + Identified + Send,
+    {
+        let limit = PAGE_LIMIT.to_string();
+        let mut seen = HashSet::new();
+        let mut items = Vec::new();
+        for page in 1..=MAX_COMMENT_PAGES {
+            let page_str = page.to_string();
+            let batch: Vec<T> = client
+                .get(
+                    path,
+                    &[("limit", limit.as_str()), ("page", page_str.as_str())],
+                )
+                .await?;
+            let full = batch.len() >= PAGE_LIMIT;
+            let before = items.len();
+            items.extend(batch.into_iter().filter(|item| seen.insert(item.id())));
+            if !full || items.len() == before {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn get_repo_info(url: &str) -> Result<GiteaRepoInfo, GitHostError> {
+        api::parse_repo_url(url).ok_or_else(|| {
+            GitHostError::Repository(format!("Could not parse Gitea repository URL: {url}"))
+        })
+    }
+
+    async fn client(&self, repo: &GiteaRepoInfo) -> RestClient {
+        let credentials = Credentials::resolve(&repo.host, api::TOKEN_VARS).await;
+        RestClient::new(
+            self.http.clone(),
+            self.api_base_url.clone().unwrap_or_else(|| repo.api_base()),
+            credentials,
+            ProviderKind::Gitea,
+        )
+    }
+}
+
+#[async_trait]
+impl GitHostProvider for GiteaProvider {
+    async fn create_pr(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        request: &CreatePrRequest,
+    ) -> Result<PullRequestInfo, GitHostError> {
+        let target = Self::get_repo_info(remote_url)?;
+
+        // Cross-fork PRs name the head branch as "owner:branch"
+        let head = match &request.head_repo_url {
+            Some(head_url) => {
+                let head_repo = Self::get_repo_info(head_url)?;
+                if head_repo.owner != target.owner {
+                    format!("{}:{}", head_repo.owner, request.head_branch)
+                } else {
+                    request.head_branch.clone()
+                }
+            }
+            None => request.head_branch.clone(),
+        };
+        // Gitea marks PRs as work in progress by title prefix
+        let title = if request.draft.unwrap_or(false) {
+            format!("WIP: {}", request.title)
+        } else {
+            request.title.clone()
+        };
+
+        let client = self.client(&target).await;
+        let pr: GiteaPullRequest = client
+            .post(
+                &format!("{}/pulls", target.api_path()),
+                &json!({
+                    "head": head,
+                    "base": request.base_branch,
+                    "title": title,
+                    "body": request.body.as_deref().unwrap_or(""),
+                }),
+            )
+            .await?;
+        let pr_info = PullRequestInfo::from(pr);
+
+        info!(
+            "Created Gitea PR #{} for branch {}",
+            pr_info.number, request.head_branch
+        );
+
+        Ok(pr_info)
+    }
+
+    async fn get_pr_status(&self, pr_url: &str) -> Result<PullRequestInfo, GitHostError> {
+        let (repo, number) = api::parse_pr_url(pr_url).ok_or_else(|| {
+            GitHostError::PullRequest(format!("Could not parse Gitea PR URL: {pr_url}"))
+        })?;
+
+        let client = self.client(&repo).await;
+        let pr: GiteaPullRequest = client
+            .get(&format!("{}/pulls/{number}", repo.api_path()), &[])
+            .await?;
+        Ok(pr.into())
+    }
+
+    async fn list_prs_for_branch(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        branch_name: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitHostError> {
+        let repo = Self::get_repo_info(remote_url)?;
+        let client = self.client(&repo).await;
+        let limit = PAGE_LIMIT.to_string();
+
+        // The list endpoint cannot filter by head branch, so scan the most
+        // recently updated PRs instead.
+        let mut prs = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let page_str = page.to_string();
+            let batch: Vec<GiteaPullRequest> = client
+                .get(
+                    &format!("{}/pulls", repo.api_path()),
+                    &[
+                        ("state", "all"),
+                        ("sort", "recentupdate"),
+                        ("limit", limit.as_str()),
+                        ("page", page_str.as_str()),
+                    ],
+                )
+                .await?;
+            let done = batch.len() < PAGE_LIMIT;
+            prs.extend(
+                batch
+                    .into_iter()
+                    .filter(|pr| pr.head_ref() == Some(branch_name))
+                    .map(PullRequestInfo::from),
+            );
+            if done {
+                break;
+            }
+        }
+
+        Ok(prs)
+    }
+
+    async fn get_pr_comments(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        pr_number: i64,
+    ) -> Result<Vec<UnifiedPrComment>, GitHostError> {
+        let repo = Self::get_repo_info(remote_url)?;
+        let client = self.client(&repo).await;
+
+        let comments: Vec<GiteaComment> = Self::get_all(
+            &client,
+            &format!("{}/issues/{pr_number}/comments", repo.api_path()),
+        )
+        .await?;
+        let reviews: Vec<GiteaReview> = Self::get_all(
+            &client,
+            &format!("{}/pulls/{pr_number}/reviews", repo.api_path()),
+        )
+        .await?;
+
+        let mut reviews_with_comments = Vec::with_capacity(reviews.len());
+        for review in reviews {
+            let review_comments: Vec<GiteaReviewComment> = if review.comments_count > 0 {
+                Self::get_all(
+                    &client,
+                    &format!(
+                        "{}/pulls/{pr_number}/reviews/{}/comments",
+                        repo.api_path(),
+                        review.id
+                    ),
+                )
+                .await?
+            } else {
+                Vec::new()
+            };
+            reviews_with_comments.push((review, review_comments));
+        }
+
+        Ok(api::to_unified_comments(comments, reviews_with_comments))
+    }
+
+    async fn update_pr_base(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        pr_number: i64,
+        base_branch: &str,
+    ) -> Result<(), GitHostError> {
+        let repo = Self::get_repo_info(remote_url)?;
+        let client = self.client(&repo).await;
+
+        let _: GiteaPullRequest = client
+            .patch(
+                &format!("{}/pulls/{pr_number}", repo.api_path()),
+                &api::retarget_body(base_branch),
+            )
+            .await?;
+        Ok(())
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::Gitea
+    }
+}
@@ -17,14 +17,27 @@ use tempfile::NamedTempFile;
 use thiserror::Error;
 use utils::shell::resolve_executable_path_blocking;
 
-use crate::services::git_host::types::{
-    CreatePrRequest, PrComment, PrCommentAuthor, PrReviewComment, ReviewCommentUser,
+use crate::services::git_host::{
+    gitlab::parse_remote_url,
+    types::{CreatePrRequest, PrComment, PrCommentAuthor, PrReviewComment, ReviewCommentUser},
 };
 
 #[derive(Debug, Clone)]
 pub struct GitHubRepoInfo {
     pub owner: String,
     pub repo_name: String,
+    /// GitHub Enterprise host, `None` for github.com
+    pub host: Option<String>,
+}
+
+impl GitHubRepoInfo {
+    /// Repository in the `[HOST/]OWNER/REPO` form accepted by `--repo`.
+    fn repo_arg(&self) -> String {
+        match &self.host {
+            Some(host) => format!("{host}/{}/{}", self.owner, self.repo_name),
+            None => format!("{}/{}", self.owner, self.repo_name),
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -110,11 +123,14 @@ pub enum GhCliError {
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct GhCli;
+pub struct GhCli {
+    /// Host passed to `gh`, overriding the one in remote URLs
+    hostname: Option<String>,
+}
 
 impl GhCli {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(hostname: Option<String>) -> Self {
+        Self { hostname }
     }
 
     /// Ensure the GitHub CLI binary is discoverable.
@@ -171,14 +187,28 @@ impl GhCli {
         remote_url: &str,
         repo_path: &Path,
     ) -> Result<GitHubRepoInfo, GhCliError> {
+        let location = parse_remote_url(remote_url);
+        let host = self
+            .hostname
+            .clone()
+            .or_else(|| location.as_ref().map(|l| l.host.clone()))
+            .filter(|host| !host.eq_ignore_ascii_case("github.com"));
+        // Remotes may use an SSH alias, so name the repo on the configured host instead
+        let repo = match (&self.hostname, &location) {
+            (Some(hostname), Some(location)) => format!("{hostname}/{}", location.path),
+            _ => remote_url.to_string(),
+        };
         let raw = self.run(
-            ["repo", "view", remote_url, "--json", "owner,name"],
+            ["repo", "view", repo.as_str(), "--json", "owner,name"],
             Some(repo_path),
         )?;
-        Self::parse_repo_info_response(&raw)
+        Self::parse_repo_info_response(&raw, host)
     }
 
-    fn parse_repo_info_response(raw: &str) -> Result<GitHubRepoInfo, GhCliError> {
+    fn parse_repo_info_response(
+        raw: &str,
+        host: Option<String>,
+    ) -> Result<GitHubRepoInfo, GhCliError> {
         let resp: GhRepoViewResponse = serde_json::from_str(raw).map_err(|e| {
             GhCliError::UnexpectedOutput(format!("Failed to parse gh repo view response: {e}"))
         })?;
@@ -186,6 +216,7 @@ impl GhCli {
         Ok(GitHubRepoInfo {
             owner: resp.owner.login,
             repo_name: resp.name,
+            host,
         })
     }
 
@@ -197,8 +228,7 @@ impl GhCli {
     pub fn create_pr(
         &self,
         request: &CreatePrRequest,
-        owner: &str,
-        repo_name: &str,
+        repo: &GitHubRepoInfo,
         repo_path: &Path,
     ) -> Result<PullRequestInfo, GhCliError> {
         // Write body to temp file to avoid shell escaping and length issues
@@ -213,7 +243,7 @@ impl GhCli {
         args.push(OsString::from("pr"));
         args.push(OsString::from("create"));
         args.push(OsString::from("--repo"));
-        args.push(OsString::from(format!("{}/{}", owner, repo_name)));
+        args.push(OsString::from(repo.repo_arg()));
         args.push(OsString::from("--head"));
         args.push(OsString::from(&request.head_branch));
         args.push(OsString::from("--base"));
@@ -249,8 +279,7 @@ impl GhCli {
     /// List pull requests for a branch (includes closed/merged).
     pub fn list_prs_for_branch(
         &self,
-        owner: &str,
-        repo: &str,
+        repo: &GitHubRepoInfo,
         branch: &str,
     ) -> Result<Vec<PullRequestInfo>, GhCliError> {
         let raw = self.run(
@@ -258,7 +287,7 @@ impl GhCli {
                 "pr",
                 "list",
                 "--repo",
-                &format!("{owner}/{repo}"),
+                &repo.repo_arg(),
                 "--state",
                 "all",
                 "--head",
@@ -271,11 +300,21 @@ impl GhCli {
         Self::parse_pr_list(&raw)
     }
 
+    /// Change the base branch of a pull request.
+    pub fn edit_pr_base(
+        &self,
+        repo: &GitHubRepoInfo,
+        pr_number: i64,
+        base_branch: &str,
+    ) -> Result<(), GhCliError> {
+        self.run(Self::edit_pr_base_args(repo, pr_number, base_branch), None)?;
+        Ok(())
+    }
+
     /// Fetch comments for a pull request.
     pub fn get_pr_comments(
         &self,
-        owner: &str,
-        repo: &str,
+        repo: &GitHubRepoInfo,
         pr_number: i64,
     ) -> Result<Vec<PrComment>, GhCliError> {
         let raw = self.run(
@@ -284,7 +323,7 @@ impl GhCli {
                 "view",
                 &pr_number.to_string(),
                 "--repo",
-                &format!("{owner}/{repo}"),
+                &repo.repo_arg(),
                 "--json",
                 "comments",
             ],
@@ -296,22 +335,43 @@ impl GhCli {
     /// Fetch inline review comments for a pull request via API.
     pub fn get_pr_review_comments(
         &self,
-        owner: &str,
-        repo: &str,
+        repo: &GitHubRepoInfo,
         pr_number: i64,
     ) -> Result<Vec<PrReviewComment>, GhCliError> {
-        let raw = self.run(
-            [
-                "api",
-                &format!("repos/{owner}/{repo}/pulls/{pr_number}/comments"),
-            ],
-            None,
-        )?;
+        let raw = self.run(Self::review_comments_args(repo, pr_number), None)?;
         Self::parse_pr_review_comments(&raw)
     }
 }
 
 impl GhCli {
+    /// Arguments of `gh pr edit` that change the base branch of a PR.
+    fn edit_pr_base_args(repo: &GitHubRepoInfo, pr_number: i64, base_branch: &str) -> Vec<String> {
+        vec![
+            "pr".to_string(),
+            "edit".to_string(),
+            pr_number.to_string(),
+            "--repo".to_string(),
+            repo.repo_arg(),
+            "--base".to_string(),
+            base_branch.to_string(),
+        ]
+    }
+
+    /// Arguments of `gh api` that list the review comments of a PR, on the repo's host.
+    fn review_comments_args(repo: &GitHubRepoInfo, pr_number: i64) -> Vec<String> {
+        let mut args = vec![
+            "api".to_string(),
+            format!(
+                "repos/{}/{}/pulls/{pr_number}/comments",
+                repo.owner, repo.repo_name
+            ),
+        ];
+        if let Some(host) = &repo.host {
+            args.extend(["--hostname".to_string(), host.clone()]);
+        }
+        args
+    }
+
     fn parse_pr_create_text(raw: &str) -> Result<PullRequestInfo, GhCliError> {
         let pr_url = raw
             .lines()
@@ -446,3 +506,58 @@ impl GhCli {
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_info(host: Option<&str>) -> GitHubRepoInfo {
+        GitHubRepoInfo {
+            owner: "team".to_string(),
+            repo_name: "app".to_string(),
+            host: host.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_repo_arg() {
+        assert_eq!(repo_info(None).repo_arg(), "team/app");
+        assert_eq!(
+            repo_info(Some("github.corp.example")).repo_arg(),
+            "github.corp.example/team/app"
+        );
+    }
+
+    #[test]
+    fn test_edit_pr_base_args() {
+        assert_eq!(
+            GhCli::edit_pr_base_args(&repo_info(Some("github.corp.example")), 12, "main"),
+            [
+                "pr",
+                "edit",
+                "12",
+                "--repo",
+                "github.corp.example/team/app",
+                "--base",
+                "main"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_review_comments_pass_hostname() {
+        assert_eq!(
+            GhCli::review_comments_args(&repo_info(None), 7),
+            ["api", "repos/team/app/pulls/7/comments"]
+        );
+        assert_eq!(
+            GhCli::review_comments_args(&repo_info(Some("github.corp.example")), 7),
+            [
+                "api",
+                "repos/team/app/pulls/7/comments",
+                "--hostname",
+                "github.corp.example"
+            ]
+        );
+    }
+}
@@ -23,9 +23,11 @@ pub struct GitHubProvider {
 }
 
 impl GitHubProvider {
-    pub fn new() -> Result<Self, GitHostError> {
+    /// `hostname` is the GitHub Enterprise host to run `gh` against; by default the host
+    /// of each remote URL is used.
+    pub fn new(hostname: Option<String>) -> Result<Self, GitHostError> {
         Ok(Self {
-            gh_cli: GhCli::new(),
+            gh_cli: GhCli::new(hostname),
         })
     }
 
@@ -48,27 +50,23 @@ impl GitHubProvider {
     async fn fetch_general_comments(
         &self,
         cli: &GhCli,
-        owner: &str,
-        repo: &str,
+        repo: &GitHubRepoInfo,
         pr_number: i64,
     ) -> Result<Vec<super::types::PrComment>, GitHostError> {
         let cli = cli.clone();
-        let owner = owner.to_string();
-        let repo = repo.to_string();
+        let repo = repo.clone();
 
         (|| async {
             let cli = cli.clone();
-            let owner = owner.clone();
             let repo = repo.clone();
 
-            let comments =
-                task::spawn_blocking(move || cli.get_pr_comments(&owner, &repo, pr_number))
-                    .await
-                    .map_err(|err| {
-                        GitHostError::PullRequest(format!(
-                            "Failed to execute GitHub CLI for fetching PR comments: {err}"
-                        ))
-                    })?;
+            let comments = task::spawn_blocking(move || cli.get_pr_comments(&repo, pr_number))
+                .await
+                .map_err(|err| {
+                    GitHostError::PullRequest(format!(
+                        "Failed to execute GitHub CLI for fetching PR comments: {err}"
+                    ))
+                })?;
             comments.map_err(GitHostError::from)
         })
         .retry(
@@ -92,21 +90,18 @@ impl GitHubProvider {
     async fn fetch_review_comments(
         &self,
         cli: &GhCli,
-        owner: &str,
-        repo: &str,
+        repo: &GitHubRepoInfo,
         pr_number: i64,
     ) -> Result<Vec<super::types::PrReviewComment>, GitHostError> {
         let cli = cli.clone();
-        let owner = owner.to_string();
-        let repo = repo.to_string();
+        let repo = repo.clone();
 
         (|| async {
             let cli = cli.clone();
-            let owner = owner.clone();
             let repo = repo.clone();
 
             let comments =
-                task::spawn_blocking(move || cli.get_pr_review_comments(&owner, &repo, pr_number))
+                task::spawn_blocking(move || cli.get_pr_review_comments(&repo, pr_number))
                     .await
                     .map_err(|err| {
                         GitHostError::PullRequest(format!(
@@ -185,20 +180,18 @@ impl GitHostProvider for GitHubProvider {
         (|| async {
             let cli = self.gh_cli.clone();
             let request = request_clone.clone();
-            let owner = target_repo_info.owner.clone();
-            let repo_name = target_repo_info.repo_name.clone();
+            let repo = target_repo_info.clone();
             let repo_path = repo_path.to_path_buf();
 
-            let cli_result = task::spawn_blocking(move || {
-                cli.create_pr(&request, &owner, &repo_name, &repo_path)
-            })
-            .await
-            .map_err(|err| {
-                GitHostError::PullRequest(format!(
-                    "Failed to execute GitHub CLI for PR creation: {err}"
-                ))
-            })?
-            .map_err(GitHostError::from)?;
+            let cli_result =
+                task::spawn_blocking(move || cli.create_pr(&request, &repo, &repo_path))
+                    .await
+                    .map_err(|err| {
+                        GitHostError::PullRequest(format!(
+                            "Failed to execute GitHub CLI for PR creation: {err}"
+                        ))
+                    })?
+                    .map_err(GitHostError::from)?;
 
             info!(
                 "Created GitHub PR #{} for branch {}",
@@ -272,18 +265,16 @@ impl GitHostProvider for GitHubProvider {
 
         (|| async {
             let cli = cli.clone();
-            let owner = repo_info.owner.clone();
-            let repo_name = repo_info.repo_name.clone();
+            let repo = repo_info.clone();
             let branch = branch.clone();
 
-            let prs =
-                task::spawn_blocking(move || cli.list_prs_for_branch(&owner, &repo_name, &branch))
-                    .await
-                    .map_err(|err| {
-                        GitHostError::PullRequest(format!(
-                            "Failed to execute GitHub CLI for listing PRs: {err}"
-                        ))
-                    })?;
+            let prs = task::spawn_blocking(move || cli.list_prs_for_branch(&repo, &branch))
+                .await
+                .map_err(|err| {
+                    GitHostError::PullRequest(format!(
+                        "Failed to execute GitHub CLI for listing PRs: {err}"
+                    ))
+                })?;
             prs.map_err(GitHostError::from)
         })
         .retry(
@@ -317,8 +308,8 @@ impl GitHostProvider for GitHubProvider {
         let cli2 = self.gh_cli.clone();
 
         let (general_result, review_result) = tokio::join!(
-            self.fetch_general_comments(&cli1, &repo_info.owner, &repo_info.repo_name, pr_number),
-            self.fetch_review_comments(&cli2, &repo_info.owner, &repo_info.repo_name, pr_number)
+            self.fetch_general_comments(&cli1, &repo_info, pr_number),
+            self.fetch_review_comments(&cli2, &repo_info, pr_number)
         );
 
         let general_comments = general_result?;
@@ -359,6 +350,48 @@ impl GitHostProvider for GitHubProvider {
         Ok(unified)
     }
 
+    async fn update_pr_base(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        pr_number: i64,
+        base_branch: &str,
+    ) -> Result<(), GitHostError> {
+        let repo_info = self.get_repo_info(remote_url, repo_path).await?;
+        let base = base_branch.to_string();
+
+        (|| async {
+            let cli = self.gh_cli.clone();
+            let repo = repo_info.clone();
+            let base = base.clone();
+
+            task::spawn_blocking(move || cli.edit_pr_base(&repo, pr_number, &base))
+                .await
+                .map_err(|err| {
+                    GitHostError::PullRequest(format!(
+                        "Failed to execute GitHub CLI for editing PR: {err}"
+                    ))
+                })?
+                .map_err(GitHostError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHostError| e.should_retry())
+        .notify(|err: &GitHostError, dur: Duration| {
+            tracing::warn!(
+                "GitHub API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
     fn provider_kind(&self) -> ProviderKind {
         ProviderKind::GitHub
     }
@@ -0,0 +1,295 @@
+//! GitLab hosting service implementation.
+
+mod cli;
+
+use std::{path::Path, time::Duration};
+
+use async_trait::async_trait;
+use backon::{ExponentialBuilder, Retryable};
+pub use cli::GlabCli;
+pub(super) use cli::parse_remote_url;
+use cli::{GitLabProjectInfo, GlabCliError};
+use db::models::merge::PullRequestInfo;
+use tokio::task;
+use tracing::info;
+
+use super::{
+    GitHostProvider,
+    types::{CreatePrRequest, GitHostError, ProviderKind, UnifiedPrComment},
+};
+
+#[derive(Debug, Clone)]
+pub struct GitLabProvider {
+    glab_cli: GlabCli,
+}
+
+impl GitLabProvider {
+    /// `hostname` is the instance to run `glab` against; by default the host of each
+    /// remote URL is used.
+    pub fn new(hostname: Option<String>) -> Result<Self, GitHostError> {
+        Ok(Self {
+            glab_cli: GlabCli::new(hostname),
+        })
+    }
+
+    fn get_project_info(&self, remote_url: &str) -> Result<GitLabProjectInfo, GitHostError> {
+        self.glab_cli
+            .get_project_info(remote_url)
+            .map_err(Into::into)
+    }
+
+    async fn get_project_id(&self, project: &GitLabProjectInfo) -> Result<i64, GitHostError> {
+        let cli = self.glab_cli.clone();
+        let project = project.clone();
+        task::spawn_blocking(move || cli.get_project_id(&project))
+            .await
+            .map_err(|err| GitHostError::Repository(format!("Failed to get project id: {err}")))?
+            .map_err(Into::into)
+    }
+}
+
+impl From<GlabCliError> for GitHostError {
+    fn from(error: GlabCliError) -> Self {
+        match &error {
+            GlabCliError::AuthFailed(msg) => GitHostError::AuthFailed(msg.clone()),
+            GlabCliError::NotAvailable => GitHostError::CliNotInstalled {
+                provider: ProviderKind::GitLab,
+            },
+            GlabCliError::CommandFailed(msg) => {
+                let lower = msg.to_ascii_lowercase();
+                if lower.contains("403") || lower.contains("forbidden") {
+                    GitHostError::InsufficientPermissions(msg.clone())
+                } else if lower.contains("404") || lower.contains("not found") {
+                    GitHostError::RepoNotFoundOrNoAccess(msg.clone())
+                } else {
+                    GitHostError::PullRequest(msg.clone())
+                }
+            }
+            GlabCliError::UnexpectedOutput(msg) => GitHostError::UnexpectedOutput(msg.clone()),
+        }
+    }
+}
+
+#[async_trait]
+impl GitHostProvider for GitLabProvider {
+    async fn create_pr(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        request: &CreatePrRequest,
+    ) -> Result<PullRequestInfo, GitHostError> {
+        let target = self.get_project_info(remote_url)?;
+
+        // Cross-fork merge requests are created in the fork and point at the
+        // target project by ID.
+        let (source, target_project_id) = match &request.head_repo_url {
+            Some(head_url) => {
+                let head = self.get_project_info(head_url)?;
+                if head != target {
+                    let target_id = self.get_project_id(&target).await?;
+                    (head, Some(target_id))
+                } else {
+                    (target, None)
+                }
+            }
+            None => (target, None),
+        };
+
+        (|| async {
+            let cli = self.glab_cli.clone();
+            let request_clone = request.clone();
+            let source = source.clone();
+
+            let cli_result = task::spawn_blocking(move || {
+                cli.create_mr(&request_clone, &source, target_project_id)
+            })
+            .await
+            .map_err(|err| {
+                GitHostError::PullRequest(format!(
+                    "Failed to execute GitLab CLI for MR creation: {err}"
+                ))
+            })?
+            .map_err(GitHostError::from)?;
+
+            info!(
+                "Created GitLab MR !{} for branch {}",
+                cli_result.number, request.head_branch
+            );
+
+            Ok(cli_result)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHostError| e.should_retry())
+        .notify(|err: &GitHostError, dur: Duration| {
+            tracing::warn!(
+                "GitLab API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    async fn get_pr_status(&self, pr_url: &str) -> Result<PullRequestInfo, GitHostError> {
+        (|| async {
+            let cli = self.glab_cli.clone();
+            let url = pr_url.to_string();
+
+            let mr = task::spawn_blocking(move || cli.view_mr(&url))
+                .await
+                .map_err(|err| {
+                    GitHostError::PullRequest(format!(
+                        "Failed to execute GitLab CLI for viewing MR: {err}"
+                    ))
+                })?;
+            mr.map_err(GitHostError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|err: &GitHostError| err.should_retry())
+        .notify(|err: &GitHostError, dur: Duration| {
+            tracing::warn!(
+                "GitLab API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    async fn list_prs_for_branch(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        branch_name: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitHostError> {
+        let project = self.get_project_info(remote_url)?;
+
+        (|| async {
+            let cli = self.glab_cli.clone();
+            let project = project.clone();
+            let branch = branch_name.to_string();
+
+            let mrs = task::spawn_blocking(move || cli.list_mrs_for_branch(&project, &branch))
+                .await
+                .map_err(|err| {
+                    GitHostError::PullRequest(format!(
+                        "Failed to execute GitLab CLI for listing MRs: {err}"
+                    ))
+                })?;
+            mrs.map_err(GitHostError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHostError| e.should_retry())
+        .notify(|err: &GitHostError, dur: Duration| {
+            tracing::warn!(
+                "GitLab API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    async fn get_pr_comments(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        pr_number: i64,
+    ) -> Result<Vec<UnifiedPrComment>, GitHostError> {
+        let project = self.get_project_info(remote_url)?;
+
+        (|| async {
+            let cli = self.glab_cli.clone();
+            let project = project.clone();
+
+            let comments = task::spawn_blocking(move || cli.get_mr_notes(&project, pr_number))
+                .await
+                .map_err(|err| {
+                    GitHostError::PullRequest(format!(
+                        "Failed to execute GitLab CLI for fetching MR comments: {err}"
+                    ))
+                })?;
+            comments.map_err(GitHostError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHostError| e.should_retry())
+        .notify(|err: &GitHostError, dur: Duration| {
+            tracing::warn!(
+                "GitLab API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    async fn update_pr_base(
+        &self,
+        _repo_path: &Path,
+        remote_url: &str,
+        pr_number: i64,
+        base_branch: &str,
+    ) -> Result<(), GitHostError> {
+        let project = self.get_project_info(remote_url)?;
+        let base = base_branch.to_string();
+
+        (|| async {
+            let cli = self.glab_cli.clone();
+            let project = project.clone();
+            let base = base.clone();
+
+            task::spawn_blocking(move || cli.update_mr_target(&project, pr_number, &base))
+                .await
+                .map_err(|err| {
+                    GitHostError::PullRequest(format!(
+                        "Failed to execute GitLab CLI for updating MR: {err}"
+                    ))
+                })?
+                .map_err(GitHostError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHostError| e.should_retry())
+        .notify(|err: &GitHostError, dur: Duration| {
+            tracing::warn!(
+                "GitLab API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::GitLab
+    }
+}
@@ -1,21 +1,29 @@
 mod detection;
+mod rest;
 mod types;
 
 pub mod azure;
+pub mod bitbucket;
+pub mod gitea;
 pub mod github;
+pub mod gitlab;
 
 use std::path::Path;
 
 use async_trait::async_trait;
-use db::models::merge::PullRequestInfo;
-use detection::detect_provider_from_url;
+use db::models::{merge::PullRequestInfo, repo::Repo};
+use detection::{resolve_provider, url_host};
 use enum_dispatch::enum_dispatch;
 pub use types::{
     CreatePrRequest, GitHostError, PrComment, PrCommentAuthor, PrReviewComment, ProviderKind,
     ReviewCommentUser, UnifiedPrComment,
 };
 
-use self::{azure::AzureDevOpsProvider, github::GitHubProvider};
+use self::{
+    azure::AzureDevOpsProvider, bitbucket::BitbucketProvider, gitea::GiteaProvider,
+    github::GitHubProvider, gitlab::GitLabProvider,
+};
+use crate::services::config::GitHostConfig;
 
 #[async_trait]
 #[enum_dispatch(GitHostService)]
@@ -43,6 +51,15 @@ pub trait GitHostProvider: Send + Sync {
         pr_number: i64,
     ) -> Result<Vec<UnifiedPrComment>, GitHostError>;
 
+    /// Change the branch a PR merges into, used to restack PRs whose base was merged.
+    async fn update_pr_base(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        pr_number: i64,
+        base_branch: &str,
+    ) -> Result<(), GitHostError>;
+
     fn provider_kind(&self) -> ProviderKind;
 }
 
@@ -50,13 +67,46 @@ pub trait GitHostProvider: Send + Sync {
 pub enum GitHostService {
     GitHub(GitHubProvider),
     AzureDevOps(AzureDevOpsProvider),
+    GitLab(GitLabProvider),
+    Gitea(GiteaProvider),
+    Bitbucket(BitbucketProvider),
 }
 
 impl GitHostService {
-    pub fn from_url(url: &str) -> Result<Self, GitHostError> {
-        match detect_provider_from_url(url) {
-            ProviderKind::GitHub => Ok(Self::GitHub(GitHubProvider::new()?)),
+    /// Service for the provider serving `url`, preferring the configured `hosts` over
+    /// detection from the URL.
+    pub fn from_url(url: &str, hosts: &[GitHostConfig]) -> Result<Self, GitHostError> {
+        let (provider, host) = resolve_provider(None, url, hosts);
+        Self::from_provider(provider, host)
+    }
+
+    /// Service for a repo, preferring the provider selected on the repo over
+    /// the configured `hosts` and detection from `url`.
+    pub fn for_repo(repo: &Repo, url: &str, hosts: &[GitHostConfig]) -> Result<Self, GitHostError> {
+        let (provider, host) = resolve_provider(repo.git_host_provider, url, hosts);
+        Self::from_provider(provider, host)
+    }
+
+    /// Service for `provider`, using the API endpoint of `host` when it is configured.
+    pub fn from_provider(
+        provider: ProviderKind,
+        host: Option<&GitHostConfig>,
+    ) -> Result<Self, GitHostError> {
+        let api_base_url = host.and_then(|h| h.api_base_url.clone());
+        // CLIs take the host name and derive the API endpoint themselves
+        let api_host = host.map(|h| {
+            h.api_base_url
+                .as_deref()
+                .and_then(url_host)
+                .unwrap_or(&h.host)
+                .to_string()
+        });
+        match provider {
+            ProviderKind::GitHub => Ok(Self::GitHub(GitHubProvider::new(api_host)?)),
             ProviderKind::AzureDevOps => Ok(Self::AzureDevOps(AzureDevOpsProvider::new()?)),
+            ProviderKind::GitLab => Ok(Self::GitLab(GitLabProvider::new(api_host)?)),
+            ProviderKind::Gitea => Ok(Self::Gitea(GiteaProvider::new(api_base_url)?)),
+            ProviderKind::Bitbucket => Ok(Self::Bitbucket(BitbucketProvider::new(api_base_url)?)),
             ProviderKind::Unknown => Err(GitHostError::UnsupportedProvider),
         }
     }
@@ -0,0 +1,305 @@
+//! Shared HTTP plumbing for providers that are driven through their REST API
+//! rather than a CLI.
+
+use std::{process::Stdio, time::Duration};
+
+use backon::{ExponentialBuilder, Retryable};
+use reqwest::{Client, Method, StatusCode};
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+use tokio::{io::AsyncWriteExt, process::Command};
+use utils::shell::resolve_executable_path;
+
+use super::types::{GitHostError, ProviderKind};
+
+/// Credentials sent with REST API requests.
+#[derive(Clone)]
+pub enum Credentials {
+    Token(String),
+    Basic { username: String, password: String },
+}
+
+impl Credentials {
+    /// Resolve credentials for `host`, preferring the first non-empty
+    /// environment variable in `token_vars` and falling back to the
+    /// credentials git uses to push over HTTPS.
+    pub async fn resolve(host: &str, token_vars: &[&str]) -> Option<Self> {
+        let token = token_vars
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.trim().is_empty());
+        match token {
+            Some(token) => Some(Self::Token(token.trim().to_string())),
+            None => Self::from_git_credential(host).await,
+        }
+    }
+
+    async fn from_git_credential(host: &str) -> Option<Self> {
+        let git = resolve_executable_path("git").await?;
+        let mut child = Command::new(git)
+            .args(["credential", "fill"])
+            // Never block on an interactive prompt
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GCM_INTERACTIVE", "never")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .ok()?;
+        child
+            .stdin
+            .take()?
+            .write_all(format!("protocol=https\nhost={host}\n\n").as_bytes())
+            .await
+            .ok()?;
+        let output = child.wait_with_output().await.ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Self::parse_git_credential(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn parse_git_credential(raw: &str) -> Option<Self> {
+        let mut username = None;
+        let mut password = None;
+        for line in raw.lines() {
+            match line.split_once('=') {
+                Some(("username", value)) => username = Some(value.to_string()),
+                Some(("password", value)) => password = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        Some(Self::Basic {
+            username: username.unwrap_or_default(),
+            password: password.filter(|p| !p.is_empty())?,
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RestError {
+    #[error("network error: {0}")]
+    Transport(String),
+    #[error("HTTP {status}: {body}")]
+    Http { status: u16, body: String },
+    #[error("unexpected response: {0}")]
+    Serde(String),
+}
+
+impl RestError {
+    fn should_retry(&self) -> bool {
+        match self {
+            Self::Transport(_) => true,
+            Self::Http { status, .. } => *status == 429 || (500..=599).contains(status),
+            Self::Serde(_) => false,
+        }
+    }
+}
+
+/// Whether a request may be sent again after a transient failure. A POST that timed out may
+/// still have created its PR or comment, so it is never repeated.
+fn is_retryable(method: &Method) -> bool {
+    [Method::GET, Method::PUT, Method::PATCH].contains(method)
+}
+
+impl From<RestError> for GitHostError {
+    fn from(error: RestError) -> Self {
+        match error {
+            RestError::Http { status: 401, body } => GitHostError::AuthFailed(body),
+            RestError::Http { status: 403, body } => GitHostError::InsufficientPermissions(body),
+            RestError::Http { status: 404, body } => GitHostError::RepoNotFoundOrNoAccess(body),
+            RestError::Serde(msg) => GitHostError::UnexpectedOutput(msg),
+            other => GitHostError::PullRequest(other.to_string()),
+        }
+    }
+}
+
+/// JSON client for one provider API, with automatic retries of transient failures of requests
+/// other than POST.
+#[derive(Clone)]
+pub struct RestClient {
+    http: Client,
+    base: String,
+    credentials: Option<Credentials>,
+    provider: ProviderKind,
+}
+
+impl std::fmt::Debug for RestClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RestClient")
+            .field("base", &self.base)
+            .field(
+                "credentials",
+                &self.credentials.as_ref().map(|_| "<present>"),
+            )
+            .field("provider", &self.provider)
+            .finish()
+    }
+}
+
+impl RestClient {
+    pub fn new(
+        http: Client,
+        base: impl Into<String>,
+        credentials: Option<Credentials>,
+        provider: ProviderKind,
+    ) -> Self {
+        Self {
+            http,
+            base: base.into().trim_end_matches('/').to_string(),
+            credentials,
+            provider,
+        }
+    }
+
+    /// GET a path relative to the API base, or an absolute URL such as a
+    /// pagination link.
+    pub async fn get<T>(&self, path: &str, query: &[(&str, &str)]) -> Result<T, RestError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        self.send(Method::GET, path, query, None::<&()>).await
+    }
+
+    pub async fn post<B, T>(&self, path: &str, body: &B) -> Result<T, RestError>
+    where
+        B: Serialize + Sync,
+        T: DeserializeOwned + Send,
+    {
+        self.send(Method::POST, path, &[], Some(body)).await
+    }
+
+    /// PATCH a resource. Failed requests are retried, so the update must be idempotent.
+    pub async fn patch<B, T>(&self, path: &str, body: &B) -> Result<T, RestError>
+    where
+        B: Serialize + Sync,
+        T: DeserializeOwned + Send,
+    {
+        self.send(Method::PATCH, path, &[], Some(body)).await
+    }
+
+    /// PUT a resource. Failed requests are retried, so the update must be idempotent.
+    pub async fn put<B, T>(&self, path: &str, body: &B) -> Result<T, RestError>
+    where
+        B: Serialize + Sync,
+        T: DeserializeOwned + Send,
+    {
+        self.send(Method::PUT, path, &[], Some(body)).await
+    }
+
+    async fn send<B, T>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&B>,
+    ) -> Result<T, RestError>
+    where
+        B: Serialize + Sync,
+        T: DeserializeOwned + Send,
+    {
+        let url = if path.starts_with("https://") || path.starts_with("http://") {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.base, path.trim_start_matches('/'))
+        };
+        let retryable = is_retryable(&method);
+
+        (|| async {
+            let mut req = self
+                .http
+                .request(method.clone(), &url)
+                .header(reqwest::header::ACCEPT, "application/json")
+                .query(query);
+            req = match &self.credentials {
+                Some(Credentials::Token(token)) => req.bearer_auth(token),
+                Some(Credentials::Basic { username, password }) => {
+                    req.basic_auth(username, Some(password))
+                }
+                None => req,
+            };
+            if let Some(b) = body {
+                req = req.json(b);
+            }
+
+            let res = req
+                .send()
+                .await
+                .map_err(|e| RestError::Transport(e.to_string()))?;
+            let status = res.status();
+            if !status.is_success() {
+                let body = res.text().await.unwrap_or_default();
+                let body = if body.is_empty() && status == StatusCode::UNAUTHORIZED {
+                    format!("No valid {} credentials for {url}", self.provider)
+                } else {
+                    body
+                };
+                return Err(RestError::Http {
+                    status: status.as_u16(),
+                    body,
+                });
+            }
+            res.json::<T>()
+                .await
+                .map_err(|e| RestError::Serde(e.to_string()))
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &RestError| retryable && e.should_retry())
+        .notify(|err: &RestError, dur: Duration| {
+            tracing::warn!(
+                "{} API call failed, retrying after {:.2}s: {}",
+                self.provider,
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_credential() {
+        match Credentials::parse_git_credential(
+            "protocol=https\nhost=codeberg.org\nusername=alice\npassword=secret\n",
+        ) {
+            Some(Credentials::Basic { username, password }) => {
+                assert_eq!(username, "alice");
+                assert_eq!(password, "secret");
+            }
+            _ => panic!("expected basic credentials"),
+        }
+        assert!(Credentials::parse_git_credential("protocol=https\nhost=codeberg.org\n").is_none());
+    }
+
+    #[test]
+    fn test_http_errors_map_to_git_host_errors() {
+        let error = |status| RestError::Http {
+            status,
+            body: String::new(),
+        };
+        assert!(matches!(
+            GitHostError::from(error(401)),
+            GitHostError::AuthFailed(_)
+        ));
+        assert!(matches!(
+            GitHostError::from(error(404)),
+            GitHostError::RepoNotFoundOrNoAccess(_)
+        ));
+        assert!(error(503).should_retry());
+        assert!(!error(422).should_retry());
+        assert!(is_retryable(&Method::GET));
+        assert!(is_retryable(&Method::PUT));
+        assert!(!is_retryable(&Method::POST));
+    }
+}
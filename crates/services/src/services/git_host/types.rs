@@ -1,26 +1,9 @@
 use chrono::{DateTime, Utc};
+pub use db::models::repo::ProviderKind;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ts_rs::TS;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
-#[serde(rename_all = "snake_case")]
-pub enum ProviderKind {
-    GitHub,
-    AzureDevOps,
-    Unknown,
-}
-
-impl std::fmt::Display for ProviderKind {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ProviderKind::GitHub => write!(f, "GitHub"),
-            ProviderKind::AzureDevOps => write!(f, "Azure DevOps"),
-            ProviderKind::Unknown => write!(f, "Unknown"),
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct CreatePrRequest {
     pub title: String,
@@ -0,0 +1,95 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use db::{DBService, models::execution_process_logs::ExecutionProcessLogs};
+use sqlx::error::Error as SqlxError;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info};
+
+use crate::services::config::{Config, LogRetentionConfig};
+
+/// Executions are compacted once they have been finished this long, so late log writes land
+/// before their lines are compressed
+const COMPACT_AFTER: chrono::Duration = chrono::Duration::minutes(5);
+const BATCH_SIZE: i64 = 100;
+
+/// Service that compresses the logs of finished executions and applies the log retention policy
+pub struct LogCompactorService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+}
+
+impl LogCompactorService {
+    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            poll_interval: Duration::from_secs(10 * 60),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting log compactor service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.compact_finished_executions().await {
+                error!("Error compacting execution logs: {}", e);
+            }
+            let retention = self.config.read().await.log_retention.clone();
+            if let Err(e) = self.apply_retention(&retention).await {
+                error!("Error applying log retention: {}", e);
+            }
+        }
+    }
+
+    async fn compact_finished_executions(&self) -> Result<(), SqlxError> {
+        let finished_before = Utc::now() - COMPACT_AFTER;
+        loop {
+            let ids = ExecutionProcessLogs::find_uncompacted_execution_ids(
+                &self.db.pool,
+                finished_before,
+                BATCH_SIZE,
+            )
+            .await?;
+            if ids.is_empty() {
+                return Ok(());
+            }
+            debug!("Compacting logs of {} executions", ids.len());
+            for id in ids {
+                ExecutionProcessLogs::compact(&self.db.pool, id).await?;
+            }
+        }
+    }
+
+    async fn apply_retention(&self, retention: &LogRetentionConfig) -> Result<(), SqlxError> {
+        let pool = &self.db.pool;
+        let mut deleted = 0;
+        if let Some(days) = retention.max_age_days {
+            let cutoff = Utc::now() - chrono::Duration::days(days.into());
+            deleted += ExecutionProcessLogs::delete_finished_before(pool, cutoff).await?;
+        }
+        if let Some(keep) = retention.keep_last_processes_per_project {
+            deleted +=
+                ExecutionProcessLogs::delete_beyond_last_per_project(pool, keep.into()).await?;
+        }
+        if let Some(max_bytes) = retention.max_bytes_per_project {
+            let max_bytes = i64::try_from(max_bytes).unwrap_or(i64::MAX);
+            deleted +=
+                ExecutionProcessLogs::delete_beyond_bytes_per_project(pool, max_bytes).await?;
+        }
+        if deleted > 0 {
+            info!("Log retention deleted {} log rows", deleted);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use db::models::execution_log_search::{LogSearchEntry, LogSearchEntryKind};
+use executors::logs::{ActionType, NormalizedEntry, NormalizedEntryType};
+
+/// Command output is indexed from its end, where errors and panics usually are
+const MAX_INDEXED_OUTPUT_BYTES: usize = 16 * 1024;
+
+fn tail(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut start = text.len() - max_bytes;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// The searchable parts of a normalized conversation: assistant messages, commands and their
+/// output, edited file paths and errors. Entries keep the index they have in the conversation,
+/// so hits can link to them.
+pub fn search_entries(entries: &[(usize, NormalizedEntry)]) -> Vec<LogSearchEntry> {
+    let mut out = Vec::new();
+    for (idx, entry) in entries {
+        let mut push = |kind, content: &str| {
+            if !content.trim().is_empty() {
+                out.push(LogSearchEntry {
+                    entry_index: *idx as i64,
+                    kind,
+                    content: content.to_string(),
+                });
+            }
+        };
+        match &entry.entry_type {
+            NormalizedEntryType::AssistantMessage => {
+                push(LogSearchEntryKind::AssistantMessage, &entry.content)
+            }
+            NormalizedEntryType::ErrorMessage { .. } => {
+                push(LogSearchEntryKind::Error, &entry.content)
+            }
+            NormalizedEntryType::ToolUse { action_type, .. } => match action_type {
+                ActionType::CommandRun { command, result } => {
+                    push(LogSearchEntryKind::Command, command);
+                    if let Some(output) = result.as_ref().and_then(|r| r.output.as_deref()) {
+                        push(
+                            LogSearchEntryKind::CommandOutput,
+                            tail(output, MAX_INDEXED_OUTPUT_BYTES),
+                        );
+                    }
+                }
+                ActionType::FileEdit { path, .. } => push(LogSearchEntryKind::FilePath, path),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::{CommandRunResult, ToolStatus};
+
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn tool(action_type: ActionType) -> NormalizedEntry {
+        entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "tool".to_string(),
+                action_type,
+                status: ToolStatus::Success,
+            },
+            "",
+        )
+    }
+
+    #[test]
+    fn extracts_messages_commands_paths_and_errors() {
+        let entries: Vec<_> = [
+            entry(NormalizedEntryType::UserMessage, "update deps"),
+            tool(ActionType::CommandRun {
+                command: "cargo update".to_string(),
+                result: Some(CommandRunResult {
+                    exit_status: None,
+                    output: Some("thread 'main' panicked at src/lib.rs".to_string()),
+                }),
+            }),
+            tool(ActionType::FileRead {
+                path: "Cargo.toml".to_string(),
+            }),
+            tool(ActionType::FileEdit {
+                path: "Cargo.lock".to_string(),
+                changes: vec![],
+            }),
+            entry(
+                NormalizedEntryType::AssistantMessage,
+                "Updated the lockfile",
+            ),
+            entry(
+                NormalizedEntryType::ErrorMessage {
+                    error_type: executors::logs::NormalizedEntryError::Other,
+                },
+                "rate limited",
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        .collect();
+
+        let indexed: Vec<_> = search_entries(&entries)
+            .into_iter()
+            .map(|e| (e.entry_index, e.kind, e.content))
+            .collect();
+        assert_eq!(
+            indexed,
+            vec![
+                (1, LogSearchEntryKind::Command, "cargo update".to_string()),
+                (
+                    1,
+                    LogSearchEntryKind::CommandOutput,
+                    "thread 'main' panicked at src/lib.rs".to_string()
+                ),
+                (3, LogSearchEntryKind::FilePath, "Cargo.lock".to_string()),
+                (
+                    4,
+                    LogSearchEntryKind::AssistantMessage,
+                    "Updated the lockfile".to_string()
+                ),
+                (5, LogSearchEntryKind::Error, "rate limited".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_conversation_indices_across_gaps() {
+        let entries = vec![
+            (
+                2,
+                entry(NormalizedEntryType::AssistantMessage, "Looking at the test"),
+            ),
+            (9, entry(NormalizedEntryType::AssistantMessage, "Fixed it")),
+        ];
+
+        let indices: Vec<_> = search_entries(&entries)
+            .into_iter()
+            .map(|e| e.entry_index)
+            .collect();
+        assert_eq!(indices, vec![2, 9]);
+    }
+
+    #[test]
+    fn long_output_keeps_its_end() {
+        let output = format!("{}é panic", "x".repeat(MAX_INDEXED_OUTPUT_BYTES));
+        let kept = tail(&output, MAX_INDEXED_OUTPUT_BYTES);
+        assert!(kept.len() <= MAX_INDEXED_OUTPUT_BYTES);
+        assert!(kept.ends_with("é panic"));
+    }
+}
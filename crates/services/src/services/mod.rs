@@ -1,10 +1,13 @@
 pub mod analytics;
 pub mod approvals;
 pub mod auth;
+pub mod auto_rebase;
+pub mod budget;
 pub mod config;
 pub mod container;
 pub mod diff_stream;
 pub mod events;
+pub mod executor_fallback;
 pub mod file_ranker;
 pub mod file_search;
 pub mod filesystem;
@@ -12,8 +15,11 @@ pub mod filesystem_watcher;
 pub mod git;
 pub mod git_host;
 pub mod image;
+pub mod log_compactor;
+pub mod log_search;
 pub mod notification;
 pub mod oauth_credentials;
+pub mod pipeline;
 pub mod pr_monitor;
 pub mod project;
 #[cfg(feature = "qa-mode")]
@@ -21,5 +27,8 @@ pub mod qa_repos;
 pub mod queued_message;
 pub mod remote_client;
 pub mod repo;
+pub mod token_usage;
+pub mod transcript;
+pub mod workspace_lock;
 pub mod workspace_manager;
 pub mod worktree_manager;
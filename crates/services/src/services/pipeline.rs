@@ -0,0 +1,151 @@
+use executors::logs::{ActionType, NormalizedEntry, NormalizedEntryType};
+
+const DEFAULT_PLAN_PROMPT: &str = "{task}\n\nImplement the following plan:\n\n{plan}";
+
+/// Prompt of an agent stage. `template` may use the `{task}` and `{plan}` placeholders; without
+/// one the task is sent on its own, followed by the plan when there is one.
+pub fn render_stage_prompt(
+    template: Option<&str>,
+    task_prompt: &str,
+    plan: Option<&str>,
+) -> String {
+    let template = match (template, plan) {
+        (Some(template), _) => template,
+        (None, Some(_)) => DEFAULT_PLAN_PROMPT,
+        (None, None) => return task_prompt.to_string(),
+    };
+    // A single pass, so placeholders inside the task or the plan are left as they are
+    let mut prompt = String::with_capacity(template.len() + task_prompt.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        prompt.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(after) = tail.strip_prefix("{task}") {
+            prompt.push_str(task_prompt);
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("{plan}") {
+            prompt.push_str(plan.unwrap_or_default());
+            rest = after;
+        } else {
+            prompt.push('{');
+            rest = &tail[1..];
+        }
+    }
+    prompt.push_str(rest);
+    prompt
+}
+
+/// Extra review instructions asking the reviewer to check the changes against the plan
+pub fn review_prompt_with_plan(
+    additional_prompt: Option<&str>,
+    plan: Option<&str>,
+) -> Option<String> {
+    let plan = plan.map(|plan| format!("The changes were meant to implement this plan:\n\n{plan}"));
+    match (additional_prompt, plan) {
+        (Some(extra), Some(plan)) => Some(format!("{extra}\n\n{plan}")),
+        (Some(extra), None) => Some(extra.to_string()),
+        (None, plan) => plan,
+    }
+}
+
+/// The plan an agent stage produced: the last plan it presented, or else its final message
+pub fn plan_from_entries(entries: &[NormalizedEntry]) -> Option<String> {
+    let presented = entries
+        .iter()
+        .rev()
+        .find_map(|entry| match &entry.entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::PlanPresentation { plan },
+                ..
+            } => Some(plan.clone()),
+            _ => None,
+        });
+    presented
+        .or_else(|| {
+            entries
+                .iter()
+                .rev()
+                .find(|entry| matches!(entry.entry_type, NormalizedEntryType::AssistantMessage))
+                .map(|entry| entry.content.clone())
+        })
+        .filter(|plan| !plan.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::ToolStatus;
+
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn plan(plan: &str) -> NormalizedEntry {
+        entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "ExitPlanMode".to_string(),
+                action_type: ActionType::PlanPresentation {
+                    plan: plan.to_string(),
+                },
+                status: ToolStatus::Success,
+            },
+            "",
+        )
+    }
+
+    #[test]
+    fn prefers_last_presented_plan_over_final_message() {
+        let entries = vec![
+            plan("1. draft"),
+            plan("1. add parser\n2. add tests"),
+            entry(NormalizedEntryType::AssistantMessage, "Plan ready"),
+        ];
+        assert_eq!(
+            plan_from_entries(&entries).as_deref(),
+            Some("1. add parser\n2. add tests")
+        );
+
+        let entries = vec![entry(NormalizedEntryType::AssistantMessage, "Do X then Y")];
+        assert_eq!(plan_from_entries(&entries).as_deref(), Some("Do X then Y"));
+        assert_eq!(plan_from_entries(&[]), None);
+    }
+
+    #[test]
+    fn renders_task_and_plan_into_prompt() {
+        assert_eq!(render_stage_prompt(None, "Fix login", None), "Fix login");
+        assert_eq!(
+            render_stage_prompt(None, "Fix login", Some("1. patch")),
+            "Fix login\n\nImplement the following plan:\n\n1. patch"
+        );
+        assert_eq!(
+            render_stage_prompt(
+                Some("Plan: {plan}\nTask: {task}"),
+                "Fix login",
+                Some("1. patch")
+            ),
+            "Plan: 1. patch\nTask: Fix login"
+        );
+        assert_eq!(
+            render_stage_prompt(
+                Some("{task} {plan} {other}"),
+                "Support {plan} syntax",
+                Some("Mention {task}")
+            ),
+            "Support {plan} syntax Mention {task} {other}"
+        );
+        assert_eq!(
+            render_stage_prompt(None, "Fix {task}", Some("1. {plan}")),
+            "Fix {task}\n\nImplement the following plan:\n\n1. {plan}"
+        );
+        assert_eq!(
+            review_prompt_with_plan(Some("Be strict"), Some("1. patch")).as_deref(),
+            Some("Be strict\n\nThe changes were meant to implement this plan:\n\n1. patch")
+        );
+    }
+}
@@ -1,50 +1,74 @@
-use std::time::Duration;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use db::{
     DBService,
     models::{
+        execution_process::ExecutionProcess,
         merge::{Merge, MergeStatus, PrMerge},
+        repo::{AutoRebasePolicy, Repo},
         task::{Task, TaskStatus},
         workspace::{Workspace, WorkspaceError},
+        workspace_repo::WorkspaceRepo,
     },
 };
 use serde_json::json;
 use sqlx::error::Error as SqlxError;
 use thiserror::Error;
-use tokio::time::interval;
-use tracing::{debug, error, info};
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info, warn};
 
 use crate::services::{
     analytics::AnalyticsContext,
-    git_host::{self, GitHostError, GitHostProvider},
+    config::Config,
+    git::{GitService, GitServiceError},
+    git_host::{GitHostError, GitHostProvider, GitHostService},
+    workspace_lock::WorkspaceLocks,
+    worktree_manager::{WorktreeError, WorktreeManager},
 };
 
 #[derive(Debug, Error)]
-enum PrMonitorError {
+pub enum PrMonitorError {
     #[error(transparent)]
     GitHostError(#[from] GitHostError),
     #[error(transparent)]
     WorkspaceError(#[from] WorkspaceError),
     #[error(transparent)]
     Sqlx(#[from] SqlxError),
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error(transparent)]
+    Worktree(#[from] WorktreeError),
 }
 
 /// Service to monitor PRs and update task status when they are merged
 pub struct PrMonitorService {
     db: DBService,
+    config: Arc<RwLock<Config>>,
+    git: GitService,
     poll_interval: Duration,
     analytics: Option<AnalyticsContext>,
+    /// Shared with process start, so a restack never races a new process
+    workspace_locks: WorkspaceLocks,
 }
 
 impl PrMonitorService {
     pub async fn spawn(
         db: DBService,
+        config: Arc<RwLock<Config>>,
         analytics: Option<AnalyticsContext>,
+        workspace_locks: WorkspaceLocks,
     ) -> tokio::task::JoinHandle<()> {
         let service = Self {
             db,
+            config,
+            git: GitService::new(),
             poll_interval: Duration::from_secs(60), // Check every minute
             analytics,
+            workspace_locks,
         };
         tokio::spawn(async move {
             service.start().await;
@@ -64,6 +88,9 @@ impl PrMonitorService {
             if let Err(e) = self.check_all_open_prs().await {
                 error!("Error checking open PRs: {}", e);
             }
+            if let Err(e) = self.restack_merged_prs().await {
+                error!("Error restacking workspaces of merged PRs: {}", e);
+            }
         }
     }
 
@@ -91,7 +118,12 @@ impl PrMonitorService {
 
     /// Check the status of a specific PR
     async fn check_pr_status(&self, pr_merge: &PrMerge) -> Result<(), PrMonitorError> {
-        let git_host = git_host::GitHostService::from_url(&pr_merge.pr_info.url)?;
+        let git_hosts = self.config.read().await.git_hosts.clone();
+        let repo = Repo::find_by_id(&self.db.pool, pr_merge.repo_id).await?;
+        let git_host = match &repo {
+            Some(repo) => GitHostService::for_repo(repo, &pr_merge.pr_info.url, &git_hosts)?,
+            None => GitHostService::from_url(&pr_merge.pr_info.url, &git_hosts)?,
+        };
         let pr_status = git_host.get_pr_status(&pr_merge.pr_info.url).await?;
 
         debug!(
@@ -145,4 +177,234 @@ impl PrMonitorService {
 
         Ok(())
     }
+
+    /// Restack the workspaces still stacked on merged PRs. Workspaces that are busy are
+    /// retried on the next tick, and ones that hit conflicts once the new base moves again.
+    async fn restack_merged_prs(&self) -> Result<(), PrMonitorError> {
+        let git_hosts = self.config.read().await.git_hosts.clone();
+        for pr_merge in Merge::get_merged_prs_with_stacked_children(&self.db.pool).await? {
+            let (Some(parent), Some(repo)) = (
+                Workspace::find_by_id(&self.db.pool, pr_merge.workspace_id).await?,
+                Repo::find_by_id(&self.db.pool, pr_merge.repo_id).await?,
+            ) else {
+                continue;
+            };
+            match GitHostService::for_repo(&repo, &pr_merge.pr_info.url, &git_hosts) {
+                Ok(git_host) => {
+                    self.restack_children(&parent, &repo, &pr_merge, &git_host)
+                        .await
+                }
+                Err(e) => error!(
+                    "Error restacking workspaces stacked on PR #{}: {}",
+                    pr_merge.pr_info.number, e
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the workspaces stacked on a merged PR onto the branch it merged into, rebasing
+    /// each from the merged branch onto the new base and retargeting its open PR.
+    async fn restack_children(
+        &self,
+        parent: &Workspace,
+        repo: &Repo,
+        pr_merge: &PrMerge,
+        git_host: &GitHostService,
+    ) {
+        let children = match Workspace::find_stacked_children(
+            &self.db.pool,
+            parent.id,
+            repo.id,
+            &parent.branch,
+        )
+        .await
+        {
+            Ok(children) => children,
+            Err(e) => {
+                error!(
+                    "Error finding workspaces stacked on workspace {}: {}",
+                    parent.id, e
+                );
+                return;
+            }
+        };
+
+        for child in children {
+            if let Err(e) = self
+                .restack_child(parent, &child, repo, pr_merge, git_host)
+                .await
+            {
+                error!(
+                    "Error restacking workspace {} after PR #{} merged: {}",
+                    child.id, pr_merge.pr_info.number, e
+                );
+            }
+        }
+    }
+
+    async fn restack_child(
+        &self,
+        parent: &Workspace,
+        child: &Workspace,
+        repo: &Repo,
+        pr_merge: &PrMerge,
+        git_host: &GitHostService,
+    ) -> Result<(), PrMonitorError> {
+        let Some(parent_repo) =
+            WorkspaceRepo::find_by_workspace_and_repo_id(&self.db.pool, parent.id, repo.id).await?
+        else {
+            return Ok(());
+        };
+        let new_base = parent_repo.target_branch;
+
+        let outcome = restack_workspace(
+            &self.db,
+            &self.git,
+            &self.workspace_locks,
+            &parent.branch,
+            child,
+            repo,
+            &new_base,
+        )
+        .await?;
+        let RestackOutcome::Restacked { worktree_path } = outcome else {
+            return Ok(());
+        };
+
+        let open_pr = Merge::find_by_workspace_and_repo_id(&self.db.pool, child.id, repo.id)
+            .await?
+            .into_iter()
+            .find_map(|merge| match merge {
+                Merge::Pr(pr) if matches!(pr.pr_info.status, MergeStatus::Open) => Some(pr),
+                _ => None,
+            });
+        if let Some(child_pr) = open_pr {
+            self.git
+                .push_to_remote(&worktree_path, &child.branch, true)?;
+            let remote = self
+                .git
+                .resolve_remote_name_for_branch(&repo.path, &new_base)?;
+            let remote_url = self.git.get_remote_url(&repo.path, &remote)?;
+            git_host
+                .update_pr_base(
+                    &repo.path,
+                    &remote_url,
+                    child_pr.pr_info.number,
+                    &pr_merge.target_branch_name,
+                )
+                .await?;
+            Merge::update_target_branch(&self.db.pool, child_pr.id, &pr_merge.target_branch_name)
+                .await?;
+
+            info!(
+                "Retargeted PR #{} onto {}",
+                child_pr.pr_info.number, pr_merge.target_branch_name
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// What [`restack_workspace`] did with a stacked workspace
+#[derive(Debug, PartialEq, Eq)]
+pub enum RestackOutcome {
+    /// Rebased onto the new base, which is now its target branch
+    Restacked { worktree_path: PathBuf },
+    /// Running, starting a process or mid-rebase; retried on a later tick
+    Busy,
+    /// The rebase hit conflicts and was aborted; the workspace is flagged until it is rebased
+    Conflicts,
+    /// Has no worktree, or already conflicted with the current new base
+    Skipped,
+}
+
+/// Rebase `child`, which targets `parent_branch` in `repo`, onto `new_base`. Like automatic
+/// rebases it only touches running workspaces when the repo's auto-rebase policy is `always`,
+/// and never while a process is being started in the workspace.
+pub async fn restack_workspace(
+    db: &DBService,
+    git: &GitService,
+    workspace_locks: &WorkspaceLocks,
+    parent_branch: &str,
+    child: &Workspace,
+    repo: &Repo,
+    new_base: &str,
+) -> Result<RestackOutcome, PrMonitorError> {
+    let pool = &db.pool;
+    let Some(_workspace_lock) = workspace_locks.try_lock(child.id) else {
+        return Ok(RestackOutcome::Busy);
+    };
+    if repo.auto_rebase != AutoRebasePolicy::Always
+        && ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, child.id)
+            .await?
+    {
+        debug!(
+            "Workspace {} is running, not restacking it onto {}",
+            child.id, new_base
+        );
+        return Ok(RestackOutcome::Busy);
+    }
+
+    let Some(child_repo) =
+        WorkspaceRepo::find_by_workspace_and_repo_id(pool, child.id, repo.id).await?
+    else {
+        return Ok(RestackOutcome::Skipped);
+    };
+    let Some(container_ref) = child.container_ref.as_deref().filter(|r| !r.is_empty()) else {
+        debug!(
+            "Workspace {} has no worktree to restack; rebase it onto {} manually",
+            child.id, new_base
+        );
+        return Ok(RestackOutcome::Skipped);
+    };
+
+    // A conflicting restack is only retried once the new base moves again
+    let new_base_oid = git.get_branch_oid(&repo.path, new_base)?;
+    if child_repo.auto_rebase_conflict_oid.as_deref() == Some(new_base_oid.as_str()) {
+        return Ok(RestackOutcome::Skipped);
+    }
+
+    let worktree_path = Path::new(container_ref).join(&repo.name);
+    WorktreeManager::ensure_worktree_exists(&repo.path, &child.branch, &worktree_path).await?;
+
+    match git.rebase_branch(
+        &repo.path,
+        &worktree_path,
+        new_base,
+        parent_branch,
+        &child.branch,
+    ) {
+        Ok(_) => {}
+        Err(GitServiceError::MergeConflicts { message, .. }) => {
+            // Leave the stack as it was and flag the workspace for a manual rebase
+            git.abort_conflicts(&worktree_path)?;
+            WorkspaceRepo::set_auto_rebase_conflict(pool, child.id, repo.id, Some(&new_base_oid))
+                .await?;
+            warn!(
+                "Could not restack workspace {} onto {}: {}",
+                child.id, new_base, message
+            );
+            return Ok(RestackOutcome::Conflicts);
+        }
+        Err(e @ (GitServiceError::WorktreeDirty(..) | GitServiceError::RebaseInProgress)) => {
+            debug!(
+                "Not restacking workspace {} onto {}: {}",
+                child.id, new_base, e
+            );
+            return Ok(RestackOutcome::Busy);
+        }
+        Err(e) => return Err(e.into()),
+    }
+    WorkspaceRepo::update_target_branch(pool, child.id, repo.id, new_base).await?;
+    if child_repo.auto_rebase_conflict_oid.is_some() {
+        WorkspaceRepo::set_auto_rebase_conflict(pool, child.id, repo.id, None).await?;
+    }
+
+    info!(
+        "Restacked workspace {} from {} onto {}",
+        child.id, parent_branch, new_base
+    );
+    Ok(RestackOutcome::Restacked { worktree_path })
 }
@@ -0,0 +1,193 @@
+use db::models::execution_process_token_usage::TokenUsageTotals;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::services::config::ModelPrice;
+
+const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+
+/// Usage of one executor/model pair together with its estimated cost
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TokenUsageBreakdown {
+    pub executor: String,
+    pub model: Option<String>,
+    pub execution_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    /// From the price table, else the cost reported by the agent. None when neither is known
+    pub estimated_cost_usd: Option<f64>,
+}
+
+/// Rolled up token usage for a session, task, project or the whole instance
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct TokenUsageReport {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    /// Sum of the costs of all priced models
+    pub estimated_cost_usd: f64,
+    /// Models that could not be priced and are missing from `estimated_cost_usd`
+    pub unpriced_models: Vec<String>,
+    pub breakdown: Vec<TokenUsageBreakdown>,
+}
+
+impl TokenUsageReport {
+    pub fn from_totals(totals: Vec<TokenUsageTotals>, prices: &[ModelPrice]) -> Self {
+        let mut report = Self::default();
+
+        for row in totals {
+            let estimated_cost_usd = row
+                .model
+                .as_deref()
+                .and_then(|model| find_model_price(prices, model))
+                .map(|price| {
+                    estimate_cost(
+                        price,
+                        row.input_tokens,
+                        row.output_tokens,
+                        row.cache_read_tokens,
+                        row.cache_write_tokens,
+                    )
+                })
+                .or(row.reported_cost_usd);
+
+            report.input_tokens += row.input_tokens;
+            report.output_tokens += row.output_tokens;
+            report.cache_read_tokens += row.cache_read_tokens;
+            report.cache_write_tokens += row.cache_write_tokens;
+            match estimated_cost_usd {
+                Some(cost) => report.estimated_cost_usd += cost,
+                None => {
+                    let model = row
+                        .model
+                        .clone()
+                        .unwrap_or_else(|| format!("{} (unknown model)", row.executor));
+                    if !report.unpriced_models.contains(&model) {
+                        report.unpriced_models.push(model);
+                    }
+                }
+            }
+
+            report.breakdown.push(TokenUsageBreakdown {
+                executor: row.executor,
+                model: row.model,
+                execution_count: row.execution_count,
+                input_tokens: row.input_tokens,
+                output_tokens: row.output_tokens,
+                cache_read_tokens: row.cache_read_tokens,
+                cache_write_tokens: row.cache_write_tokens,
+                estimated_cost_usd,
+            });
+        }
+
+        report
+    }
+
+    /// All tokens processed, including cache reads and writes
+    pub fn total_tokens(&self) -> i64 {
+        self.input_tokens + self.output_tokens + self.cache_read_tokens + self.cache_write_tokens
+    }
+}
+
+/// Find the price entry whose `model` is the longest prefix of the reported model name
+pub fn find_model_price<'a>(prices: &'a [ModelPrice], model: &str) -> Option<&'a ModelPrice> {
+    prices
+        .iter()
+        .filter(|price| model.starts_with(&price.model))
+        .max_by_key(|price| price.model.len())
+}
+
+pub fn estimate_cost(
+    price: &ModelPrice,
+    input_tokens: i64,
+    output_tokens: i64,
+    cache_read_tokens: i64,
+    cache_write_tokens: i64,
+) -> f64 {
+    (input_tokens as f64 * price.input_per_mtok
+        + output_tokens as f64 * price.output_per_mtok
+        + cache_read_tokens as f64 * price.cache_read_per_mtok
+        + cache_write_tokens as f64 * price.cache_write_per_mtok)
+        / TOKENS_PER_PRICE_UNIT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(model: &str, input: f64, output: f64) -> ModelPrice {
+        ModelPrice {
+            model: model.to_string(),
+            input_per_mtok: input,
+            output_per_mtok: output,
+            cache_read_per_mtok: 0.0,
+            cache_write_per_mtok: 0.0,
+        }
+    }
+
+    fn totals(model: Option<&str>, input_tokens: i64, output_tokens: i64) -> TokenUsageTotals {
+        TokenUsageTotals {
+            executor: "CLAUDE_CODE".to_string(),
+            model: model.map(str::to_string),
+            execution_count: 1,
+            input_tokens,
+            output_tokens,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reported_cost_usd: None,
+        }
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let prices = vec![
+            price("claude-opus-4", 15.0, 75.0),
+            price("claude-opus-4-5", 5.0, 25.0),
+        ];
+        let found = find_model_price(&prices, "claude-opus-4-5-20251101").unwrap();
+        assert_eq!(found.model, "claude-opus-4-5");
+        assert!(find_model_price(&prices, "gpt-5").is_none());
+    }
+
+    #[test]
+    fn report_sums_priced_models_and_lists_unpriced_ones() {
+        let prices = vec![price("claude-sonnet-4", 3.0, 15.0)];
+        let report = TokenUsageReport::from_totals(
+            vec![
+                totals(Some("claude-sonnet-4-5"), 1_000_000, 100_000),
+                totals(Some("mystery-model"), 10, 10),
+            ],
+            &prices,
+        );
+
+        assert_eq!(report.input_tokens, 1_000_010);
+        assert!((report.estimated_cost_usd - 4.5).abs() < 1e-9);
+        assert_eq!(report.unpriced_models, vec!["mystery-model".to_string()]);
+        assert_eq!(report.breakdown[1].estimated_cost_usd, None);
+    }
+
+    #[test]
+    fn reported_cost_prices_models_missing_from_the_table() {
+        let prices = vec![price("claude-sonnet-4", 3.0, 15.0)];
+        let report = TokenUsageReport::from_totals(
+            vec![
+                TokenUsageTotals {
+                    reported_cost_usd: Some(0.25),
+                    ..totals(Some("claude-sonnet-4-5"), 1_000_000, 0)
+                },
+                TokenUsageTotals {
+                    reported_cost_usd: Some(0.5),
+                    ..totals(Some("anthropic/claude-haiku-4-5"), 10, 10)
+                },
+            ],
+            &prices,
+        );
+
+        // The price table wins over the reported cost when both are known
+        assert!((report.estimated_cost_usd - 3.5).abs() < 1e-9);
+        assert!(report.unpriced_models.is_empty());
+    }
+}
@@ -0,0 +1,652 @@
+//! Self-contained transcripts of a session's normalized conversation, for pasting into design
+//! docs and incident reports.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use executors::logs::{
+    ActionType, CommandExitStatus, FileChange, NormalizedEntry, NormalizedEntryType, ToolResult,
+    ToolStatus, utils::patch::extract_normalized_entry_from_patch,
+};
+use serde::{Deserialize, Serialize};
+use utils::{
+    diff::{Diff, DiffChangeKind},
+    log_msg::LogMsg,
+};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    #[default]
+    Markdown,
+    Html,
+    Json,
+}
+
+impl TranscriptFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Html => "text/html; charset=utf-8",
+            Self::Json => "application/json",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// One coding agent execution of a session
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptTurn {
+    pub execution_process_id: Uuid,
+    pub executor: Option<String>,
+    pub prompt: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub entries: Vec<NormalizedEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptFileChange {
+    pub path: String,
+    pub change: DiffChangeKind,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Transcript {
+    pub title: String,
+    pub session_id: Uuid,
+    pub exported_at: DateTime<Utc>,
+    pub turns: Vec<TranscriptTurn>,
+    /// Files the workspace changed compared to its target branches
+    pub files: Vec<TranscriptFileChange>,
+}
+
+/// The normalized conversation in a log history with the index of each entry, which can have
+/// gaps where the normalizer removed entries
+pub fn indexed_entries_from_history(history: &[LogMsg]) -> Vec<(usize, NormalizedEntry)> {
+    let mut entries = BTreeMap::new();
+    for msg in history {
+        if let LogMsg::JsonPatch(patch) = msg
+            && let Some((idx, entry)) = extract_normalized_entry_from_patch(patch)
+        {
+            entries.insert(idx, entry);
+        }
+    }
+    entries.into_iter().collect()
+}
+
+impl TranscriptFileChange {
+    /// Summarise workspace diffs, with paths prefixed by the name of their repo
+    pub fn from_diffs(repo_name: &str, diffs: &[Diff]) -> Vec<Self> {
+        diffs
+            .iter()
+            .map(|diff| {
+                let path = diff
+                    .new_path
+                    .as_deref()
+                    .or(diff.old_path.as_deref())
+                    .unwrap_or_default();
+                Self {
+                    path: format!("{repo_name}/{path}"),
+                    change: diff.change.clone(),
+                    additions: diff.additions.unwrap_or(0),
+                    deletions: diff.deletions.unwrap_or(0),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Transcript {
+    pub fn render(&self, format: TranscriptFormat) -> Result<String, serde_json::Error> {
+        Ok(match format {
+            TranscriptFormat::Markdown => self.to_markdown(),
+            TranscriptFormat::Html => self.to_html(),
+            TranscriptFormat::Json => serde_json::to_string_pretty(self)?,
+        })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# {}\n\n_Session `{}`, exported {}_\n",
+            self.title,
+            self.session_id,
+            self.exported_at.to_rfc3339()
+        );
+
+        for (idx, turn) in self.turns.iter().enumerate() {
+            out.push_str(&format!(
+                "\n## Turn {}{} ({})\n",
+                idx + 1,
+                turn.executor
+                    .as_deref()
+                    .map(|executor| format!(": {executor}"))
+                    .unwrap_or_default(),
+                turn.started_at.to_rfc3339()
+            ));
+            if let Some(prompt) = &turn.prompt {
+                out.push_str(&format!("\n**User**\n\n{}\n", prompt.trim()));
+            }
+            for entry in &turn.entries {
+                if let Some(block) = markdown_entry(entry) {
+                    out.push('\n');
+                    out.push_str(&block);
+                }
+            }
+        }
+
+        if !self.files.is_empty() {
+            out.push_str(
+                "\n## Changed files\n\n| File | Change | + | - |\n| --- | --- | ---: | ---: |\n",
+            );
+            for file in &self.files {
+                out.push_str(&format!(
+                    "| `{}` | {} | {} | {} |\n",
+                    file.path,
+                    change_label(&file.change),
+                    file.additions,
+                    file.deletions
+                ));
+            }
+            let (additions, deletions) = self.diff_totals();
+            out.push_str(&format!(
+                "\n{} files changed, {additions} insertions(+), {deletions} deletions(-)\n",
+                self.files.len()
+            ));
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut body = format!(
+            "<h1>{}</h1>\n<p class=\"meta\">Session <code>{}</code>, exported {}</p>\n",
+            escape_html(&self.title),
+            self.session_id,
+            self.exported_at.to_rfc3339()
+        );
+
+        for (idx, turn) in self.turns.iter().enumerate() {
+            body.push_str(&format!(
+                "<section>\n<h2>Turn {}{} <span class=\"meta\">{}</span></h2>\n",
+                idx + 1,
+                turn.executor
+                    .as_deref()
+                    .map(|executor| format!(": {}", escape_html(executor)))
+                    .unwrap_or_default(),
+                turn.started_at.to_rfc3339()
+            ));
+            if let Some(prompt) = &turn.prompt {
+                body.push_str(&message_html("user", "User", prompt.trim()));
+            }
+            for entry in &turn.entries {
+                if let Some(block) = html_entry(entry) {
+                    body.push_str(&block);
+                }
+            }
+            body.push_str("</section>\n");
+        }
+
+        if !self.files.is_empty() {
+            body.push_str("<section>\n<h2>Changed files</h2>\n<table>\n<tr><th>File</th><th>Change</th><th>+</th><th>-</th></tr>\n");
+            for file in &self.files {
+                body.push_str(&format!(
+                    "<tr><td><code>{}</code></td><td>{}</td><td class=\"add\">{}</td><td class=\"del\">{}</td></tr>\n",
+                    escape_html(&file.path),
+                    change_label(&file.change),
+                    file.additions,
+                    file.deletions
+                ));
+            }
+            let (additions, deletions) = self.diff_totals();
+            body.push_str(&format!(
+                "</table>\n<p class=\"meta\">{} files changed, {additions} insertions(+), {deletions} deletions(-)</p>\n</section>\n",
+                self.files.len()
+            ));
+        }
+
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+            escape_html(&self.title)
+        )
+    }
+
+    fn diff_totals(&self) -> (usize, usize) {
+        self.files
+            .iter()
+            .fold((0, 0), |(additions, deletions), file| {
+                (additions + file.additions, deletions + file.deletions)
+            })
+    }
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:960px;margin:2rem auto;padding:0 1rem;color:#1f2328}\
+.meta{color:#59636e;font-size:.9em;font-weight:normal}\
+.message{white-space:pre-wrap;margin:.75rem 0;padding:.5rem .75rem;border-left:3px solid #d1d9e0}\
+.message.user{border-color:#0969da}.message.error{border-color:#d1242f}\
+.tool{margin:.75rem 0;padding:.5rem .75rem;background:#f6f8fa;border-radius:6px}\
+pre{overflow-x:auto;background:#f6f8fa;padding:.5rem;border-radius:6px}\
+.diff .add,td.add{color:#1a7f37}.diff .del,td.del{color:#d1242f}\
+table{border-collapse:collapse}td,th{border:1px solid #d1d9e0;padding:.25rem .5rem;text-align:left}";
+
+fn change_label(change: &DiffChangeKind) -> &'static str {
+    match change {
+        DiffChangeKind::Added => "added",
+        DiffChangeKind::Deleted => "deleted",
+        DiffChangeKind::Modified => "modified",
+        DiffChangeKind::Renamed => "renamed",
+        DiffChangeKind::Copied => "copied",
+        DiffChangeKind::PermissionChange => "permissions",
+    }
+}
+
+fn status_label(status: &ToolStatus) -> Option<String> {
+    match status {
+        ToolStatus::Created | ToolStatus::Success => None,
+        ToolStatus::Failed => Some("failed".to_string()),
+        ToolStatus::Denied {
+            reason: Some(reason),
+        } => Some(format!("denied: {reason}")),
+        ToolStatus::Denied { reason: None } => Some("denied".to_string()),
+        ToolStatus::PendingApproval { .. } => Some("awaiting approval".to_string()),
+        ToolStatus::TimedOut => Some("timed out".to_string()),
+    }
+}
+
+fn exit_label(status: &Option<CommandExitStatus>) -> String {
+    match status {
+        Some(CommandExitStatus::ExitCode { code }) => format!("exit code {code}"),
+        Some(CommandExitStatus::Success { success: true }) => "succeeded".to_string(),
+        Some(CommandExitStatus::Success { success: false }) => "failed".to_string(),
+        None => "output".to_string(),
+    }
+}
+
+fn tool_result_text(result: &ToolResult) -> String {
+    match &result.value {
+        serde_json::Value::String(text) => text.clone(),
+        value => serde_json::to_string_pretty(value).unwrap_or_default(),
+    }
+}
+
+/// A fenced code block whose fence cannot be closed by the content
+fn fence(lang: &str, content: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let ticks = "`".repeat((longest + 1).max(3));
+    format!(
+        "{ticks}{lang}\n{}\n{ticks}\n",
+        content.trim_end_matches('\n')
+    )
+}
+
+fn details_markdown(summary: &str, content: &str) -> String {
+    format!("<details>\n<summary>{summary}</summary>\n\n{content}\n</details>\n")
+}
+
+fn markdown_entry(entry: &NormalizedEntry) -> Option<String> {
+    let content = entry.content.trim();
+    match &entry.entry_type {
+        NormalizedEntryType::UserMessage => Some(format!("**User**\n\n{content}\n")),
+        NormalizedEntryType::UserFeedback { denied_tool } => Some(format!(
+            "**User feedback** on denied `{denied_tool}`\n\n{content}\n"
+        )),
+        NormalizedEntryType::AssistantMessage => Some(format!("**Assistant**\n\n{content}\n")),
+        NormalizedEntryType::Thinking => Some(details_markdown("Thinking", content)),
+        NormalizedEntryType::SystemMessage => Some(format!(
+            "{}\n",
+            content
+                .lines()
+                .map(|line| format!("> {line}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )),
+        NormalizedEntryType::ErrorMessage { .. } => Some(format!("**Error:** {content}\n")),
+        NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            status,
+        } => {
+            let mut out = format!("**Tool `{tool_name}`**: {content}");
+            if let Some(status) = status_label(status) {
+                out.push_str(&format!(" _({status})_"));
+            }
+            out.push('\n');
+            let detail = markdown_action(action_type);
+            if !detail.is_empty() {
+                out.push('\n');
+                out.push_str(&detail);
+            }
+            Some(out)
+        }
+        NormalizedEntryType::Loading
+        | NormalizedEntryType::NextAction { .. }
+        | NormalizedEntryType::TokenUsageInfo(_) => None,
+    }
+}
+
+fn markdown_action(action: &ActionType) -> String {
+    match action {
+        ActionType::FileEdit { path, changes } => changes
+            .iter()
+            .map(|change| match change {
+                FileChange::Edit { unified_diff, .. } => fence("diff", unified_diff),
+                FileChange::Write { content } => details_markdown(
+                    &format!("Wrote `{path}` ({} lines)", content.lines().count()),
+                    &fence("", content),
+                ),
+                FileChange::Delete => format!("Deleted `{path}`\n"),
+                FileChange::Rename { new_path } => format!("Renamed to `{new_path}`\n"),
+            })
+            .collect(),
+        ActionType::CommandRun {
+            command,
+            result: Some(result),
+        } => match &result.output {
+            Some(output) if !output.trim().is_empty() => details_markdown(
+                &exit_label(&result.exit_status),
+                &fence("", &format!("$ {command}\n{output}")),
+            ),
+            _ => format!("_{}_\n", exit_label(&result.exit_status)),
+        },
+        ActionType::Tool {
+            arguments, result, ..
+        } => {
+            let mut out = String::new();
+            if let Some(arguments) = arguments {
+                out.push_str(&details_markdown(
+                    "Arguments",
+                    &fence(
+                        "json",
+                        &serde_json::to_string_pretty(arguments).unwrap_or_default(),
+                    ),
+                ));
+            }
+            if let Some(result) = result {
+                out.push_str(&details_markdown(
+                    "Result",
+                    &fence("", &tool_result_text(result)),
+                ));
+            }
+            out
+        }
+        ActionType::PlanPresentation { plan } => format!("{}\n", plan.trim()),
+        ActionType::TodoManagement { todos, .. } => todos
+            .iter()
+            .map(|todo| {
+                let check = if todo.status == "completed" { "x" } else { " " };
+                format!("- [{check}] {}\n", todo.content)
+            })
+            .collect(),
+        _ => String::new(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn message_html(class: &str, label: &str, content: &str) -> String {
+    format!(
+        "<div class=\"message {class}\"><strong>{label}</strong>\n{}</div>\n",
+        escape_html(content)
+    )
+}
+
+fn pre_html(content: &str) -> String {
+    format!("<pre>{}</pre>", escape_html(content.trim_end_matches('\n')))
+}
+
+fn diff_html(unified_diff: &str) -> String {
+    let lines: Vec<String> = unified_diff
+        .trim_end_matches('\n')
+        .lines()
+        .map(|line| {
+            let class = match line.chars().next() {
+                Some('+') if !line.starts_with("+++") => "add",
+                Some('-') if !line.starts_with("---") => "del",
+                _ => "",
+            };
+            format!("<span class=\"{class}\">{}</span>", escape_html(line))
+        })
+        .collect();
+    format!("<pre class=\"diff\">{}</pre>", lines.join("\n"))
+}
+
+fn details_html(summary: &str, content: &str) -> String {
+    format!(
+        "<details><summary>{}</summary>{content}</details>",
+        escape_html(summary)
+    )
+}
+
+fn html_entry(entry: &NormalizedEntry) -> Option<String> {
+    let content = entry.content.trim();
+    match &entry.entry_type {
+        NormalizedEntryType::UserMessage => Some(message_html("user", "User", content)),
+        NormalizedEntryType::UserFeedback { denied_tool } => Some(message_html(
+            "user",
+            &format!("User feedback on denied {}", escape_html(denied_tool)),
+            content,
+        )),
+        NormalizedEntryType::AssistantMessage => {
+            Some(message_html("assistant", "Assistant", content))
+        }
+        NormalizedEntryType::Thinking => Some(format!(
+            "<div class=\"message\">{}</div>\n",
+            details_html("Thinking", &pre_html(content))
+        )),
+        NormalizedEntryType::SystemMessage => Some(message_html("system", "System", content)),
+        NormalizedEntryType::ErrorMessage { .. } => Some(message_html("error", "Error", content)),
+        NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            status,
+        } => {
+            let status = status_label(status)
+                .map(|status| format!(" <em>({})</em>", escape_html(&status)))
+                .unwrap_or_default();
+            Some(format!(
+                "<div class=\"tool\"><strong>{}</strong>: {}{status}{}</div>\n",
+                escape_html(tool_name),
+                escape_html(content),
+                html_action(action_type)
+            ))
+        }
+        NormalizedEntryType::Loading
+        | NormalizedEntryType::NextAction { .. }
+        | NormalizedEntryType::TokenUsageInfo(_) => None,
+    }
+}
+
+fn html_action(action: &ActionType) -> String {
+    match action {
+        ActionType::FileEdit { path, changes } => changes
+            .iter()
+            .map(|change| match change {
+                FileChange::Edit { unified_diff, .. } => diff_html(unified_diff),
+                FileChange::Write { content } => details_html(
+                    &format!("Wrote {path} ({} lines)", content.lines().count()),
+                    &pre_html(content),
+                ),
+                FileChange::Delete => format!("<p>Deleted {}</p>", escape_html(path)),
+                FileChange::Rename { new_path } => {
+                    format!("<p>Renamed to {}</p>", escape_html(new_path))
+                }
+            })
+            .collect(),
+        ActionType::CommandRun {
+            command,
+            result: Some(result),
+        } => match &result.output {
+            Some(output) if !output.trim().is_empty() => details_html(
+                &exit_label(&result.exit_status),
+                &pre_html(&format!("$ {command}\n{output}")),
+            ),
+            _ => format!(
+                " <em>({})</em>",
+                escape_html(&exit_label(&result.exit_status))
+            ),
+        },
+        ActionType::Tool {
+            arguments, result, ..
+        } => {
+            let mut out = String::new();
+            if let Some(arguments) = arguments {
+                out.push_str(&details_html(
+                    "Arguments",
+                    &pre_html(&serde_json::to_string_pretty(arguments).unwrap_or_default()),
+                ));
+            }
+            if let Some(result) = result {
+                out.push_str(&details_html(
+                    "Result",
+                    &pre_html(&tool_result_text(result)),
+                ));
+            }
+            out
+        }
+        ActionType::PlanPresentation { plan } => pre_html(plan),
+        ActionType::TodoManagement { todos, .. } => {
+            let items: String = todos
+                .iter()
+                .map(|todo| {
+                    format!(
+                        "<li>[{}] {}</li>",
+                        if todo.status == "completed" { "x" } else { " " },
+                        escape_html(&todo.content)
+                    )
+                })
+                .collect();
+            format!("<ul>{items}</ul>")
+        }
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::CommandRunResult;
+
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn transcript() -> Transcript {
+        Transcript {
+            title: "Fix <login> redirect".to_string(),
+            session_id: Uuid::nil(),
+            exported_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            turns: vec![TranscriptTurn {
+                execution_process_id: Uuid::nil(),
+                executor: Some("CLAUDE_CODE".to_string()),
+                prompt: Some("Fix the redirect".to_string()),
+                started_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                entries: vec![
+                    entry(NormalizedEntryType::Loading, ""),
+                    entry(
+                        NormalizedEntryType::ToolUse {
+                            tool_name: "Bash".to_string(),
+                            action_type: ActionType::CommandRun {
+                                command: "cargo test".to_string(),
+                                result: Some(CommandRunResult {
+                                    exit_status: Some(CommandExitStatus::ExitCode { code: 0 }),
+                                    output: Some("test result: ok\n".to_string()),
+                                }),
+                            },
+                            status: ToolStatus::Success,
+                        },
+                        "cargo test",
+                    ),
+                    entry(
+                        NormalizedEntryType::ToolUse {
+                            tool_name: "Edit".to_string(),
+                            action_type: ActionType::FileEdit {
+                                path: "src/auth.rs".to_string(),
+                                changes: vec![FileChange::Edit {
+                                    unified_diff: "@@ -1 +1 @@\n-old\n+new\n".to_string(),
+                                    has_line_numbers: true,
+                                }],
+                            },
+                            status: ToolStatus::Denied {
+                                reason: Some("not now".to_string()),
+                            },
+                        },
+                        "src/auth.rs",
+                    ),
+                    entry(
+                        NormalizedEntryType::AssistantMessage,
+                        "Done, see ```code```",
+                    ),
+                ],
+            }],
+            files: vec![TranscriptFileChange {
+                path: "app/src/auth.rs".to_string(),
+                change: DiffChangeKind::Modified,
+                additions: 3,
+                deletions: 1,
+            }],
+        }
+    }
+
+    #[test]
+    fn markdown_collapses_command_output_and_renders_diffs() {
+        let markdown = transcript().to_markdown();
+
+        assert!(markdown.starts_with("# Fix <login> redirect\n"));
+        assert!(markdown.contains("**User**\n\nFix the redirect\n"));
+        assert!(markdown.contains(
+            "<details>\n<summary>exit code 0</summary>\n\n```\n$ cargo test\ntest result: ok\n```\n"
+        ));
+        assert!(markdown.contains("**Tool `Edit`**: src/auth.rs _(denied: not now)_\n"));
+        assert!(markdown.contains("```diff\n@@ -1 +1 @@\n-old\n+new\n```\n"));
+        assert!(markdown.contains("| `app/src/auth.rs` | modified | 3 | 1 |"));
+        assert!(markdown.contains("1 files changed, 3 insertions(+), 1 deletions(-)"));
+        assert!(!markdown.contains("Loading"));
+    }
+
+    #[test]
+    fn html_escapes_content() {
+        let html = transcript().to_html();
+
+        assert!(html.contains("<title>Fix &lt;login&gt; redirect</title>"));
+        assert!(html.contains("<span class=\"add\">+new</span>"));
+        assert!(html.contains("<details><summary>exit code 0</summary>"));
+        assert!(!html.contains("<login>"));
+    }
+
+    #[test]
+    fn fences_outgrow_backticks_in_content() {
+        assert_eq!(fence("", "a ```b```"), "````\na ```b```\n````\n");
+    }
+}
@@ -0,0 +1,51 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use uuid::Uuid;
+
+/// Per-workspace locks that keep background rebases of a worktree from running while a
+/// process is being started in it. Cloning shares the locks.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceLocks {
+    locks: Arc<Mutex<HashMap<Uuid, Arc<AsyncMutex<()>>>>>,
+}
+
+impl WorkspaceLocks {
+    fn lock_for(&self, workspace_id: Uuid) -> Arc<AsyncMutex<()>> {
+        let mut locks = self.locks.lock().unwrap();
+        // Drop the locks nobody holds or waits for
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry(workspace_id).or_default().clone()
+    }
+
+    /// Wait for the workspace to be free and hold it until the guard is dropped
+    pub async fn lock(&self, workspace_id: Uuid) -> OwnedMutexGuard<()> {
+        self.lock_for(workspace_id).lock_owned().await
+    }
+
+    /// Hold the workspace if nothing else does
+    pub fn try_lock(&self, workspace_id: Uuid) -> Option<OwnedMutexGuard<()>> {
+        self.lock_for(workspace_id).try_lock_owned().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn locks_each_workspace_separately() {
+        let locks = WorkspaceLocks::default();
+        let workspace = Uuid::new_v4();
+
+        let guard = locks.lock(workspace).await;
+        assert!(locks.clone().try_lock(workspace).is_none());
+        assert!(locks.try_lock(Uuid::new_v4()).is_some());
+
+        drop(guard);
+        assert!(locks.try_lock(workspace).is_some());
+    }
+}
@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use db::models::{repo::Repo, workspace::Workspace as DbWorkspace};
+use db::models::{repo::Repo, workspace::Workspace as DbWorkspace, workspace_repo::WorkspaceRepo};
 use sqlx::{Pool, Sqlite};
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
@@ -204,6 +204,40 @@ impl WorkspaceManager {
         Ok(())
     }
 
+    /// Delete a workspace whose setup failed part way, together with the worktrees already
+    /// created for it. Failures are only logged, as the caller is reporting the setup error.
+    pub async fn discard_workspace(db: &Pool<Sqlite>, workspace_id: Uuid) {
+        let workspace = match DbWorkspace::find_by_id(db, workspace_id).await {
+            Ok(Some(workspace)) => workspace,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(
+                    "Failed to load workspace {} to discard: {}",
+                    workspace_id, e
+                );
+                return;
+            }
+        };
+
+        if let Some(container_ref) = &workspace.container_ref {
+            let repos = WorkspaceRepo::find_repos_for_workspace(db, workspace_id)
+                .await
+                .unwrap_or_default();
+            if let Err(e) = Self::cleanup_workspace(Path::new(container_ref), &repos).await {
+                warn!(
+                    "Failed to clean up worktrees of discarded workspace {}: {}",
+                    workspace_id, e
+                );
+            }
+        }
+        if let Err(e) = DbWorkspace::delete(db, workspace_id).await {
+            warn!(
+                "Failed to delete discarded workspace {}: {}",
+                workspace_id, e
+            );
+        }
+    }
+
     /// Get the base directory for workspaces (same as worktree base dir)
     pub fn get_workspace_base_dir() -> PathBuf {
         WorktreeManager::get_worktree_base_dir()
@@ -398,3 +432,77 @@ impl WorkspaceManager {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use db::{
+        DBService, models::workspace_repo::CreateWorkspaceRepo, test_utils::create_task_fixture,
+    };
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::services::git::GitService;
+
+    #[tokio::test]
+    async fn discard_workspace_removes_worktrees_and_rows() {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "Discard").await;
+        let root = TempDir::new().unwrap();
+        let repo_path = root.path().join("repo");
+        let git = GitService::new();
+        git.initialize_repo_with_main_branch(&repo_path).unwrap();
+        let repo = Repo::find_or_create(&db.pool, &repo_path, "repo")
+            .await
+            .unwrap();
+        WorkspaceRepo::create_many(
+            &db.pool,
+            fixture.workspace.id,
+            &[CreateWorkspaceRepo {
+                repo_id: repo.id,
+                target_branch: "main".to_string(),
+            }],
+        )
+        .await
+        .unwrap();
+
+        let workspace_dir = root.path().join("workspace");
+        WorkspaceManager::create_workspace(
+            &workspace_dir,
+            &[RepoWorkspaceInput::new(repo.clone(), "main".to_string())],
+            &fixture.workspace.branch,
+        )
+        .await
+        .unwrap();
+        DbWorkspace::update_container_ref(
+            &db.pool,
+            fixture.workspace.id,
+            &workspace_dir.to_string_lossy(),
+        )
+        .await
+        .unwrap();
+        // Setup fails like a fork whose starting commit no longer exists
+        assert!(
+            git.reset_worktree_to_commit(
+                &workspace_dir.join(&repo.name),
+                "0000000000000000000000000000000000000000",
+                true,
+            )
+            .is_err()
+        );
+
+        WorkspaceManager::discard_workspace(&db.pool, fixture.workspace.id).await;
+
+        assert!(!workspace_dir.exists());
+        assert!(
+            DbWorkspace::find_by_id(&db.pool, fixture.workspace.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let worktrees = git2::Repository::open(&repo_path)
+            .unwrap()
+            .worktrees()
+            .unwrap();
+        assert!(worktrees.is_empty());
+    }
+}
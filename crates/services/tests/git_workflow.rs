@@ -4,8 +4,24 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use db::{
+    DBService,
+    models::{
+        merge::{Merge, MergeStatus},
+        repo::{AutoRebasePolicy, Repo, UpdateRepo},
+        task::{Task, TaskStatus},
+        workspace::Workspace,
+        workspace_repo::{AutoRebaseCandidate, CreateWorkspaceRepo, WorkspaceRepo},
+    },
+    test_utils::{TaskFixture, add_task, create_coding_agent_process, create_task_fixture},
+};
 use git2::{Repository, build::CheckoutBuilder};
-use services::services::git::{DiffTarget, GitCli, GitService};
+use services::services::{
+    auto_rebase::{AutoRebaseOutcome, auto_rebase_workspace},
+    git::{DiffTarget, GitCli, GitService},
+    pr_monitor::{RestackOutcome, restack_workspace},
+    workspace_lock::WorkspaceLocks,
+};
 use tempfile::TempDir;
 use utils::diff::DiffChangeKind;
 
@@ -520,3 +536,446 @@ fn squash_merge_libgit2_sets_author_without_user() {
         assert_eq!(email.as_deref(), Some("noreply@vibekanban.com"));
     }
 }
+
+/// A parent workspace targeting main with a subtask workspace stacked on its branch, after
+/// the parent's PR was squash merged into main
+struct Stack {
+    db: DBService,
+    repo_path: PathBuf,
+    repo: Repo,
+    parent: TaskFixture,
+    child: TaskFixture,
+}
+
+impl Stack {
+    async fn new(td: &TempDir) -> Self {
+        let db = DBService::new_in_memory().await;
+        let parent = create_task_fixture(&db, "parent").await;
+        let child = add_task(&db, parent.project.clone(), "child").await;
+        Task::update(
+            &db.pool,
+            child.task.id,
+            child.task.project_id,
+            child.task.title.clone(),
+            None,
+            TaskStatus::Todo,
+            Some(parent.workspace.id),
+        )
+        .await
+        .unwrap();
+
+        let repo_path = init_repo_main(td);
+        let s = GitService::new();
+        write_file(&repo_path, "base.txt", "base\n");
+        s.commit(&repo_path, "base").unwrap();
+        create_branch(&repo_path, &parent.workspace.branch);
+        checkout_branch(&repo_path, &parent.workspace.branch);
+        write_file(&repo_path, "parent.txt", "parent\n");
+        s.commit(&repo_path, "parent").unwrap();
+        create_branch(&repo_path, &child.workspace.branch);
+        checkout_branch(&repo_path, &child.workspace.branch);
+        write_file(&repo_path, "child.txt", "child\n");
+        s.commit(&repo_path, "child").unwrap();
+        checkout_branch(&repo_path, "main");
+        write_file(&repo_path, "parent.txt", "parent\n");
+        s.commit(&repo_path, "parent (#1)").unwrap();
+
+        let repo = Repo::find_or_create(&db.pool, &repo_path, "repo")
+            .await
+            .unwrap();
+        for (workspace, target_branch) in [
+            (&parent.workspace, "main"),
+            (&child.workspace, parent.workspace.branch.as_str()),
+        ] {
+            WorkspaceRepo::create_many(
+                &db.pool,
+                workspace.id,
+                &[CreateWorkspaceRepo {
+                    repo_id: repo.id,
+                    target_branch: target_branch.to_string(),
+                }],
+            )
+            .await
+            .unwrap();
+        }
+        let container_ref = td.path().join("worktrees").join("child");
+        Workspace::update_container_ref(
+            &db.pool,
+            child.workspace.id,
+            &container_ref.to_string_lossy(),
+        )
+        .await
+        .unwrap();
+        let child = TaskFixture {
+            workspace: Workspace::find_by_id(&db.pool, child.workspace.id)
+                .await
+                .unwrap()
+                .unwrap(),
+            ..child
+        };
+
+        Stack {
+            db,
+            repo_path,
+            repo,
+            parent,
+            child,
+        }
+    }
+
+    async fn restack(&self, repo: &Repo, locks: &WorkspaceLocks) -> RestackOutcome {
+        restack_workspace(
+            &self.db,
+            &GitService::new(),
+            locks,
+            &self.parent.workspace.branch,
+            &self.child.workspace,
+            repo,
+            "main",
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn child_repo(&self) -> WorkspaceRepo {
+        WorkspaceRepo::find_by_workspace_and_repo_id(
+            &self.db.pool,
+            self.child.workspace.id,
+            self.repo.id,
+        )
+        .await
+        .unwrap()
+        .unwrap()
+    }
+
+    async fn stacked_children(&self) -> Vec<Workspace> {
+        Workspace::find_stacked_children(
+            &self.db.pool,
+            self.parent.workspace.id,
+            self.repo.id,
+            &self.parent.workspace.branch,
+        )
+        .await
+        .unwrap()
+    }
+}
+
+#[tokio::test]
+async fn finds_workspaces_stacked_on_merged_prs() {
+    let td = TempDir::new().unwrap();
+    let stack = Stack::new(&td).await;
+    let pool = &stack.db.pool;
+
+    let children = stack.stacked_children().await;
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0].id, stack.child.workspace.id);
+    assert!(
+        Workspace::find_stacked_children(pool, stack.parent.workspace.id, stack.repo.id, "main")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let pr = Merge::create_pr(
+        pool,
+        stack.parent.workspace.id,
+        stack.repo.id,
+        "main",
+        1,
+        "https://github.com/team/app/pull/1",
+    )
+    .await
+    .unwrap();
+    assert!(
+        Merge::get_merged_prs_with_stacked_children(pool)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    Merge::update_status(pool, pr.id, MergeStatus::Merged, None)
+        .await
+        .unwrap();
+    let merged = Merge::get_merged_prs_with_stacked_children(pool)
+        .await
+        .unwrap();
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].id, pr.id);
+
+    Workspace::set_archived(pool, stack.child.workspace.id, true)
+        .await
+        .unwrap();
+    assert!(stack.stacked_children().await.is_empty());
+    assert!(
+        Merge::get_merged_prs_with_stacked_children(pool)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn restack_moves_stacked_workspace_onto_new_base() {
+    let td = TempDir::new().unwrap();
+    let stack = Stack::new(&td).await;
+
+    let outcome = stack.restack(&stack.repo, &WorkspaceLocks::default()).await;
+    let RestackOutcome::Restacked { worktree_path } = outcome else {
+        panic!("expected a restack, got {outcome:?}");
+    };
+    assert!(worktree_path.join("child.txt").exists());
+
+    // Only the child's own commit is left on top of main
+    let (ahead, behind) = GitService::new()
+        .get_branch_status(&stack.repo_path, &stack.child.workspace.branch, "main")
+        .unwrap();
+    assert_eq!((ahead, behind), (1, 0));
+    assert_eq!(stack.child_repo().await.target_branch, "main");
+    assert!(stack.stacked_children().await.is_empty());
+}
+
+#[tokio::test]
+async fn restack_waits_for_busy_workspaces() {
+    let td = TempDir::new().unwrap();
+    let stack = Stack::new(&td).await;
+    let locks = WorkspaceLocks::default();
+
+    // A process is being started
+    let guard = locks.lock(stack.child.workspace.id).await;
+    assert_eq!(
+        stack.restack(&stack.repo, &locks).await,
+        RestackOutcome::Busy
+    );
+    drop(guard);
+
+    // A coding agent is running
+    create_coding_agent_process(&stack.db, stack.child.session.id).await;
+    assert_eq!(
+        stack.restack(&stack.repo, &locks).await,
+        RestackOutcome::Busy
+    );
+    assert_eq!(
+        stack.child_repo().await.target_branch,
+        stack.parent.workspace.branch
+    );
+
+    // Repos that rebase running workspaces are restacked anyway
+    let always = Repo {
+        auto_rebase: AutoRebasePolicy::Always,
+        ..stack.repo.clone()
+    };
+    assert!(matches!(
+        stack.restack(&always, &locks).await,
+        RestackOutcome::Restacked { .. }
+    ));
+}
+
+#[tokio::test]
+async fn restack_conflicts_are_flagged_until_the_new_base_moves() {
+    let td = TempDir::new().unwrap();
+    let stack = Stack::new(&td).await;
+    let locks = WorkspaceLocks::default();
+    let s = GitService::new();
+
+    write_file(&stack.repo_path, "child.txt", "conflicting\n");
+    s.commit(&stack.repo_path, "conflict").unwrap();
+    let conflict_oid = s.get_branch_oid(&stack.repo_path, "main").unwrap();
+
+    assert_eq!(
+        stack.restack(&stack.repo, &locks).await,
+        RestackOutcome::Conflicts
+    );
+    let child_repo = stack.child_repo().await;
+    assert_eq!(
+        child_repo.auto_rebase_conflict_oid.as_deref(),
+        Some(conflict_oid.as_str())
+    );
+    assert_eq!(child_repo.target_branch, stack.parent.workspace.branch);
+
+    // Not retried against the same commit
+    assert_eq!(
+        stack.restack(&stack.repo, &locks).await,
+        RestackOutcome::Skipped
+    );
+
+    // Retried once main moves, which clears the flag when it applies
+    fs::remove_file(stack.repo_path.join("child.txt")).unwrap();
+    s.commit(&stack.repo_path, "drop conflict").unwrap();
+    assert!(matches!(
+        stack.restack(&stack.repo, &locks).await,
+        RestackOutcome::Restacked { .. }
+    ));
+    assert!(stack.child_repo().await.auto_rebase_conflict_oid.is_none());
+}
+
+/// A workspace on a branch with one commit whose target branch main moved on by one commit
+struct BehindWorkspace {
+    db: DBService,
+    repo_path: PathBuf,
+    repo: Repo,
+    fixture: TaskFixture,
+}
+
+impl BehindWorkspace {
+    async fn new(td: &TempDir) -> Self {
+        let db = DBService::new_in_memory().await;
+        let fixture = create_task_fixture(&db, "feature").await;
+        let branch = &fixture.workspace.branch;
+
+        let repo_path = init_repo_main(td);
+        let s = GitService::new();
+        write_file(&repo_path, "base.txt", "base\n");
+        s.commit(&repo_path, "base").unwrap();
+        create_branch(&repo_path, branch);
+        write_file(&repo_path, "main.txt", "main\n");
+        s.commit(&repo_path, "main moves").unwrap();
+
+        let container_ref = td.path().join("worktrees").join("feature");
+        let worktree_path = container_ref.join("repo");
+        s.add_worktree(&repo_path, &worktree_path, branch, false)
+            .unwrap();
+        write_file(&worktree_path, "feature.txt", "feature\n");
+        s.commit(&worktree_path, "feature").unwrap();
+
+        let repo = Repo::find_or_create(&db.pool, &repo_path, "repo")
+            .await
+            .unwrap();
+        WorkspaceRepo::create_many(
+            &db.pool,
+            fixture.workspace.id,
+            &[CreateWorkspaceRepo {
+                repo_id: repo.id,
+                target_branch: "main".to_string(),
+            }],
+        )
+        .await
+        .unwrap();
+        Workspace::update_container_ref(
+            &db.pool,
+            fixture.workspace.id,
+            &container_ref.to_string_lossy(),
+        )
+        .await
+        .unwrap();
+
+        BehindWorkspace {
+            db,
+            repo_path,
+            repo,
+            fixture,
+        }
+    }
+
+    async fn set_policy(&self, policy: &str) {
+        let update: UpdateRepo =
+            serde_json::from_value(serde_json::json!({ "auto_rebase": policy })).unwrap();
+        Repo::update(&self.db.pool, self.repo.id, &update)
+            .await
+            .unwrap();
+    }
+
+    async fn candidate(&self) -> Option<AutoRebaseCandidate> {
+        WorkspaceRepo::find_auto_rebase_candidates(&self.db.pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|candidate| candidate.workspace_id == self.fixture.workspace.id)
+    }
+
+    async fn auto_rebase(&self, locks: &WorkspaceLocks) -> AutoRebaseOutcome {
+        let candidate = self.candidate().await.expect("auto-rebase candidate");
+        auto_rebase_workspace(&self.db, &GitService::new(), locks, &candidate, &self.repo)
+            .await
+            .unwrap()
+    }
+
+    fn branch_status(&self) -> (usize, usize) {
+        GitService::new()
+            .get_branch_status(&self.repo_path, &self.fixture.workspace.branch, "main")
+            .unwrap()
+    }
+}
+
+#[tokio::test]
+async fn auto_rebase_follows_repo_policy() {
+    let td = TempDir::new().unwrap();
+    let ws = BehindWorkspace::new(&td).await;
+    let locks = WorkspaceLocks::default();
+
+    // `never` is the default and leaves the workspace alone
+    assert!(ws.candidate().await.is_none());
+
+    ws.set_policy("idle_only").await;
+    create_coding_agent_process(&ws.db, ws.fixture.session.id).await;
+    assert_eq!(ws.auto_rebase(&locks).await, AutoRebaseOutcome::Busy);
+
+    // `always` rebases running workspaces, but never while a process is starting
+    ws.set_policy("always").await;
+    let guard = locks.lock(ws.fixture.workspace.id).await;
+    assert_eq!(ws.auto_rebase(&locks).await, AutoRebaseOutcome::Busy);
+    drop(guard);
+    assert_eq!(ws.branch_status(), (1, 1));
+
+    assert_eq!(ws.auto_rebase(&locks).await, AutoRebaseOutcome::Rebased);
+    assert_eq!(ws.branch_status(), (1, 0));
+    assert_eq!(ws.auto_rebase(&locks).await, AutoRebaseOutcome::UpToDate);
+}
+
+#[tokio::test]
+async fn auto_rebase_conflicts_are_only_retried_once_the_target_moves() {
+    let td = TempDir::new().unwrap();
+    let ws = BehindWorkspace::new(&td).await;
+    let locks = WorkspaceLocks::default();
+    let s = GitService::new();
+    ws.set_policy("idle_only").await;
+
+    write_file(&ws.repo_path, "feature.txt", "conflicting\n");
+    s.commit(&ws.repo_path, "conflict").unwrap();
+    let conflict_oid = s.get_branch_oid(&ws.repo_path, "main").unwrap();
+
+    assert_eq!(ws.auto_rebase(&locks).await, AutoRebaseOutcome::Conflicts);
+    assert_eq!(
+        ws.candidate().await.unwrap().auto_rebase_conflict_oid,
+        Some(conflict_oid)
+    );
+    assert_eq!(ws.auto_rebase(&locks).await, AutoRebaseOutcome::Skipped);
+    assert_eq!(ws.branch_status(), (1, 2));
+
+    fs::remove_file(ws.repo_path.join("feature.txt")).unwrap();
+    s.commit(&ws.repo_path, "drop conflict").unwrap();
+    assert_eq!(ws.auto_rebase(&locks).await, AutoRebaseOutcome::Rebased);
+    assert!(
+        ws.candidate()
+            .await
+            .unwrap()
+            .auto_rebase_conflict_oid
+            .is_none()
+    );
+    assert_eq!(ws.branch_status(), (1, 0));
+}
+
+#[tokio::test]
+async fn auto_rebase_clears_the_flag_of_workspaces_rebased_by_hand() {
+    let td = TempDir::new().unwrap();
+    let ws = BehindWorkspace::new(&td).await;
+    let locks = WorkspaceLocks::default();
+    ws.set_policy("idle_only").await;
+
+    assert_eq!(ws.auto_rebase(&locks).await, AutoRebaseOutcome::Rebased);
+    WorkspaceRepo::set_auto_rebase_conflict(
+        &ws.db.pool,
+        ws.fixture.workspace.id,
+        ws.repo.id,
+        Some("0000000000000000000000000000000000000000"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(ws.auto_rebase(&locks).await, AutoRebaseOutcome::UpToDate);
+    assert!(
+        ws.candidate()
+            .await
+            .unwrap()
+            .auto_rebase_conflict_oid
+            .is_none()
+    );
+}
@@ -57,4 +57,39 @@ pub enum ApprovalStatus {
 pub struct ApprovalResponse {
     pub execution_process_id: Uuid,
     pub status: ApprovalStatus,
+    /// Apply the same decision to later matching tool calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub remember: Option<RememberApproval>,
+}
+
+/// How long a remembered approval decision applies
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalScope {
+    /// The rest of the session the tool call was made in
+    Session,
+    /// Every session of the workspace the tool call was made in
+    Workspace,
+}
+
+/// Which later tool calls a remembered approval decision applies to
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApprovalMatcher {
+    /// The same tool called with the same input
+    ExactInput,
+    /// Any call of the same tool
+    Tool,
+    /// Shell commands starting with `prefix`
+    CommandPrefix { prefix: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RememberApproval {
+    pub scope: ApprovalScope,
+    pub matcher: ApprovalMatcher,
 }
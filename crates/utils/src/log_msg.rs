@@ -1,6 +1,7 @@
 use axum::{extract::ws::Message, response::sse::Event};
 use json_patch::Patch;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 pub const EV_STDOUT: &str = "stdout";
 pub const EV_STDERR: &str = "stderr";
@@ -8,6 +9,17 @@ pub const EV_JSON_PATCH: &str = "json_patch";
 pub const EV_SESSION_ID: &str = "session_id";
 pub const EV_READY: &str = "ready";
 pub const EV_FINISHED: &str = "finished";
+pub const EV_RESOURCE_USAGE: &str = "resource_usage";
+
+/// CPU and memory use of an execution's process group at one sample
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, TS)]
+pub struct ResourceUsage {
+    /// CPU use since the previous sample, where 100 is one core
+    pub cpu_percent: f64,
+    /// Resident memory of all processes in the group
+    pub rss_bytes: u64,
+    pub process_count: u32,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum LogMsg {
@@ -17,6 +29,7 @@ pub enum LogMsg {
     SessionId(String),
     Ready,
     Finished,
+    ResourceUsage(ResourceUsage),
 }
 
 impl LogMsg {
@@ -28,6 +41,7 @@ impl LogMsg {
             LogMsg::SessionId(_) => EV_SESSION_ID,
             LogMsg::Ready => EV_READY,
             LogMsg::Finished => EV_FINISHED,
+            LogMsg::ResourceUsage(_) => EV_RESOURCE_USAGE,
         }
     }
 
@@ -42,6 +56,10 @@ impl LogMsg {
             LogMsg::SessionId(s) => Event::default().event(EV_SESSION_ID).data(s.clone()),
             LogMsg::Ready => Event::default().event(EV_READY).data(""),
             LogMsg::Finished => Event::default().event(EV_FINISHED).data(""),
+            LogMsg::ResourceUsage(usage) => {
+                let data = serde_json::to_string(usage).unwrap_or_else(|_| "{}".to_string());
+                Event::default().event(EV_RESOURCE_USAGE).data(data)
+            }
         }
     }
 
@@ -80,6 +98,9 @@ impl LogMsg {
             LogMsg::SessionId(s) => EV_SESSION_ID.len() + s.len() + OVERHEAD,
             LogMsg::Ready => EV_READY.len() + OVERHEAD,
             LogMsg::Finished => EV_FINISHED.len() + OVERHEAD,
+            LogMsg::ResourceUsage(_) => {
+                EV_RESOURCE_USAGE.len() + std::mem::size_of::<ResourceUsage>() + OVERHEAD
+            }
         }
     }
 }
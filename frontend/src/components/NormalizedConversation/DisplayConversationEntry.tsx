@@ -869,6 +869,12 @@ function DisplayConversationEntry({
         <PendingApprovalEntry
           pendingStatus={status}
           executionProcessId={executionProcessId}
+          toolName={toolEntry.tool_name}
+          command={
+            toolEntry.action_type.action === 'command_run'
+              ? toolEntry.action_type.command
+              : undefined
+          }
         >
           {content}
         </PendingApprovalEntry>
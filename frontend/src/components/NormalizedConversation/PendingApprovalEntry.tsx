@@ -7,8 +7,21 @@ import {
   useState,
 } from 'react';
 import type { ReactNode } from 'react';
-import type { ApprovalStatus, ToolStatus } from 'shared/types';
+import type {
+  ApprovalMatcher,
+  ApprovalScope,
+  ApprovalStatus,
+  RememberApproval,
+  ToolStatus,
+} from 'shared/types';
 import { Button } from '@/components/ui/button';
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from '@/components/ui/select';
 import {
   Tooltip,
   TooltipContent,
@@ -31,9 +44,15 @@ const DEFAULT_DENIAL_REASON = 'User denied this tool use request.';
 interface PendingApprovalEntryProps {
   pendingStatus: Extract<ToolStatus, { status: 'pending_approval' }>;
   executionProcessId?: string;
+  toolName: string;
+  /** Shell command of the tool call, offered as a prefix to remember */
+  command?: string;
   children: ReactNode;
 }
 
+type RememberScope = ApprovalScope | 'once';
+type RememberMatcher = ApprovalMatcher['type'];
+
 function useApprovalCountdown(
   requestedAt: string | number | Date,
   timeoutAt: string | number | Date,
@@ -124,6 +143,70 @@ function ActionButtons({
   );
 }
 
+function RememberControl({
+  disabled,
+  toolName,
+  command,
+  scope,
+  matcher,
+  onScopeChange,
+  onMatcherChange,
+}: {
+  disabled: boolean;
+  toolName: string;
+  command?: string;
+  scope: RememberScope;
+  matcher: RememberMatcher;
+  onScopeChange: (scope: RememberScope) => void;
+  onMatcherChange: (matcher: RememberMatcher) => void;
+}) {
+  return (
+    <div className="flex flex-wrap items-center gap-1.5 pl-4 pt-1.5">
+      <span className="text-muted-foreground">Remember</span>
+      <Select
+        value={scope}
+        onValueChange={(value) => onScopeChange(value as RememberScope)}
+        disabled={disabled}
+      >
+        <SelectTrigger
+          className="h-7 w-auto text-xs"
+          aria-label="Remember decision for"
+        >
+          <SelectValue />
+        </SelectTrigger>
+        <SelectContent>
+          <SelectItem value="once">Just this once</SelectItem>
+          <SelectItem value="session">For this session</SelectItem>
+          <SelectItem value="workspace">For this workspace</SelectItem>
+        </SelectContent>
+      </Select>
+      {scope !== 'once' && (
+        <Select
+          value={matcher}
+          onValueChange={(value) => onMatcherChange(value as RememberMatcher)}
+          disabled={disabled}
+        >
+          <SelectTrigger
+            className="h-7 w-auto max-w-[280px] text-xs"
+            aria-label="Remember decision for calls matching"
+          >
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            <SelectItem value="exact_input">This exact call</SelectItem>
+            {command && (
+              <SelectItem value="command_prefix">
+                Commands starting with <code>{command}</code>
+              </SelectItem>
+            )}
+            <SelectItem value="tool">Any {toolName} call</SelectItem>
+          </SelectContent>
+        </Select>
+      )}
+    </div>
+  );
+}
+
 function DenyReasonForm({
   isResponding,
   value,
@@ -171,11 +254,16 @@ function DenyReasonForm({
 const PendingApprovalEntry = ({
   pendingStatus,
   executionProcessId,
+  toolName,
+  command,
   children,
 }: PendingApprovalEntryProps) => {
   const [isResponding, setIsResponding] = useState(false);
   const [hasResponded, setHasResponded] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const [rememberScope, setRememberScope] = useState<RememberScope>('once');
+  const [rememberMatcher, setRememberMatcher] =
+    useState<RememberMatcher>('exact_input');
 
   const {
     isEnteringReason,
@@ -255,10 +343,22 @@ const PendingApprovalEntry = ({
         ? { status: 'approved' }
         : { status: 'denied', reason };
 
+      let remember: RememberApproval | undefined;
+      if (rememberScope !== 'once') {
+        const matcher: ApprovalMatcher =
+          rememberMatcher === 'command_prefix' && command
+            ? { type: 'command_prefix', prefix: command }
+            : rememberMatcher === 'tool'
+              ? { type: 'tool' }
+              : { type: 'exact_input' };
+        remember = { scope: rememberScope, matcher };
+      }
+
       try {
         await approvalsApi.respond(pendingStatus.approval_id, {
           execution_process_id: executionProcessId,
           status,
+          remember,
         });
         setHasResponded(true);
         clear();
@@ -271,7 +371,15 @@ const PendingApprovalEntry = ({
         setIsResponding(false);
       }
     },
-    [disabled, executionProcessId, pendingStatus.approval_id, clear]
+    [
+      disabled,
+      executionProcessId,
+      pendingStatus.approval_id,
+      clear,
+      rememberScope,
+      rememberMatcher,
+      command,
+    ]
   );
 
   const handleApprove = useCallback(() => respond(true), [respond]);
@@ -338,6 +446,18 @@ const PendingApprovalEntry = ({
               )}
             </div>
 
+            {!hasResponded && (
+              <RememberControl
+                disabled={disabled}
+                toolName={toolName}
+                command={command}
+                scope={rememberScope}
+                matcher={rememberMatcher}
+                onScopeChange={setRememberScope}
+                onMatcherChange={setRememberMatcher}
+              />
+            )}
+
             {error && (
               <div
                 className="mt-1 text-xs text-red-600"
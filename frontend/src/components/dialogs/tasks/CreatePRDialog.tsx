@@ -61,6 +61,7 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
       null
     );
     const [isDraft, setIsDraft] = useState(false);
+    const [isStacked, setIsStacked] = useState(false);
     const [autoGenerateDescription, setAutoGenerateDescription] = useState(
       config?.pr_auto_description_enabled ?? false
     );
@@ -148,11 +149,27 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
         repo_id: repoId,
       });
 
+      // Subtask PRs reuse the PR just opened as the bottom of the stack
+      if (result.success && isStacked) {
+        const stackResult = await attemptsApi.createStackedPRs(attempt.id, {
+          repo_id: repoId,
+          draft: isDraft,
+        });
+        if (!stackResult.success) {
+          setCreatingPR(false);
+          setError(
+            stackResult.message || t('createPrDialog.errors.failedToCreate')
+          );
+          return;
+        }
+      }
+
       if (result.success) {
         setPrTitle('');
         setPrBody('');
         setPrBaseBranch('');
         setIsDraft(false);
+        setIsStacked(false);
         setAutoGenerateDescription(
           config?.pr_auto_description_enabled ?? false
         );
@@ -189,12 +206,26 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
                 ? 'GitHub'
                 : result.error.provider === 'azure_dev_ops'
                   ? 'Azure DevOps'
-                  : 'Git host';
+                  : result.error.provider === 'git_lab'
+                    ? 'GitLab'
+                    : result.error.provider === 'gitea'
+                      ? 'Gitea'
+                      : result.error.provider === 'bitbucket'
+                        ? 'Bitbucket'
+                        : 'Git host';
+            // Gitea and Bitbucket are reached over their REST APIs, not a CLI
+            const usesApi =
+              result.error.provider === 'gitea' ||
+              result.error.provider === 'bitbucket';
             const action =
               result.error.type === 'cli_not_installed'
                 ? 'not installed'
                 : 'not logged in';
-            setError(`${providerName} CLI is ${action}`);
+            setError(
+              usesApi
+                ? `${providerName} credentials are missing or invalid`
+                : `${providerName} CLI is ${action}`
+            );
             setGhCliHelp(null);
           }
           return;
@@ -235,6 +266,7 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
       prBody,
       prTitle,
       isDraft,
+      isStacked,
       autoGenerateDescription,
       config?.pr_auto_description_enabled,
       modal,
@@ -254,6 +286,7 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
       setPrBody('');
       setPrBaseBranch('');
       setIsDraft(false);
+      setIsStacked(false);
       setAutoGenerateDescription(config?.pr_auto_description_enabled ?? false);
     }, [modal, config?.pr_auto_description_enabled, error]);
 
@@ -354,6 +387,20 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
                     {t('createPrDialog.draftLabel')}
                   </Label>
                 </div>
+                <div className="flex items-center space-x-2">
+                  <Checkbox
+                    id="pr-stacked"
+                    checked={isStacked}
+                    onCheckedChange={setIsStacked}
+                    className="h-5 w-5"
+                  />
+                  <Label
+                    htmlFor="pr-stacked"
+                    className="cursor-pointer text-sm"
+                  >
+                    {t('createPrDialog.stackedLabel')}
+                  </Label>
+                </div>
                 {ghCliHelp?.variant && (
                   <Alert variant="default">
                     <AlertTitle>
@@ -338,6 +338,17 @@ function GitOperations({
             </span>
           );
         }
+        if (selectedRepoStatus?.auto_rebase_conflict) {
+          chips.push(
+            <span
+              key="auto-rebase-conflicts"
+              className="inline-flex items-center gap-1 px-2 py-0.5 rounded-full bg-amber-100/60 dark:bg-amber-900/30 text-amber-700 dark:text-amber-300"
+            >
+              <AlertTriangle className="h-3.5 w-3.5" />
+              {t('git.status.autoRebaseConflicts')}
+            </span>
+          );
+        }
         if (commitsBehind > 0) {
           chips.push(
             <span
@@ -67,6 +67,7 @@ export function GitPanelContainer({
           targetBranch: repo.target_branch || 'main',
           commitsAhead: repoStatus?.commits_ahead ?? 0,
           commitsBehind: repoStatus?.commits_behind ?? 0,
+          hasRebaseConflict: repoStatus?.auto_rebase_conflict ?? false,
           remoteCommitsAhead: repoStatus?.remote_commits_ahead ?? 0,
           prNumber,
           prUrl,
@@ -6,7 +6,12 @@ import { GitBranchIcon, SpinnerIcon } from '@phosphor-icons/react';
 import { useRepoBranches } from '@/hooks/useRepoBranches';
 import { useScriptPlaceholders } from '@/hooks/useScriptPlaceholders';
 import { repoApi } from '@/lib/api';
-import type { Repo, UpdateRepo } from 'shared/types';
+import type {
+  AutoRebasePolicy,
+  ProviderKind,
+  Repo,
+  UpdateRepo,
+} from 'shared/types';
 import { SearchableDropdownContainer } from '../../containers/SearchableDropdownContainer';
 import {
   DropdownMenu,
@@ -22,11 +27,32 @@ import {
   SettingsTextarea,
   SettingsCheckbox,
   SettingsSaveBar,
+  SettingsSelect,
 } from './SettingsComponents';
 
+// 'auto' detects the provider from the remote URL
+type GitHostProviderOption = ProviderKind | 'auto';
+
+const GIT_HOST_PROVIDER_OPTIONS: GitHostProviderOption[] = [
+  'auto',
+  'git_hub',
+  'azure_dev_ops',
+  'git_lab',
+  'gitea',
+  'bitbucket',
+];
+
+const AUTO_REBASE_OPTIONS: AutoRebasePolicy[] = [
+  'never',
+  'idle_only',
+  'always',
+];
+
 interface RepoScriptsFormState {
   display_name: string;
   default_target_branch: string;
+  git_host_provider: GitHostProviderOption;
+  auto_rebase: AutoRebasePolicy;
   setup_script: string;
   parallel_setup_script: boolean;
   cleanup_script: string;
@@ -38,6 +64,8 @@ function repoToFormState(repo: Repo): RepoScriptsFormState {
   return {
     display_name: repo.display_name,
     default_target_branch: repo.default_target_branch ?? '',
+    git_host_provider: repo.git_host_provider ?? 'auto',
+    auto_rebase: repo.auto_rebase,
     setup_script: repo.setup_script ?? '',
     parallel_setup_script: repo.parallel_setup_script,
     cleanup_script: repo.cleanup_script ?? '',
@@ -151,6 +179,9 @@ export function ReposSettingsSection() {
       const updateData: UpdateRepo = {
         display_name: draft.display_name.trim() || null,
         default_target_branch: draft.default_target_branch.trim() || null,
+        git_host_provider:
+          draft.git_host_provider === 'auto' ? null : draft.git_host_provider,
+        auto_rebase: draft.auto_rebase,
         setup_script: draft.setup_script.trim() || null,
         cleanup_script: draft.cleanup_script.trim() || null,
         copy_files: draft.copy_files.trim() || null,
@@ -343,6 +374,38 @@ export function ReposSettingsSection() {
                 }
               />
             </SettingsField>
+
+            <SettingsField
+              label={t('settings.repos.general.gitHostProvider.label')}
+              description={t('settings.repos.general.gitHostProvider.helper')}
+            >
+              <SettingsSelect
+                value={draft.git_host_provider}
+                options={GIT_HOST_PROVIDER_OPTIONS.map((provider) => ({
+                  value: provider,
+                  label: t(
+                    `settings.repos.general.gitHostProvider.options.${provider}`
+                  ),
+                }))}
+                onChange={(value) => updateDraft({ git_host_provider: value })}
+              />
+            </SettingsField>
+
+            <SettingsField
+              label={t('settings.repos.general.autoRebase.label')}
+              description={t('settings.repos.general.autoRebase.helper')}
+            >
+              <SettingsSelect
+                value={draft.auto_rebase}
+                options={AUTO_REBASE_OPTIONS.map((policy) => ({
+                  value: policy,
+                  label: t(
+                    `settings.repos.general.autoRebase.options.${policy}`
+                  ),
+                }))}
+                onChange={(value) => updateDraft({ auto_rebase: value })}
+              />
+            </SettingsField>
           </SettingsCard>
 
           {/* Scripts settings */}
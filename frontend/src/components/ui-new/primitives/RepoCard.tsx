@@ -45,6 +45,7 @@ interface RepoCardProps {
   targetBranch: string;
   commitsAhead?: number;
   commitsBehind?: number;
+  hasRebaseConflict?: boolean;
   prNumber?: number;
   prUrl?: string;
   prStatus?: 'open' | 'merged' | 'closed' | 'unknown';
@@ -67,6 +68,7 @@ export function RepoCard({
   targetBranch,
   commitsAhead = 0,
   commitsBehind = 0,
+  hasRebaseConflict = false,
   prNumber,
   prUrl,
   prStatus,
@@ -158,6 +160,14 @@ export function RepoCard({
             <span className="font-medium">{commitsBehind}</span>
           </span>
         )}
+        {hasRebaseConflict && (
+          <span
+            className="inline-flex items-center text-xs text-warning shrink-0"
+            title={t('git.status.autoRebaseConflicts')}
+          >
+            <WarningCircleIcon className="size-icon-xs" weight="fill" />
+          </span>
+        )}
 
         <button
           onClick={onMoreClick}
@@ -14,6 +14,7 @@ export interface RepoInfo {
   targetBranch: string;
   commitsAhead: number;
   commitsBehind: number;
+  hasRebaseConflict?: boolean;
   remoteCommitsAhead?: number;
   prNumber?: number;
   prUrl?: string;
@@ -66,6 +67,7 @@ export function GitPanel({
             targetBranch={repo.targetBranch}
             commitsAhead={repo.commitsAhead}
             commitsBehind={repo.commitsBehind}
+            hasRebaseConflict={repo.hasRebaseConflict}
             prNumber={repo.prNumber}
             prUrl={repo.prUrl}
             prStatus={repo.prStatus}
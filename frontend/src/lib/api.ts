@@ -7,6 +7,7 @@ import {
   CreateFollowUpAttempt,
   EditorType,
   CreatePrApiRequest,
+  CreateStackedPrsRequest,
   CreateTask,
   CreateAndStartTaskRequest,
   CreateTaskAttemptBody,
@@ -53,6 +54,7 @@ import {
   GhCliSetupError,
   RunScriptError,
   StatusResponse,
+  StackedPr,
   ListOrganizationsResponse,
   OrganizationMemberWithProfile,
   ListMembersResponse,
@@ -690,6 +692,20 @@ export const attemptsApi = {
     return handleApiResponseAsResult<string, PrError>(response);
   },
 
+  createStackedPRs: async (
+    attemptId: string,
+    data: CreateStackedPrsRequest
+  ): Promise<Result<StackedPr[], PrError>> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/pr/stack`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponseAsResult<StackedPr[], PrError>(response);
+  },
+
   startDevServer: async (attemptId: string): Promise<ExecutionProcess[]> => {
     const response = await makeRequest(
       `/api/task-attempts/${attemptId}/start-dev-server`,
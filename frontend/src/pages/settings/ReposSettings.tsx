@@ -27,10 +27,35 @@ import { AutoExpandingTextarea } from '@/components/ui/auto-expanding-textarea';
 import { MultiFileSearchTextarea } from '@/components/ui/multi-file-search-textarea';
 import { repoApi } from '@/lib/api';
 import { useQuery, useQueryClient } from '@tanstack/react-query';
-import type { Repo, UpdateRepo } from 'shared/types';
+import type {
+  AutoRebasePolicy,
+  ProviderKind,
+  Repo,
+  UpdateRepo,
+} from 'shared/types';
+
+// 'auto' detects the provider from the remote URL
+type GitHostProviderOption = ProviderKind | 'auto';
+
+const GIT_HOST_PROVIDER_OPTIONS: GitHostProviderOption[] = [
+  'auto',
+  'git_hub',
+  'azure_dev_ops',
+  'git_lab',
+  'gitea',
+  'bitbucket',
+];
+
+const AUTO_REBASE_OPTIONS: AutoRebasePolicy[] = [
+  'never',
+  'idle_only',
+  'always',
+];
 
 interface RepoScriptsFormState {
   display_name: string;
+  git_host_provider: GitHostProviderOption;
+  auto_rebase: AutoRebasePolicy;
   setup_script: string;
   parallel_setup_script: boolean;
   cleanup_script: string;
@@ -41,6 +66,8 @@ interface RepoScriptsFormState {
 function repoToFormState(repo: Repo): RepoScriptsFormState {
   return {
     display_name: repo.display_name,
+    git_host_provider: repo.git_host_provider ?? 'auto',
+    auto_rebase: repo.auto_rebase,
     setup_script: repo.setup_script ?? '',
     parallel_setup_script: repo.parallel_setup_script,
     cleanup_script: repo.cleanup_script ?? '',
@@ -177,6 +204,9 @@ export function ReposSettings() {
     try {
       const updateData: UpdateRepo = {
         display_name: draft.display_name.trim() || null,
+        git_host_provider:
+          draft.git_host_provider === 'auto' ? null : draft.git_host_provider,
+        auto_rebase: draft.auto_rebase,
         setup_script: draft.setup_script.trim() || null,
         cleanup_script: draft.cleanup_script.trim() || null,
         copy_files: draft.copy_files.trim() || null,
@@ -325,6 +355,64 @@ export function ReposSettings() {
                   {selectedRepo.path}
                 </div>
               </div>
+
+              <div className="space-y-2">
+                <Label htmlFor="git-host-provider">
+                  {t('settings.repos.general.gitHostProvider.label')}
+                </Label>
+                <Select
+                  value={draft.git_host_provider}
+                  onValueChange={(value) =>
+                    updateDraft({
+                      git_host_provider: value as GitHostProviderOption,
+                    })
+                  }
+                >
+                  <SelectTrigger id="git-host-provider">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {GIT_HOST_PROVIDER_OPTIONS.map((provider) => (
+                      <SelectItem key={provider} value={provider}>
+                        {t(
+                          `settings.repos.general.gitHostProvider.options.${provider}`
+                        )}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+                <p className="text-sm text-muted-foreground">
+                  {t('settings.repos.general.gitHostProvider.helper')}
+                </p>
+              </div>
+
+              <div className="space-y-2">
+                <Label htmlFor="auto-rebase">
+                  {t('settings.repos.general.autoRebase.label')}
+                </Label>
+                <Select
+                  value={draft.auto_rebase}
+                  onValueChange={(value) =>
+                    updateDraft({ auto_rebase: value as AutoRebasePolicy })
+                  }
+                >
+                  <SelectTrigger id="auto-rebase">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {AUTO_REBASE_OPTIONS.map((policy) => (
+                      <SelectItem key={policy} value={policy}>
+                        {t(
+                          `settings.repos.general.autoRebase.options.${policy}`
+                        )}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+                <p className="text-sm text-muted-foreground">
+                  {t('settings.repos.general.autoRebase.helper')}
+                </p>
+              </div>
             </CardContent>
           </Card>
 
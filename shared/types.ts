@@ -18,19 +18,61 @@ score: bigint, };
 
 export type SearchMatchType = "FileName" | "DirectoryName" | "FullPath";
 
-export type Repo = { id: string, path: string, name: string, display_name: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, dev_server_script: string | null, default_target_branch: string | null, created_at: Date, updated_at: Date, };
+export type Repo = { id: string, path: string, name: string, display_name: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, dev_server_script: string | null, default_target_branch: string | null, 
+/**
+ * OCI image that executions of workspaces with this repo run in, instead of the host
+ */
+container_image: string | null, 
+/**
+ * SSH host, as accepted by `ssh`, that executions of workspaces with this repo run on
+ */
+ssh_host: string | null, 
+/**
+ * Git hosting provider for pull requests, overriding detection from the remote URL
+ */
+git_host_provider: ProviderKind | null, 
+/**
+ * When workspaces with this repo are rebased automatically after their target branch moves
+ */
+auto_rebase: AutoRebasePolicy, created_at: Date, updated_at: Date, };
+
+export type UpdateRepo = { display_name?: string | null, setup_script?: string | null, cleanup_script?: string | null, copy_files?: string | null, parallel_setup_script?: boolean | null, dev_server_script?: string | null, default_target_branch?: string | null, container_image?: string | null, ssh_host?: string | null, git_host_provider?: ProviderKind | null, auto_rebase?: AutoRebasePolicy | null, };
 
-export type UpdateRepo = { display_name?: string | null, setup_script?: string | null, cleanup_script?: string | null, copy_files?: string | null, parallel_setup_script?: boolean | null, dev_server_script?: string | null, default_target_branch?: string | null, };
+/**
+ * When workspaces of a repo are rebased onto their target branch after it moves.
+ */
+export type AutoRebasePolicy = "never" | "idle_only" | "always";
 
 export type ProjectRepo = { id: string, project_id: string, repo_id: string, };
 
 export type CreateProjectRepo = { display_name: string, git_repo_path: string, };
 
-export type WorkspaceRepo = { id: string, workspace_id: string, repo_id: string, target_branch: string, created_at: Date, updated_at: Date, };
+export type WorkspaceRepo = { id: string, workspace_id: string, repo_id: string, target_branch: string, 
+/**
+ * Target branch commit an automatic rebase hit conflicts with, cleared by the next
+ * successful rebase
+ */
+auto_rebase_conflict_oid: string | null, created_at: Date, updated_at: Date, };
 
 export type CreateWorkspaceRepo = { repo_id: string, target_branch: string, };
 
-export type RepoWithTargetBranch = { target_branch: string, id: string, path: string, name: string, display_name: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, dev_server_script: string | null, default_target_branch: string | null, created_at: Date, updated_at: Date, };
+export type RepoWithTargetBranch = { target_branch: string, id: string, path: string, name: string, display_name: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, dev_server_script: string | null, default_target_branch: string | null, 
+/**
+ * OCI image that executions of workspaces with this repo run in, instead of the host
+ */
+container_image: string | null, 
+/**
+ * SSH host, as accepted by `ssh`, that executions of workspaces with this repo run on
+ */
+ssh_host: string | null, 
+/**
+ * Git hosting provider for pull requests, overriding detection from the remote URL
+ */
+git_host_provider: ProviderKind | null, 
+/**
+ * When workspaces with this repo are rebased automatically after their target branch moves
+ */
+auto_rebase: AutoRebasePolicy, created_at: Date, updated_at: Date, };
 
 export type Tag = { id: string, tag_name: string, content: string, created_at: string, updated_at: string, };
 
@@ -78,7 +120,39 @@ export type Workspace = { id: string, task_id: string, container_ref: string | n
 
 export type WorkspaceWithStatus = { is_running: boolean, is_errored: boolean, id: string, task_id: string, container_ref: string | null, branch: string, agent_working_dir: string | null, setup_completed_at: string | null, created_at: string, updated_at: string, archived: boolean, pinned: boolean, name: string | null, };
 
-export type Session = { id: string, workspace_id: string, executor: string | null, created_at: string, updated_at: string, };
+/**
+ * Workspaces started together on the same task so their results can be compared
+ */
+export type AttemptGroup = { id: string, task_id: string, 
+/**
+ * Set when a workspace is picked, before it is merged
+ */
+winner_workspace_id: string | null, created_at: string, updated_at: string, };
+
+export type Session = { id: string, workspace_id: string, executor: string | null, 
+/**
+ * Fallbacks taken by this session, oldest first
+ */
+executor_fallbacks: Array<ExecutorFallback>, 
+/**
+ * The session this one was forked from
+ */
+forked_from_session_id: string | null, 
+/**
+ * The coding agent turn of the original session this one continues from
+ */
+forked_from_execution_process_id: string | null, created_at: string, updated_at: string, };
+
+/**
+ * A switch to another executor profile after a coding agent execution failed
+ */
+export type ExecutorFallback = { from: ExecutorProfileId, to: ExecutorProfileId, reason: ExecutorFallbackReason, 
+/**
+ * The execution process that failed
+ */
+execution_process_id: string, created_at: string, };
+
+export type ExecutorFallbackReason = "auth_required" | "executable_not_found" | "rate_limited";
 
 export type ExecutionProcess = { id: string, session_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, status: ExecutionProcessStatus, exit_code: bigint | null, 
 /**
@@ -86,14 +160,124 @@ export type ExecutionProcess = { id: string, session_id: string, run_reason: Exe
  * history view (due to restore/trimming). Hidden from logs/timeline;
  * still listed in the Processes tab.
  */
-dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, 
+/**
+ * Set when the process was stopped for exceeding an execution limit or budget
+ */
+completion_reason: ExecutionProcessCompletionReason | null, 
+/**
+ * Highest resident memory of the process group, sampled while it ran
+ */
+peak_rss_bytes: bigint | null, 
+/**
+ * Highest CPU use of the process group, where 100 is one core
+ */
+peak_cpu_percent: number | null, };
 
 export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed" }
 
 export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
 
+/**
+ * Why an execution process was ended by vibe-kanban rather than exiting on its own
+ */
+export type ExecutionProcessCompletionReason = "timeout" | "tool_call_limit" | "budget_exceeded";
+
 export type ExecutionProcessRepoState = { id: string, execution_process_id: string, repo_id: string, before_head_commit: string | null, after_head_commit: string | null, merge_commit: string | null, created_at: Date, updated_at: Date, };
 
+/**
+ * Token and spend limit of a project or task. `None` means unlimited.
+ */
+export type Budget = { max_tokens: bigint | null, max_cost_usd: number | null, updated_at: string, };
+
+export type SetBudget = { max_tokens: bigint | null, max_cost_usd: number | null, };
+
+/**
+ * Approval rules of a project. They are checked before the rules of the executor profile.
+ */
+export type ProjectApprovalPolicy = { project_id: string, rules: Array<ApprovalRule>, updated_at: string, };
+
+export type SetApprovalPolicy = { rules: Array<ApprovalRule>, };
+
+/**
+ * One step of a pipeline. Agent and review stages without a profile use the profile the
+ * attempt was started with.
+ */
+export type PipelineStage = { "type": "agent", executor_profile_id: ExecutorProfileId | null, prompt: string | null, } | { "type": "plan_approval" } | { "type": "review", executor_profile_id: ExecutorProfileId | null, additional_prompt: string | null, } | { "type": "script", script: string | null, };
+
+/**
+ * Pipeline that new task attempts of a project run through
+ */
+export type ProjectPipeline = { project_id: string, stages: Array<PipelineStage>, updated_at: string, };
+
+export type SetProjectPipeline = { stages: Array<PipelineStage>, };
+
+export type PipelineRunStatus = "running" | "awaiting_approval" | "completed" | "failed" | "cancelled";
+
+/**
+ * Progress of a workspace through the pipeline of its project
+ */
+export type PipelineRun = { id: string, workspace_id: string, 
+/**
+ * Session the current stage runs in
+ */
+session_id: string, 
+/**
+ * The project pipeline as it was when the run started
+ */
+stages: Array<PipelineStage>, 
+/**
+ * Profile the attempt was started with, used by stages that do not name one
+ */
+default_executor_profile_id: ExecutorProfileId, current_stage: bigint, status: PipelineRunStatus, 
+/**
+ * Plan produced by the first agent stage, passed on to the later stages
+ */
+plan: string | null, created_at: string, updated_at: string, };
+
+/**
+ * Audit record of a tool call approval request
+ */
+export type Approval = { id: string, 
+/**
+ * Cleared when the execution process is deleted; the record is kept for auditing
+ */
+execution_process_id: string | null, tool_name: string, tool_call_id: string, tool_input: JsonValue, status: ApprovalOutcome, reason: string | null, decided_by: ApprovalDecidedBy | null, 
+/**
+ * The user or rule that decided the request
+ */
+responder: string | null, 
+/**
+ * Time from the request to its decision in milliseconds
+ */
+latency_ms: bigint | null, requested_at: string, responded_at: string | null, };
+
+/**
+ * Final state of an approval request
+ */
+export type ApprovalOutcome = "pending" | "approved" | "denied" | "timed_out";
+
+/**
+ * Who decided an approval request
+ */
+export type ApprovalDecidedBy = "user" | "rule" | "remembered" | "timeout";
+
+export type ApprovalQuery = { project_id: string | null, task_id: string | null, execution_process_id: string | null, tool_name: string | null, status: ApprovalOutcome | null, limit: bigint | null, offset: bigint | null, };
+
+export type LogSearchEntryKind = "assistant_message" | "command" | "command_output" | "file_path" | "error";
+
+export type LogSearchHit = { execution_process_id: string, session_id: string, workspace_id: string, task_id: string, project_id: string, task_title: string, entry_index: bigint, kind: LogSearchEntryKind, 
+/**
+ * HTML-escaped excerpt with matches wrapped in `<mark>` tags
+ */
+snippet: string, started_at: string, };
+
+export type LogSearchQuery = { 
+/**
+ * Text to find; matched as a phrase of whole words
+ */
+q: string, project_id: string | null, task_id: string | null, kind: LogSearchEntryKind | null, limit: bigint | null, offset: bigint | null, };
+
 export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
 
 export type DirectMerge = { id: string, workspace_id: string, repo_id: string, merge_commit: string, target_branch_name: string, created_at: string, };
@@ -108,7 +292,23 @@ export type ApprovalStatus = { "status": "pending" } | { "status": "approved" }
 
 export type CreateApprovalRequest = { tool_name: string, tool_input: JsonValue, tool_call_id: string, };
 
-export type ApprovalResponse = { execution_process_id: string, status: ApprovalStatus, };
+export type ApprovalResponse = { execution_process_id: string, status: ApprovalStatus, 
+/**
+ * Apply the same decision to later matching tool calls
+ */
+remember?: RememberApproval, };
+
+/**
+ * How long a remembered approval decision applies
+ */
+export type ApprovalScope = "session" | "workspace";
+
+/**
+ * Which later tool calls a remembered approval decision applies to
+ */
+export type ApprovalMatcher = { "type": "exact_input" } | { "type": "tool" } | { "type": "command_prefix", prefix: string, };
+
+export type RememberApproval = { scope: ApprovalScope, matcher: ApprovalMatcher, };
 
 export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: string | null, oldContent: string | null, newContent: string | null, 
 /**
@@ -122,6 +322,19 @@ additions: number | null, deletions: number | null, repoId: string | null, };
 
 export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange";
 
+/**
+ * CPU and memory use of an execution's process group at one sample
+ */
+export type ResourceUsage = { 
+/**
+ * CPU use since the previous sample, where 100 is one core
+ */
+cpu_percent: number, 
+/**
+ * Resident memory of all processes in the group
+ */
+rss_bytes: number, process_count: number, };
+
 export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
 
 export type LoginStatus = { "status": "loggedout" } | { "status": "loggedin", profile: ProfileResponse, };
@@ -232,6 +445,19 @@ export type StartReviewRequest = { executor_profile_id: ExecutorProfileId, addit
 
 export type ReviewError = { "type": "process_already_running" };
 
+export type ForkSessionRequest = { 
+/**
+ * The coding agent turn to fork after
+ */
+execution_process_id: string, };
+
+/**
+ * Changes of a forked session's branch relative to the branch of the session it was forked from
+ */
+export type RepoForkComparison = { repo_id: string, repo_name: string, original_branch: string, fork_branch: string, diffs: Array<Diff>, };
+
+export type ForkComparison = { original_session_id: string, original_workspace_id: string, repos: Array<RepoForkComparison>, };
+
 export type OpenEditorRequest = { editor_type: string | null, file_path: string | null, };
 
 export type OpenEditorResponse = { url: string | null, };
@@ -240,6 +466,10 @@ export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id:
 
 export type CreatePrApiRequest = { title: string, body: string | null, target_branch: string | null, draft: boolean | null, repo_id: string, auto_generate_description: boolean, };
 
+export type CreateStackedPrsRequest = { repo_id: string, draft: boolean | null, };
+
+export type StackedPr = { workspace_id: string, pr_url: string, };
+
 export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
 
 export type ImageMetadata = { exists: boolean, file_name: string | null, path: string | null, size_bytes: bigint | null, format: string | null, proxy_url: string | null, };
@@ -278,7 +508,10 @@ export type GetPrCommentsQuery = { repo_id: string, };
 
 export type UnifiedPrComment = { "comment_type": "general", id: string, author: string, author_association: string | null, body: string, created_at: string, url: string | null, } | { "comment_type": "review", id: bigint, author: string, author_association: string | null, body: string, created_at: string, url: string | null, path: string, line: bigint | null, side: string | null, diff_hunk: string | null, };
 
-export type ProviderKind = "git_hub" | "azure_dev_ops" | "unknown";
+/**
+ * Git hosting service that pull requests for a repo are opened on.
+ */
+export type ProviderKind = "git_hub" | "azure_dev_ops" | "git_lab" | "gitea" | "bitbucket" | "unknown";
 
 export type RepoBranchStatus = { repo_id: string, repo_name: string, commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, target_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
 /**
@@ -296,7 +529,11 @@ conflicted_files: Array<string>,
 /**
  * True if the target branch is a remote branch (merging not allowed, must use PR)
  */
-is_target_remote: boolean, };
+is_target_remote: boolean, 
+/**
+ * True if an automatic rebase onto the target branch hit conflicts and was aborted
+ */
+auto_rebase_conflict: boolean, };
 
 export type UpdateWorkspace = { archived: boolean | null, pinned: boolean | null, name: string | null, };
 
@@ -348,13 +585,64 @@ export type WorkspaceSummaryResponse = { summaries: Array<WorkspaceSummary>, };
 
 export type DiffStats = { files_changed: number, lines_added: number, lines_removed: number, };
 
+export type CreateAttemptGroupBody = { task_id: string, repos: Array<WorkspaceRepoInput>, 
+/**
+ * Each profile is started `attempts_per_profile` times, in its own workspace
+ */
+executor_profile_ids: Array<ExecutorProfileId>, attempts_per_profile: number | null, };
+
+export type AttemptGroupWithWorkspaces = { workspaces: Array<Workspace>, id: string, task_id: string, 
+/**
+ * Set when a workspace is picked, before it is merged
+ */
+winner_workspace_id: string | null, created_at: string, updated_at: string, };
+
+/**
+ * Outcome of the latest cleanup script run, which is where repos run their tests and checks
+ * after each coding agent turn
+ */
+export type ScriptRunResult = { execution_process_id: string, status: ExecutionProcessStatus, exit_code: bigint | null, };
+
+export type AttemptComparison = { workspace: Workspace, executor_profile_id: ExecutorProfileId | null, 
+/**
+ * Status of the latest coding agent turn
+ */
+agent_status: ExecutionProcessStatus | null, 
+/**
+ * None when the workspace has no worktree
+ */
+diff_stats: DiffStats | null, test_run: ScriptRunResult | null, token_usage: TokenUsageReport, 
+/**
+ * Final assistant message of the latest turn
+ */
+summary: string | null, };
+
+export type AttemptGroupComparison = { attempts: Array<AttemptComparison>, id: string, task_id: string, 
+/**
+ * Set when a workspace is picked, before it is merged
+ */
+winner_workspace_id: string | null, created_at: string, updated_at: string, };
+
+export type PickAttemptWinnerRequest = { workspace_id: string, };
+
+export type ApprovePipelinePlanRequest = { 
+/**
+ * Replaces the plan passed on to the next stages, for edits made during review
+ */
+plan: string | null, };
+
 export type DirectoryEntry = { name: string, path: string, is_directory: boolean, is_git_repo: boolean, last_modified: bigint | null, };
 
 export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
 
 export type SearchMode = "taskform" | "settings";
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, beta_workspaces: boolean, beta_workspaces_invitation_sent: boolean, commit_reminder: boolean, send_message_shortcut: SendMessageShortcut, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, beta_workspaces: boolean, beta_workspaces_invitation_sent: boolean, commit_reminder: boolean, send_message_shortcut: SendMessageShortcut, model_prices: Array<ModelPrice>, 
+/**
+ * Profiles to retry a coding agent with, in order, when it fails with missing auth,
+ * a missing executable or a rate limit
+ */
+executor_fallbacks: Array<ExecutorProfileId>, log_retention: LogRetentionConfig, git_hosts: Array<GitHostConfig>, };
 
 export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
 
@@ -376,6 +664,54 @@ export type ShowcaseState = { seen_features: Array<string>, };
 
 export type SendMessageShortcut = "ModifierEnter" | "Enter";
 
+/**
+ * Price of a model in USD per million tokens, used to estimate spend.
+ * `model` is matched as a prefix of the reported model name; the longest match wins.
+ */
+export type ModelPrice = { model: string, input_per_mtok: number, output_per_mtok: number, cache_read_per_mtok: number, cache_write_per_mtok: number, };
+
+export type LogRetentionConfig = { max_age_days: number | null, 
+/**
+ * Counted as stored, so compressed logs count with their compressed size
+ */
+max_bytes_per_project: bigint | null, keep_last_processes_per_project: number | null, };
+
+/**
+ * Git hosting service on a custom domain, used when the provider cannot be detected from
+ * the remote URL.
+ */
+export type GitHostConfig = { 
+/**
+ * Host name as it appears in remote and pull request URLs, e.g. `github.corp.example`
+ */
+host: string, provider: ProviderKind, 
+/**
+ * API endpoint to use instead of the one derived from `host`
+ */
+api_base_url: string | null, };
+
+/**
+ * Rolled up token usage for a session, task, project or the whole instance
+ */
+export type TokenUsageReport = { input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_write_tokens: bigint, 
+/**
+ * Sum of the costs of all priced models
+ */
+estimated_cost_usd: number, 
+/**
+ * Models that could not be priced and are missing from `estimated_cost_usd`
+ */
+unpriced_models: Array<string>, breakdown: Array<TokenUsageBreakdown>, };
+
+/**
+ * Usage of one executor/model pair together with its estimated cost
+ */
+export type TokenUsageBreakdown = { executor: string, model: string | null, execution_count: bigint, input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_write_tokens: bigint, 
+/**
+ * From the price table, else the cost reported by the agent. None when neither is known
+ */
+estimated_cost_usd: number | null, };
+
 export type GitBranch = { name: string, is_current: boolean, is_remote: boolean, last_commit_date: Date, };
 
 export type QueuedMessage = { 
@@ -413,9 +749,9 @@ working_dir: string | null, };
 
 export type ScriptRequestLanguage = "Bash";
 
-export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT", DROID = "DROID" }
+export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT", DROID = "DROID", CUSTOM_AGENT = "CUSTOM_AGENT", ACP = "ACP" }
 
-export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid };
+export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid } | { "CUSTOM_AGENT": CustomAgent } | { "ACP": Acp };
 
 export type SlashCommandDescription = { 
 /**
@@ -435,6 +771,23 @@ base: string,
  */
 params: Array<string> | null, };
 
+/**
+ * What to do with a tool call matched by an approval rule
+ */
+export type ApprovalDecision = "allow" | "deny" | "ask";
+
+/**
+ * Matches tool calls by tool name, command and file path. Every condition that is set must match.
+ */
+export type ApprovalRule = { decision: ApprovalDecision, tool?: string | null, command?: string | null, path?: string | null, reason?: string | null, };
+
+/**
+ * Landlock sandbox for agent processes on Linux. Outside the workspace, the config directories
+ * of the agent, the object store and refs of the workspace repositories and `writable_paths`,
+ * the filesystem is read-only.
+ */
+export type LinuxSandbox = { deny_network: boolean, writable_paths?: Array<string> | null, };
+
 export type ExecutorProfileId = { 
 /**
  * The executor type (e.g., "CLAUDE_CODE", "AMP")
@@ -445,19 +798,19 @@ executor: BaseCodingAgent,
  */
 variant: string | null, };
 
-export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid } };
+export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid } | { "CUSTOM_AGENT": CustomAgent } | { "ACP": Acp } };
 
 export type ExecutorConfigs = { executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
 
 export enum BaseAgentCapability { SESSION_FORK = "SESSION_FORK", SETUP_HELPER = "SETUP_HELPER" }
 
-export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
 
-export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
 
-export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
 
-export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_apply_patch_tool?: boolean | null, model_provider?: string | null, compact_prompt?: string | null, developer_instructions?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_apply_patch_tool?: boolean | null, model_provider?: string | null, compact_prompt?: string | null, developer_instructions?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
 
 export type SandboxMode = "auto" | "read-only" | "workspace-write" | "danger-full-access";
 
@@ -469,9 +822,9 @@ export type ReasoningSummary = "auto" | "concise" | "detailed" | "none";
 
 export type ReasoningSummaryFormat = "none" | "experimental";
 
-export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
 
-export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
 
 export type Opencode = { append_prompt: AppendPrompt, model?: string | null, variant?: string | null, mode?: string | null, 
 /**
@@ -481,16 +834,46 @@ auto_approve: boolean,
 /**
  * Enable auto-compaction when the context length approaches the model's context window limit
  */
-auto_compact: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+auto_compact: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
 
-export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
 
-export type Droid = { append_prompt: AppendPrompt, autonomy: Autonomy, model?: string | null, reasoning_effort?: DroidReasoningEffort | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Droid = { append_prompt: AppendPrompt, autonomy: Autonomy, model?: string | null, reasoning_effort?: DroidReasoningEffort | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
 
 export type Autonomy = "normal" | "low" | "medium" | "high" | "skip-permissions-unsafe";
 
 export type DroidReasoningEffort = "none" | "dynamic" | "off" | "low" | "medium" | "high";
 
+/**
+ * Executor for agent CLIs described entirely by their profile
+ */
+export type CustomAgent = { append_prompt: AppendPrompt, command: string, prompt_delivery: PromptDelivery, follow_up_params?: Array<string> | null, session_id?: SessionIdRule | null, log_mapping?: Array<JsonlMappingRule> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
+
+/**
+ * How the prompt is handed to the agent process
+ */
+export type PromptDelivery = "stdin" | "argument";
+
+/**
+ * Rule used to discover the agent session id from stdout
+ */
+export type SessionIdRule = { "type": "json_pointer", pointer: string, } | { "type": "regex", pattern: string, };
+
+/**
+ * Kind of normalized entry produced by a mapping rule
+ */
+export type MappedEntryType = "user_message" | "assistant_message" | "thinking" | "system_message" | "error_message" | "tool_use" | "command_run" | "file_read" | "file_edit";
+
+/**
+ * Maps one kind of stdout JSONL object to a `NormalizedEntry`
+ */
+export type JsonlMappingRule = { match_pointer: string, match_value: string, entry_type: MappedEntryType, content?: string | null, tool_name?: string | null, result?: string | null, status?: string | null, };
+
+/**
+ * Generic executor for any binary speaking the Agent Client Protocol over stdio
+ */
+export type Acp = { append_prompt: AppendPrompt, program: string, args?: Array<string> | null, model?: string | null, mode?: string | null, auto_approve?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, max_runtime_secs?: number | null, max_tool_calls?: number | null, max_memory_mb?: number | null, max_cpu_percent?: number | null, max_pids?: number | null, approval_rules?: Array<ApprovalRule> | null, linux_sandbox?: LinuxSandbox | null, };
+
 export type AppendPrompt = string | null;
 
 export type CodingAgentInitialRequest = { prompt: string, 
@@ -535,7 +918,17 @@ export type NormalizedEntry = { timestamp: string | null, entry_type: Normalized
 
 export type NormalizedEntryType = { "type": "user_message" } | { "type": "user_feedback", denied_tool: string, } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, status: ToolStatus, } | { "type": "system_message" } | { "type": "error_message", error_type: NormalizedEntryError, } | { "type": "thinking" } | { "type": "loading" } | { "type": "next_action", failed: boolean, execution_processes: number, needs_setup: boolean, } | { "type": "token_usage_info" } & TokenUsageInfo;
 
-export type TokenUsageInfo = { total_tokens: number, model_context_window: number, };
+export type TokenUsageInfo = { total_tokens: number, model_context_window: number, 
+/**
+ * Cumulative token counts of the current execution, when the agent reports them
+ */
+usage?: TokenUsage, };
+
+export type TokenUsage = { model: string | null, input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_write_tokens: bigint, 
+/**
+ * Cost the agent reported for the run, used when the model has no price entry
+ */
+cost_usd?: number, };
 
 export type FileChange = { "action": "write", content: string, } | { "action": "delete" } | { "action": "rename", new_path: string, } | { "action": "edit", 
 /**
@@ -578,4 +971,4 @@ Analyze the changes in this branch and write:
    - Any important implementation details
    - At the end, include a note: "This PR was written using [Vibe Kanban](https://vibekanban.com)"
 
-Use the appropriate CLI tool to update the PR (gh pr edit for GitHub, az repos pr update for Azure DevOps).`;
\ No newline at end of file
+Use the appropriate CLI tool to update the PR (gh pr edit for GitHub, az repos pr update for Azure DevOps, glab mr update for GitLab).`;
\ No newline at end of file